@@ -52,7 +52,12 @@ use std::ops::Range;
 use std::process::ExitStatus;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
-use std::{fmt::Display, mem, path::PathBuf, sync::Arc};
+use std::{
+    fmt::Display,
+    mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use text::Bias;
 use ui::App;
 use util::{ResultExt, get_default_system_shell_preferring_bash, paths::PathStyle};
@@ -909,6 +914,12 @@ pub struct TokenUsage {
 }
 
 impl TokenUsage {
+    /// The fraction of the context window in use, or `None` when the maximum is unknown
+    /// because there is no selected model.
+    pub fn fraction(&self) -> Option<f32> {
+        (self.max_tokens > 0).then(|| self.used_tokens as f32 / self.max_tokens as f32)
+    }
+
     pub fn ratio(&self) -> TokenUsageRatio {
         #[cfg(debug_assertions)]
         let warning_threshold: f32 = std::env::var("ZED_THREAD_WARNING_THRESHOLD")
@@ -1244,6 +1255,22 @@ impl AcpThread {
         &self.entries
     }
 
+    /// Returns the files mentioned in the thread's user messages, in the order they were
+    /// first mentioned and without duplicates. Relative paths are resolved against `root`.
+    pub fn referenced_files(&self, root: Option<&Path>, cx: &App) -> Vec<PathBuf> {
+        let path_style = self.project.read(cx).path_style(cx);
+        let uris = self
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                AgentThreadEntry::UserMessage(message) => Some(message.chunks.iter()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(chunk_resource_uri);
+        referenced_file_paths(uris, path_style, root)
+    }
+
     pub fn session_id(&self) -> &acp::SessionId {
         &self.session_id
     }
@@ -2146,6 +2173,17 @@ impl AcpThread {
         })
     }
 
+    /// Returns the id and content of the last user message, if it can be rewound to.
+    pub fn last_user_message_prompt(&self) -> Option<(UserMessageId, Vec<acp::ContentBlock>)> {
+        self.entries.iter().rev().find_map(|entry| {
+            if let AgentThreadEntry::UserMessage(message) = entry {
+                Some((message.id.clone()?, message.chunks.clone()))
+            } else {
+                None
+            }
+        })
+    }
+
     fn update_last_checkpoint(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
         let git_store = self.project.read(cx).git_store().clone();
 
@@ -2601,6 +2639,44 @@ fn markdown_for_raw_output(
     }
 }
 
+fn chunk_resource_uri(chunk: &acp::ContentBlock) -> Option<&str> {
+    match chunk {
+        acp::ContentBlock::ResourceLink(resource_link) => Some(&resource_link.uri),
+        acp::ContentBlock::Resource(resource) => match &resource.resource {
+            acp::EmbeddedResourceResource::TextResourceContents(resource) => Some(&resource.uri),
+            acp::EmbeddedResourceResource::BlobResourceContents(resource) => Some(&resource.uri),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Collects the files that the given mention URIs point at. Mentions of symbols and
+/// selections count as mentions of their file, while directories and other kinds of
+/// mentions are ignored. Relative paths that can't be resolved against `root` are dropped.
+fn referenced_file_paths<'a>(
+    uris: impl IntoIterator<Item = &'a str>,
+    path_style: PathStyle,
+    root: Option<&Path>,
+) -> Vec<PathBuf> {
+    let mut seen = HashSet::default();
+    uris.into_iter()
+        .filter_map(|uri| match MentionUri::parse(uri, path_style).ok()? {
+            MentionUri::File { abs_path } | MentionUri::Symbol { abs_path, .. } => Some(abs_path),
+            MentionUri::Selection { abs_path, .. } => abs_path,
+            _ => None,
+        })
+        .filter_map(|path| {
+            if path.is_absolute() {
+                Some(path)
+            } else {
+                Some(root?.join(path))
+            }
+        })
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2631,6 +2707,48 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_referenced_file_paths() {
+        let file = |path: &str| {
+            MentionUri::File {
+                abs_path: PathBuf::from(path),
+            }
+            .to_uri()
+            .to_string()
+        };
+        let symbol = MentionUri::Symbol {
+            abs_path: PathBuf::from(path!("/root/src/lib.rs")),
+            name: "main".into(),
+            line_range: 0..=3,
+        }
+        .to_uri()
+        .to_string();
+        let directory = MentionUri::Directory {
+            abs_path: PathBuf::from(path!("/root/src/")),
+        }
+        .to_uri()
+        .to_string();
+        let uris = [
+            file(path!("/root/src/lib.rs")),
+            symbol,
+            directory,
+            file(path!("/root/README.md")),
+            "not a uri".to_string(),
+        ];
+
+        assert_eq!(
+            referenced_file_paths(
+                uris.iter().map(String::as_str),
+                PathStyle::local(),
+                Some(Path::new(path!("/root"))),
+            ),
+            [
+                PathBuf::from(path!("/root/src/lib.rs")),
+                PathBuf::from(path!("/root/README.md")),
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_terminal_output_buffered_before_created_renders(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -3595,6 +3713,89 @@ mod tests {
         assert_eq!(fs.files(), vec![Path::new(path!("/test/file-0"))]);
     }
 
+    #[gpui::test]
+    async fn test_last_user_message_prompt(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, None, cx).await;
+
+        let prompt_count = Arc::new(AtomicUsize::new(0));
+        let connection = Rc::new(FakeAgentConnection::new().on_user_message({
+            let prompt_count = prompt_count.clone();
+            move |request, thread, mut cx| {
+                let count = prompt_count.fetch_add(1, SeqCst);
+                async move {
+                    let acp::ContentBlock::Text(content) = &request.prompt[0] else {
+                        panic!("expected text content block");
+                    };
+                    let response = format!("{} #{count}", content.text.to_uppercase());
+                    thread.update(&mut cx, |thread, cx| {
+                        thread
+                            .handle_session_update(
+                                acp::SessionUpdate::AgentMessageChunk(acp::ContentChunk::new(
+                                    response.into(),
+                                )),
+                                cx,
+                            )
+                            .unwrap();
+                    })?;
+                    Ok(acp::PromptResponse::new(acp::StopReason::EndTurn))
+                }
+                .boxed_local()
+            }
+        }));
+        let thread = cx
+            .update(|cx| connection.new_thread(project, Path::new(path!("/test")), cx))
+            .await
+            .unwrap();
+
+        cx.update(|cx| thread.update(cx, |thread, cx| thread.send(vec!["Lorem".into()], cx)))
+            .await
+            .unwrap();
+        cx.update(|cx| thread.update(cx, |thread, cx| thread.send(vec!["ipsum".into()], cx)))
+            .await
+            .unwrap();
+
+        let (id, chunks) = thread
+            .read_with(cx, |thread, _| thread.last_user_message_prompt())
+            .unwrap();
+        assert!(
+            matches!(chunks.as_slice(), [acp::ContentBlock::Text(text)] if text.text == "ipsum")
+        );
+        thread
+            .update(cx, |thread, cx| thread.rewind(id, cx))
+            .await
+            .unwrap();
+        cx.update(|cx| thread.update(cx, |thread, cx| thread.send(chunks, cx)))
+            .await
+            .unwrap();
+        assert_eq!(prompt_count.load(SeqCst), 3);
+        thread.read_with(cx, |thread, cx| {
+            assert_eq!(
+                thread.to_markdown(cx),
+                indoc! {"
+                    ## User
+
+                    Lorem
+
+                    ## Assistant
+
+                    LOREM #0
+
+                    ## User
+
+                    ipsum
+
+                    ## Assistant
+
+                    IPSUM #2
+
+                "}
+            );
+        });
+    }
+
     #[gpui::test]
     async fn test_tool_result_refusal(cx: &mut TestAppContext) {
         use std::sync::atomic::AtomicUsize;
@@ -855,6 +855,34 @@ impl NativeAgent {
         });
     }
 
+    /// Closes the session `id` and persists `db_thread` in its place, so that opening the
+    /// session again picks up the replaced state. The thread keeps its session id.
+    pub fn replace_thread(
+        &mut self,
+        id: acp::SessionId,
+        db_thread: DbThread,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let pending_save = self.sessions.remove(&id).map(|session| {
+            session
+                .thread
+                .update(cx, |thread, cx| thread.cancel(cx))
+                .detach();
+            session.pending_save
+        });
+        let database_future = ThreadsDatabase::connect(cx);
+        let thread_store = self.thread_store.clone();
+        cx.spawn(async move |_, cx| {
+            if let Some(pending_save) = pending_save {
+                pending_save.await;
+            }
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.save_thread(id, db_thread).await?;
+            thread_store.update(cx, |store, cx| store.reload(cx));
+            Ok(())
+        })
+    }
+
     fn send_mcp_prompt(
         &self,
         message_id: UserMessageId,
@@ -965,6 +993,16 @@ impl NativeAgentConnection {
         self.0.update(cx, |this, cx| this.load_thread(id, cx))
     }
 
+    pub fn replace_thread(
+        &self,
+        id: acp::SessionId,
+        db_thread: DbThread,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        self.0
+            .update(cx, |this, cx| this.replace_thread(id, db_thread, cx))
+    }
+
     fn run_turn(
         &self,
         session_id: acp::SessionId,
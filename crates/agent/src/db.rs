@@ -31,6 +31,15 @@ pub struct DbThreadMetadata {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A labelled snapshot of a thread that it can later be restored to.
+#[derive(Debug, Clone)]
+pub struct DbThreadCheckpoint {
+    pub id: i64,
+    pub session_id: acp::SessionId,
+    pub label: SharedString,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DbThread {
     pub title: SharedString,
@@ -357,6 +366,18 @@ impl ThreadsDatabase {
         "})?()
         .map_err(|e| anyhow!("Failed to create threads table: {}", e))?;
 
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS thread_checkpoints (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                label TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                data_type TEXT NOT NULL,
+                data BLOB NOT NULL
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create thread checkpoints table: {}", e))?;
+
         let db = Self {
             executor,
             connection: Arc::new(Mutex::new(connection)),
@@ -365,11 +386,7 @@ impl ThreadsDatabase {
         Ok(db)
     }
 
-    fn save_thread_sync(
-        connection: &Arc<Mutex<Connection>>,
-        id: acp::SessionId,
-        thread: DbThread,
-    ) -> Result<()> {
+    fn serialize_thread(thread: DbThread) -> Result<(DataType, Vec<u8>)> {
         const COMPRESSION_LEVEL: i32 = 3;
 
         #[derive(Serialize)]
@@ -379,18 +396,35 @@ impl ThreadsDatabase {
             version: &'static str,
         }
 
-        let title = thread.title.to_string();
-        let updated_at = thread.updated_at.to_rfc3339();
         let json_data = serde_json::to_string(&SerializedThread {
             thread,
             version: DbThread::VERSION,
         })?;
+        let compressed = zstd::encode_all(json_data.as_bytes(), COMPRESSION_LEVEL)?;
+        Ok((DataType::Zstd, compressed))
+    }
 
-        let connection = connection.lock();
+    fn deserialize_thread(data_type: DataType, data: Vec<u8>) -> Result<DbThread> {
+        let json_data = match data_type {
+            DataType::Zstd => {
+                let decompressed = zstd::decode_all(&data[..])?;
+                String::from_utf8(decompressed)?
+            }
+            DataType::Json => String::from_utf8(data)?,
+        };
+        DbThread::from_json(json_data.as_bytes())
+    }
 
-        let compressed = zstd::encode_all(json_data.as_bytes(), COMPRESSION_LEVEL)?;
-        let data_type = DataType::Zstd;
-        let data = compressed;
+    fn save_thread_sync(
+        connection: &Arc<Mutex<Connection>>,
+        id: acp::SessionId,
+        thread: DbThread,
+    ) -> Result<()> {
+        let title = thread.title.to_string();
+        let updated_at = thread.updated_at.to_rfc3339();
+        let (data_type, data) = Self::serialize_thread(thread)?;
+
+        let connection = connection.lock();
 
         let mut insert = connection.exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>)>(indoc! {"
             INSERT OR REPLACE INTO threads (id, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?)
@@ -438,15 +472,7 @@ impl ThreadsDatabase {
 
             let rows = select(id.0)?;
             if let Some((data_type, data)) = rows.into_iter().next() {
-                let json_data = match data_type {
-                    DataType::Zstd => {
-                        let decompressed = zstd::decode_all(&data[..])?;
-                        String::from_utf8(decompressed)?
-                    }
-                    DataType::Json => String::from_utf8(data)?,
-                };
-                let thread = DbThread::from_json(json_data.as_bytes())?;
-                Ok(Some(thread))
+                Ok(Some(Self::deserialize_thread(data_type, data)?))
             } else {
                 Ok(None)
             }
@@ -466,29 +492,109 @@ impl ThreadsDatabase {
         self.executor.spawn(async move {
             let connection = connection.lock();
 
-            let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
-                DELETE FROM threads WHERE id = ?
-            "})?;
+            // The thread's checkpoints go with it, so none are left without their thread.
+            connection.with_savepoint("delete_thread", || {
+                let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
+                    DELETE FROM threads WHERE id = ?
+                "})?;
+                delete(id.0.clone())?;
+
+                let mut delete_checkpoints = connection.exec_bound::<Arc<str>>(indoc! {"
+                    DELETE FROM thread_checkpoints WHERE session_id = ?
+                "})?;
+                delete_checkpoints(id.0)
+            })
+        })
+    }
+
+    pub fn delete_threads(&self) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            connection.with_savepoint("delete_threads", || {
+                let mut delete = connection.exec_bound::<()>(indoc! {"
+                    DELETE FROM threads
+                "})?;
+                delete(())?;
+
+                let mut delete_checkpoints = connection.exec_bound::<()>(indoc! {"
+                    DELETE FROM thread_checkpoints
+                "})?;
+                delete_checkpoints(())
+            })
+        })
+    }
+
+    pub fn save_checkpoint(
+        &self,
+        session_id: acp::SessionId,
+        label: SharedString,
+        thread: DbThread,
+    ) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let (data_type, data) = Self::serialize_thread(thread)?;
+            let created_at = Utc::now().to_rfc3339();
 
-            delete(id.0)?;
+            let connection = connection.lock();
+            let mut insert = connection
+                .exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>)>(indoc! {"
+                    INSERT INTO thread_checkpoints (session_id, label, created_at, data_type, data)
+                    VALUES (?, ?, ?, ?, ?)
+                "})?;
+            insert((session_id.0, label.to_string(), created_at, data_type, data))?;
 
             Ok(())
         })
     }
 
-    pub fn delete_threads(&self) -> Task<Result<()>> {
+    /// Lists the checkpoints of a thread, most recent first.
+    pub fn list_checkpoints(
+        &self,
+        session_id: acp::SessionId,
+    ) -> Task<Result<Vec<DbThreadCheckpoint>>> {
         let connection = self.connection.clone();
 
         self.executor.spawn(async move {
             let connection = connection.lock();
+            let mut select =
+                connection.select_bound::<Arc<str>, (i64, String, String)>(indoc! {"
+                    SELECT id, label, created_at FROM thread_checkpoints
+                    WHERE session_id = ? ORDER BY id DESC
+                "})?;
+
+            let mut checkpoints = Vec::new();
+            for (id, label, created_at) in select(session_id.0.clone())? {
+                checkpoints.push(DbThreadCheckpoint {
+                    id,
+                    session_id: session_id.clone(),
+                    label: label.into(),
+                    created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+                });
+            }
 
-            let mut delete = connection.exec_bound::<()>(indoc! {"
-                DELETE FROM threads
-            "})?;
+            Ok(checkpoints)
+        })
+    }
 
-            delete(())?;
+    pub fn load_checkpoint(&self, id: i64) -> Task<Result<Option<DbThread>>> {
+        let connection = self.connection.clone();
 
-            Ok(())
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let mut select = connection.select_bound::<i64, (DataType, Vec<u8>)>(indoc! {"
+                SELECT data_type, data FROM thread_checkpoints WHERE id = ? LIMIT 1
+            "})?;
+
+            let rows = select(id)?;
+            if let Some((data_type, data)) = rows.into_iter().next() {
+                Ok(Some(Self::deserialize_thread(data_type, data)?))
+            } else {
+                Ok(None)
+            }
         })
     }
 }
@@ -618,4 +724,62 @@ mod tests {
             Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()
         );
     }
+
+    #[gpui::test]
+    async fn test_checkpoints_are_scoped_to_their_thread(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let thread_id = session_id("thread-a");
+        let other_id = session_id("thread-b");
+        let updated_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        database
+            .save_checkpoint(
+                thread_id.clone(),
+                "First".into(),
+                make_thread("Before", updated_at),
+            )
+            .await
+            .unwrap();
+        database
+            .save_checkpoint(
+                thread_id.clone(),
+                "Second".into(),
+                make_thread("After", updated_at),
+            )
+            .await
+            .unwrap();
+        database
+            .save_checkpoint(
+                other_id.clone(),
+                "Other".into(),
+                make_thread("Other", updated_at),
+            )
+            .await
+            .unwrap();
+
+        let checkpoints = database.list_checkpoints(thread_id.clone()).await.unwrap();
+        let labels = checkpoints
+            .iter()
+            .map(|checkpoint| checkpoint.label.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["Second", "First"]);
+
+        let restored = database
+            .load_checkpoint(checkpoints[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.title.as_ref(), "Before");
+
+        database.delete_thread(thread_id.clone()).await.unwrap();
+        assert!(
+            database
+                .list_checkpoints(thread_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(database.list_checkpoints(other_id).await.unwrap().len(), 1);
+    }
 }
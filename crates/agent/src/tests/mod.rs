@@ -3568,6 +3568,113 @@ async fn test_tokens_before_message(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_retry_last_user_message_with_different_model(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let message_id = UserMessageId::new();
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(message_id.clone(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Poor response");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let retry_model = Arc::new(FakeLanguageModel::default());
+    thread
+        .update(cx, |thread, cx| {
+            thread.truncate(message_id.clone(), cx)?;
+            thread.set_model(retry_model.clone(), cx);
+            thread.send(message_id, ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(fake_model.pending_completions().len(), 0);
+    let pending = retry_model.pending_completions();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(
+        pending[0].messages.last().unwrap().content,
+        vec!["Hello".into()]
+    );
+    retry_model.send_last_completion_stream_text_chunk("Better response");
+    retry_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread.to_markdown(),
+            indoc! {"
+                ## User
+
+                Hello
+
+                ## Assistant
+
+                Better response
+            "}
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_to_db_before_last_user_message(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    for (message, response) in [("Message 1", "Response 1"), ("Message 2", "Response 2")] {
+        thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), [message], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk(response);
+        fake_model.end_last_completion_stream();
+        cx.run_until_parked();
+    }
+
+    let db_thread = thread
+        .read_with(cx, |thread, cx| {
+            thread.to_db_before_last_user_message(Some(model.clone()), cx)
+        })
+        .await;
+    assert_eq!(db_thread.messages.len(), 2);
+    assert!(matches!(db_thread.messages[0], Message::User(_)));
+    assert!(matches!(db_thread.messages[1], Message::Agent(_)));
+    let db_model = db_thread.model.expect("fork should be pinned to a model");
+    assert_eq!(db_model.provider, model.provider_id().to_string());
+    assert_eq!(db_model.model, model.id().0.to_string());
+
+    // The original thread is left untouched.
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread.to_markdown(),
+            indoc! {"
+                ## User
+
+                Message 1
+
+                ## Assistant
+
+                Response 1
+
+                ## User
+
+                Message 2
+
+                ## Assistant
+
+                Response 2
+            "}
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_tokens_before_message_after_truncate(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
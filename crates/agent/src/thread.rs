@@ -1158,6 +1158,38 @@ impl Thread {
         })
     }
 
+    /// Serializes this thread without its last user message and anything after it,
+    /// so that message can be sent again in a fork of the thread. The fork is pinned
+    /// to `model` when one is given.
+    pub fn to_db_before_last_user_message(
+        &self,
+        model: Option<Arc<dyn LanguageModel>>,
+        cx: &App,
+    ) -> Task<DbThread> {
+        let last_user_message_ix = self
+            .messages
+            .iter()
+            .rposition(|message| matches!(message, Message::User(_)));
+        let db_thread = self.to_db(cx);
+        cx.background_spawn(async move {
+            let mut db_thread = db_thread.await;
+            if let Some(ix) = last_user_message_ix {
+                for message in db_thread.messages.drain(ix..) {
+                    if let Message::User(message) = message {
+                        db_thread.request_token_usage.remove(&message.id);
+                    }
+                }
+            }
+            if let Some(model) = model {
+                db_thread.model = Some(DbLanguageModel {
+                    provider: model.provider_id().to_string(),
+                    model: model.id().0.to_string(),
+                });
+            }
+            db_thread
+        })
+    }
+
     /// Create a snapshot of the current project state including git information and unsaved buffers.
     fn project_snapshot(
         project: Entity<Project>,
@@ -1,9 +1,67 @@
-use crate::{DbThread, DbThreadMetadata, ThreadsDatabase};
+use crate::{DbThread, DbThreadCheckpoint, DbThreadMetadata, ThreadsDatabase};
 use agent_client_protocol as acp;
 use anyhow::{Result, anyhow};
-use gpui::{App, Context, Entity, Global, Task, prelude::*};
+use collections::HashSet;
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{App, Context, Entity, Global, SharedString, Task, prelude::*};
 use project::Project;
-use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, rc::Rc, sync::Arc};
+
+/// Extension of the files written by [`ThreadStore::export_threads`].
+pub const THREAD_ARCHIVE_EXTENSION: &str = "zedthread";
+
+#[derive(Serialize)]
+struct ArchivedThread<'a> {
+    id: &'a str,
+    version: &'static str,
+    #[serde(flatten)]
+    thread: DbThread,
+}
+
+#[derive(Deserialize)]
+struct ArchivedThreadId {
+    id: Arc<str>,
+}
+
+/// The outcome of [`ThreadStore::import_threads`]. Threads whose session id is
+/// already in the store are skipped rather than overwritten, and files that can't
+/// be parsed as threads are counted as failed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ThreadImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// A path-safe file name for the thread `id` that isn't in `used_names` yet, which it
+/// is added to. Session ids that only differ in the characters replaced to make them
+/// path-safe get a numeric suffix rather than overwriting each other's file.
+fn archive_file_name(id: &acp::SessionId, used_names: &mut HashSet<String>) -> String {
+    let name =
+        id.0.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+    let mut file_name = format!("{name}.{THREAD_ARCHIVE_EXTENSION}");
+    let mut suffix = 2;
+    while !used_names.insert(file_name.clone()) {
+        file_name = format!("{name}-{suffix}.{THREAD_ARCHIVE_EXTENSION}");
+        suffix += 1;
+    }
+    file_name
+}
+
+fn parse_archived_thread(json: &[u8]) -> Result<(acp::SessionId, DbThread)> {
+    let ArchivedThreadId { id } = serde_json::from_slice(json)?;
+    Ok((acp::SessionId::new(id), DbThread::from_json(json)?))
+}
 
 struct GlobalThreadStore(Entity<ThreadStore>);
 
@@ -110,6 +168,135 @@ impl ThreadStore {
         })
     }
 
+    /// Saves `thread` as a checkpoint of the thread with session `id`, which it can
+    /// later be restored to.
+    pub fn save_checkpoint(
+        &self,
+        id: acp::SessionId,
+        label: SharedString,
+        thread: DbThread,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.save_checkpoint(id, label, thread).await
+        })
+    }
+
+    /// The checkpoints of the thread with session `id`, most recent first.
+    pub fn checkpoints(
+        &self,
+        id: acp::SessionId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DbThreadCheckpoint>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.list_checkpoints(id).await
+        })
+    }
+
+    pub fn load_checkpoint(
+        &self,
+        checkpoint_id: i64,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<DbThread>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.load_checkpoint(checkpoint_id).await
+        })
+    }
+
+    /// Writes every thread into `directory` as one `.zedthread` file per thread,
+    /// loading them one at a time. Resolves to the number of exported threads.
+    pub fn export_threads(
+        &self,
+        fs: Arc<dyn Fs>,
+        directory: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<usize>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            fs.create_dir(&directory).await?;
+            let mut exported = 0;
+            let mut file_names = HashSet::default();
+            for metadata in database.list_threads().await? {
+                let Some(thread) = database.load_thread(metadata.id.clone()).await? else {
+                    continue;
+                };
+                let json = serde_json::to_string_pretty(&ArchivedThread {
+                    id: &metadata.id.0,
+                    version: DbThread::VERSION,
+                    thread,
+                })?;
+                let file_name = archive_file_name(&metadata.id, &mut file_names);
+                fs.atomic_write(directory.join(file_name), json).await?;
+                exported += 1;
+            }
+            Ok(exported)
+        })
+    }
+
+    /// Saves the threads exported into `directory` by [`Self::export_threads`],
+    /// skipping the ones whose session id is already in the store and the files
+    /// that can't be parsed. The store is reloaded even if importing fails midway,
+    /// so that the threads imported until then show up.
+    pub fn import_threads(
+        &mut self,
+        fs: Arc<dyn Fs>,
+        directory: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ThreadImportSummary>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let database = database_future.await.map_err(|err| anyhow!(err))?;
+                let mut known_ids = database
+                    .list_threads()
+                    .await?
+                    .into_iter()
+                    .map(|metadata| metadata.id)
+                    .collect::<HashSet<_>>();
+                let mut summary = ThreadImportSummary::default();
+                let mut paths = fs.read_dir(&directory).await?;
+                while let Some(path) = paths.next().await {
+                    let path = path?;
+                    if path
+                        .extension()
+                        .is_none_or(|extension| extension != THREAD_ARCHIVE_EXTENSION)
+                    {
+                        continue;
+                    }
+                    let parsed = match fs.load_bytes(&path).await {
+                        Ok(json) => parse_archived_thread(&json),
+                        Err(error) => Err(error),
+                    };
+                    let (id, thread) = match parsed {
+                        Ok(parsed) => parsed,
+                        Err(error) => {
+                            log::error!("Failed to import thread {}: {error:#}", path.display());
+                            summary.failed += 1;
+                            continue;
+                        }
+                    };
+                    if !known_ids.insert(id.clone()) {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    database.save_thread(id, thread).await?;
+                    summary.imported += 1;
+                }
+                anyhow::Ok(summary)
+            }
+            .await;
+            this.update(cx, |this, cx| this.reload(cx))?;
+            result
+        })
+    }
+
     pub fn reload(&self, cx: &mut Context<Self>) {
         let database_connection = ThreadsDatabase::connect(cx);
         cx.spawn(async move |this, cx| {
@@ -298,4 +485,112 @@ mod tests {
         assert_eq!(entries[0].id, first_id);
         assert_eq!(entries[1].id, second_id);
     }
+
+    #[gpui::test]
+    async fn test_export_and_import_threads(cx: &mut TestAppContext) {
+        let fs = fs::FakeFs::new(cx.executor());
+        let archive = PathBuf::from(util::path!("/backup/threads"));
+        let thread_store = cx.new(|cx| ThreadStore::new(cx));
+        cx.run_until_parked();
+
+        let first_id = session_id("thread-a");
+        let second_id = session_id("thread-b");
+        for (id, title, day) in [(&first_id, "Thread A", 1), (&second_id, "Thread B", 2)] {
+            let thread = make_thread(title, Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap());
+            thread_store
+                .update(cx, |store, cx| store.save_thread(id.clone(), thread, cx))
+                .await
+                .unwrap();
+        }
+        cx.run_until_parked();
+
+        let exported = thread_store
+            .update(cx, |store, cx| {
+                store.export_threads(fs.clone(), archive.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(exported, 2);
+        assert!(fs.is_file(&archive.join("thread-a.zedthread")).await);
+        assert!(fs.is_file(&archive.join("thread-b.zedthread")).await);
+
+        // Everything is already present, so importing the archive back is a no-op.
+        let summary = thread_store
+            .update(cx, |store, cx| {
+                store.import_threads(fs.clone(), archive.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            ThreadImportSummary {
+                imported: 0,
+                skipped: 2,
+                failed: 0,
+            }
+        );
+
+        // Files that aren't threads are counted rather than failing the whole import.
+        fs.insert_file(archive.join("corrupt.zedthread"), b"{ not json".to_vec())
+            .await;
+        thread_store
+            .update(cx, |store, cx| store.delete_thread(first_id.clone(), cx))
+            .await
+            .unwrap();
+        let summary = thread_store
+            .update(cx, |store, cx| {
+                store.import_threads(fs.clone(), archive.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            ThreadImportSummary {
+                imported: 1,
+                skipped: 1,
+                failed: 1,
+            }
+        );
+        cx.run_until_parked();
+
+        let entries: Vec<_> = thread_store.read_with(cx, |store, _cx| store.entries().collect());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].id, first_id);
+        assert_eq!(entries[1].title.as_ref(), "Thread A");
+    }
+
+    #[test]
+    fn test_archive_file_name_is_path_safe() {
+        let mut used_names = HashSet::default();
+        assert_eq!(
+            archive_file_name(&session_id("1f0c-ab_9"), &mut used_names),
+            "1f0c-ab_9.zedthread"
+        );
+        assert_eq!(
+            archive_file_name(&session_id("../etc/passwd"), &mut used_names),
+            "___etc_passwd.zedthread"
+        );
+    }
+
+    #[test]
+    fn test_archive_file_name_avoids_collisions() {
+        let mut used_names = HashSet::default();
+        assert_eq!(
+            archive_file_name(&session_id("a/b"), &mut used_names),
+            "a_b.zedthread"
+        );
+        assert_eq!(
+            archive_file_name(&session_id("a:b"), &mut used_names),
+            "a_b-2.zedthread"
+        );
+        assert_eq!(
+            archive_file_name(&session_id("a_b"), &mut used_names),
+            "a_b-3.zedthread"
+        );
+        // A session id that looks like a suffixed name doesn't take over its file either.
+        assert_eq!(
+            archive_file_name(&session_id("a_b-2"), &mut used_names),
+            "a_b-2-2.zedthread"
+        );
+    }
 }
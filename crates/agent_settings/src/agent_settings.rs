@@ -10,8 +10,8 @@ use project::DisableAiSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{
-    DefaultAgentView, DockPosition, DockSide, LanguageModelParameters, LanguageModelSelection,
-    NotifyWhenAgentWaiting, RegisterSetting, Settings, ToolPermissionMode,
+    AgentUiDensity, DefaultAgentView, DockPosition, DockSide, LanguageModelParameters,
+    LanguageModelSelection, NotifyWhenAgentWaiting, RegisterSetting, Settings, ToolPermissionMode,
 };
 
 pub use crate::agent_profile::*;
@@ -50,6 +50,13 @@ pub struct AgentSettings {
     pub use_modifier_to_send: bool,
     pub message_editor_min_lines: usize,
     pub show_turn_stats: bool,
+    pub max_detached_threads: usize,
+    pub hide_single_tab_bar: bool,
+    pub show_text_thread_tabs: bool,
+    pub auto_index_untitled_tabs: bool,
+    pub context_warning_threshold: f32,
+    pub context_summarize_threshold: f32,
+    pub ui_density: AgentUiDensity,
     pub tool_permissions: ToolPermissions,
 }
 
@@ -261,6 +268,13 @@ impl Settings for AgentSettings {
             use_modifier_to_send: agent.use_modifier_to_send.unwrap(),
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
             show_turn_stats: agent.show_turn_stats.unwrap(),
+            max_detached_threads: agent.max_detached_threads.unwrap(),
+            hide_single_tab_bar: agent.hide_single_tab_bar.unwrap(),
+            show_text_thread_tabs: agent.show_text_thread_tabs.unwrap(),
+            auto_index_untitled_tabs: agent.auto_index_untitled_tabs.unwrap(),
+            context_warning_threshold: agent.context_warning_threshold.unwrap(),
+            context_summarize_threshold: agent.context_summarize_threshold.unwrap(),
+            ui_density: agent.ui_density.unwrap(),
             tool_permissions: compile_tool_permissions(agent.tool_permissions),
         }
     }
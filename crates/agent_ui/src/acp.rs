@@ -11,4 +11,7 @@ pub use mode_selector::ModeSelector;
 pub use model_selector::AcpModelSelector;
 pub use model_selector_popover::AcpModelSelectorPopover;
 pub use thread_history::*;
-pub use thread_view::AcpServerView;
+pub use thread_view::{AcpServerView, AcpServerViewEvent};
+
+#[cfg(test)]
+pub(crate) use thread_view::tests::{StubAgentServer, init_test};
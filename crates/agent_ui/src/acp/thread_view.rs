@@ -6,7 +6,7 @@ use acp_thread::{
 };
 use acp_thread::{AgentConnection, Plan};
 use action_log::{ActionLog, ActionLogTelemetry};
-use agent::{NativeAgentServer, NativeAgentSessionList, SharedThread, ThreadStore};
+use agent::{NativeAgentSessionList, SharedThread, ThreadStore};
 use agent_client_protocol::{self as acp, PromptCapabilities};
 use agent_servers::{AgentServer, AgentServerDelegate};
 use agent_settings::{AgentProfileId, AgentSettings};
@@ -29,18 +29,18 @@ use fs::Fs;
 use futures::FutureExt as _;
 use gpui::{
     Action, Animation, AnimationExt, AnyView, App, ClickEvent, ClipboardItem, CursorStyle,
-    ElementId, Empty, Entity, FocusHandle, Focusable, Hsla, ListOffset, ListState, ObjectFit,
-    PlatformDisplay, ScrollHandle, SharedString, Subscription, Task, TextStyle, WeakEntity, Window,
-    WindowHandle, div, ease_in_out, img, linear_color_stop, linear_gradient, list, point,
-    pulsating_between,
+    ElementId, Empty, Entity, EventEmitter, FocusHandle, Focusable, Hsla, ListOffset, ListState,
+    ObjectFit, PlatformDisplay, ScrollHandle, SharedString, Subscription, Task, TextStyle,
+    WeakEntity, Window, WindowHandle, div, ease_in_out, img, linear_color_stop, linear_gradient,
+    list, point, pulsating_between,
 };
 use language::Buffer;
-use language_model::LanguageModelRegistry;
+use language_model::{LanguageModel, LanguageModelRegistry};
 use markdown::{Markdown, MarkdownElement, MarkdownFont, MarkdownStyle};
 use project::{AgentServerStore, ExternalAgentServerName, Project, ProjectEntryId};
 use prompt_store::{PromptId, PromptStore};
 use rope::Point;
-use settings::{NotifyWhenAgentWaiting, Settings as _, SettingsStore};
+use settings::{AgentUiDensity, NotifyWhenAgentWaiting, Settings as _, SettingsStore};
 use std::cell::RefCell;
 use std::path::Path;
 use std::sync::Arc;
@@ -50,7 +50,7 @@ use terminal_view::terminal_panel::TerminalPanel;
 use text::{Anchor, ToPoint as _};
 use theme::AgentFontSize;
 use ui::{
-    Callout, CommonAnimationExt, ContextMenu, ContextMenuEntry, CopyButton, DecoratedIcon,
+    Callout, Chip, CommonAnimationExt, ContextMenu, ContextMenuEntry, CopyButton, DecoratedIcon,
     DiffStat, Disclosure, Divider, DividerColor, IconButtonShape, IconDecoration,
     IconDecorationKind, KeyBinding, PopoverMenu, PopoverMenuHandle, SpinnerLabel, TintColor,
     Tooltip, WithScrollbar, prelude::*, right_click_menu,
@@ -74,9 +74,10 @@ use crate::ui::{AgentNotification, AgentNotificationEvent};
 use crate::{
     AgentDiffPane, AgentPanel, AllowAlways, AllowOnce, AuthorizeToolCall, ClearMessageQueue,
     CycleFavoriteModels, CycleModeSelector, EditFirstQueuedMessage, ExpandMessageEditor,
-    ExternalAgentInitialContent, Follow, KeepAll, NewThread, OpenAddContextMenu, OpenAgentDiff,
-    OpenHistory, RejectAll, RejectOnce, RemoveFirstQueuedMessage, SelectPermissionGranularity,
-    SendImmediately, SendNextQueuedMessage, ToggleProfileSelector, ToggleThinkingMode,
+    ExternalAgent, ExternalAgentInitialContent, Follow, KeepAll, NewThread, OpenAddContextMenu,
+    OpenAgentDiff, OpenHistory, RejectAll, RejectOnce, RemoveFirstQueuedMessage, RetryWithModel,
+    SelectPermissionGranularity, SendImmediately, SendNextQueuedMessage, ToggleProfileSelector,
+    ToggleThinkingMode,
 };
 
 const STOPWATCH_THRESHOLD: Duration = Duration::from_secs(30);
@@ -202,9 +203,27 @@ impl AcpServerView {
 enum ServerState {
     Loading(Entity<LoadingView>),
     LoadError(LoadError),
+    /// The session from the history failed to load, like after the agent was updated or
+    /// pruned its sessions.
+    ResumeFailed {
+        session: AgentSessionInfo,
+        error: SharedString,
+    },
     Connected(ConnectedServerState),
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum AcpServerViewEvent {
+    /// Resuming the session from the history failed, and the view offers ways to recover.
+    ResumeFailed(acp::SessionId),
+    /// A new thread replaced the session that failed to resume.
+    StartedFreshThread,
+    /// The session that failed to resume was deleted from the history.
+    HistoryEntryDeleted(acp::SessionId),
+}
+
+impl EventEmitter<AcpServerViewEvent> for AcpServerView {}
+
 // current -> Entity
 // hashmap of threads, current becomes session_id
 pub struct ConnectedServerState {
@@ -351,12 +370,10 @@ impl AcpServerView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> ServerState {
-        if project.read(cx).is_via_collab()
-            && agent.clone().downcast::<NativeAgentServer>().is_none()
+        if let Err(reason) =
+            ExternalAgent::for_server(&agent).availability(project.read(cx).is_via_collab())
         {
-            return ServerState::LoadError(LoadError::Other(
-                "External agents are not yet supported in shared projects.".into(),
-            ));
+            return ServerState::LoadError(LoadError::Other(reason));
         }
         let mut worktrees = project.read(cx).visible_worktrees(cx).collect::<Vec<_>>();
         // Pick the first non-single-file worktree for the root directory if there are any,
@@ -615,6 +632,10 @@ impl AcpServerView {
                             .agent_display_name(&ExternalAgentServerName(agent.name()))
                             .unwrap_or_else(|| agent.name());
 
+                        let send_initial_content = matches!(
+                            initial_content,
+                            Some(ExternalAgentInitialContent::SendPrompt(_))
+                        );
                         let weak = cx.weak_entity();
                         let current = cx.new(|cx| {
                             AcpThreadView::new(
@@ -646,6 +667,10 @@ impl AcpServerView {
                             )
                         });
 
+                        if send_initial_content {
+                            current.update(cx, |current, cx| current.send(window, cx));
+                        }
+
                         if this.focus_handle.contains_focused(window, cx) {
                             current
                                 .read(cx)
@@ -662,9 +687,10 @@ impl AcpServerView {
 
                         cx.notify();
                     }
-                    Err(err) => {
-                        this.handle_load_error(err, window, cx);
-                    }
+                    Err(err) => match resume_thread.clone() {
+                        Some(session) => this.handle_resume_error(session, err, &connection, cx),
+                        None => this.handle_load_error(err, window, cx),
+                    },
                 };
             })
             .log_err();
@@ -806,6 +832,59 @@ impl AcpServerView {
         cx.notify();
     }
 
+    fn handle_resume_error(
+        &mut self,
+        session: AgentSessionInfo,
+        err: anyhow::Error,
+        connection: &Rc<dyn AgentConnection>,
+        cx: &mut Context<Self>,
+    ) {
+        log::error!("Failed to resume session {}: {err:#}", session.session_id);
+        // Deleting the entry goes through the session list of this agent, which the history
+        // may not show yet.
+        if connection.supports_session_history(cx) {
+            let session_list = connection.session_list(cx);
+            self.history.update(cx, |history, cx| {
+                history.set_session_list(session_list, cx);
+            });
+        }
+        let session_id = session.session_id.clone();
+        self.server_state = ServerState::ResumeFailed {
+            session,
+            error: format!("{err:#}").into(),
+        };
+        cx.emit(AcpServerViewEvent::ResumeFailed(session_id));
+        cx.notify();
+    }
+
+    /// Starts a new thread with the same agent in place of the session that failed to resume.
+    pub(crate) fn start_fresh_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !matches!(self.server_state, ServerState::ResumeFailed { .. }) {
+            return;
+        }
+        // Without an active thread, there's no session to resume.
+        self.reset(window, cx);
+        cx.emit(AcpServerViewEvent::StartedFreshThread);
+    }
+
+    /// Deletes the session that failed to resume from the history.
+    pub(crate) fn delete_unresumable_session(&mut self, cx: &mut Context<Self>) {
+        let ServerState::ResumeFailed { session, .. } = &self.server_state else {
+            return;
+        };
+        let session_id = session.session_id.clone();
+        let task = self
+            .history
+            .update(cx, |history, cx| history.delete_session(&session_id, cx));
+        cx.spawn(async move |this, cx| {
+            task.await?;
+            this.update(cx, |_, cx| {
+                cx.emit(AcpServerViewEvent::HistoryEntryDeleted(session_id));
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn handle_agent_servers_updated(
         &mut self,
         _agent_server_store: &Entity<project::AgentServerStore>,
@@ -817,7 +896,7 @@ impl AcpServerView {
         // when agent.connect() fails during loading), retry loading the thread.
         // This handles the case where a thread is restored before authentication completes.
         let should_retry = match &self.server_state {
-            ServerState::Loading(_) => false,
+            ServerState::Loading(_) | ServerState::ResumeFailed { .. } => false,
             ServerState::LoadError(_) => true,
             ServerState::Connected(connected) => {
                 connected.auth_state.is_ok() && connected.has_thread_error(cx)
@@ -850,6 +929,7 @@ impl AcpServerView {
                 LoadError::Exited { .. } => format!("{} Exited", self.agent.name()).into(),
                 LoadError::Other(_) => format!("Error Loading {}", self.agent.name()).into(),
             },
+            ServerState::ResumeFailed { .. } => "Session Not Resumed".into(),
         }
     }
 
@@ -1668,6 +1748,36 @@ impl AcpServerView {
             .into_any_element()
     }
 
+    fn render_resume_failed(&self, error: &SharedString, cx: &mut Context<Self>) -> AnyElement {
+        let supports_delete = self.history.read(cx).supports_delete();
+
+        Callout::new()
+            .severity(Severity::Error)
+            .icon(IconName::XCircleFilled)
+            .title("This session could not be resumed")
+            .description(error.clone())
+            .actions_slot(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("start-fresh-thread", "Start Fresh Thread")
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.start_fresh_thread(window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("delete-history-entry", "Delete History Entry")
+                            .label_size(LabelSize::Small)
+                            .disabled(!supports_delete)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.delete_unresumable_session(cx);
+                            })),
+                    ),
+            )
+            .into_any_element()
+    }
+
     fn render_unsupported(
         &self,
         path: &SharedString,
@@ -2168,6 +2278,19 @@ fn loading_contents_spinner(size: IconSize) -> AnyElement {
         .into_any_element()
 }
 
+/// The size of the message editor's icon buttons, which shrink in the compact density.
+fn message_editor_icon_size(cx: &App) -> IconSize {
+    match AgentSettings::get_global(cx).ui_density {
+        AgentUiDensity::Comfortable => IconSize::Small,
+        AgentUiDensity::Compact => IconSize::XSmall,
+    }
+}
+
+/// The label of the pill the attached context collapses into in the compact density.
+fn context_pill_label(mention_count: usize) -> Option<SharedString> {
+    (mention_count > 0).then(|| format!("+{mention_count} context").into())
+}
+
 fn placeholder_text(agent_name: &str, has_commands: bool) -> String {
     if agent_name == "Zed Agent" {
         format!("Message the {} — @ to include context", agent_name)
@@ -2235,6 +2358,13 @@ impl Render for AcpServerView {
                     .justify_end()
                     .child(self.render_load_error(e, window, cx))
                     .into_any(),
+                ServerState::ResumeFailed { error, .. } => v_flex()
+                    .flex_1()
+                    .size_full()
+                    .items_center()
+                    .justify_end()
+                    .child(self.render_resume_failed(error, cx))
+                    .into_any(),
                 ServerState::Connected(ConnectedServerState {
                     connection,
                     auth_state:
@@ -2308,6 +2438,53 @@ pub(crate) mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_context_usage_level_thresholds() {
+        let level = |used_tokens: u64, max_tokens: u64| {
+            let usage = acp_thread::TokenUsage {
+                max_tokens,
+                used_tokens,
+                input_tokens: used_tokens,
+                output_tokens: 0,
+            };
+            ContextUsageLevel::for_usage(&usage, 0.8, 0.95)
+        };
+        assert_eq!(level(500, 1000), ContextUsageLevel::Normal);
+        assert_eq!(level(800, 1000), ContextUsageLevel::Warning);
+        assert_eq!(level(950, 1000), ContextUsageLevel::Summarize);
+        assert_eq!(level(1000, 1000), ContextUsageLevel::Exceeded);
+        // Without a model, the maximum is unknown.
+        assert_eq!(level(1000, 0), ContextUsageLevel::Normal);
+    }
+
+    #[test]
+    fn test_context_usage_callout_dismissal() {
+        use ContextUsageLevel::*;
+
+        assert!(!Normal.shows_callout(None));
+        assert!(!Warning.shows_callout(None));
+        assert!(Summarize.shows_callout(None));
+        assert!(Exceeded.shows_callout(None));
+
+        // Dismissing hides the callout until the usage gets worse.
+        let dismissed_at = Some(Summarize);
+        assert!(!Summarize.shows_callout(dismissed_at));
+        assert!(Exceeded.shows_callout(dismissed_at));
+        assert!(!Exceeded.shows_callout(Some(Exceeded)));
+
+        // The dismissal is kept while the callout would show, and forgotten otherwise.
+        assert_eq!(Exceeded.keep_dismissal(dismissed_at), dismissed_at);
+        assert_eq!(Warning.keep_dismissal(dismissed_at), None);
+        assert!(Summarize.shows_callout(Warning.keep_dismissal(dismissed_at)));
+    }
+
+    #[test]
+    fn test_context_pill_label() {
+        assert_eq!(context_pill_label(0), None);
+        assert_eq!(context_pill_label(1), Some("+1 context".into()));
+        assert_eq!(context_pill_label(12), Some("+12 context".into()));
+    }
+
     #[gpui::test]
     async fn test_drop(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2367,6 +2544,45 @@ pub(crate) mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_compact_context_chips_collapse_after_send(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let (thread_view, cx) = setup_thread_view(StubAgentServer::default_response(), cx).await;
+
+        cx.update(|_window, cx| {
+            assert_eq!(message_editor_icon_size(cx), IconSize::Small);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.agent.get_or_insert_default().ui_density =
+                        Some(AgentUiDensity::Compact);
+                });
+            });
+            assert_eq!(message_editor_icon_size(cx), IconSize::XSmall);
+        });
+
+        let active_thread = active_thread(&thread_view, cx);
+        active_thread.update(cx, |view, cx| {
+            assert!(!view.context_chips_expanded);
+            view.toggle_context_chips(cx);
+            assert!(view.context_chips_expanded);
+            view.toggle_context_chips(cx);
+            assert!(!view.context_chips_expanded);
+            view.toggle_context_chips(cx);
+        });
+
+        let message_editor = message_editor(&thread_view, cx);
+        message_editor.update_in(cx, |editor, window, cx| {
+            editor.set_text("Hello", window, cx);
+        });
+        active_thread.update_in(cx, |view, window, cx| view.send(window, cx));
+        cx.run_until_parked();
+
+        active_thread.read_with(cx, |view, _cx| {
+            assert!(!view.context_chips_expanded);
+        });
+    }
+
     #[gpui::test]
     async fn test_recent_history_refreshes_when_history_cache_updated(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2480,6 +2696,85 @@ pub(crate) mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_failed_resume_offers_recovery(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let session_id = SessionId::new("pruned-session");
+        let connection = FailedResumeAgentConnection::default();
+        let deleted_sessions = connection.deleted_sessions.clone();
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let thread_store = cx.update(|_window, cx| cx.new(|cx| ThreadStore::new(cx)));
+        let history = cx.update(|window, cx| cx.new(|cx| AcpThreadHistory::new(None, window, cx)));
+
+        let thread_view = cx.update(|window, cx| {
+            cx.new(|cx| {
+                AcpServerView::new(
+                    Rc::new(StubAgentServer::new(connection)),
+                    Some(AgentSessionInfo::new(session_id.clone())),
+                    None,
+                    workspace.downgrade(),
+                    project,
+                    Some(thread_store),
+                    None,
+                    history,
+                    window,
+                    cx,
+                )
+            })
+        });
+        let events = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|_window, cx| {
+            let events = events.clone();
+            cx.subscribe(&thread_view, move |_, event: &AcpServerViewEvent, _| {
+                events.borrow_mut().push(event.clone());
+            })
+            .detach();
+        });
+        cx.run_until_parked();
+
+        // Instead of an empty thread, the view shows that the session couldn't be resumed.
+        thread_view.read_with(cx, |view, cx| {
+            assert!(view.as_active_thread().is_none());
+            assert!(matches!(
+                &view.server_state,
+                ServerState::ResumeFailed { session, .. } if session.session_id == session_id
+            ));
+            assert_eq!(view.title(cx), "Session Not Resumed");
+            assert!(view.history.read(cx).supports_delete());
+        });
+        assert_eq!(
+            events.borrow().as_slice(),
+            [AcpServerViewEvent::ResumeFailed(session_id.clone())]
+        );
+
+        thread_view.update(cx, |view, cx| view.delete_unresumable_session(cx));
+        cx.run_until_parked();
+        assert_eq!(
+            deleted_sessions.lock().unwrap().as_slice(),
+            [session_id.clone()]
+        );
+        assert_eq!(
+            events.borrow().last(),
+            Some(&AcpServerViewEvent::HistoryEntryDeleted(session_id.clone()))
+        );
+
+        thread_view.update_in(cx, |view, window, cx| view.start_fresh_thread(window, cx));
+        cx.run_until_parked();
+        thread_view.read_with(cx, |view, cx| {
+            let thread = view.as_active_thread().unwrap().read(cx).thread.clone();
+            assert_eq!(thread.read(cx).session_id(), &SessionId::new("new-session"));
+        });
+        assert_eq!(
+            events.borrow().last(),
+            Some(&AcpServerViewEvent::StartedFreshThread)
+        );
+    }
+
     #[gpui::test]
     async fn test_refusal_handling(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2766,12 +3061,12 @@ pub(crate) mod tests {
         }
     }
 
-    struct StubAgentServer<C> {
+    pub(crate) struct StubAgentServer<C> {
         connection: C,
     }
 
     impl<C> StubAgentServer<C> {
-        fn new(connection: C) -> Self {
+        pub(crate) fn new(connection: C) -> Self {
             Self { connection }
         }
     }
@@ -2929,6 +3224,117 @@ pub(crate) mod tests {
         }
     }
 
+    /// Simulates an agent that no longer has the sessions of its history, like after an
+    /// update or after pruning them.
+    #[derive(Clone, Default)]
+    struct FailedResumeAgentConnection {
+        deleted_sessions: Arc<std::sync::Mutex<Vec<SessionId>>>,
+    }
+
+    impl AgentConnection for FailedResumeAgentConnection {
+        fn telemetry_id(&self) -> SharedString {
+            "failed-resume".into()
+        }
+
+        fn new_thread(
+            self: Rc<Self>,
+            project: Entity<Project>,
+            _cwd: &Path,
+            cx: &mut gpui::App,
+        ) -> Task<gpui::Result<Entity<AcpThread>>> {
+            let action_log = cx.new(|_| ActionLog::new(project.clone()));
+            let thread = cx.new(|cx| {
+                AcpThread::new(
+                    "FailedResumeAgentConnection",
+                    self.clone(),
+                    project,
+                    action_log,
+                    SessionId::new("new-session"),
+                    watch::Receiver::constant(acp::PromptCapabilities::new()),
+                    cx,
+                )
+            });
+            Task::ready(Ok(thread))
+        }
+
+        fn supports_load_session(&self, _cx: &App) -> bool {
+            true
+        }
+
+        fn load_session(
+            self: Rc<Self>,
+            session: AgentSessionInfo,
+            _project: Entity<Project>,
+            _cwd: &Path,
+            _cx: &mut App,
+        ) -> Task<gpui::Result<Entity<AcpThread>>> {
+            Task::ready(Err(anyhow!("Session {} not found", session.session_id)))
+        }
+
+        fn session_list(&self, _cx: &mut App) -> Option<Rc<dyn AgentSessionList>> {
+            Some(Rc::new(DeletableSessionList {
+                deleted_sessions: self.deleted_sessions.clone(),
+            }))
+        }
+
+        fn auth_methods(&self) -> &[acp::AuthMethod] {
+            &[]
+        }
+
+        fn authenticate(
+            &self,
+            _method_id: acp::AuthMethodId,
+            _cx: &mut App,
+        ) -> Task<gpui::Result<()>> {
+            Task::ready(Ok(()))
+        }
+
+        fn prompt(
+            &self,
+            _id: Option<acp_thread::UserMessageId>,
+            _params: acp::PromptRequest,
+            _cx: &mut App,
+        ) -> Task<gpui::Result<acp::PromptResponse>> {
+            Task::ready(Ok(acp::PromptResponse::new(acp::StopReason::EndTurn)))
+        }
+
+        fn cancel(&self, _session_id: &acp::SessionId, _cx: &mut App) {}
+
+        fn into_any(self: Rc<Self>) -> Rc<dyn Any> {
+            self
+        }
+    }
+
+    struct DeletableSessionList {
+        deleted_sessions: Arc<std::sync::Mutex<Vec<SessionId>>>,
+    }
+
+    impl AgentSessionList for DeletableSessionList {
+        fn list_sessions(
+            &self,
+            _request: AgentSessionListRequest,
+            _cx: &mut App,
+        ) -> Task<anyhow::Result<AgentSessionListResponse>> {
+            Task::ready(Ok(AgentSessionListResponse::new(Vec::new())))
+        }
+
+        fn supports_delete(&self) -> bool {
+            true
+        }
+
+        fn delete_session(&self, session_id: &acp::SessionId, _cx: &mut App) -> Task<Result<()>> {
+            self.deleted_sessions
+                .lock()
+                .unwrap()
+                .push(session_id.clone());
+            Task::ready(Ok(()))
+        }
+
+        fn into_any(self: Rc<Self>) -> Rc<dyn Any> {
+            self
+        }
+    }
+
     #[derive(Clone)]
     struct SaboteurAgentConnection;
 
@@ -179,7 +179,8 @@ pub struct AcpThreadView {
     pub thread_retry_status: Option<RetryStatus>,
     pub(super) thread_error: Option<ThreadError>,
     pub thread_error_markdown: Option<Entity<Markdown>>,
-    pub token_limit_callout_dismissed: bool,
+    /// The context usage level the token limit callout was last dismissed at.
+    pub token_limit_callout_dismissed_at: Option<ContextUsageLevel>,
     thread_feedback: ThreadFeedbackState,
     pub list_state: ListState,
     pub prompt_capabilities: Rc<RefCell<PromptCapabilities>>,
@@ -195,6 +196,9 @@ pub struct AcpThreadView {
     pub plan_expanded: bool,
     pub queue_expanded: bool,
     pub editor_expanded: bool,
+    /// Whether the attached context is listed in full rather than collapsed into a
+    /// single pill, in the compact density.
+    pub context_chips_expanded: bool,
     pub should_be_following: bool,
     pub editing_message: Option<usize>,
     pub local_queued_messages: Vec<QueuedMessage>,
@@ -309,6 +313,9 @@ impl AcpThreadView {
                             cx,
                         );
                     }
+                    ExternalAgentInitialContent::SendPrompt(blocks) => {
+                        editor.set_message(blocks, window, cx);
+                    }
                 }
             }
             editor
@@ -330,6 +337,15 @@ impl AcpThreadView {
             Self::handle_message_editor_event,
         ));
 
+        let mut ui_density = AgentSettings::get_global(cx).ui_density;
+        subscriptions.push(cx.observe_global::<SettingsStore>(move |_, cx| {
+            let new_ui_density = AgentSettings::get_global(cx).ui_density;
+            if new_ui_density != ui_density {
+                ui_density = new_ui_density;
+                cx.notify();
+            }
+        }));
+
         let recent_history_entries = history.read(cx).get_recent_sessions(3);
 
         Self {
@@ -355,7 +371,7 @@ impl AcpThreadView {
             thread_retry_status: None,
             thread_error: None,
             thread_error_markdown: None,
-            token_limit_callout_dismissed: false,
+            token_limit_callout_dismissed_at: None,
             thread_feedback: Default::default(),
             expanded_tool_calls: HashSet::default(),
             expanded_tool_call_raw_inputs: HashSet::default(),
@@ -366,6 +382,7 @@ impl AcpThreadView {
             plan_expanded: false,
             queue_expanded: true,
             editor_expanded: false,
+            context_chips_expanded: false,
             should_be_following: false,
             editing_message: None,
             local_queued_messages: Vec::new(),
@@ -561,6 +578,20 @@ impl AcpThreadView {
                 *tokens += usage.output_tokens;
             }
         }
+        if let Some(level) = self.context_usage_level(cx) {
+            self.token_limit_callout_dismissed_at =
+                level.keep_dismissal(self.token_limit_callout_dismissed_at);
+        }
+    }
+
+    fn context_usage_level(&self, cx: &App) -> Option<ContextUsageLevel> {
+        let usage = self.thread.read(cx).token_usage()?;
+        let settings = AgentSettings::get_global(cx);
+        Some(ContextUsageLevel::for_usage(
+            &usage,
+            settings.context_warning_threshold,
+            settings.context_summarize_threshold,
+        ))
     }
 
     // sending
@@ -712,6 +743,7 @@ impl AcpThreadView {
 
             this.update_in(cx, |this, _window, cx| {
                 this.set_editor_is_expanded(false, cx);
+                this.context_chips_expanded = false;
             })?;
             let _ = this.update(cx, |this, cx| this.scroll_to_bottom(cx));
 
@@ -960,6 +992,34 @@ impl AcpThreadView {
         .detach_and_log_err(cx);
     }
 
+    pub fn retry_last_message_with_model(
+        &mut self,
+        model: Arc<dyn LanguageModel>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_loading_contents {
+            return;
+        }
+        let Some(native_thread) = self.as_native_thread(cx) else {
+            return;
+        };
+        let Some((user_message_id, chunks)) = self.thread.read(cx).last_user_message_prompt()
+        else {
+            return;
+        };
+
+        native_thread.update(cx, |thread, cx| thread.set_model(model, cx));
+        let rewind = self
+            .thread
+            .update(cx, |thread, cx| thread.rewind(user_message_id, cx));
+        let contents_task = cx.spawn(async move |_, _| {
+            rewind.await?;
+            anyhow::Ok(Some((chunks, Vec::new())))
+        });
+        self.send_content(contents_task, window, cx);
+    }
+
     // message queueing
 
     fn queue_message(
@@ -1100,6 +1160,11 @@ impl AcpThreadView {
         cx.notify();
     }
 
+    pub fn toggle_context_chips(&mut self, cx: &mut Context<Self>) {
+        self.context_chips_expanded = !self.context_chips_expanded;
+        cx.notify();
+    }
+
     pub fn set_editor_is_expanded(&mut self, is_expanded: bool, cx: &mut Context<Self>) {
         self.editor_expanded = is_expanded;
         self.message_editor.update(cx, |editor, cx| {
@@ -1512,7 +1577,7 @@ impl AcpThreadView {
     pub fn clear_thread_error(&mut self, cx: &mut Context<Self>) {
         self.thread_error = None;
         self.thread_error_markdown = None;
-        self.token_limit_callout_dismissed = true;
+        self.token_limit_callout_dismissed_at = self.context_usage_level(cx);
         cx.notify();
     }
 
@@ -2287,23 +2352,39 @@ impl AcpThreadView {
         } else {
             (IconName::Maximize, "Expand Message Editor")
         };
+        let compact = AgentSettings::get_global(cx).ui_density == AgentUiDensity::Compact;
 
         v_flex()
             .on_action(cx.listener(Self::expand_message_editor))
-            .p_2()
-            .gap_2()
+            .map(|this| {
+                if compact {
+                    this.p(DynamicSpacing::Base04.rems(cx))
+                        .gap(DynamicSpacing::Base04.rems(cx))
+                } else {
+                    this.p_2().gap_2()
+                }
+            })
             .border_t_1()
             .border_color(cx.theme().colors().border)
             .bg(editor_bg_color)
             .when(editor_expanded, |this| {
                 this.h(vh(0.8, window)).size_full().justify_between()
             })
+            .when(compact && self.context_chips_expanded, |this| {
+                this.children(self.render_context_chips(cx))
+            })
             .child(
                 v_flex()
                     .relative()
                     .size_full()
-                    .pt_1()
-                    .pr_2p5()
+                    .map(|this| {
+                        if compact {
+                            this.pt(DynamicSpacing::Base02.rems(cx))
+                                .pr(DynamicSpacing::Base08.rems(cx))
+                        } else {
+                            this.pt_1().pr_2p5()
+                        }
+                    })
                     .child(self.message_editor.clone())
                     .child(
                         h_flex()
@@ -2314,7 +2395,7 @@ impl AcpThreadView {
                             .hover(|this| this.opacity(1.0))
                             .child(
                                 IconButton::new("toggle-height", expand_icon)
-                                    .icon_size(IconSize::Small)
+                                    .icon_size(message_editor_icon_size(cx))
                                     .icon_color(Color::Muted)
                                     .tooltip({
                                         move |_window, cx| {
@@ -2346,11 +2427,19 @@ impl AcpThreadView {
                             .gap_0p5()
                             .child(self.render_add_context_button(cx))
                             .child(self.render_follow_toggle(cx))
-                            .children(self.render_thinking_toggle(cx)),
+                            .children(self.render_thinking_toggle(cx))
+                            .when(compact, |this| this.children(self.render_context_pill(cx))),
                     )
                     .child(
                         h_flex()
-                            .gap_1()
+                            .map(|this| {
+                                if compact {
+                                    this.gap(DynamicSpacing::Base02.rems(cx))
+                                } else {
+                                    this.gap_1()
+                                }
+                            })
+                            .children(self.render_context_usage(cx))
                             .children(self.render_token_usage(cx))
                             .children(self.profile_selector.clone())
                             .map(|this| {
@@ -2582,6 +2671,20 @@ impl AcpThreadView {
             .is_some_and(|model| model.supports_split_token_display())
     }
 
+    fn render_context_usage(&self, cx: &mut Context<Self>) -> Option<Label> {
+        let fraction = self.thread.read(cx).token_usage()?.fraction()?;
+        let color = match self.context_usage_level(cx)? {
+            ContextUsageLevel::Normal => Color::Muted,
+            ContextUsageLevel::Warning => Color::Warning,
+            ContextUsageLevel::Summarize | ContextUsageLevel::Exceeded => Color::Error,
+        };
+        Some(
+            Label::new(format!("{}%", (fraction * 100.).round() as u32))
+                .size(LabelSize::Small)
+                .color(color),
+        )
+    }
+
     fn render_token_usage(&self, cx: &mut Context<Self>) -> Option<Div> {
         let thread = self.thread.read(cx);
         let usage = thread.token_usage()?;
@@ -2714,7 +2817,7 @@ impl AcpThreadView {
 
         Some(
             IconButton::new("thinking-mode", icon)
-                .icon_size(IconSize::Small)
+                .icon_size(message_editor_icon_size(cx))
                 .icon_color(Color::Muted)
                 .toggle_state(thinking)
                 .tooltip(move |_, cx| {
@@ -2813,7 +2916,7 @@ impl AcpThreadView {
         PopoverMenu::new("add-context-menu")
             .trigger_with_tooltip(
                 IconButton::new("add-context", IconName::Plus)
-                    .icon_size(IconSize::Small)
+                    .icon_size(message_editor_icon_size(cx))
                     .icon_color(Color::Muted),
                 {
                     move |_window, cx| {
@@ -2965,6 +3068,71 @@ impl AcpThreadView {
         })
     }
 
+    /// The context attached to the message being written, sorted by name.
+    fn attached_context(&self, cx: &App) -> Vec<MentionUri> {
+        let mut mentions = self
+            .message_editor
+            .read(cx)
+            .mention_set()
+            .read(cx)
+            .mentions()
+            .into_iter()
+            .collect::<Vec<_>>();
+        mentions.sort_by_cached_key(|mention| mention.name());
+        mentions
+    }
+
+    fn render_context_pill(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let mention_count = self
+            .message_editor
+            .read(cx)
+            .mention_set()
+            .read(cx)
+            .mentions()
+            .len();
+        let label = context_pill_label(mention_count)?;
+        let expanded = self.context_chips_expanded;
+
+        Some(
+            Button::new("context-pill", label)
+                .label_size(LabelSize::XSmall)
+                .color(Color::Muted)
+                .style(ButtonStyle::Outlined)
+                .toggle_state(expanded)
+                .tooltip(Tooltip::text(if expanded {
+                    "Hide Attached Context"
+                } else {
+                    "Show Attached Context"
+                }))
+                .on_click(cx.listener(|this, _, _window, cx| {
+                    this.toggle_context_chips(cx);
+                })),
+        )
+    }
+
+    fn render_context_chips(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let mentions = self.attached_context(cx);
+        if mentions.is_empty() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .flex_wrap()
+                .gap(DynamicSpacing::Base02.rems(cx))
+                .children(mentions.into_iter().map(|mention| {
+                    h_flex()
+                        .gap_0p5()
+                        .child(
+                            Icon::from_path(mention.icon_path(cx))
+                                .size(IconSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                        .child(Chip::new(mention.name()).label_color(Color::Muted))
+                })),
+        )
+    }
+
     fn render_follow_toggle(&self, cx: &mut Context<Self>) -> impl IntoElement {
         let following = self.is_following(cx);
 
@@ -2983,7 +3151,7 @@ impl AcpThreadView {
         };
 
         IconButton::new("follow-agent", IconName::Crosshair)
-            .icon_size(IconSize::Small)
+            .icon_size(message_editor_icon_size(cx))
             .icon_color(Color::Muted)
             .toggle_state(following)
             .selected_icon_color(Some(Color::Custom(cx.theme().players().agent().cursor)))
@@ -3936,6 +4104,14 @@ impl AcpThreadView {
                         })
                         .unwrap_or(false);
 
+                    let entries = this.thread.read(cx).entries();
+                    let is_last_native_response = this.as_native_thread(cx).is_some()
+                        && entries.len() == entry_ix + 1
+                        && matches!(
+                            entries.get(entry_ix),
+                            Some(AgentThreadEntry::AssistantMessage(_))
+                        );
+
                     let copy_this_agent_response =
                         ContextMenuEntry::new("Copy This Agent Response").handler({
                             let entity = entity.clone();
@@ -3993,6 +4169,9 @@ impl AcpThreadView {
                             Box::new(markdown::CopyAsMarkdown),
                         )
                         .item(copy_this_agent_response)
+                        .when(is_last_native_response, |menu| {
+                            menu.action("Retry with Model…", Box::new(RetryWithModel))
+                        })
                         .separator()
                         .item(scroll_item)
                         .item(open_thread_as_markdown)
@@ -6712,25 +6891,22 @@ impl AcpThreadView {
     }
 
     fn render_token_limit_callout(&self, cx: &mut Context<Self>) -> Option<Callout> {
-        if self.token_limit_callout_dismissed {
+        let level = self.context_usage_level(cx)?;
+        if !level.shows_callout(self.token_limit_callout_dismissed_at) {
             return None;
         }
 
-        let token_usage = self.thread.read(cx).token_usage()?;
-        let ratio = token_usage.ratio();
-
-        let (severity, icon, title) = match ratio {
-            acp_thread::TokenUsageRatio::Normal => return None,
-            acp_thread::TokenUsageRatio::Warning => (
-                Severity::Warning,
-                IconName::Warning,
-                "Thread reaching the token limit soon",
-            ),
-            acp_thread::TokenUsageRatio::Exceeded => (
+        let (severity, icon, title) = match level {
+            ContextUsageLevel::Exceeded => (
                 Severity::Error,
                 IconName::XCircle,
                 "Thread reached the token limit",
             ),
+            _ => (
+                Severity::Warning,
+                IconName::Warning,
+                "Thread reaching the token limit soon",
+            ),
         };
 
         let description = "To continue, start a new thread from a summary.";
@@ -6743,7 +6919,7 @@ impl AcpThreadView {
                 .description(description)
                 .actions_slot(
                     h_flex().gap_0p5().child(
-                        Button::new("start-new-thread", "Start New Thread")
+                        Button::new("start-new-thread", "Summarize & Continue in New Thread")
                             .label_size(LabelSize::Small)
                             .on_click(cx.listener(|this, _, window, cx| {
                                 let session_id = this.thread.read(cx).session_id().clone();
@@ -6944,6 +7120,53 @@ impl Render for AcpThreadView {
     }
 }
 
+/// How full a thread's context window is, by the `agent.context_warning_threshold` and
+/// `agent.context_summarize_threshold` settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContextUsageLevel {
+    Normal,
+    Warning,
+    Summarize,
+    Exceeded,
+}
+
+impl ContextUsageLevel {
+    pub fn for_usage(
+        usage: &acp_thread::TokenUsage,
+        warning_threshold: f32,
+        summarize_threshold: f32,
+    ) -> Self {
+        let Some(fraction) = usage.fraction() else {
+            return Self::Normal;
+        };
+        if fraction >= 1. {
+            Self::Exceeded
+        } else if fraction >= summarize_threshold {
+            Self::Summarize
+        } else if fraction >= warning_threshold {
+            Self::Warning
+        } else {
+            Self::Normal
+        }
+    }
+
+    /// Whether the callout offering to continue in a new thread shows, given the level it
+    /// was dismissed at. A dismissed callout comes back once the usage gets worse.
+    pub fn shows_callout(self, dismissed_at: Option<Self>) -> bool {
+        self >= Self::Summarize && dismissed_at.is_none_or(|dismissed_at| self > dismissed_at)
+    }
+
+    /// The dismissal to keep once the usage changed to this level. It's forgotten when the
+    /// usage drops below the point where the callout shows, as after editing earlier messages.
+    pub fn keep_dismissal(self, dismissed_at: Option<Self>) -> Option<Self> {
+        if self < Self::Summarize {
+            None
+        } else {
+            dismissed_at
+        }
+    }
+}
+
 pub(crate) fn open_link(
     url: SharedString,
     workspace: &WeakEntity<Workspace>,
@@ -18,7 +18,7 @@ use extension_host::ExtensionStore;
 use fs::Fs;
 use gpui::{
     Action, AnyView, App, AsyncWindowContext, Corner, Entity, EventEmitter, FocusHandle, Focusable,
-    ScrollHandle, Subscription, Task, WeakEntity,
+    Pixels, Point, ScrollHandle, Subscription, Task, WeakEntity,
 };
 use language::LanguageRegistry;
 use language_model::{
@@ -52,6 +52,61 @@ use crate::agent_configuration::add_llm_provider_modal::{
     AddLlmProviderModal, LlmCompatibleProvider,
 };
 
+/// A part of the configuration view to scroll to and highlight when it's opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigurationFocusTarget {
+    /// The top of the view.
+    General,
+    /// The configuration of a language model provider.
+    Provider(LanguageModelProviderId),
+    /// An external agent server.
+    Agent(ExternalAgentServerName),
+}
+
+/// The sections of the configuration view, in the order they're rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConfigurationSection {
+    AgentServers,
+    ContextServers,
+    LlmProviders,
+}
+
+impl ConfigurationSection {
+    /// The index of the section among the children of the scrollable content.
+    fn index(self) -> usize {
+        match self {
+            ConfigurationSection::AgentServers => 0,
+            ConfigurationSection::ContextServers => 1,
+            ConfigurationSection::LlmProviders => 2,
+        }
+    }
+}
+
+/// Resolves a focus target to the section containing it, along with the target to
+/// highlight within that section. Targets naming a provider or agent that doesn't exist
+/// resolve to their section without a highlight.
+fn resolve_focus_target(
+    target: ConfigurationFocusTarget,
+    providers: &[LanguageModelProviderId],
+    agents: &[ExternalAgentServerName],
+) -> (ConfigurationSection, Option<ConfigurationFocusTarget>) {
+    match target {
+        ConfigurationFocusTarget::General => (ConfigurationSection::AgentServers, None),
+        ConfigurationFocusTarget::Provider(provider_id) => (
+            ConfigurationSection::LlmProviders,
+            providers
+                .contains(&provider_id)
+                .then_some(ConfigurationFocusTarget::Provider(provider_id)),
+        ),
+        ConfigurationFocusTarget::Agent(name) => (
+            ConfigurationSection::AgentServers,
+            agents
+                .contains(&name)
+                .then_some(ConfigurationFocusTarget::Agent(name)),
+        ),
+    }
+}
+
 pub struct AgentConfiguration {
     fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
@@ -64,6 +119,8 @@ pub struct AgentConfiguration {
     context_server_registry: Entity<ContextServerRegistry>,
     _registry_subscription: Subscription,
     scroll_handle: ScrollHandle,
+    /// The provider or agent that the view was opened for, if any.
+    highlighted_target: Option<ConfigurationFocusTarget>,
     _check_for_gemini: Task<()>,
 }
 
@@ -112,6 +169,7 @@ impl AgentConfiguration {
             context_server_registry,
             _registry_subscription: registry_subscription,
             scroll_handle: ScrollHandle::new(),
+            highlighted_target: None,
             _check_for_gemini: Task::ready(()),
         };
         this.build_provider_configuration_views(window, cx);
@@ -144,6 +202,39 @@ impl AgentConfiguration {
         self.configuration_views_by_provider
             .insert(provider.id(), configuration_view);
     }
+
+    /// Scrolls to the section containing `target` and highlights the named provider or
+    /// agent, expanding the provider's configuration.
+    pub fn scroll_to_section(&mut self, target: ConfigurationFocusTarget, cx: &mut Context<Self>) {
+        let providers = LanguageModelRegistry::read_global(cx)
+            .visible_providers()
+            .iter()
+            .map(|provider| provider.id())
+            .collect::<Vec<_>>();
+        let agents = self
+            .agent_server_store
+            .read(cx)
+            .external_agents()
+            .cloned()
+            .collect::<Vec<_>>();
+        let (section, highlighted_target) = resolve_focus_target(target, &providers, &agents);
+        if let Some(ConfigurationFocusTarget::Provider(provider_id)) = &highlighted_target {
+            self.expanded_provider_configurations
+                .insert(provider_id.clone(), true);
+        }
+        self.highlighted_target = highlighted_target;
+        self.scroll_handle.scroll_to_top_of_item(section.index());
+        cx.notify();
+    }
+
+    pub fn scroll_offset(&self) -> Point<Pixels> {
+        self.scroll_handle.offset()
+    }
+
+    pub fn set_scroll_offset(&mut self, offset: Point<Pixels>, cx: &mut Context<Self>) {
+        self.scroll_handle.set_offset(offset);
+        cx.notify();
+    }
 }
 
 impl Focusable for AgentConfiguration {
@@ -214,6 +305,9 @@ impl AgentConfiguration {
             .copied()
             .unwrap_or(false);
 
+        let is_highlighted =
+            self.highlighted_target == Some(ConfigurationFocusTarget::Provider(provider.id()));
+
         let is_zed_provider = provider.id() == ZED_CLOUD_PROVIDER_ID;
         let current_plan = if is_zed_provider {
             self.workspace
@@ -233,6 +327,9 @@ impl AgentConfiguration {
         v_flex()
             .w_full()
             .when(is_expanded, |this| this.mb_2())
+            .when(is_highlighted, |this| {
+                this.rounded_sm().bg(cx.theme().colors().element_selected)
+            })
             .child(
                 div()
                     .px_2()
@@ -1162,6 +1259,8 @@ impl AgentConfiguration {
         };
 
         let agent_server_name = ExternalAgentServerName(id.clone());
+        let is_highlighted = self.highlighted_target
+            == Some(ConfigurationFocusTarget::Agent(agent_server_name.clone()));
 
         let uninstall_button = match source {
             ExternalAgentSource::Extension => Some(
@@ -1218,6 +1317,9 @@ impl AgentConfiguration {
         h_flex()
             .gap_1()
             .justify_between()
+            .when(is_highlighted, |this| {
+                this.rounded_sm().bg(cx.theme().colors().element_selected)
+            })
             .child(
                 h_flex()
                     .gap_1p5()
@@ -1496,3 +1598,72 @@ fn is_removable_provider(provider_id: &LanguageModelProviderId, cx: &App) -> boo
         .openai_compatible
         .contains_key(provider_id.0.as_ref())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_focus_target() {
+        let anthropic = LanguageModelProviderId::from("anthropic".to_string());
+        let claude = ExternalAgentServerName(CLAUDE_CODE_NAME.into());
+        let providers = [anthropic.clone()];
+        let agents = [claude.clone()];
+
+        assert_eq!(
+            resolve_focus_target(ConfigurationFocusTarget::General, &providers, &agents),
+            (ConfigurationSection::AgentServers, None)
+        );
+        assert_eq!(
+            resolve_focus_target(
+                ConfigurationFocusTarget::Provider(anthropic.clone()),
+                &providers,
+                &agents,
+            ),
+            (
+                ConfigurationSection::LlmProviders,
+                Some(ConfigurationFocusTarget::Provider(anthropic)),
+            )
+        );
+        assert_eq!(
+            resolve_focus_target(
+                ConfigurationFocusTarget::Agent(claude.clone()),
+                &providers,
+                &agents,
+            ),
+            (
+                ConfigurationSection::AgentServers,
+                Some(ConfigurationFocusTarget::Agent(claude)),
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_focus_target_for_missing_section() {
+        let providers = [LanguageModelProviderId::from("anthropic".to_string())];
+        let agents = [ExternalAgentServerName(CLAUDE_CODE_NAME.into())];
+
+        assert_eq!(
+            resolve_focus_target(
+                ConfigurationFocusTarget::Provider(LanguageModelProviderId::from(
+                    "missing".to_string(),
+                )),
+                &providers,
+                &agents,
+            ),
+            (ConfigurationSection::LlmProviders, None)
+        );
+        assert_eq!(
+            resolve_focus_target(
+                ConfigurationFocusTarget::Agent(ExternalAgentServerName("missing".into())),
+                &providers,
+                &agents,
+            ),
+            (ConfigurationSection::AgentServers, None)
+        );
+        assert_eq!(
+            resolve_focus_target(ConfigurationFocusTarget::General, &[], &[]),
+            (ConfigurationSection::AgentServers, None)
+        );
+    }
+}
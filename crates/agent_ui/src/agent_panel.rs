@@ -1,9 +1,17 @@
-use std::{ops::Range, path::Path, rc::Rc, sync::Arc};
+use std::{
+    cell::Cell,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Instant,
+};
 
-use acp_thread::{AcpThread, AgentSessionInfo};
-use agent::{ContextServerRegistry, SharedThread, ThreadStore};
+use acp_thread::{AcpThread, AcpThreadEvent, AgentSessionInfo};
+use agent::{ContextServerRegistry, DbThreadCheckpoint, SharedThread, ThreadStore};
 use agent_client_protocol as acp;
 use agent_servers::AgentServer;
+use command_palette_hooks::{CommandPaletteProviders, ProvidedCommand};
 use db::kvp::{Dismissable, KEY_VALUE_STORE};
 use serde::{Deserialize, Serialize};
 use settings::{LanguageModelProviderSetting, LanguageModelSelection};
@@ -11,17 +19,29 @@ use settings::{LanguageModelProviderSetting, LanguageModelSelection};
 use zed_actions::agent::{OpenClaudeCodeOnboardingModal, ReauthenticateAgent};
 
 use crate::ManageProfiles;
-use crate::agent_panel_tab::{AgentPanelTab, TabId};
+use crate::agent_panel_tab::{
+    AgentPanelTab, AgentPanelTabIdentity, CloseTabsScope, SessionOpen, TabId, TabLabelSource,
+    TextThreadFile, active_tab_after_removal, begin_session_open, detached_threads_to_evict,
+    duplicate_of_tab, find_tab_by_identity, migrate_text_thread_identity, next_tab_id,
+    previous_tab_after_removal, previous_tab_id, tab_view_identity, tabs_to_close,
+};
 use crate::ui::{AcpOnboardingModal, ClaudeCodeOnboardingModal};
 use crate::{
-    AddContextServer, AgentDiffPane, CopyThreadToClipboard, Follow, InlineAssistant,
-    LoadThreadFromClipboard, NewTextThread, NewThread, OpenActiveThreadAsMarkdown, OpenAgentDiff,
-    OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell, ToggleNavigationMenu, ToggleNewThreadMenu,
-    ToggleOptionsMenu,
-    acp::AcpServerView,
-    agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
+    AddContextServer, AgentDiffPane, CheckpointThread, CopyThreadToClipboard, ExportAllThreads,
+    Follow, ImportAllThreads, InlineAssistant, InsertSlashCommand, LoadThreadFromClipboard,
+    NewTextThread, NewThread, NewThreadFromClipboardText, OpenActiveThreadAsMarkdown,
+    OpenAgentDiff, OpenHistory, OpenThreadReferencedFiles, RenameThread, ResetTrialEndUpsell,
+    ResetTrialUpsell, RestoreCheckpoint, RetryWithModel, RevealThreadFile, ToggleNavigationMenu,
+    ToggleNewThreadMenu, ToggleOptionsMenu,
+    acp::{AcpServerView, AcpServerViewEvent},
+    agent_configuration::{
+        AgentConfiguration, AssistantConfigurationEvent, ConfigurationFocusTarget,
+    },
+    language_model_selector::{LanguageModelSelector, language_model_selector},
     slash_command::SlashCommandCompletionProvider,
+    slash_command_picker::SlashCommandPickerModal,
     text_thread_editor::{AgentPanelDelegate, TextThreadEditor, make_lsp_adapter_delegate},
+    thread_checkpoint_picker::RestoreCheckpointModal,
     ui::{AgentOnboardingModal, EndTrialUpsell},
 };
 use crate::{
@@ -35,22 +55,24 @@ use crate::{
 };
 use agent_settings::AgentSettings;
 use ai_onboarding::AgentPanelOnboarding;
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use assistant_slash_command::SlashCommandWorkingSet;
-use assistant_text_thread::{TextThread, TextThreadEvent, TextThreadSummary};
+use assistant_text_thread::{TextThread, TextThreadEvent};
 use client::UserStore;
 use cloud_api_types::Plan;
+use collections::{HashMap, HashSet};
 use editor::{Anchor, AnchorRangeExt as _, Editor, EditorEvent, MultiBuffer};
 use extension::ExtensionEvents;
 use extension_host::ExtensionStore;
 use fs::Fs;
 use gpui::{
     Action, AnyElement, App, AsyncWindowContext, ClipboardItem, Corner, DismissEvent, Empty,
-    Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable, KeyContext, Pixels, ScrollHandle,
-    Subscription, Task, UpdateGlobal, WeakEntity, prelude::*,
+    Entity, EntityId, EventEmitter, ExternalPaths, FocusHandle, Focusable, KeyContext,
+    PathPromptOptions, Pixels, Point, ScrollHandle, Subscription, Task, UpdateGlobal, WeakEntity,
+    prelude::*,
 };
 use language::LanguageRegistry;
-use language_model::{ConfigurationError, LanguageModelRegistry};
+use language_model::{ConfigurationError, ConfiguredModel, LanguageModel, LanguageModelRegistry};
 use project::{Project, ProjectPath, Worktree};
 use prompt_store::{PromptBuilder, PromptStore, UserPromptId};
 use rules_library::{RulesLibrary, open_rules_library};
@@ -59,13 +81,16 @@ use settings::{Settings, update_settings_file};
 use std::cmp::Ordering;
 use theme::ThemeSettings;
 use ui::{
-    Callout, ContextMenu, IconButtonShape, KeyBinding, PopoverMenu, PopoverMenuHandle, Tab, TabBar,
-    TabCloseSide, TabPosition, Tooltip, prelude::*, utils::WithRemSize,
+    Callout, ContextMenu, ContextMenuEntry, DocumentationSide, IconButtonShape, KeyBinding,
+    PopoverMenu, PopoverMenuHandle, Tab, TabBar, TabCloseSide, TabPosition, Tooltip, prelude::*,
+    right_click_menu, utils::WithRemSize,
 };
 use util::ResultExt as _;
 use workspace::{
-    CollaboratorId, DraggedSelection, DraggedTab, ToggleZoom, ToolbarItemView, Workspace,
+    CollaboratorId, DraggedSelection, DraggedTab, ModalView, OpenOptions, OpenVisible, ToggleZoom,
+    ToolbarItemView, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::DetachAndPromptErr as _,
 };
 use zed_actions::{
     DecreaseBufferFontSize, IncreaseBufferFontSize, ResetBufferFontSize,
@@ -76,9 +101,26 @@ use zed_actions::{
 };
 
 const AGENT_PANEL_KEY: &str = "agent_panel";
-const RECENTLY_UPDATED_MENU_LIMIT: usize = 6;
-const DEFAULT_THREAD_TITLE: &str = "New Thread";
-const LOADING_SUMMARY_PLACEHOLDER: &str = "Loading Summary…";
+const RECENTLY_UPDATED_MENU_MIN_ENTRIES: usize = 4;
+const RECENTLY_UPDATED_MENU_MAX_ENTRIES: usize = 12;
+/// Rows of the window taken by the panel toolbar and the menu's header, separator and footer.
+const RECENTLY_UPDATED_MENU_RESERVED_ROWS: usize = 6;
+pub(crate) const DEFAULT_THREAD_TITLE: &str = "New Thread";
+
+/// Returns how many recent threads the navigation menu shows for a window of
+/// `available_height`, leaving room for the rest of the menu.
+fn recently_updated_menu_limit(available_height: Pixels, entry_height: Pixels) -> usize {
+    let rows = if entry_height > Pixels::ZERO {
+        (available_height / entry_height).floor() as usize
+    } else {
+        0
+    };
+    rows.saturating_sub(RECENTLY_UPDATED_MENU_RESERVED_ROWS)
+        .clamp(
+            RECENTLY_UPDATED_MENU_MIN_ENTRIES,
+            RECENTLY_UPDATED_MENU_MAX_ENTRIES,
+        )
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 struct SerializedAgentPanel {
@@ -122,6 +164,11 @@ pub fn init(cx: &mut App) {
                         panel.update(cx, |panel, cx| panel.activate_previous_tab(window, cx));
                     }
                 })
+                .register_action(|workspace, _: &crate::ToggleLastActiveTab, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.toggle_last_active_tab(window, cx));
+                    }
+                })
                 .register_action(
                     |workspace, action: &crate::CloseActiveThreadTabOrDock, window, cx| {
                         if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
@@ -140,7 +187,7 @@ pub fn init(cx: &mut App) {
                 .register_action(|workspace, _: &OpenSettings, window, cx| {
                     if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
                         workspace.focus_panel::<AgentPanel>(window, cx);
-                        panel.update(cx, |panel, cx| panel.open_configuration(window, cx));
+                        panel.update(cx, |panel, cx| panel.open_configuration(None, window, cx));
                     }
                 })
                 .register_action(|workspace, _: &NewTextThread, window, cx| {
@@ -168,6 +215,18 @@ pub fn init(cx: &mut App) {
                 .register_action(|workspace, _: &Follow, window, cx| {
                     workspace.follow(CollaboratorId::Agent, window, cx);
                 })
+                .register_action(|workspace, _: &OpenThreadReferencedFiles, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.open_thread_referenced_files(window, cx)
+                        });
+                    }
+                })
+                .register_action(|workspace, _: &RevealThreadFile, _window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.reveal_thread_file(cx));
+                    }
+                })
                 .register_action(|workspace, _: &OpenAgentDiff, window, cx| {
                     let thread = workspace
                         .panel::<AgentPanel>(cx)
@@ -247,10 +306,159 @@ pub fn init(cx: &mut App) {
                             panel.load_thread_from_clipboard(window, cx);
                         });
                     }
-                });
+                })
+                .register_action(|workspace, _: &ExportAllThreads, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.export_all_threads(window, cx));
+                    }
+                })
+                .register_action(|workspace, _: &ImportAllThreads, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.import_all_threads(window, cx));
+                    }
+                })
+                .register_action(|workspace, _: &NewThreadFromClipboardText, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        workspace.focus_panel::<AgentPanel>(window, cx);
+                        panel.update(cx, |panel, cx| {
+                            panel.new_thread_from_clipboard_text(window, cx);
+                        });
+                    }
+                })
+                .register_action(|workspace, _: &InsertSlashCommand, window, cx| {
+                    let Some(text_thread_editor) = workspace
+                        .panel::<AgentPanel>(cx)
+                        .and_then(|panel| panel.read(cx).active_text_thread_editor())
+                    else {
+                        return;
+                    };
+                    let working_set = text_thread_editor
+                        .read(cx)
+                        .text_thread()
+                        .read(cx)
+                        .slash_commands()
+                        .clone();
+                    SlashCommandPickerModal::toggle(
+                        workspace,
+                        working_set,
+                        text_thread_editor.downgrade(),
+                        window,
+                        cx,
+                    );
+                })
+                .register_action(|workspace, _: &RenameThread, window, cx| {
+                    let Some(target) = workspace
+                        .panel::<AgentPanel>(cx)
+                        .and_then(|panel| panel.read(cx).rename_thread_target(cx))
+                    else {
+                        return;
+                    };
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        RenameThreadModal::new(target, window, cx)
+                    });
+                })
+                .register_action(|workspace, _: &CheckpointThread, window, cx| {
+                    let Some(panel) = workspace.panel::<AgentPanel>(cx) else {
+                        return;
+                    };
+                    if panel.read(cx).active_native_agent_thread(cx).is_none() {
+                        return;
+                    }
+                    let panel = panel.downgrade();
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        CheckpointThreadModal::new(panel, window, cx)
+                    });
+                })
+                .register_action(|workspace, _: &RestoreCheckpoint, window, cx| {
+                    let Some(panel) = workspace.panel::<AgentPanel>(cx) else {
+                        return;
+                    };
+                    let Some(thread_view) = panel.read(cx).active_thread_view().cloned() else {
+                        return;
+                    };
+                    let Some(native_thread) = thread_view.read(cx).as_native_thread(cx) else {
+                        return;
+                    };
+                    let session_id = native_thread.read(cx).id().clone();
+                    let checkpoints = panel
+                        .read(cx)
+                        .thread_store()
+                        .update(cx, |store, cx| store.checkpoints(session_id, cx));
+                    let panel = panel.downgrade();
+                    cx.spawn_in(window, async move |workspace, cx| {
+                        let checkpoints = checkpoints.await?;
+                        workspace.update_in(cx, |workspace, window, cx| {
+                            workspace.toggle_modal(window, cx, |window, cx| {
+                                RestoreCheckpointModal::new(
+                                    panel,
+                                    thread_view,
+                                    checkpoints,
+                                    window,
+                                    cx,
+                                )
+                            });
+                        })
+                    })
+                    .detach_and_log_err(cx);
+                })
+                .register_action(|workspace, _: &RetryWithModel, window, cx| {
+                    let Some(panel) = workspace.panel::<AgentPanel>(cx) else {
+                        return;
+                    };
+                    let Some(thread_view) = panel.read(cx).active_thread_view().cloned() else {
+                        return;
+                    };
+                    let Some(native_thread) = thread_view.read(cx).as_native_thread(cx) else {
+                        return;
+                    };
+                    let has_user_message = panel
+                        .read(cx)
+                        .active_agent_thread(cx)
+                        .is_some_and(|thread| thread.read(cx).last_user_message_prompt().is_some());
+                    if !has_user_message {
+                        return;
+                    }
+                    let focus_handle = panel.focus_handle(cx);
+                    let panel = panel.downgrade();
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        RetryWithModelModal::new(
+                            panel,
+                            thread_view,
+                            native_thread,
+                            focus_handle,
+                            window,
+                            cx,
+                        )
+                    });
+                })
+                .register_action(
+                    |workspace, action: &crate::ActivateTabBySessionId, window, cx| {
+                        if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                            let activated = panel.update(cx, |panel, cx| {
+                                panel.activate_tab_by_session_id(&action.session_id, window, cx)
+                            });
+                            if activated {
+                                workspace.focus_panel::<AgentPanel>(window, cx);
+                            }
+                        }
+                    },
+                );
         },
     )
     .detach();
+
+    CommandPaletteProviders::register(cx, |workspace, cx| {
+        agent_tab_summaries(workspace, cx)
+            .into_iter()
+            .filter_map(|summary| {
+                let session_id = summary.session_id?;
+                Some(ProvidedCommand {
+                    name: format!("agent: activate tab: {}", summary.title),
+                    action: Box::new(crate::ActivateTabBySessionId { session_id }),
+                })
+            })
+            .collect()
+    });
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -296,6 +504,19 @@ pub enum AgentType {
     },
 }
 
+impl AgentType {
+    fn label(&self) -> SharedString {
+        match self {
+            Self::NativeAgent => "Zed Agent".into(),
+            Self::TextThread => "Text Thread".into(),
+            Self::Gemini => "Gemini CLI".into(),
+            Self::ClaudeCode => "Claude Code".into(),
+            Self::Codex => "Codex".into(),
+            Self::Custom { name } => name.clone(),
+        }
+    }
+}
+
 impl From<ExternalAgent> for AgentType {
     fn from(value: ExternalAgent) -> Self {
         match value {
@@ -407,6 +628,25 @@ impl ActiveView {
     }
 }
 
+/// A snapshot of an agent panel tab, for listing the open tabs outside of the panel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AgentTabSummary {
+    pub tab_id: TabId,
+    pub title: SharedString,
+    pub agent_label: SharedString,
+    pub is_generating: bool,
+    /// The session shown in the tab, if it shows an agent thread.
+    pub session_id: Option<acp::SessionId>,
+}
+
+/// Returns the tabs open in the agent panel of `workspace`, or nothing if it has no panel.
+pub fn agent_tab_summaries(workspace: &Workspace, cx: &App) -> Vec<AgentTabSummary> {
+    workspace
+        .panel::<AgentPanel>(cx)
+        .map(|panel| panel.read(cx).tab_summaries(cx))
+        .unwrap_or_default()
+}
+
 pub struct AgentPanel {
     workspace: WeakEntity<Workspace>,
     user_store: Entity<UserStore>,
@@ -421,6 +661,8 @@ pub struct AgentPanel {
     context_server_registry: Entity<ContextServerRegistry>,
     configuration: Option<Entity<AgentConfiguration>>,
     configuration_subscription: Option<Subscription>,
+    /// Where the configuration view was scrolled to when it was last closed.
+    configuration_scroll_offset: Option<Point<Pixels>>,
     focus_handle: FocusHandle,
     overlay_view: Option<ActiveView>,
     overlay_previous_tab_id: Option<TabId>,
@@ -428,6 +670,8 @@ pub struct AgentPanel {
     agent_panel_menu_handle: PopoverMenuHandle<ContextMenu>,
     agent_navigation_menu_handle: PopoverMenuHandle<ContextMenu>,
     agent_navigation_menu: Option<Entity<ContextMenu>>,
+    /// The recents last seen by `refresh_navigation_menu`.
+    navigation_menu_recents: Vec<SharedString>,
     panel_focus_handle: FocusHandle,
     _extension_subscription: Option<Subscription>,
     width: Option<Pixels>,
@@ -438,9 +682,28 @@ pub struct AgentPanel {
     selected_agent: AgentType,
     tabs: Vec<AgentPanelTab>,
     active_tab_id: TabId,
+    /// The tab that was active before `active_tab_id`, for `ToggleLastActiveTab`.
+    last_active_tab_id: Option<TabId>,
+    /// The `untitled_index` of the next tab added to the panel.
+    next_untitled_index: usize,
     tab_bar_scroll_handle: ScrollHandle,
     show_trust_workspace_message: bool,
     overlay_title_editor: Option<Entity<Editor>>,
+    detached_threads: HashMap<acp::SessionId, DetachedThread>,
+    /// Sessions that are being resumed but whose tab hasn't been added yet.
+    pending_session_opens: HashSet<acp::SessionId>,
+    /// Observations of thread views whose tab doesn't know which session it shows yet,
+    /// keyed by the thread view.
+    thread_identity_subscriptions: HashMap<EntityId, Subscription>,
+}
+
+/// A thread whose tab was closed while it was generating, kept alive so that the
+/// generation can finish in the background.
+struct DetachedThread {
+    _thread_view: Entity<AcpServerView>,
+    thread: Entity<AcpThread>,
+    detached_at: Instant,
+    _subscription: Subscription,
 }
 
 impl AgentPanel {
@@ -537,6 +800,14 @@ impl AgentPanel {
         let acp_history = cx.new(|cx| AcpThreadHistory::new(None, window, cx));
         let text_thread_history =
             cx.new(|cx| TextThreadHistory::new(text_thread_store.clone(), window, cx));
+        cx.observe_in(&acp_history, window, |this, _, window, cx| {
+            this.refresh_navigation_menu(window, cx)
+        })
+        .detach();
+        cx.observe_in(&text_thread_store, window, |this, _, window, cx| {
+            this.refresh_navigation_menu(window, cx)
+        })
+        .detach();
         cx.subscribe_in(
             &acp_history,
             window,
@@ -566,11 +837,16 @@ impl AgentPanel {
         window.defer(cx, move |window, cx| {
             let panel = weak_panel.clone();
             let agent_navigation_menu =
-                ContextMenu::build_persistent(window, cx, move |mut menu, _window, cx| {
+                ContextMenu::build_persistent(window, cx, move |mut menu, window, cx| {
                     if let Some(panel) = panel.upgrade() {
                         if let Some(kind) = panel.read(cx).history_kind_for_selected_agent(cx) {
-                            menu =
-                                Self::populate_recently_updated_menu_section(menu, panel, kind, cx);
+                            let limit = recently_updated_menu_limit(
+                                window.viewport_size().height,
+                                rems(1.75).to_pixels(window.rem_size()),
+                            );
+                            menu = Self::populate_recently_updated_menu_section(
+                                menu, panel, kind, limit, cx,
+                            );
                             let view_all_label = match kind {
                                 HistoryKind::AgentThreads => "View All",
                                 HistoryKind::TextThreads => "View All Text Threads",
@@ -642,6 +918,7 @@ impl AgentPanel {
             prompt_store,
             configuration: None,
             configuration_subscription: None,
+            configuration_scroll_offset: None,
             focus_handle: cx.focus_handle(),
             context_server_registry,
             overlay_view: None,
@@ -650,6 +927,7 @@ impl AgentPanel {
             agent_panel_menu_handle: PopoverMenuHandle::default(),
             agent_navigation_menu_handle: PopoverMenuHandle::default(),
             agent_navigation_menu: None,
+            navigation_menu_recents: Vec::new(),
             panel_focus_handle: cx.focus_handle(),
             _extension_subscription: extension_subscription,
             width: None,
@@ -663,14 +941,19 @@ impl AgentPanel {
             selected_agent: AgentType::default(),
             tabs: vec![],
             active_tab_id: 0,
+            last_active_tab_id: None,
+            next_untitled_index: 1,
             tab_bar_scroll_handle: ScrollHandle::new(),
             show_trust_workspace_message: false,
+            detached_threads: HashMap::default(),
+            pending_session_opens: HashSet::default(),
+            thread_identity_subscriptions: HashMap::default(),
             overlay_title_editor: None,
         };
 
         // Initialize with an empty tab to ensure there's always at least one tab
         let initial_view = ActiveView::Uninitialized;
-        let initial_tab = AgentPanelTab::new(initial_view, AgentType::default());
+        let initial_tab = AgentPanelTab::new(initial_view, AgentType::default(), cx);
         panel.tabs.push(initial_tab);
 
         // Initial sync of agent servers from extensions
@@ -808,18 +1091,59 @@ impl AgentPanel {
             self.serialize(cx);
         }
 
-        self.set_active_view(
-            ActiveView::text_thread(
-                text_thread_editor.clone(),
-                self.language_registry.clone(),
+        self.show_text_thread_editor(text_thread_editor.clone(), window, cx);
+        text_thread_editor.focus_handle(cx).focus(window, cx);
+    }
+
+    /// Shows `text_thread_editor` in a tab of the panel, or as an item of the workspace
+    /// when `agent.show_text_thread_tabs` is disabled.
+    fn show_text_thread_editor(
+        &mut self,
+        text_thread_editor: Entity<TextThreadEditor>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if AgentSettings::get_global(cx).show_text_thread_tabs {
+            self.set_active_view(
+                ActiveView::text_thread(
+                    text_thread_editor,
+                    self.language_registry.clone(),
+                    window,
+                    cx,
+                ),
+                true,
                 window,
                 cx,
-            ),
-            true,
-            window,
-            cx,
-        );
-        text_thread_editor.focus_handle(cx).focus(window, cx);
+            );
+            return;
+        }
+
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        // The panel is usually updated from within the workspace, so add the item afterwards.
+        window.defer(cx, move |window, cx| {
+            workspace.update(cx, |workspace, cx| {
+                let text_thread = text_thread_editor.read(cx).text_thread().clone();
+                let existing_editor = workspace
+                    .items_of_type::<TextThreadEditor>(cx)
+                    .find(|editor| editor.read(cx).text_thread() == &text_thread);
+                match existing_editor {
+                    Some(existing_editor) => {
+                        workspace.activate_item(&existing_editor, true, true, window, cx);
+                    }
+                    None => {
+                        workspace.add_item_to_active_pane(
+                            Box::new(text_thread_editor),
+                            None,
+                            true,
+                            window,
+                            cx,
+                        );
+                    }
+                }
+            });
+        });
     }
 
     fn external_thread(
@@ -830,6 +1154,27 @@ impl AgentPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if let Some(agent) = &agent_choice
+            && !self.ensure_external_agent_available(agent, cx)
+        {
+            return;
+        }
+
+        let pending_session_id = match resume_thread.as_ref() {
+            Some(thread) => {
+                let pending_opens = &mut self.pending_session_opens;
+                match begin_session_open(&self.tabs, pending_opens, &thread.session_id) {
+                    SessionOpen::ActivateTab(tab_id) => {
+                        self.set_active_tab_by_id(tab_id, window, cx);
+                        return;
+                    }
+                    SessionOpen::Pending => return,
+                    SessionOpen::Open => Some(thread.session_id.clone()),
+                }
+            }
+            None => None,
+        };
+
         let workspace = self.workspace.clone();
         let project = self.project.clone();
         let fs = self.fs.clone();
@@ -885,6 +1230,9 @@ impl AgentPanel {
 
             let server = ext_agent.server(fs, thread_store);
             this.update_in(cx, |agent_panel, window, cx| {
+                if let Some(session_id) = &pending_session_id {
+                    agent_panel.pending_session_opens.remove(session_id);
+                }
                 agent_panel._external_thread(
                     server,
                     resume_thread,
@@ -1018,17 +1366,18 @@ impl AgentPanel {
             self.serialize(cx);
         }
 
-        self.set_active_view(
-            ActiveView::text_thread(editor, self.language_registry.clone(), window, cx),
-            true,
-            window,
-            cx,
-        );
+        self.show_text_thread_editor(editor, window, cx);
     }
 
     pub fn go_back(&mut self, _: &workspace::GoBack, window: &mut Window, cx: &mut Context<Self>) {
         match self.active_view() {
             ActiveView::Configuration | ActiveView::History { .. } => {
+                if matches!(self.active_view(), ActiveView::Configuration)
+                    && let Some(configuration) = &self.configuration
+                {
+                    self.configuration_scroll_offset = Some(configuration.read(cx).scroll_offset());
+                }
+
                 // In the new tab system, we don't have a previous view concept
                 // We need to handle this differently
                 if let Some(_overlay_view) = self.overlay_view.take() {
@@ -1169,7 +1518,14 @@ impl AgentPanel {
         }
     }
 
-    pub(crate) fn open_configuration(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+    /// Opens the configuration view, scrolled to `focus_target` when given and to where it
+    /// was last left otherwise.
+    pub(crate) fn open_configuration(
+        &mut self,
+        focus_target: Option<ConfigurationFocusTarget>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         let agent_server_store = self.project.read(cx).agent_server_store().clone();
         let context_server_store = self.project.read(cx).context_server_store();
         let fs = self.fs.clone();
@@ -1195,6 +1551,13 @@ impl AgentPanel {
                 Self::handle_agent_configuration_event,
             ));
 
+            configuration.update(cx, |configuration, cx| {
+                if let Some(focus_target) = focus_target {
+                    configuration.scroll_to_section(focus_target, cx);
+                } else if let Some(offset) = self.configuration_scroll_offset {
+                    configuration.set_scroll_offset(offset, cx);
+                }
+            });
             configuration.focus_handle(cx).focus(window, cx);
         }
     }
@@ -1217,6 +1580,142 @@ impl AgentPanel {
         }
     }
 
+    /// Reveals the file of the active text thread in the project panel when it's part of the
+    /// project, and in the system's file manager otherwise.
+    fn reveal_thread_file(&mut self, cx: &mut Context<Self>) {
+        let Some(TextThreadFile::Saved(path)) = TextThreadFile::for_view(self.active_view(), cx)
+        else {
+            return;
+        };
+        let entry_id = self.project.update(cx, |project, cx| {
+            let project_path = project.find_project_path(&path, cx)?;
+            project
+                .entry_for_path(&project_path, cx)
+                .map(|entry| entry.id)
+        });
+        self.project.update(cx, |project, cx| match entry_id {
+            Some(entry_id) => cx.emit(project::Event::RevealInProjectPanel(entry_id)),
+            None => project.reveal_path(&path, cx),
+        });
+    }
+
+    fn open_thread_referenced_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let Some(thread) = self.active_agent_thread(cx) else {
+            self.show_referenced_files_toast("No active thread".into(), cx);
+            return;
+        };
+        let root = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path());
+        let paths = thread.read(cx).referenced_files(root.as_deref(), cx);
+        if paths.is_empty() {
+            self.show_referenced_files_toast("The thread doesn't mention any files".into(), cx);
+            return;
+        }
+
+        let fs = self.fs.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let mut existing = Vec::new();
+            let mut missing = 0;
+            for path in paths {
+                if fs.is_file(&path).await {
+                    existing.push(path);
+                } else {
+                    missing += 1;
+                }
+            }
+
+            let open_tasks = workspace.update_in(cx, |workspace, window, cx| {
+                existing
+                    .into_iter()
+                    .map(|path| {
+                        workspace.open_abs_path(
+                            path,
+                            OpenOptions {
+                                visible: Some(OpenVisible::None),
+                                focus: Some(false),
+                                ..Default::default()
+                            },
+                            window,
+                            cx,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+            let opened = futures::future::join_all(open_tasks)
+                .await
+                .into_iter()
+                .filter_map(|result| result.log_err())
+                .count();
+
+            let mut message = format!(
+                "Opened {opened} referenced {}",
+                if opened == 1 { "file" } else { "files" }
+            );
+            if missing > 0 {
+                message.push_str(&format!(", {missing} missing"));
+            }
+            this.update(cx, |this, cx| this.show_referenced_files_toast(message, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Whether a thread can be started with `agent` in this project, and otherwise why not.
+    pub(crate) fn can_start_external_agent(
+        &self,
+        agent: &ExternalAgent,
+        cx: &App,
+    ) -> Result<(), SharedString> {
+        agent.availability(self.project.read(cx).is_via_collab())
+    }
+
+    /// Checks [`Self::can_start_external_agent`], showing the reason as a toast when the
+    /// agent is unavailable.
+    fn ensure_external_agent_available(&self, agent: &ExternalAgent, cx: &mut App) -> bool {
+        let Err(reason) = self.can_start_external_agent(agent, cx) else {
+            return true;
+        };
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace.update(cx, |workspace, cx| {
+                struct ExternalAgentUnavailableToast;
+                workspace.show_toast(
+                    workspace::Toast::new(
+                        workspace::notifications::NotificationId::unique::<
+                            ExternalAgentUnavailableToast,
+                        >(),
+                        reason,
+                    )
+                    .autohide(),
+                    cx,
+                );
+            });
+        }
+        false
+    }
+
+    fn show_referenced_files_toast(&self, message: String, cx: &mut App) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            struct ReferencedFilesToast;
+            workspace.show_toast(
+                workspace::Toast::new(
+                    workspace::notifications::NotificationId::unique::<ReferencedFilesToast>(),
+                    message,
+                )
+                .autohide(),
+                cx,
+            );
+        });
+    }
+
     fn copy_thread_to_clipboard(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(thread) = self.active_native_agent_thread(cx) else {
             if let Some(workspace) = self.workspace.upgrade() {
@@ -1266,31 +1765,100 @@ impl AgentPanel {
         .detach_and_log_err(cx);
     }
 
-    fn load_thread_from_clipboard(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        let Some(clipboard) = cx.read_from_clipboard() else {
-            if let Some(workspace) = self.workspace.upgrade() {
-                workspace.update(cx, |workspace, cx| {
-                    struct NoClipboardToast;
-                    workspace.show_toast(
-                        workspace::Toast::new(
-                            workspace::notifications::NotificationId::unique::<NoClipboardToast>(),
-                            "No clipboard content available",
-                        )
-                        .autohide(),
-                        cx,
-                    );
-                });
-            }
+    fn prompt_for_thread_archive(&self, prompt: &'static str, cx: &App) -> Task<Option<PathBuf>> {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some(prompt.into()),
+        });
+        cx.background_spawn(async move {
+            let paths = paths.await.ok()?.log_err()??;
+            paths.into_iter().next()
+        })
+    }
+
+    fn show_thread_archive_toast(&self, message: String, cx: &mut App) {
+        let Some(workspace) = self.workspace.upgrade() else {
             return;
         };
+        workspace.update(cx, |workspace, cx| {
+            struct ThreadArchiveToast;
+            workspace.show_toast(
+                workspace::Toast::new(
+                    workspace::notifications::NotificationId::unique::<ThreadArchiveToast>(),
+                    message,
+                )
+                .autohide(),
+                cx,
+            );
+        });
+    }
 
-        let Some(encoded) = clipboard.text() else {
+    fn export_all_threads(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let directory = self.prompt_for_thread_archive("Export Threads", cx);
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(directory) = directory.await else {
+                return Ok(());
+            };
+            let export = this.update(cx, |this, cx| {
+                let fs = this.fs.clone();
+                this.thread_store.update(cx, |thread_store, cx| {
+                    thread_store.export_threads(fs, directory.clone(), cx)
+                })
+            })?;
+            let exported = export.await?;
+            this.update(cx, |this, cx| {
+                this.show_thread_archive_toast(
+                    format!("Exported {exported} threads to {}", directory.display()),
+                    cx,
+                );
+            })
+        })
+        .detach_and_prompt_err("Failed to export threads", window, cx, |_, _, _| None);
+    }
+
+    fn import_all_threads(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let directory = self.prompt_for_thread_archive("Import Threads", cx);
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(directory) = directory.await else {
+                return Ok(());
+            };
+            let import = this.update(cx, |this, cx| {
+                let fs = this.fs.clone();
+                this.thread_store.update(cx, |thread_store, cx| {
+                    thread_store.import_threads(fs, directory, cx)
+                })
+            })?;
+            let summary = import.await?;
+            this.update(cx, |this, cx| {
+                let mut message = format!("Imported {} threads", summary.imported);
+                if summary.skipped > 0 {
+                    message.push_str(&format!(", skipped {} already in history", summary.skipped));
+                }
+                if summary.failed > 0 {
+                    message.push_str(&format!(", {} could not be read", summary.failed));
+                }
+                this.show_thread_archive_toast(message, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to import threads", window, cx, |_, _, _| None);
+    }
+
+    fn new_thread_from_clipboard_text(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = cx
+            .read_from_clipboard()
+            .and_then(|clipboard| clipboard.text())
+            .filter(|text| !text.trim().is_empty())
+        else {
             if let Some(workspace) = self.workspace.upgrade() {
                 workspace.update(cx, |workspace, cx| {
-                    struct InvalidClipboardToast;
+                    struct NoClipboardTextToast;
                     workspace.show_toast(
                         workspace::Toast::new(
-                            workspace::notifications::NotificationId::unique::<InvalidClipboardToast>(),
+                            workspace::notifications::NotificationId::unique::<
+                                NoClipboardTextToast,
+                            >(),
                             "Clipboard does not contain text",
                         )
                         .autohide(),
@@ -1301,13 +1869,62 @@ impl AgentPanel {
             return;
         };
 
-        let thread_data = match base64::Engine::decode(&base64::prelude::BASE64_STANDARD, &encoded)
-        {
-            Ok(data) => data,
-            Err(_) => {
-                if let Some(workspace) = self.workspace.upgrade() {
-                    workspace.update(cx, |workspace, cx| {
-                        struct DecodeErrorToast;
+        if is_encoded_shared_thread(&text) {
+            self.load_thread_from_clipboard(window, cx);
+            return;
+        }
+
+        self.external_thread(
+            Some(crate::ExternalAgent::NativeAgent),
+            None,
+            Some(ExternalAgentInitialContent::Text(text)),
+            window,
+            cx,
+        );
+    }
+
+    fn load_thread_from_clipboard(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(clipboard) = cx.read_from_clipboard() else {
+            if let Some(workspace) = self.workspace.upgrade() {
+                workspace.update(cx, |workspace, cx| {
+                    struct NoClipboardToast;
+                    workspace.show_toast(
+                        workspace::Toast::new(
+                            workspace::notifications::NotificationId::unique::<NoClipboardToast>(),
+                            "No clipboard content available",
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                });
+            }
+            return;
+        };
+
+        let Some(encoded) = clipboard.text() else {
+            if let Some(workspace) = self.workspace.upgrade() {
+                workspace.update(cx, |workspace, cx| {
+                    struct InvalidClipboardToast;
+                    workspace.show_toast(
+                        workspace::Toast::new(
+                            workspace::notifications::NotificationId::unique::<InvalidClipboardToast>(),
+                            "Clipboard does not contain text",
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                });
+            }
+            return;
+        };
+
+        let thread_data = match base64::Engine::decode(&base64::prelude::BASE64_STANDARD, &encoded)
+        {
+            Ok(data) => data,
+            Err(_) => {
+                if let Some(workspace) = self.workspace.upgrade() {
+                    workspace.update(cx, |workspace, cx| {
+                        struct DecodeErrorToast;
                         workspace.show_toast(
                             workspace::Toast::new(
                                 workspace::notifications::NotificationId::unique::<DecodeErrorToast>(),
@@ -1389,6 +2006,164 @@ impl AgentPanel {
         .detach_and_log_err(cx);
     }
 
+    /// Saves the current state of the active native thread as a checkpoint named `label`.
+    fn checkpoint_active_thread(&mut self, label: SharedString, cx: &mut Context<Self>) {
+        let Some(native_thread) = self.active_native_agent_thread(cx) else {
+            return;
+        };
+        let session_id = native_thread.read(cx).id().clone();
+        let db_thread = native_thread.read(cx).to_db(cx);
+        let thread_store = self.thread_store.clone();
+        cx.spawn(async move |_, cx| {
+            let db_thread = db_thread.await;
+            thread_store
+                .update(cx, |store, cx| {
+                    store.save_checkpoint(session_id, label, db_thread, cx)
+                })
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the thread shown in `thread_view` with `checkpoint`, keeping its session.
+    /// The state being replaced is saved as a checkpoint first, so the restore can be undone.
+    pub(crate) fn restore_checkpoint(
+        &mut self,
+        thread_view: Entity<AcpServerView>,
+        checkpoint: DbThreadCheckpoint,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(connection) = thread_view.read(cx).as_native_connection(cx) else {
+            return;
+        };
+        let Some(native_thread) = thread_view.read(cx).as_native_thread(cx) else {
+            return;
+        };
+        let session_id = checkpoint.session_id.clone();
+        // Restoring replaces the session in this view's agent only, so any other view of
+        // it would keep showing, and saving, the thread from before the restore.
+        if self.is_session_open_elsewhere(&session_id, &thread_view, cx) {
+            if let Some(workspace) = self.workspace.upgrade() {
+                workspace.update(cx, |workspace, cx| {
+                    struct CheckpointRestoreToast;
+                    workspace.show_toast(
+                        workspace::Toast::new(
+                            workspace::notifications::NotificationId::unique::<
+                                CheckpointRestoreToast,
+                            >(),
+                            "Can't restore a checkpoint while the thread is open in another tab or kept in the background",
+                        )
+                        .autohide(),
+                        cx,
+                    );
+                });
+            }
+            return;
+        }
+        let current_thread = native_thread.read(cx).to_db(cx);
+        let thread_store = self.thread_store.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let current_thread = current_thread.await;
+            let mut restored_thread = thread_store
+                .update(cx, |store, cx| store.load_checkpoint(checkpoint.id, cx))
+                .await?
+                .context("checkpoint not found")?;
+            let label = format!("Before restoring \"{}\"", checkpoint.label);
+            thread_store
+                .update(cx, |store, cx| {
+                    store.save_checkpoint(session_id.clone(), label.into(), current_thread, cx)
+                })
+                .await?;
+
+            restored_thread.updated_at = chrono::Utc::now();
+            let title = restored_thread.title.clone();
+            cx.update(|_, cx| connection.replace_thread(session_id.clone(), restored_thread, cx))?
+                .await?;
+
+            let thread_metadata = acp_thread::AgentSessionInfo {
+                session_id,
+                cwd: None,
+                title: Some(title),
+                updated_at: Some(chrono::Utc::now()),
+                meta: None,
+            };
+            this.update_in(cx, |this, window, cx| {
+                this.reload_native_thread_view(&thread_view, thread_metadata, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn retry_last_message_with_model(
+        &mut self,
+        thread_view: Entity<AcpServerView>,
+        model: Arc<dyn LanguageModel>,
+        in_new_tab: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_thread) = thread_view.read(cx).as_active_thread() else {
+            return;
+        };
+        if !in_new_tab {
+            active_thread.update(cx, |active_thread, cx| {
+                active_thread.retry_last_message_with_model(model, window, cx)
+            });
+            return;
+        }
+
+        let Some(native_thread) = thread_view.read(cx).as_native_thread(cx) else {
+            return;
+        };
+        let Some((_, prompt)) = active_thread
+            .read(cx)
+            .thread
+            .read(cx)
+            .last_user_message_prompt()
+        else {
+            return;
+        };
+
+        let db_thread = native_thread
+            .read(cx)
+            .to_db_before_last_user_message(Some(model), cx);
+        let session_id = acp::SessionId::new(uuid::Uuid::new_v4().to_string());
+        let thread_store = self.thread_store.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let db_thread = db_thread.await;
+            let title = db_thread.title.clone();
+            thread_store
+                .update(&mut cx.clone(), |store, cx| {
+                    store.save_thread(session_id.clone(), db_thread, cx)
+                })
+                .await?;
+
+            let thread_metadata = acp_thread::AgentSessionInfo {
+                session_id,
+                cwd: None,
+                title: Some(title),
+                updated_at: Some(chrono::Utc::now()),
+                meta: None,
+            };
+
+            this.update_in(cx, |this, window, cx| {
+                this.external_thread(
+                    Some(ExternalAgent::NativeAgent),
+                    Some(thread_metadata),
+                    Some(ExternalAgentInitialContent::SendPrompt(prompt)),
+                    window,
+                    cx,
+                );
+            })?;
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn handle_agent_configuration_event(
         &mut self,
         _entity: &Entity<AgentConfiguration>,
@@ -1459,11 +2234,33 @@ impl AgentPanel {
 
     fn set_active_view(
         &mut self,
-        new_view: ActiveView,
+        mut new_view: ActiveView,
         focus: bool,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if let ActiveView::TextThread {
+            text_thread_editor,
+            _subscriptions,
+            ..
+        } = &mut new_view
+        {
+            let text_thread = text_thread_editor.read(cx).text_thread().clone();
+            _subscriptions.push(cx.subscribe(
+                &text_thread,
+                |this, text_thread, event: &TextThreadEvent, _| {
+                    if let TextThreadEvent::PathChanged { old_path, new_path } = event {
+                        migrate_text_thread_identity(
+                            &mut this.tabs,
+                            text_thread.entity_id(),
+                            old_path.as_ref(),
+                            new_path.clone(),
+                        );
+                    }
+                },
+            ));
+        }
+
         let current_is_uninitialized = matches!(self.active_view(), ActiveView::Uninitialized);
         let current_is_history = matches!(self.active_view(), ActiveView::History { .. });
         let new_is_history = matches!(new_view, ActiveView::History { .. });
@@ -1476,9 +2273,16 @@ impl AgentPanel {
 
         if current_is_uninitialized {
             // Replace the uninitialized tab with the new view
+            let identity = tab_view_identity(&new_view, cx);
+            let untitled_index = self.next_untitled_index;
             if let Some(tab) = self.tabs.get_mut(self.active_tab_id) {
                 tab.view = new_view;
                 tab.agent = self.selected_agent.clone();
+                tab.identity = identity;
+                if tab.untitled_index.is_none() {
+                    tab.untitled_index = Some(untitled_index);
+                    self.next_untitled_index += 1;
+                }
             }
         } else if new_is_special {
             // History/Configuration are shown as overlays
@@ -1490,11 +2294,8 @@ impl AgentPanel {
             self.overlay_previous_tab_id.take();
 
             let agent = self.selected_agent.clone();
-            let tab = AgentPanelTab::new(new_view, agent);
-            self.tabs.push(tab);
-            self.active_tab_id = self.tabs.len() - 1;
-            self.tab_bar_scroll_handle
-                .scroll_to_item(self.active_tab_id);
+            let tab = AgentPanelTab::new(new_view, agent, cx);
+            self.push_tab(tab);
         }
 
         if focus {
@@ -1503,10 +2304,37 @@ impl AgentPanel {
         cx.notify();
     }
 
+    /// Activates the tab showing the same thing as `tab` if there is one, and
+    /// otherwise adds `tab` at the end.
+    fn push_tab(&mut self, mut tab: AgentPanelTab) {
+        let existing_tab_id = tab
+            .identity
+            .as_ref()
+            .and_then(|identity| find_tab_by_identity(&self.tabs, identity));
+        let tab_id = match existing_tab_id {
+            Some(tab_id) => tab_id,
+            None => {
+                tab.untitled_index = Some(self.next_untitled_index);
+                self.next_untitled_index += 1;
+                self.tabs.push(tab);
+                self.tabs.len() - 1
+            }
+        };
+        if tab_id != self.active_tab_id {
+            self.last_active_tab_id = Some(self.active_tab_id);
+        }
+        self.active_tab_id = tab_id;
+        self.tab_bar_scroll_handle
+            .scroll_to_item(self.active_tab_id);
+    }
+
     fn set_active_tab_by_id(&mut self, tab_id: TabId, window: &mut Window, cx: &mut Context<Self>) {
         if tab_id < self.tabs.len() {
             self.overlay_view = None;
             self.overlay_previous_tab_id = None;
+            if tab_id != self.active_tab_id {
+                self.last_active_tab_id = Some(self.active_tab_id);
+            }
             self.active_tab_id = tab_id;
             self.tab_bar_scroll_handle.scroll_to_item(tab_id);
             self.focus_handle(cx).focus(window, cx);
@@ -1515,8 +2343,9 @@ impl AgentPanel {
     }
 
     fn remove_tab_by_id(&mut self, tab_id: TabId, window: &mut Window, cx: &mut Context<Self>) {
-        if self.tabs.len() <= 1 {
-            // If there's only one tab, close the panel instead
+        if self.tabs.len() == 1 {
+            // Closing the only tab also closes the panel, which shows the empty state when
+            // it is opened again.
             if let Some(workspace) = self.workspace.upgrade() {
                 window.defer(cx, move |window, cx| {
                     workspace.update(cx, |workspace, cx| {
@@ -1524,16 +2353,17 @@ impl AgentPanel {
                     });
                 });
             }
-            return;
         }
 
         if tab_id < self.tabs.len() {
-            self.tabs.remove(tab_id);
-
-            // Adjust active tab ID if needed
-            if self.active_tab_id >= tab_id && self.active_tab_id > 0 {
-                self.active_tab_id = self.active_tab_id.saturating_sub(1);
+            let tab = self.tabs.remove(tab_id);
+            self.detach_thread_if_generating(tab.view, cx);
+            match active_tab_after_removal(self.active_tab_id, tab_id, self.tabs.len()) {
+                Some(active_tab_id) => self.active_tab_id = active_tab_id,
+                None => self.ensure_placeholder_tab(cx),
             }
+            self.last_active_tab_id =
+                previous_tab_after_removal(self.last_active_tab_id, tab_id, self.active_tab_id);
 
             self.tab_bar_scroll_handle
                 .scroll_to_item(self.active_tab_id);
@@ -1542,37 +2372,219 @@ impl AgentPanel {
         }
     }
 
-    fn activate_next_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        if self.tabs.len() <= 1 {
-            return;
+    /// Closes the tabs `scope` selects relative to `tab_id`, one at a time, so the active and
+    /// previously active tabs are kept track of like when closing them by hand.
+    fn close_tabs(
+        &mut self,
+        tab_id: TabId,
+        scope: CloseTabsScope,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        for closed_tab_id in tabs_to_close(tab_id, self.tabs.len(), scope) {
+            self.remove_tab_by_id(closed_tab_id, window, cx);
         }
+    }
 
-        let next_id = if self.active_tab_id + 1 >= self.tabs.len() {
-            0 // Wrap around to the first tab
-        } else {
-            self.active_tab_id + 1
+    fn detach_thread_if_generating(&mut self, view: ActiveView, cx: &mut Context<Self>) {
+        let ActiveView::ExternalAgentThread { thread_view } = view else {
+            return;
+        };
+        let Some(thread) = thread_view
+            .read(cx)
+            .as_active_thread()
+            .map(|active_thread| active_thread.read(cx).thread.clone())
+        else {
+            return;
         };
+        if thread.read(cx).status() != acp_thread::ThreadStatus::Generating {
+            return;
+        }
+
+        let session_id = thread.read(cx).session_id().clone();
+        let subscription = cx.subscribe(&thread, {
+            let session_id = session_id.clone();
+            move |this, _, event: &AcpThreadEvent, _cx| match event {
+                AcpThreadEvent::Stopped
+                | AcpThreadEvent::Error
+                | AcpThreadEvent::Refusal
+                | AcpThreadEvent::LoadError(_) => {
+                    this.detached_threads.remove(&session_id);
+                }
+                _ => {}
+            }
+        });
+        self.detached_threads.insert(
+            session_id,
+            DetachedThread {
+                _thread_view: thread_view,
+                thread,
+                detached_at: Instant::now(),
+                _subscription: subscription,
+            },
+        );
+        self.evict_detached_threads(cx);
+    }
+
+    fn evict_detached_threads(&mut self, cx: &App) {
+        let max_detached_threads = AgentSettings::get_global(cx).max_detached_threads;
+        let evicted = detached_threads_to_evict(
+            self.detached_threads.iter().map(|(session_id, detached)| {
+                let is_generating =
+                    detached.thread.read(cx).status() == acp_thread::ThreadStatus::Generating;
+                (session_id.clone(), detached.detached_at, is_generating)
+            }),
+            max_detached_threads,
+        );
+        for session_id in evicted {
+            self.detached_threads.remove(&session_id);
+        }
+    }
 
-        self.set_active_tab_by_id(next_id, window, cx);
+    fn activate_next_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(next_id) = next_tab_id(self.active_tab_id, self.tabs.len()) {
+            self.set_active_tab_by_id(next_id, window, cx);
+        }
     }
 
     fn activate_previous_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        if self.tabs.len() <= 1 {
-            return;
+        if let Some(previous_id) = previous_tab_id(self.active_tab_id, self.tabs.len()) {
+            self.set_active_tab_by_id(previous_id, window, cx);
         }
+    }
 
-        let prev_id = if self.active_tab_id == 0 {
-            self.tabs.len() - 1 // Wrap around to the last tab
-        } else {
-            self.active_tab_id - 1
+    pub fn tab_summaries(&self, cx: &App) -> Vec<AgentTabSummary> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(tab_id, tab)| {
+                let source = TabLabelSource::for_view(&tab.view, cx);
+                let session_id = match &tab.identity {
+                    Some(AgentPanelTabIdentity::AgentThread(session_id)) => {
+                        Some(session_id.clone())
+                    }
+                    _ => None,
+                };
+                AgentTabSummary {
+                    tab_id,
+                    title: source.indexed_title(self.untitled_index(tab, cx)),
+                    agent_label: tab.agent.label(),
+                    is_generating: source.is_generating(),
+                    session_id,
+                }
+            })
+            .collect()
+    }
+
+    /// Activates the tab showing `session_id`, returning whether there is one.
+    pub fn activate_tab_by_session_id(
+        &mut self,
+        session_id: &acp::SessionId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let identity = AgentPanelTabIdentity::AgentThread(session_id.clone());
+        let Some(tab_id) = find_tab_by_identity(&self.tabs, &identity) else {
+            return false;
         };
+        self.set_active_tab_by_id(tab_id, window, cx);
+        true
+    }
+
+    fn toggle_last_active_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(last_active_tab_id) = self.last_active_tab_id
+            && self.tabs.len() > 1
+        {
+            self.set_active_tab_by_id(last_active_tab_id, window, cx);
+        }
+    }
 
-        self.set_active_tab_by_id(prev_id, window, cx);
+    /// Keeps an uninitialized tab around when every tab has been closed, so that the
+    /// panel always has something to render and the empty state can be shown.
+    fn ensure_placeholder_tab(&mut self, cx: &App) {
+        if self.tabs.is_empty() {
+            self.tabs.push(AgentPanelTab::new(
+                ActiveView::Uninitialized,
+                self.selected_agent.clone(),
+                cx,
+            ));
+        }
+        self.active_tab_id = self.active_tab_id.min(self.tabs.len() - 1);
+    }
+
+    /// Rebuilds the recents in the navigation menu while it is open, so that threads
+    /// created or deleted in the meantime show up without reopening it.
+    fn refresh_navigation_menu(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        // Histories notify for unrelated changes too, and rebuilding drops the menu's
+        // selection, so only rebuild when the listed recents actually changed.
+        let recents = self.navigation_menu_recents(cx);
+        if recents == self.navigation_menu_recents {
+            return;
+        }
+        self.navigation_menu_recents = recents;
+        if !self.agent_navigation_menu_handle.is_deployed() {
+            return;
+        }
+        if let Some(menu) = self.agent_navigation_menu.clone() {
+            window.defer(cx, move |window, cx| {
+                menu.update(cx, |menu, cx| menu.rebuild(window, cx));
+            });
+        }
+    }
+
+    fn navigation_menu_recents(&self, cx: &App) -> Vec<SharedString> {
+        match self.history_kind_for_selected_agent(cx) {
+            Some(HistoryKind::AgentThreads) => self
+                .acp_history
+                .read(cx)
+                .sessions()
+                .iter()
+                .take(RECENTLY_UPDATED_MENU_MAX_ENTRIES)
+                .flat_map(|session| {
+                    [
+                        SharedString::from(session.session_id.0.clone()),
+                        session.title.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+            Some(HistoryKind::TextThreads) => self
+                .text_thread_store
+                .read(cx)
+                .ordered_text_threads()
+                .take(RECENTLY_UPDATED_MENU_MAX_ENTRIES)
+                .flat_map(|text_thread| {
+                    [
+                        SharedString::from(text_thread.path.to_string_lossy().into_owned()),
+                        text_thread.title.clone(),
+                    ]
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn show_deleted_thread_toast(&self, cx: &mut Context<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            struct DeletedThreadToast;
+            workspace.show_toast(
+                workspace::Toast::new(
+                    workspace::notifications::NotificationId::unique::<DeletedThreadToast>(),
+                    "This thread has been deleted",
+                )
+                .autohide(),
+                cx,
+            );
+        });
     }
+
     fn populate_recently_updated_menu_section(
         mut menu: ContextMenu,
         panel: Entity<Self>,
         kind: HistoryKind,
+        limit: usize,
         cx: &mut Context<ContextMenu>,
     ) -> ContextMenu {
         match kind {
@@ -1583,7 +2595,7 @@ impl AgentPanel {
                     .read(cx)
                     .sessions()
                     .iter()
-                    .take(RECENTLY_UPDATED_MENU_LIMIT)
+                    .take(limit)
                     .cloned()
                     .collect::<Vec<_>>();
 
@@ -1608,7 +2620,16 @@ impl AgentPanel {
                             let entry = entry.clone();
                             panel
                                 .update(cx, move |this, cx| {
-                                    this.load_agent_thread(entry.clone(), window, cx);
+                                    let is_deleted = this
+                                        .acp_history
+                                        .read(cx)
+                                        .session_for_id(&entry.session_id)
+                                        .is_none();
+                                    if is_deleted {
+                                        this.show_deleted_thread_toast(cx);
+                                    } else {
+                                        this.load_agent_thread(entry.clone(), window, cx);
+                                    }
                                 })
                                 .ok();
                         }
@@ -1621,7 +2642,7 @@ impl AgentPanel {
                     .text_thread_store
                     .read(cx)
                     .ordered_text_threads()
-                    .take(RECENTLY_UPDATED_MENU_LIMIT)
+                    .take(limit)
                     .cloned()
                     .collect::<Vec<_>>();
 
@@ -1645,8 +2666,17 @@ impl AgentPanel {
                             let path = entry.path.clone();
                             panel
                                 .update(cx, move |this, cx| {
-                                    this.open_saved_text_thread(path.clone(), window, cx)
-                                        .detach_and_log_err(cx);
+                                    let is_deleted = !this
+                                        .text_thread_store
+                                        .read(cx)
+                                        .ordered_text_threads()
+                                        .any(|text_thread| text_thread.path == path);
+                                    if is_deleted {
+                                        this.show_deleted_thread_toast(cx);
+                                    } else {
+                                        this.open_saved_text_thread(path.clone(), window, cx)
+                                            .detach_and_log_err(cx);
+                                    }
                                 })
                                 .ok();
                         }
@@ -1734,6 +2764,9 @@ impl AgentPanel {
                 self.external_thread(Some(crate::ExternalAgent::Gemini), None, None, window, cx)
             }
             AgentType::ClaudeCode => {
+                if !self.ensure_external_agent_available(&ExternalAgent::ClaudeCode, cx) {
+                    return;
+                }
                 self.selected_agent = AgentType::ClaudeCode;
                 self.serialize(cx);
                 self.external_thread(
@@ -1745,6 +2778,9 @@ impl AgentPanel {
                 )
             }
             AgentType::Codex => {
+                if !self.ensure_external_agent_available(&ExternalAgent::Codex, cx) {
+                    return;
+                }
                 self.selected_agent = AgentType::Codex;
                 self.serialize(cx);
                 self.external_thread(Some(crate::ExternalAgent::Codex), None, None, window, cx)
@@ -1804,18 +2840,166 @@ impl AgentPanel {
             self.selected_agent = selected_agent;
             self.serialize(cx);
         }
-        let thread_store = server
-            .clone()
+        let session_id = resume_thread
+            .as_ref()
+            .map(|thread| thread.session_id.clone());
+        let thread_view = self.new_thread_view(
+            server,
+            resume_thread,
+            initial_content,
+            workspace,
+            project,
+            window,
+            cx,
+        );
+
+        self.set_active_view(
+            ActiveView::ExternalAgentThread {
+                thread_view: thread_view.clone(),
+            },
+            true,
+            window,
+            cx,
+        );
+
+        let Some(tab_id) = self.tab_for_thread_view(&thread_view) else {
+            return;
+        };
+        if let Some(session_id) = session_id {
+            cx.subscribe_in(&thread_view, window, Self::handle_thread_view_event)
+                .detach();
+            if self.tabs[tab_id].identity.is_none() {
+                self.tabs[tab_id].identity = Some(AgentPanelTabIdentity::AgentThread(session_id));
+            }
+        }
+        if self.tabs[tab_id].identity.is_none() {
+            self.observe_thread_tab_identity(&thread_view, window, cx);
+        }
+    }
+
+    fn observe_thread_tab_identity(
+        &mut self,
+        thread_view: &Entity<AcpServerView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let subscription = cx.observe_in(thread_view, window, |this, thread_view, window, cx| {
+            this.resolve_thread_tab_identity(&thread_view, window, cx);
+        });
+        self.thread_identity_subscriptions
+            .insert(thread_view.entity_id(), subscription);
+    }
+
+    fn handle_thread_view_event(
+        &mut self,
+        thread_view: &Entity<AcpServerView>,
+        event: &AcpServerViewEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tab_id) = self.tab_for_thread_view(thread_view) else {
+            return;
+        };
+        match event {
+            // The tab's title comes from the view, which now shows the failure.
+            AcpServerViewEvent::ResumeFailed(_) => cx.notify(),
+            AcpServerViewEvent::StartedFreshThread => {
+                // The tab no longer shows the session it was opened for, but the new one.
+                self.tabs[tab_id].identity = None;
+                self.observe_thread_tab_identity(thread_view, window, cx);
+            }
+            AcpServerViewEvent::HistoryEntryDeleted(_) => {
+                self.remove_tab_by_id(tab_id, window, cx);
+            }
+        }
+    }
+
+    /// Whether `session_id` is shown by a tab other than `thread_view`'s, or still kept
+    /// alive as a detached thread.
+    fn is_session_open_elsewhere(
+        &self,
+        session_id: &acp::SessionId,
+        thread_view: &Entity<AcpServerView>,
+        cx: &App,
+    ) -> bool {
+        if self.detached_threads.contains_key(session_id) {
+            return true;
+        }
+        let identity = AgentPanelTabIdentity::AgentThread(session_id.clone());
+        self.tabs.iter().any(|tab| match &tab.view {
+            ActiveView::ExternalAgentThread { thread_view: view } => {
+                view != thread_view && tab_view_identity(&tab.view, cx).as_ref() == Some(&identity)
+            }
+            _ => false,
+        })
+    }
+
+    fn tab_for_thread_view(&self, thread_view: &Entity<AcpServerView>) -> Option<TabId> {
+        self.tabs.iter().position(|tab| {
+            matches!(
+                &tab.view,
+                ActiveView::ExternalAgentThread { thread_view: view } if view == thread_view
+            )
+        })
+    }
+
+    /// Once `thread_view` knows which session it shows, records that on its tab, and closes
+    /// the tab in favor of an earlier one showing the same session.
+    fn resolve_thread_tab_identity(
+        &mut self,
+        thread_view: &Entity<AcpServerView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tab_id) = self.tab_for_thread_view(thread_view) else {
+            self.thread_identity_subscriptions
+                .remove(&thread_view.entity_id());
+            return;
+        };
+        let Some(identity) = tab_view_identity(&self.tabs[tab_id].view, cx) else {
+            return;
+        };
+        self.thread_identity_subscriptions
+            .remove(&thread_view.entity_id());
+        self.tabs[tab_id].identity = Some(identity);
+
+        let Some(existing_tab_id) = duplicate_of_tab(&self.tabs, tab_id) else {
+            return;
+        };
+        let was_active = tab_id == self.active_tab_id;
+        self.remove_tab_by_id(tab_id, window, cx);
+        if was_active {
+            let existing_tab_id = if existing_tab_id > tab_id {
+                existing_tab_id - 1
+            } else {
+                existing_tab_id
+            };
+            self.set_active_tab_by_id(existing_tab_id, window, cx);
+        }
+    }
+
+    fn new_thread_view(
+        &self,
+        server: Rc<dyn AgentServer>,
+        resume_thread: Option<AgentSessionInfo>,
+        initial_content: Option<ExternalAgentInitialContent>,
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<AcpServerView> {
+        let thread_store = server
+            .clone()
             .downcast::<agent::NativeAgentServer>()
             .is_some()
             .then(|| self.thread_store.clone());
 
-        let thread_view = cx.new(|cx| {
+        cx.new(|cx| {
             crate::acp::AcpServerView::new(
                 server,
                 resume_thread,
                 initial_content,
-                workspace.clone(),
+                workspace,
                 project,
                 thread_store,
                 self.prompt_store.clone(),
@@ -1823,14 +3007,40 @@ impl AgentPanel {
                 window,
                 cx,
             )
-        });
+        })
+    }
 
-        self.set_active_view(
-            ActiveView::ExternalAgentThread { thread_view },
-            true,
+    /// Swaps the tab showing `thread_view` for a new view of the native `thread`, loaded
+    /// from the thread store. Opens it in a new tab when `thread_view` is no longer open.
+    fn reload_native_thread_view(
+        &mut self,
+        thread_view: &Entity<AcpServerView>,
+        thread: AgentSessionInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tab_id) = self.tab_for_thread_view(thread_view) else {
+            self.open_thread(thread, window, cx);
+            return;
+        };
+
+        let server = ExternalAgent::NativeAgent.server(self.fs.clone(), self.thread_store.clone());
+        let new_view = self.new_thread_view(
+            server,
+            Some(thread),
+            None,
+            self.workspace.clone(),
+            self.project.clone(),
             window,
             cx,
         );
+        self.tabs[tab_id].view = ActiveView::ExternalAgentThread {
+            thread_view: new_view,
+        };
+        if tab_id == self.active_tab_id && self.overlay_view.is_none() {
+            self.focus_handle(cx).focus(window, cx);
+        }
+        cx.notify();
     }
 }
 
@@ -1857,6 +3067,12 @@ impl Focusable for AgentPanel {
     }
 }
 
+/// Whether `text` is a thread copied with [`CopyThreadToClipboard`] rather than plain prompt text.
+fn is_encoded_shared_thread(text: &str) -> bool {
+    base64::Engine::decode(&base64::prelude::BASE64_STANDARD, text.trim())
+        .is_ok_and(|data| SharedThread::from_bytes(&data).is_ok())
+}
+
 fn agent_panel_dock_position(cx: &App) -> DockPosition {
     AgentSettings::get_global(cx).dock.into()
 }
@@ -2012,6 +3228,9 @@ impl AgentPanel {
             }
             _ => false,
         };
+        let is_native_thread = self.active_native_agent_thread(cx).is_some();
+        let can_rename_thread = self.rename_thread_target(cx).is_some();
+        let text_thread_file = TextThreadFile::for_view(self.active_view(), cx);
 
         PopoverMenu::new("agent-options-menu")
             .trigger_with_tooltip(
@@ -2037,7 +3256,15 @@ impl AgentPanel {
                         menu = menu.context(focus_handle.clone());
 
                         if thread_with_messages | text_thread_with_messages {
-                            menu = menu.header("Current Thread");
+                            menu = menu
+                                .header("Current Thread")
+                                .when(can_rename_thread, |menu| {
+                                    menu.action("Rename Thread…", Box::new(RenameThread))
+                                })
+                                .when(is_native_thread, |menu| {
+                                    menu.action("Create Checkpoint…", Box::new(CheckpointThread))
+                                        .action("Restore Checkpoint…", Box::new(RestoreCheckpoint))
+                                });
 
                             if let Some(text_thread_view) = text_thread_view.as_ref() {
                                 menu = menu
@@ -2064,10 +3291,30 @@ impl AgentPanel {
                                             );
                                         }
                                     })
+                                    .when(is_native_thread, |menu| {
+                                        menu.action("Retry with Model…", Box::new(RetryWithModel))
+                                    })
                                     .separator();
                             }
                         }
 
+                        if let Some(text_thread_file) = text_thread_file.as_ref() {
+                            let can_reveal = text_thread_file.can_reveal();
+                            let entry = ContextMenuEntry::new("Reveal Thread File")
+                                .action(Box::new(RevealThreadFile))
+                                .disabled(!can_reveal)
+                                .handler(|window, cx| {
+                                    window.dispatch_action(Box::new(RevealThreadFile), cx)
+                                })
+                                .when(!can_reveal, |entry| {
+                                    entry.documentation_aside(DocumentationSide::Left, |_| {
+                                        Label::new("The thread has no file until it's saved.")
+                                            .into_any_element()
+                                    })
+                                });
+                            menu = menu.item(entry).separator();
+                        }
+
                         menu = menu
                             .header("MCP Servers")
                             .action(
@@ -2382,6 +3629,31 @@ impl AgentPanel {
             .child(self.render_drag_target(cx))
     }
 
+    fn render_empty_state(&self, cx: &Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        v_flex()
+            .size_full()
+            .items_center()
+            .justify_center()
+            .gap_2()
+            .child(Label::new("No agent threads — press + to start one").color(Color::Muted))
+            .child(
+                Button::new("start-agent-thread", "New Thread")
+                    .icon(IconName::Plus)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .icon_color(Color::Muted)
+                    .key_binding(
+                        KeyBinding::for_action_in(&NewThread, &focus_handle, cx)
+                            .map(|kb| kb.size(rems_from_px(12.))),
+                    )
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        let agent = this.selected_agent.clone();
+                        this.new_agent_thread(agent, window, cx);
+                    })),
+            )
+    }
+
     fn render_drag_target(&self, cx: &Context<Self>) -> Div {
         let is_local = self.project.read(cx).is_local();
         div()
@@ -2514,68 +3786,41 @@ impl AgentPanel {
         key_context
     }
 
+    /// The index `tab` is named after while untitled, if `agent.auto_index_untitled_tabs` is on.
+    fn untitled_index(&self, tab: &AgentPanelTab, cx: &App) -> Option<usize> {
+        tab.untitled_index
+            .filter(|_| AgentSettings::get_global(cx).auto_index_untitled_tabs)
+    }
+
+    fn tab_title(
+        &self,
+        tab: &ActiveView,
+        untitled_index: Option<usize>,
+        cx: &Context<Self>,
+    ) -> SharedString {
+        TabLabelSource::for_view(tab, cx).indexed_title(untitled_index)
+    }
+
     fn render_tab_label(
         &self,
         tab: &ActiveView,
+        untitled_index: Option<usize>,
         is_active: bool,
         cx: &Context<Self>,
     ) -> crate::agent_panel_tab::TabLabelRender {
-        let is_generating = match tab {
-            ActiveView::ExternalAgentThread { thread_view } => {
-                thread_view.read(cx).as_active_thread().map_or(false, |t| {
-                    t.read(cx).thread.read(cx).status() == acp_thread::ThreadStatus::Generating
-                })
-            }
-            ActiveView::TextThread {
-                text_thread_editor, ..
-            } => text_thread_editor
-                .read(cx)
-                .text_thread()
-                .read(cx)
-                .messages(cx)
-                .any(|msg| matches!(msg.status, assistant_text_thread::MessageStatus::Pending)),
-            ActiveView::History { .. } => false,
-            ActiveView::Configuration => false,
-            ActiveView::Uninitialized => false,
-        };
-
-        let title = match tab {
-            ActiveView::ExternalAgentThread { thread_view } => thread_view.read(cx).title(cx),
-            ActiveView::TextThread {
-                text_thread_editor, ..
-            } => {
-                let text_thread = text_thread_editor.read(cx).text_thread().read(cx);
-                match text_thread.summary() {
-                    TextThreadSummary::Pending => LOADING_SUMMARY_PLACEHOLDER.into(),
-                    TextThreadSummary::Content(summary) => {
-                        if summary.done {
-                            text_thread_editor.read(cx).title(cx).to_string().into()
-                        } else {
-                            LOADING_SUMMARY_PLACEHOLDER.into()
-                        }
-                    }
-                    TextThreadSummary::Error => {
-                        format!("⚠️ {}", text_thread_editor.read(cx).title(cx)).into()
-                    }
-                }
-            }
-            ActiveView::History { kind } => match kind {
-                HistoryKind::AgentThreads => "History".into(),
-                HistoryKind::TextThreads => "Text Thread History".into(),
-            },
-            ActiveView::Configuration => "Settings".into(),
-            ActiveView::Uninitialized => "Agent".into(),
-        };
-
-        let label = Label::new(title)
+        let source = TabLabelSource::for_view(tab, cx);
+        let title = source.indexed_title(untitled_index);
+        let text_thread_file = TextThreadFile::for_view(tab, cx);
+        let label = Label::new(title.clone())
             .truncate()
             .when(!is_active, |label| label.color(Color::Muted))
             .into_any_element();
 
         crate::agent_panel_tab::TabLabelRender {
             element: label,
-            tooltip: None,
-            is_generating,
+            tooltip: text_thread_file.is_some().then_some(title),
+            tooltip_meta: text_thread_file.map(|file| file.display_path()),
+            is_generating: source.is_generating(),
         }
     }
 
@@ -2603,25 +3848,113 @@ impl AgentPanel {
         }
     }
 
-    fn render_tab_bar(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let agent_server_store = self.project.read(cx).agent_server_store().clone();
+    /// The breadcrumb of the zoomed header, naming the agent and title of the active tab.
+    fn zoomed_header_label(&self, cx: &Context<Self>) -> String {
+        let active_tab = self.tabs.get(self.active_tab_id);
+        let agent = active_tab.map_or(&self.selected_agent, |tab| tab.agent());
+        let untitled_index = active_tab
+            .filter(|_| self.overlay_view.is_none())
+            .and_then(|tab| self.untitled_index(tab, cx));
+        let title = self.tab_title(self.active_view(), untitled_index, cx);
+        format!("Zoomed — {} · {}", agent.label(), title)
+    }
 
-        let end_slot = h_flex()
+    /// Shown above the tab bar while the panel is zoomed. The panel has no dock controls of
+    /// its own to dim while zoomed: docking is controlled from the status bar, which isn't
+    /// part of the panel and stays usable while it is zoomed.
+    ///
+    /// With `hide_tab_bar`, the header also holds the tab bar's menus.
+    fn render_zoomed_header(
+        &self,
+        hide_tab_bar: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+
+        h_flex()
+            .w_full()
+            .py_0p5()
+            .px(DynamicSpacing::Base06.rems(cx))
+            .gap_2()
+            .justify_between()
+            .bg(cx.theme().colors().tab_bar_background)
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(self.zoomed_header_label(cx))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .truncate(),
+            )
+            .child(
+                h_flex()
+                    .when(hide_tab_bar, |this| {
+                        this.child(self.render_tab_bar_controls(window, cx))
+                    })
+                    .child(
+                        IconButton::new("agent-panel-zoom-out", IconName::Minimize)
+                            .icon_size(IconSize::Small)
+                            .tooltip(move |_window, cx| {
+                                Tooltip::for_action_in("Zoom Out", &ToggleZoom, &focus_handle, cx)
+                            })
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(ToggleZoom.boxed_clone(), cx);
+                            }),
+                    ),
+            )
+    }
+
+    fn render_tab_bar_controls(&self, window: &mut Window, cx: &mut Context<Self>) -> Div {
+        h_flex()
             .gap(DynamicSpacing::Base02.rems(cx))
             .pl(DynamicSpacing::Base04.rems(cx))
             .pr(DynamicSpacing::Base06.rems(cx))
             .child(self.render_recent_entries_menu(IconName::MenuAltTemp, Corner::TopRight, cx))
-            .child(self.render_panel_options_menu(window, cx));
+            .child(self.render_panel_options_menu(window, cx))
+    }
+
+    /// Whether `agent.hide_single_tab_bar` applies: there's a single tab and nothing, such as
+    /// an overlay, needs the tab bar.
+    fn is_tab_bar_hidden(&self, cx: &App) -> bool {
+        AgentSettings::get_global(cx).hide_single_tab_bar
+            && self.tabs.len() == 1
+            && self.overlay_view.is_none()
+            && self.overlay_title_editor.is_none()
+    }
+
+    /// Keeps the tab bar's menus reachable while the tab bar is hidden, in a strip overlaid on
+    /// the top right of the panel. While zoomed, the zoomed header holds them instead, so that
+    /// they don't cover its Zoom Out button.
+    fn render_hidden_tab_bar_controls(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .id("agent-tab-bar-controls")
+            .absolute()
+            .top_0()
+            .right_0()
+            .py(DynamicSpacing::Base02.rems(cx))
+            .rounded_bl_md()
+            .bg(cx.theme().colors().panel_background)
+            .occlude()
+            .child(self.render_tab_bar_controls(window, cx))
+    }
+
+    fn render_tab_bar(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let agent_server_store = self.project.read(cx).agent_server_store().clone();
 
         let mut tab_bar = TabBar::new("agent-tab-bar")
             .track_scroll(&self.tab_bar_scroll_handle)
-            .end_child(end_slot);
+            .end_child(self.render_tab_bar_controls(window, cx));
 
         if let Some(overlay_view) = &self.overlay_view {
             let crate::agent_panel_tab::TabLabelRender {
                 element: overlay_label,
                 ..
-            } = self.render_tab_label(overlay_view, true, cx);
+            } = self.render_tab_label(overlay_view, None, true, cx);
 
             let overlay_title = h_flex()
                 .flex_grow()
@@ -2641,6 +3974,8 @@ impl AgentPanel {
         }
 
         let active_index = self.active_tab_id;
+        let tab_count = self.tabs.len();
+        let panel = cx.entity().downgrade();
         for (index, tab) in self.tabs.iter().enumerate() {
             let is_active = index == active_index;
             let position = if index == 0 {
@@ -2661,8 +3996,9 @@ impl AgentPanel {
             let crate::agent_panel_tab::TabLabelRender {
                 element: tab_label,
                 tooltip,
+                tooltip_meta,
                 is_generating,
-            } = self.render_tab_label(tab.view(), is_active, cx);
+            } = self.render_tab_label(tab.view(), self.untitled_index(tab, cx), is_active, cx);
 
             let indicator = is_generating.then(|| ui::Indicator::dot().color(Color::Accent));
             let agent_icon =
@@ -2697,9 +4033,43 @@ impl AgentPanel {
                 );
 
             if let Some(tooltip_text) = tooltip {
-                tab_component = tab_component.tooltip(Tooltip::text(tooltip_text));
+                tab_component = match tooltip_meta {
+                    Some(meta) => tab_component.tooltip(move |_window, cx| {
+                        Tooltip::with_meta(tooltip_text.clone(), None, meta.clone(), cx)
+                    }),
+                    None => tab_component.tooltip(Tooltip::text(tooltip_text)),
+                };
             }
-            tab_bar = tab_bar.child(tab_component);
+            let panel = panel.clone();
+            tab_bar = tab_bar.child(
+                right_click_menu(("agent-tab-context-menu", index))
+                    .trigger(move |_, _, _| tab_component)
+                    .menu(move |window, cx| {
+                        let panel = panel.clone();
+                        ContextMenu::build(window, cx, move |menu, _, _| {
+                            let close_tabs = |scope| {
+                                let panel = panel.clone();
+                                move |window: &mut Window, cx: &mut App| {
+                                    panel
+                                        .update(cx, |panel, cx| {
+                                            panel.close_tabs(index, scope, window, cx)
+                                        })
+                                        .ok();
+                                }
+                            };
+                            menu.item(
+                                ContextMenuEntry::new("Close Others")
+                                    .disabled(tab_count == 1)
+                                    .handler(close_tabs(CloseTabsScope::Others)),
+                            )
+                            .item(
+                                ContextMenuEntry::new("Close to the Right")
+                                    .disabled(index + 1 == tab_count)
+                                    .handler(close_tabs(CloseTabsScope::ToTheRight)),
+                            )
+                        })
+                    }),
+            );
         }
         tab_bar.into_any_element()
     }
@@ -2726,17 +4096,30 @@ impl AgentPanel {
         }
     }
 
+    /// What `RenameThread` renames, if the active view is a thread whose title can be changed.
+    fn rename_thread_target(&self, cx: &App) -> Option<RenameThreadTarget> {
+        match self.active_view() {
+            ActiveView::TextThread {
+                text_thread_editor, ..
+            } => Some(RenameThreadTarget::TextThread(text_thread_editor.clone())),
+            ActiveView::ExternalAgentThread { thread_view } => thread_view
+                .read(cx)
+                .as_active_thread()?
+                .read(cx)
+                .title_editor
+                .clone()
+                .map(RenameThreadTarget::ExternalThread),
+            _ => None,
+        }
+    }
+
     fn active_view(&self) -> &ActiveView {
         if let Some(overlay_view) = &self.overlay_view {
             overlay_view
-        } else if let Some(tab) = self.tabs.get(self.active_tab_id) {
+        } else if let Some(tab) = self.tabs.get(self.active_tab_id).or(self.tabs.last()) {
             &tab.view
         } else {
-            // This should not happen in practice since we ensure there's always at least one tab
-            // But if it does, we need to return a reference somehow.
-            // Since ActiveView contains non-Sync fields, we can't make a static instance.
-            // Instead, we'll panic with a clearer message.
-            panic!("No active view available - AgentPanel should always have at least one tab");
+            &ActiveView::Uninitialized
         }
     }
 }
@@ -2752,6 +4135,7 @@ impl Render for AgentPanel {
         // - Font size works as expected and can be changed with cmd-+/cmd-
         // - Scrolling in all views works as expected
         // - Files can be dropped into the panel
+        let hide_tab_bar = self.is_tab_bar_hidden(cx);
         let content = v_flex()
             .relative()
             .size_full()
@@ -2765,7 +4149,7 @@ impl Render for AgentPanel {
                 this.open_history(window, cx);
             }))
             .on_action(cx.listener(|this, _: &OpenSettings, window, cx| {
-                this.open_configuration(window, cx);
+                this.open_configuration(None, window, cx);
             }))
             .on_action(cx.listener(Self::open_active_thread_as_markdown))
             .on_action(cx.listener(Self::deploy_rules_library))
@@ -2786,11 +4170,16 @@ impl Render for AgentPanel {
                     thread_view.update(cx, |thread_view, cx| thread_view.reauthenticate(window, cx))
                 }
             }))
-            .child(self.render_tab_bar(window, cx))
+            .when(self.zoomed, |this| {
+                this.child(self.render_zoomed_header(hide_tab_bar, window, cx))
+            })
+            .when(!hide_tab_bar, |this| {
+                this.child(self.render_tab_bar(window, cx))
+            })
             .children(self.render_workspace_trust_message(cx))
             .children(self.render_onboarding(window, cx))
             .map(|parent| match self.active_view() {
-                ActiveView::Uninitialized => parent,
+                ActiveView::Uninitialized => parent.child(self.render_empty_state(cx)),
                 ActiveView::ExternalAgentThread { thread_view, .. } => parent
                     .child(thread_view.clone())
                     .child(self.render_drag_target(cx)),
@@ -2830,7 +4219,10 @@ impl Render for AgentPanel {
                 }
                 ActiveView::Configuration => parent.children(self.configuration.clone()),
             })
-            .children(self.render_trial_end_upsell(window, cx));
+            .children(self.render_trial_end_upsell(window, cx))
+            .when(hide_tab_bar && !self.zoomed, |this| {
+                this.child(self.render_hidden_tab_bar_controls(window, cx))
+            });
 
         match self.active_view().which_font_size_used() {
             WhichFontSize::AgentFont => {
@@ -2844,6 +4236,269 @@ impl Render for AgentPanel {
     }
 }
 
+/// What the `RenameThread` prompt renames.
+enum RenameThreadTarget {
+    TextThread(Entity<TextThreadEditor>),
+    /// The title editor of an external agent thread, which updates the thread's title when
+    /// edited.
+    ExternalThread(Entity<Editor>),
+}
+
+impl RenameThreadTarget {
+    fn title(&self, cx: &App) -> String {
+        match self {
+            RenameThreadTarget::TextThread(text_thread_editor) => {
+                text_thread_editor.read(cx).title(cx).to_string()
+            }
+            RenameThreadTarget::ExternalThread(title_editor) => title_editor.read(cx).text(cx),
+        }
+    }
+
+    fn rename(&self, title: String, window: &mut Window, cx: &mut App) {
+        match self {
+            RenameThreadTarget::TextThread(text_thread_editor) => {
+                let text_thread = text_thread_editor.read(cx).text_thread().clone();
+                text_thread.update(cx, |text_thread, cx| {
+                    text_thread.set_custom_summary(title, cx);
+                });
+            }
+            RenameThreadTarget::ExternalThread(title_editor) => {
+                title_editor.update(cx, |editor, cx| editor.set_text(title, window, cx));
+            }
+        }
+    }
+}
+
+/// A single-line prompt for renaming the active thread, independent of the tab bar.
+struct RenameThreadModal {
+    target: RenameThreadTarget,
+    editor: Entity<Editor>,
+}
+
+impl RenameThreadModal {
+    fn new(target: RenameThreadTarget, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let title = target.title(cx);
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_text(title, window, cx);
+            editor.select_all(&Default::default(), window, cx);
+            editor
+        });
+        Self { target, editor }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let title = self.editor.read(cx).text(cx).trim().to_string();
+        if !title.is_empty() && title != self.target.title(cx) {
+            self.target.rename(title, window, cx);
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for RenameThreadModal {}
+
+impl EventEmitter<DismissEvent> for RenameThreadModal {}
+
+impl Focusable for RenameThreadModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for RenameThreadModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RenameThreadModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .child(
+                h_flex()
+                    .px_3()
+                    .pt_2()
+                    .pb_1()
+                    .w_full()
+                    .gap_1p5()
+                    .child(Icon::new(IconName::Pencil).size(IconSize::XSmall))
+                    .child(Headline::new("Rename Thread").size(HeadlineSize::XSmall)),
+            )
+            .child(div().px_3().pb_3().w_full().child(self.editor.clone()))
+    }
+}
+
+struct CheckpointThreadModal {
+    panel: WeakEntity<AgentPanel>,
+    editor: Entity<Editor>,
+    default_label: SharedString,
+}
+
+impl CheckpointThreadModal {
+    fn new(panel: WeakEntity<AgentPanel>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let default_label: SharedString = chrono::Local::now()
+            .format("Checkpoint %b %-d, %H:%M")
+            .to_string()
+            .into();
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_text(default_label.clone(), window, cx);
+            editor.select_all(&Default::default(), window, cx);
+            editor
+        });
+        Self {
+            panel,
+            editor,
+            default_label,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _window: &mut Window, cx: &mut Context<Self>) {
+        let label = self.editor.read(cx).text(cx).trim().to_string();
+        let label = if label.is_empty() {
+            self.default_label.clone()
+        } else {
+            label.into()
+        };
+        self.panel
+            .update(cx, |panel, cx| panel.checkpoint_active_thread(label, cx))
+            .ok();
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for CheckpointThreadModal {}
+
+impl EventEmitter<DismissEvent> for CheckpointThreadModal {}
+
+impl Focusable for CheckpointThreadModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for CheckpointThreadModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("CheckpointThreadModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .child(
+                h_flex()
+                    .px_3()
+                    .pt_2()
+                    .pb_1()
+                    .w_full()
+                    .gap_1p5()
+                    .child(Icon::new(IconName::HistoryRerun).size(IconSize::XSmall))
+                    .child(Headline::new("Create Checkpoint").size(HeadlineSize::XSmall)),
+            )
+            .child(div().px_3().pb_3().w_full().child(self.editor.clone()))
+    }
+}
+
+struct RetryWithModelModal {
+    picker: Entity<LanguageModelSelector>,
+    in_new_tab: Rc<Cell<bool>>,
+    _subscription: Subscription,
+}
+
+impl RetryWithModelModal {
+    fn new(
+        panel: WeakEntity<AgentPanel>,
+        thread_view: Entity<AcpServerView>,
+        native_thread: Entity<agent::Thread>,
+        focus_handle: FocusHandle,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let selected_model = Rc::new(Cell::new(None));
+        let picker = cx.new(|cx| {
+            language_model_selector(
+                move |cx| {
+                    let model = native_thread.read(cx).model()?.clone();
+                    let provider =
+                        LanguageModelRegistry::read_global(cx).provider(&model.provider_id())?;
+                    Some(ConfiguredModel { provider, model })
+                },
+                {
+                    let selected_model = selected_model.clone();
+                    move |model, _cx| selected_model.set(Some(model))
+                },
+                |model, should_be_favorite, cx| {
+                    crate::favorite_models::toggle_in_settings(
+                        model,
+                        should_be_favorite,
+                        <dyn Fs>::global(cx),
+                        cx,
+                    );
+                },
+                true,
+                focus_handle,
+                window,
+                cx,
+            )
+        });
+
+        let in_new_tab = Rc::new(Cell::new(false));
+        let subscription = cx.subscribe_in(&picker, window, {
+            let in_new_tab = in_new_tab.clone();
+            move |_, _, _: &DismissEvent, window, cx| {
+                if let Some(model) = selected_model.take() {
+                    panel
+                        .update(cx, |panel, cx| {
+                            panel.retry_last_message_with_model(
+                                thread_view.clone(),
+                                model,
+                                in_new_tab.get(),
+                                window,
+                                cx,
+                            )
+                        })
+                        .log_err();
+                }
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            picker,
+            in_new_tab,
+            _subscription: subscription,
+        }
+    }
+}
+
+impl ModalView for RetryWithModelModal {}
+
+impl EventEmitter<DismissEvent> for RetryWithModelModal {}
+
+impl Focusable for RetryWithModelModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for RetryWithModelModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        let in_new_tab = self.in_new_tab.clone();
+        v_flex()
+            .key_context("RetryWithModelModal")
+            .capture_action(move |_: &menu::SecondaryConfirm, _, _| in_new_tab.set(true))
+            .child(self.picker.clone())
+    }
+}
+
 struct PromptLibraryInlineAssist {
     workspace: WeakEntity<Workspace>,
 }
@@ -3020,7 +4675,7 @@ impl Dismissable for TrialEndUpsell {
     const KEY: &'static str = "dismissed-trial-end-upsell";
 }
 
-#[cfg(feature = "test-support")]
+#[cfg(any(test, feature = "test-support"))]
 impl AgentPanel {
     /// Opens an external thread using an arbitrary AgentServer.
     ///
@@ -3053,3 +4708,403 @@ impl AgentPanel {
         self.active_thread_view()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::{StubAgentServer, init_test};
+    use acp_thread::StubAgentConnection;
+    use fs::FakeFs;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language_model::fake_provider::FakeLanguageModel;
+    use settings::SettingsStore;
+
+    async fn setup_panel(cx: &mut TestAppContext) -> (Entity<AgentPanel>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            LanguageModelRegistry::test(cx);
+            ThreadStore::init_global(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let panel = workspace.update_in(cx, |workspace, window, cx| {
+            let text_thread_store =
+                cx.new(|cx| assistant_text_thread::TextThreadStore::fake(project.clone(), cx));
+            let panel =
+                cx.new(|cx| AgentPanel::new(workspace, text_thread_store, None, window, cx));
+            workspace.add_panel(panel.clone(), window, cx);
+            panel
+        });
+        cx.run_until_parked();
+        (panel, cx)
+    }
+
+    /// Opens a Zed Agent thread in a new tab, answering with `model`.
+    fn open_native_thread(
+        panel: &Entity<AgentPanel>,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut VisualTestContext,
+    ) -> Entity<AcpServerView> {
+        panel.update_in(cx, |panel, window, cx| {
+            panel.external_thread(Some(ExternalAgent::NativeAgent), None, None, window, cx)
+        });
+        cx.run_until_parked();
+
+        let thread_view =
+            panel.read_with(cx, |panel, _| panel.active_thread_view().unwrap().clone());
+        thread_view.update(cx, |thread_view, cx| {
+            let native_thread = thread_view.as_native_thread(cx).unwrap();
+            native_thread.update(cx, |thread, cx| thread.set_model(model, cx));
+        });
+        thread_view
+    }
+
+    fn open_stub_thread(
+        panel: &Entity<AgentPanel>,
+        connection: &StubAgentConnection,
+        cx: &mut VisualTestContext,
+    ) -> Entity<AcpServerView> {
+        panel.update_in(cx, |panel, window, cx| {
+            let server = Rc::new(StubAgentServer::new(connection.clone()));
+            panel.open_external_thread_with_server(server, window, cx)
+        });
+        cx.run_until_parked();
+        panel.read_with(cx, |panel, _| panel.active_thread_view().unwrap().clone())
+    }
+
+    fn send_message(thread_view: &Entity<AcpServerView>, text: &str, cx: &mut VisualTestContext) {
+        let active_thread = thread_view.read_with(cx, |view, _| view.as_active_thread().unwrap());
+        let message_editor = active_thread.read_with(cx, |view, _| view.message_editor.clone());
+        message_editor.update_in(cx, |editor, window, cx| editor.set_text(text, window, cx));
+        active_thread.update_in(cx, |view, window, cx| view.send(window, cx));
+        cx.run_until_parked();
+    }
+
+    fn thread_markdown(thread_view: &Entity<AcpServerView>, cx: &VisualTestContext) -> String {
+        cx.read(|cx| {
+            let active_thread = thread_view.read(cx).as_active_thread().unwrap();
+            active_thread.read(cx).thread.read(cx).to_markdown(cx)
+        })
+    }
+
+    fn answer(model: &FakeLanguageModel, text: &str, cx: &mut VisualTestContext) {
+        model.send_last_completion_stream_text_chunk(text);
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    async fn test_retry_last_message_with_model_in_place(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let (panel, cx) = setup_panel(cx).await;
+
+        let model = Arc::new(FakeLanguageModel::default());
+        let thread_view = open_native_thread(&panel, model.clone(), cx);
+        send_message(&thread_view, "Hello", cx);
+        answer(&model, "First answer", cx);
+
+        let retry_model = Arc::new(FakeLanguageModel::default());
+        panel.update_in(cx, |panel, window, cx| {
+            panel.retry_last_message_with_model(
+                thread_view.clone(),
+                retry_model.clone(),
+                false,
+                window,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        assert!(model.pending_completions().is_empty());
+        answer(&retry_model, "Second answer", cx);
+
+        panel.read_with(cx, |panel, _| assert_eq!(panel.tabs.len(), 1));
+        let markdown = thread_markdown(&thread_view, cx);
+        assert!(markdown.contains("Hello"));
+        assert!(markdown.contains("Second answer"));
+        assert!(!markdown.contains("First answer"));
+    }
+
+    #[gpui::test]
+    async fn test_retry_last_message_with_model_in_new_tab(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let (panel, cx) = setup_panel(cx).await;
+
+        let model = Arc::new(FakeLanguageModel::default());
+        let thread_view = open_native_thread(&panel, model.clone(), cx);
+        send_message(&thread_view, "Hello", cx);
+        answer(&model, "First answer", cx);
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.retry_last_message_with_model(
+                thread_view.clone(),
+                Arc::new(FakeLanguageModel::default()),
+                true,
+                window,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let fork_view = panel.read_with(cx, |panel, _| {
+            assert_eq!(panel.tabs.len(), 2);
+            panel.active_thread_view().unwrap().clone()
+        });
+        assert_ne!(fork_view, thread_view);
+
+        // The fork resends the last prompt to the model it was retried with.
+        let fork_model = fork_view.read_with(cx, |view, cx| {
+            let native_thread = view.as_native_thread(cx).unwrap();
+            native_thread.read(cx).model().unwrap().clone()
+        });
+        assert!(model.pending_completions().is_empty());
+        answer(fork_model.as_fake(), "Forked answer", cx);
+
+        let fork_markdown = thread_markdown(&fork_view, cx);
+        assert!(fork_markdown.contains("Hello"));
+        assert!(fork_markdown.contains("Forked answer"));
+        assert!(!fork_markdown.contains("First answer"));
+
+        let original_markdown = thread_markdown(&thread_view, cx);
+        assert!(original_markdown.contains("First answer"));
+        assert!(!original_markdown.contains("Forked answer"));
+    }
+
+    #[gpui::test]
+    async fn test_closing_last_tab_shows_empty_state(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (panel, cx) = setup_panel(cx).await;
+
+        open_stub_thread(&panel, &StubAgentConnection::new(), cx);
+        panel.update_in(cx, |panel, window, cx| {
+            panel.remove_tab_by_id(0, window, cx)
+        });
+        cx.run_until_parked();
+        panel.read_with(cx, |panel, _| {
+            assert_eq!(panel.tabs.len(), 1);
+            assert!(matches!(panel.active_view(), ActiveView::Uninitialized));
+        });
+
+        // A new thread takes the place of the empty state rather than opening next to it.
+        let thread_view = open_stub_thread(&panel, &StubAgentConnection::new(), cx);
+        panel.read_with(cx, |panel, _| {
+            assert_eq!(panel.tabs.len(), 1);
+            assert_eq!(panel.active_thread_view(), Some(&thread_view));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_zoomed_header_follows_active_tab(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (panel, cx) = setup_panel(cx).await;
+
+        let connection = StubAgentConnection::new();
+        for title in ["First thread", "Second thread"] {
+            let thread_view = open_stub_thread(&panel, &connection, cx);
+            let thread = thread_view.read_with(cx, |view, cx| {
+                view.as_active_thread().unwrap().read(cx).thread.clone()
+            });
+            thread
+                .update(cx, |thread, cx| thread.set_title(title.into(), cx))
+                .detach();
+        }
+        panel.update_in(cx, |panel, window, cx| panel.set_zoomed(true, window, cx));
+
+        let header_label = |cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, cx| panel.zoomed_header_label(cx))
+        };
+        assert_eq!(header_label(cx), "Zoomed — Test · Second thread");
+        panel.update_in(cx, |panel, window, cx| {
+            panel.activate_previous_tab(window, cx)
+        });
+        assert_eq!(header_label(cx), "Zoomed — Test · First thread");
+    }
+
+    #[gpui::test]
+    async fn test_detached_threads_are_dropped_once_finished(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (panel, cx) = setup_panel(cx).await;
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.agent.get_or_insert_default().max_detached_threads = Some(1);
+                });
+            });
+        });
+
+        // The stub connection keeps generating until the turn is ended.
+        let connection = StubAgentConnection::new();
+        let session_ids = (0..3)
+            .map(|_| {
+                let thread_view = open_stub_thread(&panel, &connection, cx);
+                send_message(&thread_view, "Hello", cx);
+                thread_view.read_with(cx, |view, cx| {
+                    let thread = view.as_active_thread().unwrap().read(cx).thread.clone();
+                    thread.read(cx).session_id().clone()
+                })
+            })
+            .collect::<Vec<_>>();
+        let detached = |cx: &mut VisualTestContext| {
+            panel.read_with(cx, |panel, _| {
+                session_ids
+                    .iter()
+                    .map(|session_id| panel.detached_threads.contains_key(session_id))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // Threads that are still generating are kept, even past the limit.
+        for _ in 0..2 {
+            panel.update_in(cx, |panel, window, cx| {
+                panel.remove_tab_by_id(0, window, cx)
+            });
+        }
+        assert_eq!(detached(cx), [true, true, false]);
+
+        // Each thread is dropped as soon as it finishes.
+        connection.end_turn(session_ids[0].clone(), acp::StopReason::EndTurn);
+        cx.run_until_parked();
+        assert_eq!(detached(cx), [false, true, false]);
+        connection.end_turn(session_ids[1].clone(), acp::StopReason::EndTurn);
+        cx.run_until_parked();
+        assert_eq!(detached(cx), [false, false, false]);
+    }
+
+    async fn thread_checkpoints(
+        panel: &Entity<AgentPanel>,
+        session_id: acp::SessionId,
+        cx: &mut VisualTestContext,
+    ) -> Vec<DbThreadCheckpoint> {
+        let thread_store = panel.read_with(cx, |panel, _| panel.thread_store.clone());
+        thread_store
+            .update(cx, |store, cx| store.checkpoints(session_id, cx))
+            .await
+            .unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_restore_checkpoint_replaces_thread_in_place(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let (panel, cx) = setup_panel(cx).await;
+
+        let model = Arc::new(FakeLanguageModel::default());
+        let thread_view = open_native_thread(&panel, model.clone(), cx);
+        let session_id = thread_view.read_with(cx, |view, cx| {
+            view.as_native_thread(cx).unwrap().read(cx).id().clone()
+        });
+        send_message(&thread_view, "Hello", cx);
+        answer(&model, "First answer", cx);
+
+        panel.update(cx, |panel, cx| {
+            panel.checkpoint_active_thread("First".into(), cx)
+        });
+        cx.run_until_parked();
+        let checkpoints = thread_checkpoints(&panel, session_id.clone(), cx).await;
+        assert_eq!(checkpoints.len(), 1);
+
+        send_message(&thread_view, "Again", cx);
+        answer(&model, "Second answer", cx);
+
+        let checkpoint = checkpoints.into_iter().next().unwrap();
+        panel.update_in(cx, |panel, window, cx| {
+            panel.restore_checkpoint(thread_view.clone(), checkpoint, window, cx)
+        });
+        cx.run_until_parked();
+
+        // The tab now shows a freshly loaded view of the restored thread.
+        let restored_view = panel.read_with(cx, |panel, _| {
+            assert_eq!(panel.tabs.len(), 1);
+            panel.active_thread_view().unwrap().clone()
+        });
+        assert_ne!(restored_view, thread_view);
+        let markdown = thread_markdown(&restored_view, cx);
+        assert!(markdown.contains("First answer"));
+        assert!(!markdown.contains("Second answer"));
+
+        // The thread as it was before the restore is kept as a checkpoint of its own.
+        let labels = thread_checkpoints(&panel, session_id, cx)
+            .await
+            .into_iter()
+            .map(|checkpoint| checkpoint.label)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![
+                SharedString::from("Before restoring \"First\""),
+                SharedString::from("First"),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_restore_checkpoint_after_detached_thread_finishes(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let (panel, cx) = setup_panel(cx).await;
+
+        let model = Arc::new(FakeLanguageModel::default());
+        let thread_view = open_native_thread(&panel, model.clone(), cx);
+        let session_id = thread_view.read_with(cx, |view, cx| {
+            view.as_native_thread(cx).unwrap().read(cx).id().clone()
+        });
+        send_message(&thread_view, "Hello", cx);
+        answer(&model, "First answer", cx);
+        panel.update(cx, |panel, cx| {
+            panel.checkpoint_active_thread("First".into(), cx)
+        });
+        cx.run_until_parked();
+
+        // Closing the tab mid-generation detaches the thread until it finishes.
+        send_message(&thread_view, "Again", cx);
+        let tab_id = panel.read_with(cx, |panel, _| panel.active_tab_id);
+        panel.update_in(cx, |panel, window, cx| {
+            panel.remove_tab_by_id(tab_id, window, cx)
+        });
+        panel.read_with(cx, |panel, _| {
+            assert!(panel.detached_threads.contains_key(&session_id))
+        });
+        answer(&model, "Second answer", cx);
+        panel.read_with(cx, |panel, _| {
+            assert!(!panel.detached_threads.contains_key(&session_id))
+        });
+
+        panel.update_in(cx, |panel, window, cx| {
+            panel.open_thread(
+                AgentSessionInfo {
+                    session_id: session_id.clone(),
+                    cwd: None,
+                    title: None,
+                    updated_at: None,
+                    meta: None,
+                },
+                window,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        let reopened_view =
+            panel.read_with(cx, |panel, _| panel.active_thread_view().unwrap().clone());
+        assert!(thread_markdown(&reopened_view, cx).contains("Second answer"));
+
+        let checkpoint = thread_checkpoints(&panel, session_id, cx)
+            .await
+            .into_iter()
+            .next()
+            .unwrap();
+        panel.update_in(cx, |panel, window, cx| {
+            panel.restore_checkpoint(reopened_view.clone(), checkpoint, window, cx)
+        });
+        cx.run_until_parked();
+
+        let restored_view =
+            panel.read_with(cx, |panel, _| panel.active_thread_view().unwrap().clone());
+        assert_ne!(restored_view, reopened_view);
+        let markdown = thread_markdown(&restored_view, cx);
+        assert!(markdown.contains("First answer"));
+        assert!(!markdown.contains("Second answer"));
+    }
+}
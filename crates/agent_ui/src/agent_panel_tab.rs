@@ -1,16 +1,52 @@
-use crate::agent_panel::{ActiveView, AgentType};
-use gpui::{AnyElement, SharedString};
+use crate::agent_panel::{ActiveView, AgentType, DEFAULT_THREAD_TITLE, HistoryKind};
+use agent_client_protocol as acp;
+use assistant_text_thread::{MessageStatus, TextThreadSummary};
+use collections::HashSet;
+use gpui::{AnyElement, App, EntityId, SharedString};
+use std::{path::Path, sync::Arc, time::Instant};
+use util::paths::PathExt as _;
 
 pub type TabId = usize;
 
+const LOADING_SUMMARY_PLACEHOLDER: &str = "Loading Summary…";
+
+/// Identifies what a tab shows, so that opening it again activates the existing tab.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AgentPanelTabIdentity {
+    TextThread(Arc<Path>),
+    /// A text thread that hasn't been saved yet, keyed by its `TextThread` entity.
+    UnsavedTextThread(EntityId),
+    /// An agent thread, once its view knows which session it shows.
+    AgentThread(acp::SessionId),
+}
+
+impl AgentPanelTabIdentity {
+    pub fn for_text_thread(path: Option<&Arc<Path>>, text_thread_id: EntityId) -> Self {
+        match path {
+            Some(path) => Self::TextThread(path.clone()),
+            None => Self::UnsavedTextThread(text_thread_id),
+        }
+    }
+}
+
 pub struct AgentPanelTab {
     pub view: ActiveView,
     pub agent: AgentType,
+    pub identity: Option<AgentPanelTabIdentity>,
+    /// Where the tab comes in the order tabs were opened in, which names it while its
+    /// thread is untitled. Assigned once the tab is added to the panel.
+    pub untitled_index: Option<usize>,
 }
 
 impl AgentPanelTab {
-    pub fn new(view: ActiveView, agent: AgentType) -> Self {
-        Self { view, agent }
+    pub fn new(view: ActiveView, agent: AgentType, cx: &App) -> Self {
+        let identity = tab_view_identity(&view, cx);
+        Self {
+            view,
+            agent,
+            identity,
+            untitled_index: None,
+        }
     }
 
     pub fn view(&self) -> &ActiveView {
@@ -22,8 +58,654 @@ impl AgentPanelTab {
     }
 }
 
+pub fn tab_view_identity(view: &ActiveView, cx: &App) -> Option<AgentPanelTabIdentity> {
+    match view {
+        ActiveView::TextThread {
+            text_thread_editor, ..
+        } => {
+            let text_thread = text_thread_editor.read(cx).text_thread();
+            Some(AgentPanelTabIdentity::for_text_thread(
+                text_thread.read(cx).path(),
+                text_thread.entity_id(),
+            ))
+        }
+        ActiveView::ExternalAgentThread { thread_view } => {
+            let active_thread = thread_view.read(cx).as_active_thread()?;
+            let session_id = active_thread.read(cx).thread.read(cx).session_id().clone();
+            Some(AgentPanelTabIdentity::AgentThread(session_id))
+        }
+        ActiveView::Uninitialized | ActiveView::History { .. } | ActiveView::Configuration => None,
+    }
+}
+
+pub fn find_tab_by_identity(
+    tabs: &[AgentPanelTab],
+    identity: &AgentPanelTabIdentity,
+) -> Option<TabId> {
+    tabs.iter()
+        .position(|tab| tab.identity.as_ref() == Some(identity))
+}
+
+/// Returns another tab showing the same thing as the tab at `tab_id`.
+pub fn duplicate_of_tab(tabs: &[AgentPanelTab], tab_id: TabId) -> Option<TabId> {
+    let identity = tabs.get(tab_id)?.identity.as_ref()?;
+    tabs.iter()
+        .enumerate()
+        .find(|(other_id, tab)| *other_id != tab_id && tab.identity.as_ref() == Some(identity))
+        .map(|(other_id, _)| other_id)
+}
+
+/// What opening an agent thread session should do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionOpen {
+    /// A tab already shows the session.
+    ActivateTab(TabId),
+    /// The session is already being opened, and its tab will show up once it's loaded.
+    Pending,
+    /// The session isn't open yet, and has now been recorded in `pending_opens`.
+    Open,
+}
+
+/// Decides how to open `session_id` given the open tabs and the sessions that are still
+/// being opened, so that opening a session twice in quick succession results in one tab.
+pub fn begin_session_open(
+    tabs: &[AgentPanelTab],
+    pending_opens: &mut HashSet<acp::SessionId>,
+    session_id: &acp::SessionId,
+) -> SessionOpen {
+    let identity = AgentPanelTabIdentity::AgentThread(session_id.clone());
+    if let Some(tab_id) = find_tab_by_identity(tabs, &identity) {
+        SessionOpen::ActivateTab(tab_id)
+    } else if pending_opens.insert(session_id.clone()) {
+        SessionOpen::Open
+    } else {
+        SessionOpen::Pending
+    }
+}
+
+/// Moves the tab of a text thread that was saved to `new_path` over to its new
+/// identity. Returns whether a tab was updated.
+pub fn migrate_text_thread_identity(
+    tabs: &mut [AgentPanelTab],
+    text_thread_id: EntityId,
+    old_path: Option<&Arc<Path>>,
+    new_path: Arc<Path>,
+) -> bool {
+    let old_identity = AgentPanelTabIdentity::for_text_thread(old_path, text_thread_id);
+    let Some(tab_id) = find_tab_by_identity(tabs, &old_identity) else {
+        return false;
+    };
+    tabs[tab_id].identity = Some(AgentPanelTabIdentity::TextThread(new_path));
+    true
+}
+
+/// Returns the tab to activate after the tab at `removed_id` was closed, or `None`
+/// when no tabs remain.
+pub fn active_tab_after_removal(
+    active_id: TabId,
+    removed_id: TabId,
+    remaining_tabs: usize,
+) -> Option<TabId> {
+    let last_id = remaining_tabs.checked_sub(1)?;
+    let active_id = if active_id >= removed_id {
+        active_id.saturating_sub(1)
+    } else {
+        active_id
+    };
+    Some(active_id.min(last_id))
+}
+
+/// Returns where the previously active tab ended up after the tab at `removed_id` was
+/// closed, or `None` when it was the closed tab or is now the active one.
+pub fn previous_tab_after_removal(
+    previous_id: Option<TabId>,
+    removed_id: TabId,
+    active_id: TabId,
+) -> Option<TabId> {
+    let previous_id = previous_id?;
+    let previous_id = match previous_id.cmp(&removed_id) {
+        std::cmp::Ordering::Less => previous_id,
+        std::cmp::Ordering::Equal => return None,
+        std::cmp::Ordering::Greater => previous_id - 1,
+    };
+    (previous_id != active_id).then_some(previous_id)
+}
+
+/// Which tabs "Close Others" and "Close to the Right" close, besides the clicked one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseTabsScope {
+    Others,
+    ToTheRight,
+}
+
+/// Returns the tabs `scope` closes relative to `tab_id`, last first, so that closing them
+/// in order doesn't shift the ids of the ones still to be closed.
+pub fn tabs_to_close(tab_id: TabId, tab_count: usize, scope: CloseTabsScope) -> Vec<TabId> {
+    if tab_id >= tab_count {
+        return Vec::new();
+    }
+    let first_id = match scope {
+        CloseTabsScope::Others => 0,
+        CloseTabsScope::ToTheRight => tab_id + 1,
+    };
+    (first_id..tab_count)
+        .rev()
+        .filter(|&id| id != tab_id)
+        .collect()
+}
+
+/// Returns the tab after `active_id`, wrapping around to the first one.
+pub fn next_tab_id(active_id: TabId, tab_count: usize) -> Option<TabId> {
+    if tab_count <= 1 {
+        return None;
+    }
+    Some(if active_id + 1 >= tab_count {
+        0
+    } else {
+        active_id + 1
+    })
+}
+
+/// Returns the tab before `active_id`, wrapping around to the last one.
+pub fn previous_tab_id(active_id: TabId, tab_count: usize) -> Option<TabId> {
+    if tab_count <= 1 {
+        return None;
+    }
+    Some(if active_id == 0 || active_id >= tab_count {
+        tab_count - 1
+    } else {
+        active_id - 1
+    })
+}
+
+/// Picks the detached threads to drop so that at most `max_threads` remain, oldest first.
+/// Threads that are still generating are never picked, even if that leaves more than
+/// `max_threads` around.
+pub fn detached_threads_to_evict<K>(
+    threads: impl IntoIterator<Item = (K, Instant, bool)>,
+    max_threads: usize,
+) -> Vec<K> {
+    let mut threads = threads.into_iter().collect::<Vec<_>>();
+    let excess = threads.len().saturating_sub(max_threads);
+    threads.sort_by_key(|(_, detached_at, _)| *detached_at);
+    threads
+        .into_iter()
+        .filter(|(_, _, is_generating)| !is_generating)
+        .take(excess)
+        .map(|(key, _, _)| key)
+        .collect()
+}
+
+/// What a tab's label is derived from, read out of its view without rendering anything.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum TabLabelSource {
+    AgentThread {
+        title: SharedString,
+        is_generating: bool,
+    },
+    TextThread {
+        title: SharedString,
+        summary: TextThreadSummary,
+        has_pending_message: bool,
+    },
+    History(HistoryKind),
+    Configuration,
+    Uninitialized,
+}
+
+impl TabLabelSource {
+    pub fn for_view(view: &ActiveView, cx: &App) -> Self {
+        match view {
+            ActiveView::ExternalAgentThread { thread_view } => {
+                let thread_view = thread_view.read(cx);
+                Self::AgentThread {
+                    title: thread_view.title(cx),
+                    is_generating: thread_view.as_active_thread().is_some_and(|active| {
+                        active.read(cx).thread.read(cx).status()
+                            == acp_thread::ThreadStatus::Generating
+                    }),
+                }
+            }
+            ActiveView::TextThread {
+                text_thread_editor, ..
+            } => {
+                let text_thread_editor = text_thread_editor.read(cx);
+                let text_thread = text_thread_editor.text_thread().read(cx);
+                Self::TextThread {
+                    title: text_thread_editor.title(cx),
+                    summary: text_thread.summary().clone(),
+                    has_pending_message: text_thread
+                        .messages(cx)
+                        .any(|message| matches!(message.status, MessageStatus::Pending)),
+                }
+            }
+            ActiveView::History { kind } => Self::History(*kind),
+            ActiveView::Configuration => Self::Configuration,
+            ActiveView::Uninitialized => Self::Uninitialized,
+        }
+    }
+
+    pub fn title(&self) -> SharedString {
+        match self {
+            Self::AgentThread { title, .. } => title.clone(),
+            Self::TextThread { title, summary, .. } => match summary {
+                TextThreadSummary::Pending => LOADING_SUMMARY_PLACEHOLDER.into(),
+                TextThreadSummary::Content(summary) if !summary.done => {
+                    LOADING_SUMMARY_PLACEHOLDER.into()
+                }
+                TextThreadSummary::Content(_) => title.clone(),
+                TextThreadSummary::Error => format!("⚠️ {title}").into(),
+            },
+            Self::History(HistoryKind::AgentThreads) => "History".into(),
+            Self::History(HistoryKind::TextThreads) => "Text Thread History".into(),
+            Self::Configuration => "Settings".into(),
+            Self::Uninitialized => "Agent".into(),
+        }
+    }
+
+    /// Whether the tab shows a thread that hasn't gotten a title or summary yet.
+    pub fn is_untitled(&self) -> bool {
+        match self {
+            Self::AgentThread { title, .. } => title.as_ref() == DEFAULT_THREAD_TITLE,
+            Self::TextThread { summary, .. } => matches!(summary, TextThreadSummary::Pending),
+            Self::History(_) | Self::Configuration | Self::Uninitialized => false,
+        }
+    }
+
+    /// Like `title`, but names an untitled thread after `untitled_index`, if given.
+    pub fn indexed_title(&self, untitled_index: Option<usize>) -> SharedString {
+        match untitled_index {
+            Some(index) if self.is_untitled() => format!("Agent {index}").into(),
+            _ => self.title(),
+        }
+    }
+
+    pub fn is_generating(&self) -> bool {
+        match self {
+            Self::AgentThread { is_generating, .. } => *is_generating,
+            Self::TextThread {
+                has_pending_message,
+                ..
+            } => *has_pending_message,
+            Self::History(_) | Self::Configuration | Self::Uninitialized => false,
+        }
+    }
+}
+
+/// The file backing a text thread, which its tab's tooltip shows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextThreadFile {
+    Saved(Arc<Path>),
+    Unsaved,
+}
+
+impl TextThreadFile {
+    /// Returns `None` when `view` isn't a text thread.
+    pub fn for_view(view: &ActiveView, cx: &App) -> Option<Self> {
+        let ActiveView::TextThread {
+            text_thread_editor, ..
+        } = view
+        else {
+            return None;
+        };
+        let text_thread = text_thread_editor.read(cx).text_thread().read(cx);
+        Some(match text_thread.path() {
+            Some(path) => Self::Saved(path.clone()),
+            None => Self::Unsaved,
+        })
+    }
+
+    /// Whether `RevealThreadFile` can reveal the file, which it can't before the thread is
+    /// saved.
+    pub fn can_reveal(&self) -> bool {
+        matches!(self, Self::Saved(_))
+    }
+
+    pub fn display_path(&self) -> SharedString {
+        match self {
+            Self::Saved(path) => path.compact().to_string_lossy().into_owned().into(),
+            Self::Unsaved => "(unsaved)".into(),
+        }
+    }
+}
+
 pub struct TabLabelRender {
     pub element: AnyElement,
     pub tooltip: Option<SharedString>,
+    /// A muted line shown under `tooltip`.
+    pub tooltip_meta: Option<SharedString>,
     pub is_generating: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn tab_with_identity(identity: Option<AgentPanelTabIdentity>) -> AgentPanelTab {
+        AgentPanelTab {
+            view: ActiveView::Uninitialized,
+            agent: AgentType::default(),
+            identity,
+            untitled_index: None,
+        }
+    }
+
+    #[test]
+    fn test_find_tab_by_identity_dedupes_unsaved_text_threads() {
+        let first = EntityId::from(1);
+        let second = EntityId::from(2);
+        let tabs = vec![
+            tab_with_identity(None),
+            tab_with_identity(Some(AgentPanelTabIdentity::for_text_thread(None, first))),
+            tab_with_identity(Some(AgentPanelTabIdentity::for_text_thread(None, second))),
+        ];
+
+        assert_eq!(
+            find_tab_by_identity(&tabs, &AgentPanelTabIdentity::UnsavedTextThread(first)),
+            Some(1)
+        );
+        assert_eq!(
+            find_tab_by_identity(&tabs, &AgentPanelTabIdentity::UnsavedTextThread(second)),
+            Some(2)
+        );
+        assert_eq!(
+            find_tab_by_identity(
+                &tabs,
+                &AgentPanelTabIdentity::UnsavedTextThread(EntityId::from(3))
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_saved_text_thread_identity_migrates_to_path() {
+        let text_thread_id = EntityId::from(1);
+        let path: Arc<Path> = Path::new("/threads/notes.zed.json").into();
+        let mut tabs = vec![
+            tab_with_identity(None),
+            tab_with_identity(Some(AgentPanelTabIdentity::UnsavedTextThread(
+                text_thread_id,
+            ))),
+        ];
+
+        assert!(migrate_text_thread_identity(
+            &mut tabs,
+            text_thread_id,
+            None,
+            path.clone()
+        ));
+        assert_eq!(
+            tabs[1].identity,
+            Some(AgentPanelTabIdentity::TextThread(path.clone()))
+        );
+        let saved = AgentPanelTabIdentity::for_text_thread(Some(&path), text_thread_id);
+        assert_eq!(find_tab_by_identity(&tabs, &saved), Some(1));
+        assert!(!migrate_text_thread_identity(
+            &mut tabs,
+            text_thread_id,
+            None,
+            path.clone()
+        ));
+
+        let renamed: Arc<Path> = Path::new("/threads/renamed.zed.json").into();
+        assert!(migrate_text_thread_identity(
+            &mut tabs,
+            text_thread_id,
+            Some(&path),
+            renamed.clone()
+        ));
+        assert_eq!(
+            tabs[1].identity,
+            Some(AgentPanelTabIdentity::TextThread(renamed))
+        );
+    }
+
+    #[test]
+    fn test_double_dispatched_session_open_results_in_one_tab() {
+        let session_id = acp::SessionId::new("session");
+        let mut pending_opens = HashSet::default();
+        let mut tabs = vec![tab_with_identity(None)];
+
+        assert_eq!(
+            begin_session_open(&tabs, &mut pending_opens, &session_id),
+            SessionOpen::Open
+        );
+        assert_eq!(
+            begin_session_open(&tabs, &mut pending_opens, &session_id),
+            SessionOpen::Pending
+        );
+
+        // The tab lands, taking the identity of the session it resumes.
+        pending_opens.remove(&session_id);
+        tabs.push(tab_with_identity(Some(AgentPanelTabIdentity::AgentThread(
+            session_id.clone(),
+        ))));
+        assert_eq!(
+            begin_session_open(&tabs, &mut pending_opens, &session_id),
+            SessionOpen::ActivateTab(1)
+        );
+        assert!(pending_opens.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_of_tab() {
+        let session_id = acp::SessionId::new("session");
+        let tabs = vec![
+            tab_with_identity(None),
+            tab_with_identity(Some(AgentPanelTabIdentity::AgentThread(session_id.clone()))),
+            tab_with_identity(None),
+            tab_with_identity(Some(AgentPanelTabIdentity::AgentThread(session_id))),
+        ];
+
+        assert_eq!(duplicate_of_tab(&tabs, 3), Some(1));
+        assert_eq!(duplicate_of_tab(&tabs, 1), Some(3));
+        assert_eq!(duplicate_of_tab(&tabs, 0), None);
+        assert_eq!(duplicate_of_tab(&tabs, 4), None);
+    }
+
+    #[test]
+    fn test_active_tab_after_removal() {
+        assert_eq!(active_tab_after_removal(2, 2, 2), Some(1));
+        assert_eq!(active_tab_after_removal(2, 0, 2), Some(1));
+        assert_eq!(active_tab_after_removal(0, 1, 2), Some(0));
+        assert_eq!(active_tab_after_removal(0, 0, 1), Some(0));
+        assert_eq!(active_tab_after_removal(5, 1, 2), Some(1));
+        assert_eq!(active_tab_after_removal(0, 0, 0), None);
+    }
+
+    #[test]
+    fn test_tabs_to_close() {
+        assert_eq!(tabs_to_close(1, 4, CloseTabsScope::Others), [3, 2, 0]);
+        assert_eq!(tabs_to_close(1, 4, CloseTabsScope::ToTheRight), [3, 2]);
+        assert!(tabs_to_close(3, 4, CloseTabsScope::ToTheRight).is_empty());
+        assert!(tabs_to_close(0, 1, CloseTabsScope::Others).is_empty());
+        assert!(tabs_to_close(4, 4, CloseTabsScope::Others).is_empty());
+
+        // Closing them one at a time leaves the clicked tab active when the active one was
+        // closed, and keeps the active one otherwise.
+        let active_after_closing = |active_id, tab_id, tab_count, scope| {
+            tabs_to_close(tab_id, tab_count, scope)
+                .into_iter()
+                .enumerate()
+                .try_fold(active_id, |active_id, (closed, removed_id)| {
+                    active_tab_after_removal(active_id, removed_id, tab_count - closed - 1)
+                })
+        };
+        assert_eq!(
+            active_after_closing(3, 1, 5, CloseTabsScope::Others),
+            Some(0)
+        );
+        assert_eq!(
+            active_after_closing(4, 1, 5, CloseTabsScope::ToTheRight),
+            Some(1)
+        );
+        assert_eq!(
+            active_after_closing(0, 1, 5, CloseTabsScope::ToTheRight),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_previous_tab_after_removal() {
+        assert_eq!(previous_tab_after_removal(None, 0, 0), None);
+        assert_eq!(previous_tab_after_removal(Some(1), 1, 0), None);
+        assert_eq!(previous_tab_after_removal(Some(0), 2, 1), Some(0));
+        assert_eq!(previous_tab_after_removal(Some(3), 1, 0), Some(2));
+        // The closed tab was between the two, so the previous tab became the active one.
+        assert_eq!(previous_tab_after_removal(Some(2), 1, 1), None);
+    }
+
+    #[test]
+    fn test_detached_threads_to_evict() {
+        let now = Instant::now();
+        let at = |seconds| now + Duration::from_secs(seconds);
+
+        assert_eq!(
+            detached_threads_to_evict([("a", at(0), false), ("b", at(1), false)], 2),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            detached_threads_to_evict(
+                [
+                    ("c", at(2), false),
+                    ("a", at(0), false),
+                    ("b", at(1), false)
+                ],
+                1
+            ),
+            ["a", "b"]
+        );
+        // Generating threads are skipped in favor of newer completed ones.
+        assert_eq!(
+            detached_threads_to_evict(
+                [("a", at(0), true), ("b", at(1), false), ("c", at(2), false)],
+                2
+            ),
+            ["b"]
+        );
+        assert_eq!(
+            detached_threads_to_evict([("a", at(0), true), ("b", at(1), true)], 0),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn test_tab_navigation_wraps_and_tolerates_empty_tab_list() {
+        assert_eq!(next_tab_id(0, 3), Some(1));
+        assert_eq!(next_tab_id(2, 3), Some(0));
+        assert_eq!(previous_tab_id(0, 3), Some(2));
+        assert_eq!(previous_tab_id(2, 3), Some(1));
+        assert_eq!(previous_tab_id(7, 3), Some(2));
+
+        assert_eq!(next_tab_id(0, 1), None);
+        assert_eq!(previous_tab_id(0, 1), None);
+        assert_eq!(next_tab_id(0, 0), None);
+        assert_eq!(previous_tab_id(0, 0), None);
+    }
+
+    #[test]
+    fn test_tab_label_source_title_and_generating_status() {
+        let agent_thread = |is_generating| TabLabelSource::AgentThread {
+            title: "Fix the build".into(),
+            is_generating,
+        };
+        assert_eq!(agent_thread(false).title(), "Fix the build");
+        assert!(!agent_thread(false).is_generating());
+        assert!(agent_thread(true).is_generating());
+
+        let text_thread = |summary, has_pending_message| TabLabelSource::TextThread {
+            title: "Notes".into(),
+            summary,
+            has_pending_message,
+        };
+        let content = |done| {
+            TextThreadSummary::Content(assistant_text_thread::TextThreadSummaryContent {
+                text: "Notes".into(),
+                done,
+                timestamp: clock::Lamport::MIN,
+            })
+        };
+        assert_eq!(
+            text_thread(TextThreadSummary::Pending, false).title(),
+            LOADING_SUMMARY_PLACEHOLDER
+        );
+        assert_eq!(
+            text_thread(content(false), false).title(),
+            LOADING_SUMMARY_PLACEHOLDER
+        );
+        assert_eq!(text_thread(content(true), false).title(), "Notes");
+        assert_eq!(
+            text_thread(TextThreadSummary::Error, false).title(),
+            "⚠️ Notes"
+        );
+        assert!(!text_thread(content(true), false).is_generating());
+        assert!(text_thread(content(true), true).is_generating());
+
+        for (source, title) in [
+            (
+                TabLabelSource::History(HistoryKind::AgentThreads),
+                "History",
+            ),
+            (
+                TabLabelSource::History(HistoryKind::TextThreads),
+                "Text Thread History",
+            ),
+            (TabLabelSource::Configuration, "Settings"),
+            (TabLabelSource::Uninitialized, "Agent"),
+        ] {
+            assert_eq!(source.title(), title);
+            assert!(!source.is_generating());
+        }
+    }
+
+    #[test]
+    fn test_text_thread_file_can_only_be_revealed_once_saved() {
+        let saved = TextThreadFile::Saved(Path::new("/threads/notes.zed.json").into());
+        assert!(saved.can_reveal());
+        assert_eq!(saved.display_path(), "/threads/notes.zed.json");
+
+        let unsaved = TextThreadFile::Unsaved;
+        assert!(!unsaved.can_reveal());
+        assert_eq!(unsaved.display_path(), "(unsaved)");
+    }
+
+    #[test]
+    fn test_indexed_title_names_untitled_threads() {
+        let agent_thread = |title: &'static str| TabLabelSource::AgentThread {
+            title: title.into(),
+            is_generating: false,
+        };
+        assert_eq!(
+            agent_thread(DEFAULT_THREAD_TITLE).indexed_title(Some(2)),
+            "Agent 2"
+        );
+        assert_eq!(
+            agent_thread(DEFAULT_THREAD_TITLE).indexed_title(None),
+            DEFAULT_THREAD_TITLE
+        );
+        assert_eq!(
+            agent_thread("Fix the build").indexed_title(Some(2)),
+            "Fix the build"
+        );
+
+        let text_thread = |summary| TabLabelSource::TextThread {
+            title: "Notes".into(),
+            summary,
+            has_pending_message: false,
+        };
+        assert_eq!(
+            text_thread(TextThreadSummary::Pending).indexed_title(Some(3)),
+            "Agent 3"
+        );
+        assert_eq!(
+            text_thread(TextThreadSummary::Error).indexed_title(Some(3)),
+            "⚠️ Notes"
+        );
+
+        assert_eq!(
+            TabLabelSource::Configuration.indexed_title(Some(1)),
+            "Settings"
+        );
+    }
+}
@@ -21,6 +21,7 @@ mod terminal_codegen;
 mod terminal_inline_assistant;
 mod text_thread_editor;
 mod text_thread_history;
+mod thread_checkpoint_picker;
 mod ui;
 
 use std::rc::Rc;
@@ -50,7 +51,9 @@ use std::any::TypeId;
 use workspace::Workspace;
 
 use crate::agent_configuration::{ConfigureContextServerModal, ManageProfilesModal};
-pub use crate::agent_panel::{AgentPanel, ConcreteAssistantPanelDelegate};
+pub use crate::agent_panel::{
+    AgentPanel, AgentTabSummary, ConcreteAssistantPanelDelegate, agent_tab_summaries,
+};
 use crate::agent_registry_ui::AgentRegistryPage;
 pub use crate::inline_assistant::InlineAssistant;
 pub use agent_diff::{AgentDiffPane, AgentDiffToolbar};
@@ -100,12 +103,39 @@ actions!(
         FocusRight,
         /// Opens the active thread as a markdown file.
         OpenActiveThreadAsMarkdown,
+        /// Opens the files mentioned in the active thread's messages as editor tabs.
+        OpenThreadReferencedFiles,
+        /// Reveals the file backing the active text thread in the project panel, or in the
+        /// system's file manager when it's outside the project.
+        RevealThreadFile,
         /// Opens the agent diff view to review changes.
         OpenAgentDiff,
         /// Copies the current thread to the clipboard as JSON for debugging.
         CopyThreadToClipboard,
         /// Loads a thread from the clipboard JSON for debugging.
         LoadThreadFromClipboard,
+        /// Exports every thread in the history into a chosen directory, one file per thread.
+        ExportAllThreads,
+        /// Imports the threads exported with `ExportAllThreads` from a chosen directory,
+        /// skipping the ones that are already in the history.
+        ImportAllThreads,
+        /// Starts a new thread with the clipboard text as its unsent first message,
+        /// or loads the thread if the clipboard contains an encoded one.
+        NewThreadFromClipboardText,
+        /// Opens a picker of the available slash commands and inserts the chosen one,
+        /// with its argument placeholders, into the active text thread.
+        InsertSlashCommand,
+        /// Re-sends the last message of the active thread with a different model.
+        /// Confirming the model with the secondary modifier sends it in a new tab instead.
+        RetryWithModel,
+        /// Renames the active thread through a prompt pre-filled with its current title.
+        RenameThread,
+        /// Saves the state of the active thread as a named checkpoint it can be restored to.
+        CheckpointThread,
+        /// Opens a picker of the active thread's checkpoints and restores the chosen one,
+        /// replacing the messages that came after it. The replaced state is saved as a
+        /// checkpoint first, so the restore can be undone.
+        RestoreCheckpoint,
         /// Keeps the current suggestion or change.
         Keep,
         /// Rejects the current suggestion or change.
@@ -148,6 +178,8 @@ actions!(
         ActivateNextTab,
         /// Activates the previous tab in the agent panel.
         ActivatePreviousTab,
+        /// Switches between the active tab and the one that was active before it.
+        ToggleLastActiveTab,
         /// Closes the currently active thread tab.
         CloseActiveThreadTab,
         /// Toggles the plan view in the thread.
@@ -207,6 +239,15 @@ pub struct NewNativeAgentThreadFromSummary {
     from_session_id: agent_client_protocol::SessionId,
 }
 
+/// Activates the agent panel tab showing the given thread session.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Action)]
+#[action(namespace = agent)]
+#[serde(deny_unknown_fields)]
+pub struct ActivateTabBySessionId {
+    /// The session of the thread whose tab to activate.
+    pub session_id: agent_client_protocol::SessionId,
+}
+
 // TODO unify this with AgentType
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -232,12 +273,46 @@ impl ExternalAgent {
             Self::Custom { name } => Rc::new(agent_servers::CustomAgentServer::new(name.clone())),
         }
     }
+
+    /// The agent that `server` runs. Servers not created by [`Self::server`] count as
+    /// custom agents.
+    pub(crate) fn for_server(server: &Rc<dyn agent_servers::AgentServer>) -> Self {
+        let any = server.clone().into_any();
+        if any.is::<agent::NativeAgentServer>() {
+            Self::NativeAgent
+        } else if any.is::<agent_servers::Gemini>() {
+            Self::Gemini
+        } else if any.is::<agent_servers::ClaudeCode>() {
+            Self::ClaudeCode
+        } else if any.is::<agent_servers::Codex>() {
+            Self::Codex
+        } else {
+            Self::Custom {
+                name: server.name(),
+            }
+        }
+    }
+
+    /// Whether a thread can be started with this agent, and otherwise why not. Only the
+    /// native agent can run in projects shared with you over collab.
+    pub fn availability(&self, is_via_collab: bool) -> Result<(), SharedString> {
+        if is_via_collab && *self != Self::NativeAgent {
+            Err(EXTERNAL_AGENTS_UNAVAILABLE_IN_SHARED_PROJECTS.into())
+        } else {
+            Ok(())
+        }
+    }
 }
 
+pub(crate) const EXTERNAL_AGENTS_UNAVAILABLE_IN_SHARED_PROJECTS: &str =
+    "External agents aren't available in shared projects";
+
 /// Content to initialize new external agent with.
 pub enum ExternalAgentInitialContent {
     ThreadSummary(acp_thread::AgentSessionInfo),
     Text(String),
+    /// A prompt that is sent as soon as the thread is ready.
+    SendPrompt(Vec<agent_client_protocol::ContentBlock>),
 }
 
 /// Opens the profile management interface for configuring agent tools and settings.
@@ -456,6 +531,13 @@ fn update_command_palette_filter(cx: &mut App) {
     });
 }
 
+/// Registers the agent panel's workspace actions, for tests whose clients were already
+/// set up with the text thread handlers that [`init`] would register a second time.
+#[cfg(any(test, feature = "test-support"))]
+pub fn init_agent_panel_for_tests(cx: &mut App) {
+    agent_panel::init(cx);
+}
+
 fn init_language_model_settings(cx: &mut App) {
     update_active_language_model_from_settings(cx);
 
@@ -596,6 +678,13 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions: Default::default(),
             show_turn_stats: false,
+            max_detached_threads: 5,
+            hide_single_tab_bar: false,
+            show_text_thread_tabs: true,
+            auto_index_untitled_tabs: false,
+            context_warning_threshold: 0.8,
+            context_summarize_threshold: 0.95,
+            ui_density: Default::default(),
         };
 
         cx.update(|cx| {
@@ -679,4 +768,30 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_external_agent_availability() {
+        let agents = [
+            ExternalAgent::NativeAgent,
+            ExternalAgent::Gemini,
+            ExternalAgent::ClaudeCode,
+            ExternalAgent::Codex,
+            ExternalAgent::Custom {
+                name: "custom".into(),
+            },
+        ];
+
+        for agent in &agents {
+            assert_eq!(agent.availability(false), Ok(()), "{agent:?}");
+        }
+
+        assert_eq!(ExternalAgent::NativeAgent.availability(true), Ok(()));
+        for agent in &agents[1..] {
+            assert_eq!(
+                agent.availability(true),
+                Err(EXTERNAL_AGENTS_UNAVAILABLE_IN_SHARED_PROJECTS.into()),
+                "{agent:?}"
+            );
+        }
+    }
 }
@@ -12,6 +12,7 @@ use crate::context::load_context;
 use crate::mention_set::MentionSet;
 use crate::{
     AgentPanel,
+    agent_configuration::ConfigurationFocusTarget,
     buffer_codegen::{BufferCodegen, CodegenAlternative, CodegenEvent},
     inline_prompt_editor::{CodegenStatus, InlineAssistId, PromptEditor, PromptEditorEvent},
     terminal_inline_assistant::TerminalInlineAssistant,
@@ -41,7 +42,9 @@ use gpui::{
     WeakEntity, Window, point,
 };
 use language::{Buffer, Point, Selection, TransactionId};
-use language_model::{ConfigurationError, ConfiguredModel, LanguageModelRegistry};
+use language_model::{
+    ConfigurationError, ConfiguredModel, LanguageModelProviderId, LanguageModelRegistry,
+};
 use multi_buffer::MultiBufferRow;
 use parking_lot::Mutex;
 use project::{CodeAction, DisableAiSettings, LspAction, Project, ProjectTransaction};
@@ -53,7 +56,6 @@ use text::{OffsetRangeExt, ToPoint as _};
 use ui::prelude::*;
 use util::{RangeExt, ResultExt, maybe};
 use workspace::{ItemHandle, Toast, Workspace, dock::Panel, notifications::NotificationId};
-use zed_actions::agent::OpenSettings;
 
 pub fn init(fs: Arc<dyn Fs>, prompt_builder: Arc<PromptBuilder>, cx: &mut App) {
     cx.set_global(InlineAssistant::new(fs, prompt_builder));
@@ -313,7 +315,8 @@ impl InlineAssistant {
                     handle_assist(window, cx);
                 }
             } else {
-                cx.spawn_in(window, async move |_, cx| {
+                let focus_target = configuration_focus_target(cx);
+                cx.spawn_in(window, async move |workspace, cx| {
                     let answer = cx
                         .prompt(
                             gpui::PromptLevel::Warning,
@@ -326,8 +329,14 @@ impl InlineAssistant {
                     if let Some(answer) = answer
                         && answer == 0
                     {
-                        cx.update(|window, cx| window.dispatch_action(Box::new(OpenSettings), cx))
-                            .ok();
+                        workspace.update_in(cx, |workspace, window, cx| {
+                            if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                                workspace.focus_panel::<AgentPanel>(window, cx);
+                                panel.update(cx, |panel, cx| {
+                                    panel.open_configuration(Some(focus_target), window, cx)
+                                });
+                            }
+                        })?;
                     }
                     anyhow::Ok(())
                 })
@@ -1710,6 +1719,21 @@ impl InlineAssistGroup {
     }
 }
 
+/// The part of the agent configuration to show when the inline assistant's model isn't
+/// available: the provider of the configured model, if any.
+fn configuration_focus_target(cx: &App) -> ConfigurationFocusTarget {
+    let settings = AgentSettings::get_global(cx);
+    settings
+        .inline_assistant_model
+        .as_ref()
+        .or(settings.default_model.as_ref())
+        .map_or(ConfigurationFocusTarget::General, |model| {
+            ConfigurationFocusTarget::Provider(LanguageModelProviderId::from(
+                model.provider.0.clone(),
+            ))
+        })
+}
+
 fn build_assist_editor_renderer(editor: &Entity<PromptEditor<BufferCodegen>>) -> RenderBlock {
     let editor = editor.clone();
 
@@ -1,9 +1,13 @@
 use crate::text_thread_editor::TextThreadEditor;
 use assistant_slash_command::SlashCommandWorkingSet;
-use gpui::{AnyElement, AnyView, DismissEvent, SharedString, Task, WeakEntity};
+use gpui::{
+    AnyElement, AnyView, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, SharedString,
+    Subscription, Task, WeakEntity,
+};
 use picker::{Picker, PickerDelegate, PickerEditorPosition};
 use std::sync::Arc;
 use ui::{ListItem, ListItemSpacing, PopoverMenu, PopoverTrigger, Tooltip, prelude::*};
+use workspace::{ModalView, Workspace};
 
 #[derive(IntoElement)]
 pub(super) struct SlashCommandSelector<T, TT>
@@ -49,6 +53,63 @@ pub(crate) struct SlashCommandDelegate {
     filtered_commands: Vec<SlashCommandEntry>,
     active_context_editor: WeakEntity<TextThreadEditor>,
     selected_index: usize,
+    insert_argument_placeholders: bool,
+}
+
+/// A modal listing the available slash commands, inserting the chosen one together
+/// with its argument placeholders into a text thread.
+pub(crate) struct SlashCommandPickerModal {
+    picker: Entity<Picker<SlashCommandDelegate>>,
+    _subscription: Subscription,
+}
+
+impl SlashCommandPickerModal {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        working_set: Arc<SlashCommandWorkingSet>,
+        text_thread_editor: WeakEntity<TextThreadEditor>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let commands = slash_command_entries(&working_set, cx);
+            let delegate = SlashCommandDelegate {
+                all_commands: commands.clone(),
+                filtered_commands: commands,
+                active_context_editor: text_thread_editor,
+                selected_index: 0,
+                insert_argument_placeholders: true,
+            };
+            let picker = cx.new(|cx| {
+                Picker::uniform_list(delegate, window, cx).max_height(Some(rems(20.).into()))
+            });
+            let subscription = cx.subscribe(&picker, |_, _, _: &DismissEvent, cx| {
+                cx.emit(DismissEvent);
+            });
+            Self {
+                picker,
+                _subscription: subscription,
+            }
+        });
+    }
+}
+
+impl ModalView for SlashCommandPickerModal {}
+
+impl EventEmitter<DismissEvent> for SlashCommandPickerModal {}
+
+impl Focusable for SlashCommandPickerModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SlashCommandPickerModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("SlashCommandPicker")
+            .child(self.picker.clone())
+    }
 }
 
 impl<T, TT> SlashCommandSelector<T, TT>
@@ -156,7 +217,15 @@ impl PickerDelegate for SlashCommandDelegate {
                 SlashCommandEntry::Info(info) => {
                     self.active_context_editor
                         .update(cx, |text_thread_editor, cx| {
-                            text_thread_editor.insert_command(&info.name, window, cx)
+                            match info
+                                .args
+                                .as_ref()
+                                .filter(|_| self.insert_argument_placeholders)
+                            {
+                                Some(args) => text_thread_editor
+                                    .insert_command_template(&info.name, args, window, cx),
+                                None => text_thread_editor.insert_command(&info.name, window, cx),
+                            }
                         })
                         .ok();
                 }
@@ -260,70 +329,14 @@ where
     TT: Fn(&mut Window, &mut App) -> AnyView + 'static,
 {
     fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
-        let all_models = self
-            .working_set
-            .featured_command_names(cx)
-            .into_iter()
-            .filter_map(|command_name| {
-                let command = self.working_set.command(&command_name, cx)?;
-                let menu_text = SharedString::from(Arc::from(command.menu_text()));
-                let label = command.label(cx);
-                let args = label.filter_range.end.ne(&label.text.len()).then(|| {
-                    SharedString::from(
-                        label.text[label.filter_range.end..label.text.len()].to_owned(),
-                    )
-                });
-                Some(SlashCommandEntry::Info(SlashCommandInfo {
-                    name: command_name.into(),
-                    description: menu_text,
-                    args,
-                    icon: command.icon(),
-                }))
-            })
-            .chain([SlashCommandEntry::Advert {
-                name: "create-your-command".into(),
-                renderer: |_, cx| {
-                    v_flex()
-                        .w_full()
-                        .child(
-                            h_flex()
-                                .w_full()
-                                .font_buffer(cx)
-                                .items_center()
-                                .justify_between()
-                                .child(
-                                    h_flex()
-                                        .items_center()
-                                        .gap_1p5()
-                                        .child(Icon::new(IconName::Plus).size(IconSize::XSmall))
-                                        .child(
-                                            Label::new("create-your-command")
-                                                .size(LabelSize::Small)
-                                                .buffer_font(cx),
-                                        ),
-                                )
-                                .child(
-                                    Icon::new(IconName::ArrowUpRight)
-                                        .size(IconSize::Small)
-                                        .color(Color::Muted),
-                                ),
-                        )
-                        .child(
-                            Label::new("Create your custom command")
-                                .size(LabelSize::Small)
-                                .color(Color::Muted),
-                        )
-                        .into_any_element()
-                },
-                on_confirm: |_, cx| cx.open_url("https://zed.dev/docs/extensions/slash-commands"),
-            }])
-            .collect::<Vec<_>>();
+        let all_models = slash_command_entries(&self.working_set, cx);
 
         let delegate = SlashCommandDelegate {
             all_commands: all_models.clone(),
             active_context_editor: self.active_context_editor.clone(),
             filtered_commands: all_models,
             selected_index: 0,
+            insert_argument_placeholders: false,
         };
 
         let picker_view = cx.new(|cx| {
@@ -346,3 +359,61 @@ where
             .when_some(handle, |this, handle| this.with_handle(handle))
     }
 }
+
+fn slash_command_entries(working_set: &SlashCommandWorkingSet, cx: &App) -> Vec<SlashCommandEntry> {
+    working_set
+        .featured_command_names(cx)
+        .into_iter()
+        .filter_map(|command_name| {
+            let command = working_set.command(&command_name, cx)?;
+            let menu_text = SharedString::from(Arc::from(command.menu_text()));
+            let label = command.label(cx);
+            let args = label.filter_range.end.ne(&label.text.len()).then(|| {
+                SharedString::from(label.text[label.filter_range.end..label.text.len()].to_owned())
+            });
+            Some(SlashCommandEntry::Info(SlashCommandInfo {
+                name: command_name.into(),
+                description: menu_text,
+                args,
+                icon: command.icon(),
+            }))
+        })
+        .chain([SlashCommandEntry::Advert {
+            name: "create-your-command".into(),
+            renderer: |_, cx| {
+                v_flex()
+                    .w_full()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .font_buffer(cx)
+                            .items_center()
+                            .justify_between()
+                            .child(
+                                h_flex()
+                                    .items_center()
+                                    .gap_1p5()
+                                    .child(Icon::new(IconName::Plus).size(IconSize::XSmall))
+                                    .child(
+                                        Label::new("create-your-command")
+                                            .size(LabelSize::Small)
+                                            .buffer_font(cx),
+                                    ),
+                            )
+                            .child(
+                                Icon::new(IconName::ArrowUpRight)
+                                    .size(IconSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        Label::new("Create your custom command")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element()
+            },
+            on_confirm: |_, cx| cx.open_url("https://zed.dev/docs/extensions/slash-commands"),
+        }])
+        .collect()
+}
@@ -486,28 +486,7 @@ impl TextThreadEditor {
         if let Some(command) = self.slash_commands.command(name, cx) {
             self.editor.update(cx, |editor, cx| {
                 editor.transact(window, cx, |editor, window, cx| {
-                    editor.change_selections(Default::default(), window, cx, |s| s.try_cancel());
-                    let snapshot = editor.buffer().read(cx).snapshot(cx);
-                    let newest_cursor = editor
-                        .selections
-                        .newest::<Point>(&editor.display_snapshot(cx))
-                        .head();
-                    if newest_cursor.column > 0
-                        || snapshot
-                            .chars_at(newest_cursor)
-                            .next()
-                            .is_some_and(|ch| ch != '\n')
-                    {
-                        editor.move_to_end_of_line(
-                            &MoveToEndOfLine {
-                                stop_at_soft_wraps: false,
-                            },
-                            window,
-                            cx,
-                        );
-                        editor.newline(&Newline, window, cx);
-                    }
-
+                    Self::start_command_line(editor, window, cx);
                     editor.insert(&format!("/{name}"), window, cx);
                     if command.accepts_arguments() {
                         editor.insert(" ", window, cx);
@@ -521,6 +500,65 @@ impl TextThreadEditor {
         }
     }
 
+    /// Inserts the command followed by its argument placeholders, selecting the
+    /// placeholders so typing replaces them.
+    pub fn insert_command_template(
+        &mut self,
+        name: &str,
+        arguments: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let placeholder = arguments.trim();
+        if placeholder.is_empty() || self.slash_commands.command(name, cx).is_none() {
+            self.insert_command(name, window, cx);
+            return;
+        }
+
+        self.editor.update(cx, |editor, cx| {
+            editor.transact(window, cx, |editor, window, cx| {
+                Self::start_command_line(editor, window, cx);
+                editor.insert(&format!("/{name} "), window, cx);
+                let start = editor
+                    .selections
+                    .newest::<Point>(&editor.display_snapshot(cx))
+                    .head();
+                editor.insert(placeholder, window, cx);
+                let end = editor
+                    .selections
+                    .newest::<Point>(&editor.display_snapshot(cx))
+                    .head();
+                editor.change_selections(Default::default(), window, cx, |selections| {
+                    selections.select_ranges([start..end])
+                });
+            });
+        });
+    }
+
+    fn start_command_line(editor: &mut Editor, window: &mut Window, cx: &mut Context<Editor>) {
+        editor.change_selections(Default::default(), window, cx, |s| s.try_cancel());
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let newest_cursor = editor
+            .selections
+            .newest::<Point>(&editor.display_snapshot(cx))
+            .head();
+        if newest_cursor.column > 0
+            || snapshot
+                .chars_at(newest_cursor)
+                .next()
+                .is_some_and(|ch| ch != '\n')
+        {
+            editor.move_to_end_of_line(
+                &MoveToEndOfLine {
+                    stop_at_soft_wraps: false,
+                },
+                window,
+                cx,
+            );
+            editor.newline(&Newline, window, cx);
+        }
+    }
+
     pub fn confirm_command(
         &mut self,
         _: &ConfirmCommand,
@@ -0,0 +1,180 @@
+use crate::acp::AcpServerView;
+use crate::agent_panel::AgentPanel;
+use agent::DbThreadCheckpoint;
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task, WeakEntity,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use workspace::ModalView;
+
+pub(crate) struct ThreadCheckpointDelegate {
+    panel: WeakEntity<AgentPanel>,
+    thread_view: Entity<AcpServerView>,
+    all_checkpoints: Vec<DbThreadCheckpoint>,
+    filtered_checkpoints: Vec<DbThreadCheckpoint>,
+    selected_index: usize,
+    local_timezone: UtcOffset,
+}
+
+/// A modal listing the checkpoints of an agent thread, restoring the thread to the
+/// chosen one.
+pub(crate) struct RestoreCheckpointModal {
+    picker: Entity<Picker<ThreadCheckpointDelegate>>,
+    _subscription: Subscription,
+}
+
+impl RestoreCheckpointModal {
+    pub(crate) fn new(
+        panel: WeakEntity<AgentPanel>,
+        thread_view: Entity<AcpServerView>,
+        checkpoints: Vec<DbThreadCheckpoint>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = ThreadCheckpointDelegate {
+            panel,
+            thread_view,
+            all_checkpoints: checkpoints.clone(),
+            filtered_checkpoints: checkpoints,
+            selected_index: 0,
+            local_timezone: UtcOffset::from_whole_seconds(
+                chrono::Local::now().offset().local_minus_utc(),
+            )
+            .unwrap(),
+        };
+        let picker = cx.new(|cx| {
+            Picker::uniform_list(delegate, window, cx).max_height(Some(rems(20.).into()))
+        });
+        let subscription = cx.subscribe(&picker, |_, _, _: &DismissEvent, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription: subscription,
+        }
+    }
+}
+
+impl ModalView for RestoreCheckpointModal {}
+
+impl EventEmitter<DismissEvent> for RestoreCheckpointModal {}
+
+impl Focusable for RestoreCheckpointModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for RestoreCheckpointModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RestoreCheckpointModal")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl PickerDelegate for ThreadCheckpointDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.filtered_checkpoints.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selected_index = ix.min(self.filtered_checkpoints.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Restore a checkpoint...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some(if self.all_checkpoints.is_empty() {
+            "This thread has no checkpoints yet.".into()
+        } else {
+            "No matching checkpoints.".into()
+        })
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let query = query.to_lowercase();
+        self.filtered_checkpoints = self
+            .all_checkpoints
+            .iter()
+            .filter(|checkpoint| checkpoint.label.to_lowercase().contains(&query))
+            .cloned()
+            .collect();
+        self.set_selected_index(0, window, cx);
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(checkpoint) = self.filtered_checkpoints.get(self.selected_index).cloned() else {
+            return;
+        };
+        let thread_view = self.thread_view.clone();
+        self.panel
+            .update(cx, |panel, cx| {
+                panel.restore_checkpoint(thread_view, checkpoint, window, cx)
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let checkpoint = self.filtered_checkpoints.get(ix)?;
+        let created_at = OffsetDateTime::from_unix_timestamp(checkpoint.created_at.timestamp())
+            .map(|created_at| {
+                time_format::format_localized_timestamp(
+                    created_at,
+                    OffsetDateTime::now_utc(),
+                    self.local_timezone,
+                    time_format::TimestampFormat::EnhancedAbsolute,
+                )
+            })
+            .unwrap_or_default();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_between()
+                        .child(Label::new(checkpoint.label.clone()).truncate())
+                        .child(
+                            Label::new(created_at)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
@@ -1,9 +1,18 @@
+use std::sync::Arc;
+
 use agent::SharedThread;
-use gpui::{BackgroundExecutor, TestAppContext};
+use agent_ui::{AgentPanel, NewExternalAgentThread};
+use call::ActiveCall;
+use gpui::{BackgroundExecutor, Entity, TestAppContext, VisualTestContext};
+use project::Project;
+use prompt_store::PromptBuilder;
 use rpc::proto;
+use serde_json::json;
+use util::path;
 use uuid::Uuid;
+use workspace::Workspace;
 
-use crate::TestServer;
+use crate::{TestClient, TestServer};
 
 #[gpui::test]
 async fn test_share_and_retrieve_thread(
@@ -212,3 +221,87 @@ async fn test_sync_imported_thread(
     // The synced thread should have the updated title.
     assert_eq!(synced_thread.title.as_ref(), "Updated Title");
 }
+
+#[gpui::test]
+async fn test_external_agents_unavailable_in_shared_project(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+
+    client_a
+        .fs()
+        .insert_tree(path!("/a"), json!({ "a.txt": "a" }))
+        .await;
+    let (project_a, _) = client_a.build_local_project(path!("/a"), cx_a).await;
+    let project_id = cx_a
+        .read(ActiveCall::global)
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    let new_claude_thread = || {
+        serde_json::from_value::<NewExternalAgentThread>(json!({ "agent": "claude_code" })).unwrap()
+    };
+
+    // The guest is told why external agents are unavailable, and no thread is opened.
+    let (workspace_b, panel_b, cx_b) = build_agent_panel(&client_b, &project_b, cx_b).await;
+    cx_b.dispatch_action(new_claude_thread());
+    cx_b.run_until_parked();
+    workspace_b.read_with(cx_b, |workspace, _| {
+        assert_eq!(workspace.notification_ids().len(), 1)
+    });
+    panel_b.read_with(cx_b, |panel, _| {
+        assert!(panel.active_thread_view_for_tests().is_none())
+    });
+
+    // The host can start external agents in the project they share.
+    let (workspace_a, panel_a, cx_a) = build_agent_panel(&client_a, &project_a, cx_a).await;
+    cx_a.dispatch_action(new_claude_thread());
+    cx_a.run_until_parked();
+    workspace_a.read_with(cx_a, |workspace, _| {
+        assert!(workspace.notification_ids().is_empty())
+    });
+    panel_a.read_with(cx_a, |panel, _| {
+        assert!(panel.active_thread_view_for_tests().is_some())
+    });
+}
+
+async fn build_agent_panel<'a>(
+    client: &'a TestClient,
+    project: &Entity<Project>,
+    cx: &'a mut TestAppContext,
+) -> (
+    Entity<Workspace>,
+    Entity<AgentPanel>,
+    &'a mut VisualTestContext,
+) {
+    // Loading the panel opens the prompt store on disk.
+    cx.executor().allow_parking();
+    cx.update(|cx| {
+        prompt_store::init(cx);
+        agent::ThreadStore::init_global(cx);
+        agent_ui::init_agent_panel_for_tests(cx);
+    });
+    let (workspace, cx) = client.build_workspace(project, cx);
+    let load_panel = workspace.update_in(cx, |workspace, window, cx| {
+        AgentPanel::load(
+            workspace.weak_handle(),
+            Arc::new(PromptBuilder::new(None).unwrap()),
+            window.to_async(cx),
+        )
+    });
+    let panel = load_panel.await.unwrap();
+    workspace.update_in(cx, |workspace, window, cx| {
+        workspace.add_panel(panel.clone(), window, cx)
+    });
+    cx.run_until_parked();
+    (workspace, panel, cx)
+}
@@ -7,7 +7,10 @@ use std::{
     time::Duration,
 };
 
-use command_palette_hooks::{CommandInterceptItem, CommandInterceptResult, CommandPaletteFilter};
+use command_palette_hooks::{
+    CommandInterceptItem, CommandInterceptResult, CommandPaletteFilter, CommandPaletteProviders,
+    ProvidedCommand,
+};
 
 use gpui::{
     Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
@@ -79,8 +82,16 @@ impl CommandPalette {
         };
 
         let entity = cx.weak_entity();
+        let provided_commands = CommandPaletteProviders::commands(workspace, cx);
         workspace.toggle_modal(window, cx, move |window, cx| {
-            CommandPalette::new(previous_focus_handle, query, entity, window, cx)
+            CommandPalette::new(
+                previous_focus_handle,
+                query,
+                entity,
+                provided_commands,
+                window,
+                cx,
+            )
         });
     }
 
@@ -88,12 +99,13 @@ impl CommandPalette {
         previous_focus_handle: FocusHandle,
         query: &str,
         workspace_entity: WeakEntity<Workspace>,
+        provided_commands: Vec<ProvidedCommand>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         let filter = CommandPaletteFilter::try_global(cx);
 
-        let commands = window
+        let mut commands: Vec<Command> = window
             .available_actions(cx)
             .into_iter()
             .filter_map(|action| {
@@ -103,10 +115,20 @@ impl CommandPalette {
                     Some(Command {
                         name: action.name().to_string(),
                         action: action.boxed_clone(),
+                        humanize: true,
                     })
                 }
             })
             .collect();
+        commands.extend(
+            provided_commands
+                .into_iter()
+                .map(|ProvidedCommand { name, action }| Command {
+                    name,
+                    action,
+                    humanize: false,
+                }),
+        );
 
         let delegate = CommandPaletteDelegate::new(
             cx.entity().downgrade(),
@@ -178,6 +200,18 @@ pub struct CommandPaletteDelegate {
 struct Command {
     name: String,
     action: Box<dyn Action>,
+    /// Whether `name` is an action name that has to be humanized before it's shown.
+    humanize: bool,
+}
+
+impl Command {
+    fn display_name(&self) -> String {
+        if self.humanize {
+            humanize_action_name(&self.name)
+        } else {
+            self.name.clone()
+        }
+    }
 }
 
 #[derive(Default)]
@@ -270,6 +304,7 @@ impl Clone for Command {
         Self {
             name: self.name.clone(),
             action: self.action.boxed_clone(),
+            humanize: self.humanize,
         }
     }
 }
@@ -323,6 +358,7 @@ impl CommandPaletteDelegate {
             commands.push(Command {
                 name: string.clone(),
                 action,
+                humanize: true,
             });
             new_matches.push(CommandMatch {
                 candidate_id: commands.len() - 1,
@@ -473,7 +509,7 @@ impl PickerDelegate for CommandPaletteDelegate {
 
                     for (ix, command) in commands.iter().enumerate() {
                         let candidate_lower = command.name.to_lowercase();
-                        let humanized_lower = humanize_action_name(&command.name).to_lowercase();
+                        let humanized_lower = command.display_name().to_lowercase();
 
                         // Check if all query words match either the original name or the humanized name
                         if words.iter().all(|word| {
@@ -631,7 +667,7 @@ impl PickerDelegate for CommandPaletteDelegate {
                         .py_px()
                         .justify_between()
                         .child(HighlightedLabel::new(
-                            humanize_action_name(&command.name),
+                            command.display_name(),
                             matching_command.positions.clone(),
                         ))
                         .child(KeyBinding::for_action_in(
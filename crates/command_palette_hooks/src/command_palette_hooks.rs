@@ -151,3 +151,42 @@ impl GlobalCommandPaletteInterceptor {
         Some(handler(query, workspace, cx))
     }
 }
+
+/// A command contributed to the command palette by a [`CommandPaletteProviders`] provider.
+pub struct ProvidedCommand {
+    /// The name to show in the command palette. Unlike action names, it is shown as is.
+    pub name: String,
+    /// The action dispatched when the command is confirmed.
+    pub action: Box<dyn Action>,
+}
+
+type CommandProvider = Rc<dyn Fn(&Workspace, &App) -> Vec<ProvidedCommand>>;
+
+/// Sources of commands that aren't registered actions, such as entries for open items,
+/// which are listed alongside the regular commands each time the command palette opens.
+#[derive(Default)]
+pub struct CommandPaletteProviders(Vec<CommandProvider>);
+
+impl Global for CommandPaletteProviders {}
+
+impl CommandPaletteProviders {
+    /// Registers a provider of commands for the command palette.
+    pub fn register(
+        cx: &mut App,
+        provider: impl Fn(&Workspace, &App) -> Vec<ProvidedCommand> + 'static,
+    ) {
+        cx.default_global::<Self>().0.push(Rc::new(provider));
+    }
+
+    /// Returns the commands of all registered providers for the given workspace.
+    pub fn commands(workspace: &Workspace, cx: &App) -> Vec<ProvidedCommand> {
+        let Some(providers) = cx.try_global::<Self>() else {
+            return Vec::new();
+        };
+        providers
+            .0
+            .iter()
+            .flat_map(|provider| provider(workspace, cx))
+            .collect()
+    }
+}
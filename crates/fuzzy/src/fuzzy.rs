@@ -2,6 +2,7 @@ mod char_bag;
 pub mod matcher;
 mod paths;
 mod strings;
+pub mod word_match;
 
 pub use char_bag::CharBag;
 pub use paths::{
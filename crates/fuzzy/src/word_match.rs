@@ -0,0 +1,748 @@
+use crate::{StringMatch, StringMatchCandidate, matcher};
+use gpui::BackgroundExecutor;
+use nucleo::{
+    Utf32Str,
+    pattern::{Atom, AtomKind, CaseMatching, Normalization},
+};
+use std::{
+    borrow::Borrow,
+    ops::Range,
+    path::Path,
+    sync::atomic::{self, AtomicBool},
+};
+
+/// Fewer candidates than this per thread are matched on the calling task, as splitting
+/// them up costs more than it saves.
+const MIN_CANDIDATES_PER_CHUNK: usize = 1024;
+
+/// How each word of the query is matched against the candidates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WordMatchMode {
+    /// A word matches where it appears as a whole.
+    #[default]
+    Substring,
+    /// A word matches where its characters appear in order, like `rcmd` in
+    /// `recent_command.rs`. Words are scored by the fuzzy matcher.
+    Fuzzy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WordMatchOptions {
+    /// Whether words are matched case-sensitively, typically set when the query contains
+    /// an uppercase letter.
+    pub smart_case: bool,
+    pub match_mode: WordMatchMode,
+    pub max_results: usize,
+    /// The factor the score of a word starting at a word boundary, like the start of the
+    /// candidate or a `/`, `_` or `-`, is multiplied by.
+    pub word_boundary_bonus: f64,
+    /// For candidates that are paths, the factor the score of a word found in the file name
+    /// is multiplied by. Such words are scored by their position within the file name, so
+    /// files named after the query rank above files in directories that are.
+    pub file_name_boost: Option<f64>,
+    /// The factor the score is multiplied by for each two consecutive words of the query
+    /// that are found in the same order in the candidate.
+    pub word_order_bonus: f64,
+    /// How much more two consecutive words found in order count the closer together they
+    /// are: adjacent words multiply the score by another `1 + proximity_bonus`, which
+    /// decreases with the number of letters and digits between them.
+    pub proximity_bonus: f64,
+}
+
+impl Default for WordMatchOptions {
+    fn default() -> Self {
+        Self {
+            smart_case: false,
+            match_mode: WordMatchMode::default(),
+            max_results: usize::MAX,
+            word_boundary_bonus: 1.,
+            file_name_boost: None,
+            word_order_bonus: 1.5,
+            proximity_bonus: 1.,
+        }
+    }
+}
+
+/// Matches strings with order-insensitive word matching: the query is split into words,
+/// and candidates containing all of them, in any order, match. A double-quoted phrase
+/// is a single word, so its words have to appear together, like `"foo bar" baz`.
+/// Candidates containing the words in the query's order, and close together, score higher.
+///
+/// Matches are ranked by their score, then by the shorter string, then by the lower
+/// candidate id. A blank query matches every candidate with a score of zero, in order.
+pub async fn match_strings_order_insensitive<T>(
+    candidates: &[T],
+    query: &str,
+    options: &WordMatchOptions,
+    cancel_flag: &AtomicBool,
+    executor: BackgroundExecutor,
+) -> Vec<StringMatch>
+where
+    T: Borrow<StringMatchCandidate> + Sync,
+{
+    if candidates.is_empty() || options.max_results == 0 {
+        return Default::default();
+    }
+
+    let words = query_words(query);
+    if words.is_empty() {
+        return candidates
+            .iter()
+            .map(|candidate| StringMatch {
+                candidate_id: candidate.borrow().id,
+                score: 0.,
+                positions: Default::default(),
+                string: candidate.borrow().string.clone(),
+            })
+            .collect();
+    }
+
+    let atoms = match options.match_mode {
+        WordMatchMode::Substring => Vec::new(),
+        WordMatchMode::Fuzzy => fuzzy_atoms(&words, options.smart_case),
+    };
+    let chunk_size = candidates
+        .len()
+        .div_ceil(executor.num_cpus())
+        .max(MIN_CANDIDATES_PER_CHUNK);
+    let mut results = if candidates.len() <= chunk_size {
+        match_chunk(candidates, &words, &atoms, options, cancel_flag)
+    } else {
+        let mut chunk_results = vec![Vec::new(); candidates.len().div_ceil(chunk_size)];
+        executor
+            .scoped(|scope| {
+                for (chunk, results) in candidates.chunks(chunk_size).zip(&mut chunk_results) {
+                    let (words, atoms) = (&words, &atoms);
+                    scope.spawn(async move {
+                        *results = match_chunk(chunk, words, atoms, options, cancel_flag);
+                    });
+                }
+            })
+            .await;
+        chunk_results.into_iter().flatten().collect()
+    };
+
+    results.sort_unstable_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.string.len().cmp(&b.string.len()))
+            .then(a.candidate_id.cmp(&b.candidate_id))
+    });
+    results.truncate(options.max_results);
+    results
+}
+
+/// Splits `query` into whitespace-separated words, keeping double-quoted phrases whole,
+/// including their whitespace. A quote without a closing one extends to the end.
+fn query_words(query: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut rest = query;
+    loop {
+        rest = rest.trim_start();
+        let Some(first) = rest.chars().next() else {
+            break;
+        };
+        let (word, remainder) = if first == '"' {
+            let phrase = &rest[1..];
+            match phrase.find('"') {
+                Some(end) => (&phrase[..end], &phrase[end + 1..]),
+                None => (phrase, ""),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '"')
+                .unwrap_or(rest.len());
+            rest.split_at(end)
+        };
+        if !word.is_empty() {
+            words.push(word);
+        }
+        rest = remainder;
+    }
+    words
+}
+
+/// The words of the query as fuzzy patterns. Phrases keep their whitespace.
+fn fuzzy_atoms(words: &[&str], smart_case: bool) -> Vec<Atom> {
+    let case_matching = if smart_case {
+        CaseMatching::Respect
+    } else {
+        CaseMatching::Ignore
+    };
+    words
+        .iter()
+        .map(|word| {
+            Atom::new(
+                word,
+                case_matching,
+                Normalization::Smart,
+                AtomKind::Fuzzy,
+                false,
+            )
+        })
+        .collect()
+}
+
+fn match_chunk<T>(
+    candidates: &[T],
+    words: &[&str],
+    atoms: &[Atom],
+    options: &WordMatchOptions,
+    cancel_flag: &AtomicBool,
+) -> Vec<StringMatch>
+where
+    T: Borrow<StringMatchCandidate>,
+{
+    let mut fuzzy_matcher = (options.match_mode == WordMatchMode::Fuzzy)
+        .then(|| matcher::get_matcher(nucleo::Config::DEFAULT));
+    let mut results = Vec::new();
+    for candidate in candidates {
+        if cancel_flag.load(atomic::Ordering::Relaxed) {
+            break;
+        }
+        let candidate = candidate.borrow();
+        let string_match = match fuzzy_matcher.as_mut() {
+            Some(fuzzy_matcher) => match_candidate_fuzzy(candidate, atoms, options, fuzzy_matcher),
+            None => match_candidate(candidate, words, options),
+        };
+        if let Some(string_match) = string_match {
+            results.push(string_match);
+        }
+    }
+    if let Some(fuzzy_matcher) = fuzzy_matcher {
+        matcher::return_matcher(fuzzy_matcher);
+    }
+    results
+}
+
+fn match_candidate(
+    candidate: &StringMatchCandidate,
+    words: &[&str],
+    options: &WordMatchOptions,
+) -> Option<StringMatch> {
+    let string = &candidate.string;
+    let find = |haystack: &str, word: &str| {
+        if options.smart_case {
+            haystack.find(word).map(|start| start..start + word.len())
+        } else {
+            find_case_insensitive(haystack, word)
+        }
+    };
+    let file_name_start = options
+        .file_name_boost
+        .map(|boost| (file_name_start(string), boost));
+
+    let mut total_score = 0.0;
+    let mut positions = Vec::new();
+    let mut ranges = Vec::with_capacity(words.len());
+    for word in words {
+        let file_name_match = file_name_start.and_then(|(start, boost)| {
+            let range = find(&string[start..], word)?;
+            // Within the file name, the position counts from its start.
+            let position_score = boost / (range.start as f64 + 1.0);
+            Some((range.start + start..range.end + start, position_score))
+        });
+        let (range, position_score) = match file_name_match {
+            Some(file_name_match) => file_name_match,
+            None => {
+                let range = find(string, word)?;
+                let position_score = 1.0 / (range.start as f64 + 1.0);
+                (range, position_score)
+            }
+        };
+
+        // Calculate a simple score based on position and word length
+        let mut word_score = position_score * (range.len() as f64 / string.len() as f64);
+        if is_word_boundary(string, range.start) {
+            word_score *= options.word_boundary_bonus;
+        }
+        total_score += word_score;
+
+        // Only the occurrence that was scored is highlighted, so the highlights show why
+        // the candidate ranks where it does. Every char of it is, by the byte index it
+        // starts at, which keeps multi-byte chars whole.
+        positions.extend(
+            string[range.clone()]
+                .char_indices()
+                .map(|(offset, _)| range.start + offset),
+        );
+        ranges.push(range);
+    }
+    positions.sort_unstable();
+    positions.dedup();
+
+    let score = total_score / words.len() as f64; // Average score across words
+    Some(StringMatch {
+        candidate_id: candidate.id,
+        score: score * word_order_factor(string, &ranges, options),
+        positions,
+        string: string.clone(),
+    })
+}
+
+/// Like [`match_candidate`], with each word matched by the fuzzy matcher and scored by it.
+fn match_candidate_fuzzy(
+    candidate: &StringMatchCandidate,
+    atoms: &[Atom],
+    options: &WordMatchOptions,
+    fuzzy_matcher: &mut nucleo::Matcher,
+) -> Option<StringMatch> {
+    let string = &candidate.string;
+    let mut buf = Vec::new();
+    let haystack = Utf32Str::new(string, &mut buf);
+    // The matcher reports char indices, which are converted to byte offsets. The end of
+    // the string comes last, where a match ending with the last char ends.
+    let char_offsets = string
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([string.len()])
+        .collect::<Vec<_>>();
+    let file_name_start = options
+        .file_name_boost
+        .map(|boost| (file_name_start(string), boost));
+
+    let mut total_score = 0.0;
+    let mut positions = Vec::new();
+    let mut ranges = Vec::with_capacity(atoms.len());
+    let mut indices = Vec::new();
+    for atom in atoms {
+        indices.clear();
+        let score = atom.indices(haystack, fuzzy_matcher, &mut indices)?;
+        let (Some(&first), Some(&last)) = (indices.iter().min(), indices.iter().max()) else {
+            continue;
+        };
+        let (first, end) = (
+            char_offsets[first as usize],
+            char_offsets[last as usize + 1],
+        );
+
+        let mut word_score = score as f64;
+        if let Some((start, boost)) = file_name_start
+            && first >= start
+        {
+            word_score *= boost;
+        }
+        if is_word_boundary(string, first) {
+            word_score *= options.word_boundary_bonus;
+        }
+        total_score += word_score;
+        positions.extend(indices.iter().map(|&ix| char_offsets[ix as usize]));
+        ranges.push(first..end);
+    }
+    positions.sort_unstable();
+    positions.dedup();
+
+    let score = total_score / atoms.len() as f64;
+    Some(StringMatch {
+        candidate_id: candidate.id,
+        score: score * word_order_factor(string, &ranges, options),
+        positions,
+        string: string.clone(),
+    })
+}
+
+/// The factor the score of a candidate is multiplied by for the words matched at `ranges`,
+/// in the query's order, that follow each other in the candidate too. Only letters and
+/// digits count as the distance between two words, so `zed/docs` has them adjacent.
+fn word_order_factor(string: &str, ranges: &[Range<usize>], options: &WordMatchOptions) -> f64 {
+    ranges
+        .windows(2)
+        .filter(|pair| pair[0].end <= pair[1].start)
+        .map(|pair| {
+            let distance = string[pair[0].end..pair[1].start]
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .count();
+            options.word_order_bonus * (1. + options.proximity_bonus / (distance as f64 + 1.))
+        })
+        .product()
+}
+
+/// The byte index where the file name of `path` starts.
+pub fn file_name_start(path: &str) -> usize {
+    let file_name_len = Path::new(path)
+        .file_name()
+        .map_or(0, |file_name| file_name.len());
+    path.len().saturating_sub(file_name_len)
+}
+
+fn is_word_boundary(string: &str, ix: usize) -> bool {
+    string[..ix]
+        .chars()
+        .next_back()
+        .is_none_or(|previous| !previous.is_alphanumeric())
+}
+
+/// Finds the first case-insensitive occurrence of `needle` in `haystack`, returning its byte
+/// range in `haystack` itself. Lowercasing can change a character's length (e.g. `İ`), so
+/// searching a lowercased copy would yield offsets that don't line up with the original.
+fn find_case_insensitive(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    let needle = needle
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    if needle.is_empty() {
+        return Some(0..0);
+    }
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut needle_chars = needle.iter();
+        for (offset, ch) in haystack[start..].char_indices() {
+            for lowercase_ch in ch.to_lowercase() {
+                if needle_chars.next() != Some(&lowercase_ch) {
+                    return None;
+                }
+            }
+            if needle_chars.len() == 0 {
+                return Some(start..start + offset + ch.len_utf8());
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    fn candidates_from(strings: &[&str]) -> Vec<StringMatchCandidate> {
+        strings
+            .iter()
+            .enumerate()
+            .map(|(id, string)| StringMatchCandidate::new(id, string))
+            .collect()
+    }
+
+    async fn matched_ids(
+        candidates: &[StringMatchCandidate],
+        query: &str,
+        options: &WordMatchOptions,
+        cx: &TestAppContext,
+    ) -> Vec<usize> {
+        let cancel_flag = AtomicBool::new(false);
+        match_strings_order_insensitive(candidates, query, options, &cancel_flag, cx.executor())
+            .await
+            .iter()
+            .map(|string_match| string_match.candidate_id)
+            .collect()
+    }
+
+    fn highlighted(string_match: &StringMatch) -> String {
+        string_match
+            .positions
+            .iter()
+            .map(|&position| string_match.string[position..].chars().next().unwrap())
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_multi_word_queries(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&[
+            "crates/search/src/buffer_search.rs",
+            "crates/search/src/project_search.rs",
+            "crates/editor/src/editor.rs",
+        ]);
+        let options = WordMatchOptions::default();
+
+        // Every word has to match, in any order.
+        assert_eq!(
+            matched_ids(&candidates, "search buffer", &options, cx).await,
+            [0]
+        );
+        assert_eq!(
+            matched_ids(&candidates, "src search", &options, cx).await,
+            [0, 1]
+        );
+        assert!(
+            matched_ids(&candidates, "search editor", &options, cx)
+                .await
+                .is_empty()
+        );
+        // A blank query matches everything, in order.
+        assert_eq!(matched_ids(&candidates, "", &options, cx).await, [0, 1, 2]);
+        assert_eq!(
+            matched_ids(&candidates, "  ", &options, cx).await,
+            [0, 1, 2]
+        );
+
+        let limited = WordMatchOptions {
+            max_results: 1,
+            ..options
+        };
+        assert_eq!(matched_ids(&candidates, "search", &limited, cx).await, [0]);
+    }
+
+    #[gpui::test]
+    async fn test_quoted_phrases(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&["x/foo bar/baz", "x/bar foo/baz", "x/foo/bar/baz"]);
+        let options = WordMatchOptions::default();
+
+        // A quoted phrase only matches where its words are adjacent.
+        assert_eq!(
+            matched_ids(&candidates, "\"foo bar\" baz", &options, cx).await,
+            [0]
+        );
+        assert_eq!(
+            matched_ids(&candidates, "baz \"bar foo\"", &options, cx).await,
+            [1]
+        );
+        // Unquoted, the words match independently, those in the query's order first.
+        assert_eq!(
+            matched_ids(&candidates, "foo bar baz", &options, cx).await,
+            [0, 2, 1]
+        );
+
+        let cancel_flag = AtomicBool::new(false);
+        let matches = match_strings_order_insensitive(
+            &candidates,
+            "\"foo bar\"",
+            &options,
+            &cancel_flag,
+            cx.executor(),
+        )
+        .await;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(highlighted(&matches[0]), "foo bar");
+
+        assert_eq!(query_words("\"foo bar\" baz"), ["foo bar", "baz"]);
+        assert_eq!(query_words("src\"a b\"c"), ["src", "a b", "c"]);
+        assert_eq!(
+            query_words("  \"unterminated phrase"),
+            ["unterminated phrase"]
+        );
+        assert!(query_words("\"\" ").is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_fuzzy_mode(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&[
+            "crates/recent_command.rs",
+            "palette/command.rs",
+            "crates/Recent/main.rs",
+        ]);
+        let substring = WordMatchOptions::default();
+        let fuzzy = WordMatchOptions {
+            match_mode: WordMatchMode::Fuzzy,
+            ..substring
+        };
+
+        assert!(
+            matched_ids(&candidates, "rcmd", &substring, cx)
+                .await
+                .is_empty()
+        );
+        assert_eq!(matched_ids(&candidates, "rcmd", &fuzzy, cx).await, [0]);
+        // Words still match in any order, each on its own.
+        assert_eq!(matched_ids(&candidates, "cmd rct", &fuzzy, cx).await, [0]);
+        assert!(
+            matched_ids(&candidates, "rcmd plt", &fuzzy, cx)
+                .await
+                .is_empty()
+        );
+        // Smart case applies to fuzzy words too.
+        let sensitive = WordMatchOptions {
+            smart_case: true,
+            ..fuzzy
+        };
+        assert_eq!(matched_ids(&candidates, "Rcnt", &sensitive, cx).await, [2]);
+
+        let cancel_flag = AtomicBool::new(false);
+        let matches = match_strings_order_insensitive(
+            &candidates,
+            "rcmd",
+            &fuzzy,
+            &cancel_flag,
+            cx.executor(),
+        )
+        .await;
+        assert_eq!(highlighted(&matches[0]), "rcmd");
+    }
+
+    #[gpui::test]
+    async fn test_unicode_candidates(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&["/trips/İstanbul/Ärzte"]);
+        let cancel_flag = AtomicBool::new(false);
+        let matches = match_strings_order_insensitive(
+            &candidates,
+            "ärzte stanbul",
+            &WordMatchOptions::default(),
+            &cancel_flag,
+            cx.executor(),
+        )
+        .await;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(highlighted(&matches[0]), "stanbulÄrzte");
+
+        let path = "/Users/dima/Prójects/日本語/main.rs";
+        let candidates = candidates_from(&[path]);
+        let matches = match_strings_order_insensitive(
+            &candidates,
+            "main 日本 PRÓJECTS",
+            &WordMatchOptions::default(),
+            &cancel_flag,
+            cx.executor(),
+        )
+        .await;
+        let positions = &matches[0].positions;
+        assert!(
+            positions
+                .iter()
+                .all(|&position| path.is_char_boundary(position))
+        );
+        assert_eq!(highlighted(&matches[0]), "Prójects日本main");
+        assert_eq!(positions.len(), "Prójects日本main".chars().count());
+        // The file name's highlights start right where the file name does.
+        let file_name_positions = positions
+            .iter()
+            .filter(|&&position| position >= file_name_start(path))
+            .map(|&position| position - file_name_start(path))
+            .collect::<Vec<_>>();
+        assert_eq!(file_name_positions, [0, 1, 2, 3]);
+
+        let lowercase_istanbul = "İstanbul".to_lowercase();
+        assert_eq!(
+            find_case_insensitive("/trips/İstanbul", &lowercase_istanbul),
+            Some(7..16)
+        );
+        assert_eq!(find_case_insensitive("Straße", "STRASSE"), None);
+        assert_eq!(find_case_insensitive("Éclair", "écl"), Some(0..4));
+    }
+
+    #[gpui::test]
+    async fn test_smart_case(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&["src/Editor.rs", "src/editor.rs"]);
+        let insensitive = WordMatchOptions::default();
+        let sensitive = WordMatchOptions {
+            smart_case: true,
+            ..insensitive
+        };
+
+        assert_eq!(
+            matched_ids(&candidates, "Editor", &insensitive, cx).await,
+            [0, 1]
+        );
+        assert_eq!(
+            matched_ids(&candidates, "Editor", &sensitive, cx).await,
+            [0]
+        );
+        assert_eq!(
+            matched_ids(&candidates, "editor", &sensitive, cx).await,
+            [1]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_file_name_boost_and_word_boundary_bonus(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&["/main-service/src/lib.rs", "/work/src/main.rs"]);
+        let options = WordMatchOptions::default();
+        // Without weights, the earlier occurrence in the directory wins.
+        assert_eq!(matched_ids(&candidates, "main", &options, cx).await, [0, 1]);
+
+        let boosted = WordMatchOptions {
+            file_name_boost: Some(2.),
+            ..options
+        };
+        assert_eq!(matched_ids(&candidates, "main", &boosted, cx).await, [1, 0]);
+
+        let candidates = candidates_from(&["/domain/x.rs", "/src/main.rs"]);
+        assert_eq!(matched_ids(&candidates, "main", &options, cx).await, [0, 1]);
+        let with_bonus = WordMatchOptions {
+            word_boundary_bonus: 2.,
+            ..options
+        };
+        assert_eq!(
+            matched_ids(&candidates, "main", &with_bonus, cx).await,
+            [1, 0]
+        );
+
+        // Only the occurrence that was scored is highlighted.
+        let candidates = candidates_from(&["/main/src/main.rs"]);
+        let cancel_flag = AtomicBool::new(false);
+        for (options, highlighted_range) in [(options, 1..5), (boosted, 10..14)] {
+            let matches = match_strings_order_insensitive(
+                &candidates,
+                "main",
+                &options,
+                &cancel_flag,
+                cx.executor(),
+            )
+            .await;
+            assert_eq!(matches[0].positions, highlighted_range.collect::<Vec<_>>());
+        }
+
+        assert_eq!(file_name_start("/a/main.rs"), 3);
+        assert_eq!(file_name_start("main.rs"), 0);
+    }
+
+    #[gpui::test]
+    async fn test_word_order_and_proximity(cx: &mut TestAppContext) {
+        let candidates = candidates_from(&[
+            "~/config/backup/zed-old/docs-archive",
+            "~/Developer/zed/docs/configuring.md",
+        ]);
+        let options = WordMatchOptions::default();
+        // Both contain every word, but only the second one in order and adjacent.
+        assert_eq!(
+            matched_ids(&candidates, "zed docs config", &options, cx).await,
+            [1, 0]
+        );
+        // A reordered query no longer favors it.
+        assert_eq!(
+            matched_ids(&candidates, "config docs zed", &options, cx).await,
+            [0, 1]
+        );
+
+        // Without the bonuses, the earlier occurrence of `config` wins.
+        let unordered = WordMatchOptions {
+            word_order_bonus: 1.,
+            proximity_bonus: 0.,
+            ..options
+        };
+        assert_eq!(
+            matched_ids(&candidates, "zed docs config", &unordered, cx).await,
+            [0, 1]
+        );
+
+        let string = "zed/docs x config";
+        let ranges = [0..3, 4..8, 11..17];
+        assert_eq!(
+            word_order_factor(string, &ranges, &options),
+            1.5 * 2. * 1.5 * 1.5
+        );
+        assert_eq!(word_order_factor(string, &[4..8, 0..3], &options), 1.);
+        assert_eq!(word_order_factor(string, &ranges[..1], &options), 1.);
+    }
+
+    #[gpui::test]
+    async fn test_cancellation(cx: &mut TestAppContext) {
+        let strings = (0..MIN_CANDIDATES_PER_CHUNK * 4)
+            .map(|ix| format!("/project/file_{ix}.rs"))
+            .collect::<Vec<_>>();
+        let candidates = strings
+            .iter()
+            .enumerate()
+            .map(|(id, string)| StringMatchCandidate::new(id, string))
+            .collect::<Vec<_>>();
+
+        // Enough candidates are split into chunks on multiple threads, which find the same
+        // matches as a single one.
+        let options = WordMatchOptions::default();
+        let ids = matched_ids(&candidates, "file_1", &options, cx).await;
+        assert_eq!(
+            ids.len(),
+            strings.iter().filter(|s| s.contains("file_1")).count()
+        );
+        assert_eq!(ids[0], 1);
+
+        let cancel_flag = AtomicBool::new(true);
+        let matches = match_strings_order_insensitive(
+            &candidates,
+            "file",
+            &options,
+            &cancel_flag,
+            cx.executor(),
+        )
+        .await;
+        assert!(matches.is_empty());
+    }
+}
@@ -1,196 +1,683 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeDelta};
+use editor::Editor;
 use file_icons::FileIcons;
-use fuzzy::{StringMatch, StringMatchCandidate};
+use fuzzy::{
+    StringMatch, StringMatchCandidate,
+    word_match::{WordMatchMode, WordMatchOptions, match_strings_order_insensitive},
+};
+use git::status::FileStatus;
 use gpui::{
-    App, AsyncApp, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
-    Subscription, Task, UniformListScrollHandle, WeakEntity, Window,
+    AnyElement, App, AsyncApp, AsyncWindowContext, BackgroundExecutor, ClipboardItem, Context,
+    DismissEvent, Entity, EntityId, EventEmitter, FocusHandle, Focusable, Image, ImageFormat,
+    ObjectFit, StyledImage, Subscription, Task, UniformListScrollHandle, WeakEntity, Window,
+    actions, img,
 };
 use gpui::{Pixels, px};
+use language::{Buffer, Language, LanguageRegistry, language_settings::SoftWrap};
 
+use collections::{HashMap, HashSet};
 use parking_lot::Mutex;
 use picker::{Picker, PickerDelegate};
+use project::{Fs, Project, WorktreeSettings};
+use settings::{RegisterSetting, Settings};
 use std::{
     borrow::Cow,
+    ffi::OsString,
+    mem,
     ops::Range,
     path::{Component, Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{self, AtomicBool, AtomicUsize},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use text::{Bias, Point};
+use ui::{
+    HighlightedLabel, Indicator, ListItem, ListItemSpacing, ListSubHeader, Tooltip, prelude::*,
+};
+use util::{
+    ResultExt as _,
+    paths::{PathExt, PathMatcher, PathStyle},
 };
-use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
-use util::paths::PathExt;
 use workspace::{
-    self, ModalView, PathList, SerializedWorkspaceLocation, WORKSPACE_DB, Workspace, WorkspaceId,
-    with_active_or_new_workspace,
+    self, ModalView, PathList, SerializedWorkspaceLocation, SplitDirection, Toast, WORKSPACE_DB,
+    Workspace, WorkspaceId, notifications::NotificationId, with_active_or_new_workspace,
 };
-use zed_actions::{OpenFileFromDirectory, OpenRecentFile};
+use zed_actions::{OpenFileFromDirectory, OpenRecentFile, workspace::OpenRecentlyClosedBuffers};
+
+actions!(
+    recent_files,
+    [
+        /// Copies the absolute path of the selected recent file.
+        CopyAbsolutePath,
+        /// Copies the path of the selected recent file relative to the root of the
+        /// workspace containing it.
+        CopyPathRelativeToWorkspaceRoot,
+        /// Copies the path of the selected recent file relative to the directory of the
+        /// file open in the active editor.
+        CopyPathRelativeToCurrentFile,
+        /// Opens the selected recent file in a split to the right of the active pane. Files
+        /// of other workspaces open in their workspace, as when confirming.
+        OpenInSplit,
+        /// Toggles whether only the files within the current workspace's worktrees are
+        /// listed.
+        ToggleCurrentWorkspaceOnly,
+        /// Checks or unchecks the selected recent file. Confirming opens all checked files.
+        ToggleChecked,
+        /// Forgets all recent files, after confirming.
+        ClearRecentFiles,
+    ]
+);
+
+/// How many places down the recent files list a word match's score halves, so that equally
+/// good matches rank by how recently their files were opened.
+const RECENCY_HALF_LIFE: f64 = 20.;
+
+/// The factor a word match's score is weighted by for the file at `recency_index` in the
+/// recent files list, most recent first.
+fn recency_decay(recency_index: usize) -> f64 {
+    0.5_f64.powf(recency_index as f64 / RECENCY_HALF_LIFE)
+}
+
+/// The git statuses of the changed files in `project`'s repositories, by absolute path, so
+/// that rendering the picker's rows doesn't query the repositories for each of them.
+fn git_statuses_by_abs_path(project: &Entity<Project>, cx: &App) -> HashMap<PathBuf, FileStatus> {
+    project
+        .read(cx)
+        .repositories(cx)
+        .values()
+        .flat_map(|repository| {
+            let repository = repository.read(cx);
+            repository.status().map(|entry| {
+                let abs_path = repository
+                    .work_directory_abs_path
+                    .join(entry.repo_path.as_std_path());
+                (abs_path, entry.status)
+            })
+        })
+        .collect()
+}
 
-/// Match strings with order-insensitive word matching.
-/// Splits the query into words and ensures all words match somewhere in the candidate,
-/// regardless of order.
-async fn match_strings_order_insensitive<T>(
-    candidates: &[T],
+/// The color a recent file is tinted with for its git `status`, if any.
+fn git_status_color(status: FileStatus) -> Option<Color> {
+    if status.is_conflicted() {
+        Some(Color::Conflict)
+    } else if status.is_modified() {
+        Some(Color::Modified)
+    } else if status.is_created() {
+        Some(Color::Created)
+    } else {
+        None
+    }
+}
+
+/// Matches `query` against `candidates` word by word with `match_mode`, falling back to fuzzy
+/// matching when `fuzzy_fallback` is set and no candidate contains all the words. `candidates`
+/// are ordered from the most recently opened file, which their ids reflect.
+///
+/// Returns the word matches ranked by their score weighted by recency, followed by the fuzzy
+/// matches in recency order, along with the index of the first fuzzy match.
+async fn match_recent_files(
+    candidates: &[StringMatchCandidate],
     query: &str,
     smart_case: bool,
+    match_mode: WordMatchMode,
+    fuzzy_fallback: bool,
+    file_name_boost: f64,
     max_results: usize,
-    cancel_flag: &std::sync::atomic::AtomicBool,
-) -> Vec<StringMatch>
-where
-    T: std::borrow::Borrow<StringMatchCandidate> + Sync,
-{
-    if candidates.is_empty() || max_results == 0 {
-        return Default::default();
+    cancel_flag: &AtomicBool,
+    executor: BackgroundExecutor,
+) -> (Vec<StringMatch>, usize) {
+    let options = WordMatchOptions {
+        smart_case,
+        match_mode,
+        file_name_boost: Some(file_name_boost),
+        ..WordMatchOptions::default()
+    };
+    let mut matches =
+        match_strings_order_insensitive(candidates, query, &options, cancel_flag, executor.clone())
+            .await;
+    for hit in &mut matches {
+        hit.score *= recency_decay(hit.candidate_id);
     }
-
-    if query.is_empty() {
-        return candidates
-            .iter()
-            .map(|candidate| StringMatch {
-                candidate_id: candidate.borrow().id,
-                score: 0.,
-                positions: Default::default(),
-                string: candidate.borrow().string.clone(),
-            })
-            .collect();
+    // An empty query scores every file zero, which keeps them in recency order.
+    matches.sort_unstable_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.candidate_id.cmp(&b.candidate_id))
+    });
+    if !query.is_empty() {
+        matches.truncate(max_results);
+    }
+    let first_fuzzy_match = matches.len();
+    if matches.is_empty() && fuzzy_fallback && !query.trim().is_empty() {
+        let mut fuzzy_matches = fuzzy::match_strings(
+            candidates,
+            query.trim(),
+            smart_case,
+            true,
+            max_results,
+            cancel_flag,
+            executor,
+        )
+        .await;
+        fuzzy_matches.sort_unstable_by_key(|m| m.candidate_id);
+        matches.extend(fuzzy_matches);
     }
+    (matches, first_fuzzy_match)
+}
 
-    // Split query into words and remove empty ones
-    let words: Vec<&str> = if query.trim().contains(' ') {
-        query.split_whitespace().collect()
-    } else {
-        // For single words, treat the whole query as one word
-        vec![query.trim()]
-    };
+const DEFAULT_MAX_ENTRIES: usize = 3000;
+
+/// How much more a query word counts when found in the file name rather than the directory.
+const DEFAULT_FILE_NAME_BOOST: f64 = 2.;
+
+#[derive(Clone, RegisterSetting)]
+pub struct RecentFilesSettings {
+    pub max_entries: usize,
+    pub max_entries_per_directory: Option<usize>,
+    pub open_as_preview: bool,
+    pub align_paths: bool,
+    pub debug_scores: bool,
+    pub fuzzy_fallback: bool,
+    pub match_mode: WordMatchMode,
+    pub file_name_boost: f64,
+    pub prune_missing: bool,
+    pub hide_missing: bool,
+    pub show_preview: bool,
+    pub track_private_files: bool,
+    pub case_insensitive_paths: bool,
+    /// The global `private_files` patterns, for the files outside of any worktree.
+    /// Compiled along with the settings, so checking a path doesn't recompile them.
+    pub private_files: PathMatcher,
+}
 
-    if words.is_empty() {
-        return Vec::new();
+impl Settings for RecentFilesSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let recent_files = content.recent_files.clone().unwrap_or_default();
+        Self {
+            max_entries: recent_files.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES),
+            max_entries_per_directory: recent_files.max_entries_per_directory,
+            open_as_preview: recent_files.open_as_preview.unwrap_or(false),
+            align_paths: recent_files.align_paths.unwrap_or(false),
+            debug_scores: recent_files.debug_scores.unwrap_or(false),
+            fuzzy_fallback: recent_files.fuzzy_fallback.unwrap_or(true),
+            match_mode: match recent_files.match_mode.unwrap_or_default() {
+                settings::RecentFilesMatchMode::Substring => WordMatchMode::Substring,
+                settings::RecentFilesMatchMode::Fuzzy => WordMatchMode::Fuzzy,
+            },
+            file_name_boost: recent_files
+                .file_name_boost
+                .map_or(DEFAULT_FILE_NAME_BOOST, f64::from)
+                .max(1.),
+            prune_missing: recent_files.prune_missing.unwrap_or(true),
+            hide_missing: recent_files.hide_missing.unwrap_or(false),
+            show_preview: recent_files.show_preview.unwrap_or(false),
+            track_private_files: recent_files.track_private_files.unwrap_or(false),
+            case_insensitive_paths: recent_files
+                .case_insensitive_paths
+                .unwrap_or(cfg!(any(target_os = "macos", target_os = "windows"))),
+            private_files: private_files_matcher(content),
+        }
     }
+}
+
+fn private_files_matcher(content: &settings::SettingsContent) -> PathMatcher {
+    let globs = content
+        .project
+        .worktree
+        .private_files
+        .clone()
+        .map(|private_files| private_files.0)
+        .unwrap_or_default();
+    PathMatcher::new(globs, PathStyle::local())
+        .log_err()
+        .unwrap_or_default()
+}
 
-    let mut results = Vec::new();
+const MAX_ALIGNED_FILE_NAME_LEN: usize = 40;
 
-    for candidate in candidates {
-        if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
-            break;
+/// File names longer than this are elided in the middle when rendered.
+const MAX_FILE_NAME_LABEL_LEN: usize = 60;
+
+/// The directory label always keeps at least this many bytes, however long the file name.
+const MIN_DIR_NAME_BUDGET: usize = 16;
+
+/// Files opened within this window are never pruned by the per-directory cap.
+const PRUNE_PROTECTED_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Clone, Debug, PartialEq)]
+struct RecentFile {
+    path: PathBuf,
+    last_opened: SystemTime,
+}
+
+impl RecentFile {
+    fn from_unix_seconds(path: PathBuf, seconds: i64) -> Self {
+        Self {
+            path,
+            last_opened: UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64),
         }
+    }
 
-        let candidate_borrowed = candidate.borrow();
-        let candidate_string = &candidate_borrowed.string;
-        let candidate_lower = candidate_string.to_lowercase();
+    fn last_opened_unix_seconds(&self) -> i64 {
+        self.last_opened
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as i64)
+    }
+}
 
-        // Check if all words are present in the candidate (case-insensitive)
-        let mut all_words_match = true;
-        let mut total_score = 0.0;
-        let mut all_positions = Vec::new();
+static RECENT_FILES: Mutex<Vec<RecentFile>> = Mutex::new(Vec::new());
 
-        for word in &words {
-            let word_lower = if smart_case {
-                word.to_string()
-            } else {
-                word.to_lowercase()
-            };
+/// Set whenever [`RECENT_FILES`] changes, so the periodic save only writes to the
+/// database when there is something new to write.
+static RECENT_FILES_DIRTY: AtomicBool = AtomicBool::new(false);
 
-            let found_match = if smart_case {
-                candidate_string.contains(word)
-            } else {
-                candidate_lower.contains(&word_lower)
-            };
+fn mark_recent_files_dirty() {
+    RECENT_FILES_DIRTY.store(true, atomic::Ordering::Release);
+}
 
-            if found_match {
-                if let Some(byte_pos) = if smart_case {
-                    candidate_string.find(word)
-                } else {
-                    candidate_lower.find(&word_lower)
-                } {
-                    // Calculate a simple score based on position and word length
-                    let word_score = 1.0 / (byte_pos as f64 + 1.0)
-                        * (word.len() as f64 / candidate_string.len() as f64);
-                    total_score += word_score;
-
-                    if let Some(original_byte_pos) = if smart_case {
-                        candidate_string.find(word)
-                    } else {
-                        candidate_string.to_lowercase().find(&word_lower)
-                    } {
-                        let word_byte_len = word.len();
-                        for i in 0..word_byte_len {
-                            let pos = original_byte_pos + i;
-                            if pos < candidate_string.len()
-                                && candidate_string.is_char_boundary(pos)
-                            {
-                                all_positions.push(pos);
-                            }
-                        }
-                    }
-                }
-            } else {
-                all_words_match = false;
-                break;
-            }
+/// Incremented by [`clear`], so that loading the list from the database at startup
+/// doesn't bring back the files that were cleared meanwhile.
+static RECENT_FILES_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Keeps at most `max_per_directory` entries per parent directory, preferring
+/// the most recent ones. `recent_files` is expected to be ordered most recent
+/// first. Entries opened within [`PRUNE_PROTECTED_WINDOW`] of `now` are never
+/// pruned, but still count towards their directory's cap.
+///
+/// Returns the pruned entries.
+fn prune_per_directory(
+    recent_files: &mut Vec<RecentFile>,
+    max_per_directory: usize,
+    now: SystemTime,
+) -> Vec<RecentFile> {
+    let mut entries_per_directory: HashMap<PathBuf, usize> = HashMap::default();
+    let mut pruned = Vec::new();
+
+    recent_files.retain(|recent_file| {
+        let directory = recent_file
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let count = entries_per_directory.entry(directory).or_default();
+        *count += 1;
+
+        // Entries from the future (e.g. after a clock change) are treated as protected.
+        let is_protected = now
+            .duration_since(recent_file.last_opened)
+            .map_or(true, |age| age < PRUNE_PROTECTED_WINDOW);
+        if *count <= max_per_directory || is_protected {
+            true
+        } else {
+            pruned.push(recent_file.clone());
+            false
         }
+    });
 
-        if all_words_match {
-            all_positions.sort_unstable();
-            all_positions.dedup();
+    pruned
+}
 
-            results.push(StringMatch {
-                candidate_id: candidate_borrowed.id,
-                score: total_score / words.len() as f64, // Average score across words
-                positions: all_positions,
-                string: candidate_string.clone(),
-            });
-        }
+/// Applies the per-directory cap from [`RecentFilesSettings`] to the in-memory list,
+/// returning the pruned entries so they can be removed from the database.
+fn prune_recent_files(max_entries_per_directory: Option<usize>) -> Vec<RecentFile> {
+    let Some(max_entries_per_directory) = max_entries_per_directory else {
+        return Vec::new();
+    };
+
+    let pruned = prune_per_directory(
+        &mut RECENT_FILES.lock(),
+        max_entries_per_directory,
+        SystemTime::now(),
+    );
+    if !pruned.is_empty() {
+        mark_recent_files_dirty();
+        log::info!(
+            "Pruned {} recent files exceeding {} entries per directory",
+            pruned.len(),
+            max_entries_per_directory
+        );
     }
+    pruned
+}
 
-    results.sort_by(|a, b| {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(std::cmp::Ordering::Equal)
+/// The key two recent files are considered the same file by: the path itself, or its
+/// lowercase form when paths are case-insensitive, so `/Foo/Bar.rs` and `/foo/bar.rs`
+/// are one file.
+fn path_identity(path: &Path, case_insensitive: bool) -> Cow<'_, Path> {
+    if case_insensitive {
+        Cow::Owned(PathBuf::from(path.to_string_lossy().to_lowercase()))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Moves `path` to the front of `recent_files`, keeping at most `max_entries` files.
+/// Returns `false` when it already was the most recent file, in which case only its
+/// timestamp is refreshed. With `case_insensitive`, entries differing from `path` only
+/// in case are replaced by it, so the most recent casing is kept.
+fn bump_recent_file(
+    recent_files: &mut Vec<RecentFile>,
+    path: &Path,
+    now: SystemTime,
+    max_entries: usize,
+    case_insensitive: bool,
+) -> bool {
+    let identity = path_identity(path, case_insensitive);
+    if let Some(most_recent) = recent_files.first_mut()
+        && path_identity(&most_recent.path, case_insensitive) == identity
+    {
+        most_recent.path = path.to_path_buf();
+        most_recent.last_opened = now;
+        recent_files.truncate(max_entries);
+        return false;
+    }
+
+    recent_files
+        .retain(|recent_file| path_identity(&recent_file.path, case_insensitive) != identity);
+    recent_files.insert(
+        0,
+        RecentFile {
+            path: path.to_path_buf(),
+            last_opened: now,
+        },
+    );
+    recent_files.truncate(max_entries);
+    true
+}
+
+/// Collapses the entries of `recent_files` that resolve to the same file, like a path
+/// through a symlinked directory and the real one, or a `~` path and its expanded form.
+/// The most recent entry of each file is kept, with its path replaced by the resolved one.
+/// With `case_insensitive`, paths differing only in case are the same file too.
+/// Returns whether anything changed.
+fn dedup_by_canonical_path(
+    recent_files: &mut Vec<RecentFile>,
+    canonicalize: impl Fn(&Path) -> PathBuf,
+    case_insensitive: bool,
+) -> bool {
+    let mut seen = HashSet::default();
+    let mut changed = false;
+    recent_files.retain_mut(|recent_file| {
+        let path = canonicalize(&recent_file.path);
+        if path != recent_file.path {
+            recent_file.path = path.clone();
+            changed = true;
+        }
+        if seen.insert(path_identity(&path, case_insensitive).into_owned()) {
+            true
+        } else {
+            changed = true;
+            false
+        }
     });
-    results.truncate(max_results);
-    results
+    changed
 }
 
-static RECENT_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+/// Removes the entries of `recent_files` matched by the `private_files` patterns,
+/// returning them so they can be removed from the database.
+fn remove_private_files(
+    recent_files: &mut Vec<RecentFile>,
+    private_files: &PathMatcher,
+) -> Vec<RecentFile> {
+    let mut removed = Vec::new();
+    recent_files.retain(|recent_file| {
+        if private_files.is_match_std_path(&recent_file.path) {
+            removed.push(recent_file.clone());
+            false
+        } else {
+            true
+        }
+    });
+    removed
+}
 
-fn add_recent_file(path: PathBuf) {
-    let mut recent_files = RECENT_FILES.lock();
-    recent_files.retain(|p| p != &path);
-    recent_files.insert(0, path.clone());
-    recent_files.truncate(3000);
-
-    // Save to database asynchronously
-    smol::spawn(async move {
-        if let Err(e) = WORKSPACE_DB.save_recent_file(&path).await {
-            log::error!("Failed to save recent file to database: {:?}", e);
+/// Whether `path` is a private file that shouldn't be recorded. Inside a worktree, the
+/// worktree's own `private_files` setting decides, otherwise the global one does.
+fn is_untracked_private_file(path: &Path, project: &Project, cx: &App) -> bool {
+    let settings = RecentFilesSettings::get_global(cx);
+    if settings.track_private_files {
+        return false;
+    }
+    match project.project_path_for_absolute_path(path, cx) {
+        Some(project_path) => WorktreeSettings::get(Some((&project_path).into()), cx)
+            .is_path_private(&project_path.path),
+        None => settings.private_files.is_match_std_path(path),
+    }
+}
+
+/// Records `path` as the most recently opened file. Both the picker's confirm and the
+/// workspace item events call this, so repeated calls for the same file are cheap.
+/// Files are recorded by their resolved path, so opening one through a symlink and
+/// through its real path results in a single entry. Private files of `project` are
+/// skipped unless they're tracked.
+fn add_recent_file(path: PathBuf, project: &Project, cx: &App) {
+    if is_untracked_private_file(&path, project, cx) {
+        return;
+    }
+    let settings = RecentFilesSettings::get_global(cx);
+    let case_insensitive_paths = settings.case_insensitive_paths;
+    bump_recent_file(
+        &mut RECENT_FILES.lock(),
+        &path,
+        SystemTime::now(),
+        settings.max_entries,
+        case_insensitive_paths,
+    );
+    // Even when the file stays on top, its timestamp was refreshed. The periodic save
+    // writes the list, so a burst of opened files results in a single write.
+    mark_recent_files_dirty();
+
+    // Resolving the path touches the file system, so the entry is recorded as opened
+    // and replaced by its resolved path on a background thread.
+    cx.background_spawn(async move {
+        let resolved_path = canonical_path(&path);
+        if resolved_path == path {
+            return;
+        }
+        let resolve = |recent_path: &Path| {
+            if recent_path == path {
+                resolved_path.clone()
+            } else {
+                recent_path.to_path_buf()
+            }
+        };
+        if dedup_by_canonical_path(&mut RECENT_FILES.lock(), resolve, case_insensitive_paths) {
+            mark_recent_files_dirty();
         }
     })
     .detach();
 }
 
+/// Forgets `path` as a recent file, both in memory and in the database.
+async fn delete_recent_file(path: PathBuf) {
+    RECENT_FILES
+        .lock()
+        .retain(|recent_file| recent_file.path != path);
+    mark_recent_files_dirty();
+    if let Err(e) = WORKSPACE_DB.delete_recent_file(&path).await {
+        log::error!("Failed to delete recent file from database: {:?}", e);
+    }
+}
+
+/// Returns the recent files, most recently opened first.
+pub fn recent_files() -> Vec<PathBuf> {
+    recent_file_paths(false)
+}
+
+/// Forgets `path` as a recent file. Returns whether it was one. The periodic save
+/// removes it from the database.
+pub fn remove(path: &Path) -> bool {
+    let mut recent_files = RECENT_FILES.lock();
+    let len = recent_files.len();
+    recent_files.retain(|recent_file| recent_file.path != path);
+    let removed = recent_files.len() != len;
+    if removed {
+        mark_recent_files_dirty();
+    }
+    removed
+}
+
+/// Forgets all recent files, both in memory and in the database.
+pub fn clear(cx: &App) -> Task<()> {
+    {
+        let mut recent_files = RECENT_FILES.lock();
+        recent_files.clear();
+        RECENT_FILES_GENERATION.fetch_add(1, atomic::Ordering::AcqRel);
+    }
+    // A save that snapshotted the list before it was cleared may still write it after the
+    // database is cleared. Marking the list dirty makes the next save write the empty one.
+    mark_recent_files_dirty();
+    cx.background_spawn(async move {
+        if let Err(e) = WORKSPACE_DB.clear_recent_files().await {
+            log::error!("Failed to clear recent files in database: {:?}", e);
+        }
+    })
+}
+
+/// Asks for confirmation, then clears the recent files and the list of an open picker.
+fn clear_recent_files(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let confirmation = window.prompt(
+        gpui::PromptLevel::Warning,
+        "Clear all recent files?",
+        Some("This can't be undone."),
+        &["Clear", "Cancel"],
+        cx,
+    );
+    cx.spawn_in(window, async move |workspace, cx| {
+        if !matches!(confirmation.await, Ok(0)) {
+            return anyhow::Ok(());
+        }
+        let cleared = cx.update(|_, cx| clear(cx))?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            if let Some(recent_files) = workspace.active_modal::<RecentFiles>(cx) {
+                recent_files.update(cx, |recent_files, cx| {
+                    recent_files.picker.update(cx, |picker, cx| {
+                        picker.delegate.clear_files();
+                        picker.refresh(window, cx);
+                    })
+                });
+            }
+        })?;
+        cleared.await;
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+const MAX_RECENTLY_CLOSED_BUFFERS: usize = 20;
+
+/// Buffers closed during this session, most recently closed first.
+/// Intentionally not persisted, so the list starts empty after a restart.
+static RECENTLY_CLOSED_BUFFERS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn add_recently_closed_buffer(path: PathBuf) {
+    let mut recently_closed = RECENTLY_CLOSED_BUFFERS.lock();
+    recently_closed.retain(|closed_path| closed_path != &path);
+    recently_closed.insert(0, path);
+    recently_closed.truncate(MAX_RECENTLY_CLOSED_BUFFERS);
+}
+
+fn forget_recently_closed_buffer(path: &Path) {
+    RECENTLY_CLOSED_BUFFERS
+        .lock()
+        .retain(|closed_path| closed_path != path);
+}
+
 /// Expand tilde (~) in path to the user's home directory
-fn expand_tilde(path: &Path) -> PathBuf {
-    if let Some(path_str) = path.to_str() {
-        PathBuf::from(shellexpand::tilde(path_str).as_ref())
-    } else {
-        path.to_path_buf()
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    let home = home_dir_from_env(|name| std::env::var_os(name));
+    expand_tilde_with_home(path, home.as_deref())
+}
+
+/// Reads the home directory from `HOME`, falling back to `USERPROFILE` and then
+/// `HOMEDRIVE` + `HOMEPATH`, which is where Windows keeps it.
+fn home_dir_from_env(var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let non_empty_var = |name: &str| var(name).filter(|value| !value.is_empty());
+    non_empty_var("HOME")
+        .or_else(|| non_empty_var("USERPROFILE"))
+        .or_else(|| {
+            let mut home = non_empty_var("HOMEDRIVE")?;
+            home.push(non_empty_var("HOMEPATH")?);
+            Some(home)
+        })
+        .map(PathBuf::from)
+}
+
+/// Replaces a leading `~`, on its own or followed by a separator, with `home`.
+fn expand_tilde_with_home(path: &Path, home: Option<&Path>) -> PathBuf {
+    let (Some(home), Some(path_str)) = (home, path.to_str()) else {
+        return path.to_path_buf();
+    };
+    if path_str == "~" {
+        return home.to_path_buf();
     }
+    match path_str
+        .strip_prefix("~/")
+        .or_else(|| path_str.strip_prefix("~\\"))
+    {
+        Some(rest) => home.join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Expands tilde in `path` and resolves it, for comparing it with other paths. Paths
+/// that can't be resolved, such as missing ones, are compared as they are.
+fn canonical_path(path: &Path) -> PathBuf {
+    let expanded_path = expand_tilde(path);
+    expanded_path.canonicalize().unwrap_or(expanded_path)
+}
+
+/// Whether `path` lies within one of `canonical_roots`, which are resolved already.
+fn is_within_roots(path: &Path, canonical_roots: &[PathBuf]) -> bool {
+    let path = canonical_path(path);
+    canonical_roots.iter().any(|root| path.starts_with(root))
 }
 
-/// Check if a path exists, expanding tilde if present
+/// Check if a path exists, expanding tilde if present. Symlinks are followed, so one
+/// whose target is gone counts as missing.
 fn path_exists(path: &Path) -> bool {
     expand_tilde(path).exists()
 }
 
+/// A recent workspace containing a file, as found by [`find_workspace_for_file`].
+type FoundWorkspace = (WorkspaceId, SerializedWorkspaceLocation, PathList);
+
+/// The names of the workspace roots containing `files`, from `workspace_roots` ordered
+/// by recency, so the most recent workspace wins as in [`find_workspace_for_file`].
+fn workspace_names_for_files(
+    files: &[PathBuf],
+    workspace_roots: &[PathBuf],
+) -> HashMap<PathBuf, SharedString> {
+    let canonical_roots = workspace_roots
+        .iter()
+        .map(|root| canonical_path(root))
+        .collect::<Vec<_>>();
+    files
+        .iter()
+        .filter_map(|file| {
+            let canonical_file = canonical_path(file);
+            let root = canonical_roots
+                .iter()
+                .find(|root| canonical_file.starts_with(root))?;
+            let name = root.file_name()?.to_string_lossy().into_owned();
+            Some((file.clone(), SharedString::from(name)))
+        })
+        .collect()
+}
+
 /// Find the most recent workspace that contains the given file path.
 /// Returns the workspace info if found, None otherwise.
 /// The workspaces are already ordered by recency (most recent first).
-async fn find_workspace_for_file(
-    file_path: &Path,
-) -> Option<(WorkspaceId, SerializedWorkspaceLocation, PathList)> {
+async fn find_workspace_for_file(file_path: &Path) -> Option<FoundWorkspace> {
     let recent_workspaces = WORKSPACE_DB.recent_workspaces_on_disk().await.ok()?;
 
-    // Expand tilde in the file path
-    let expanded_file_path = expand_tilde(file_path);
+    let canonical_file = canonical_path(file_path);
     log::debug!(
-        "Looking for workspace containing file: {:?} (expanded from {:?})",
-        expanded_file_path,
+        "Looking for workspace containing file: {:?} (resolved from {:?})",
+        canonical_file,
         file_path
     );
 
@@ -209,16 +696,7 @@ async fn find_workspace_for_file(
 
         // Check if any of the workspace paths contain the file
         for workspace_path in paths.paths() {
-            // Expand tilde in workspace path as well
-            let expanded_workspace_path = expand_tilde(workspace_path);
-
-            // Try to canonicalize both paths for robust comparison
-            let canonical_workspace = expanded_workspace_path
-                .canonicalize()
-                .unwrap_or_else(|_| expanded_workspace_path.clone());
-            let canonical_file = expanded_file_path
-                .canonicalize()
-                .unwrap_or_else(|_| expanded_file_path.clone());
+            let canonical_workspace = canonical_path(workspace_path);
 
             log::debug!(
                 "Comparing file {:?} with workspace {:?}",
@@ -237,555 +715,1105 @@ async fn find_workspace_for_file(
         }
     }
 
-    log::debug!(
-        "No workspace found containing file: {:?}",
-        expanded_file_path
-    );
+    log::debug!("No workspace found containing file: {:?}", canonical_file);
     None
 }
 
-pub fn init(cx: &mut App) {
-    // Load recent files from database on startup
-    cx.spawn(|_cx: &mut AsyncApp| async move {
-        match WORKSPACE_DB.get_recent_files(3000).await {
-            Ok(files) => {
-                // Separate existing and non-existing files while holding the lock
-                let non_existing = {
-                    let mut recent_files = RECENT_FILES.lock();
-                    recent_files.clear();
+/// The forms in which the path of a recent file can be copied to the clipboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CopiedPathForm {
+    Absolute,
+    RelativeToWorkspaceRoot,
+    RelativeToCurrentFile,
+}
 
-                    // Separate existing and non-existing files
-                    let (existing, non_existing): (Vec<_>, Vec<_>) =
-                        files.into_iter().partition(|path| path_exists(path));
+impl CopiedPathForm {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Absolute => "absolute path",
+            Self::RelativeToWorkspaceRoot => "path relative to the workspace root",
+            Self::RelativeToCurrentFile => "path relative to the current file",
+        }
+    }
+}
 
-                    recent_files.extend(existing);
+/// The innermost of `roots` that contains `path`.
+fn containing_root<'a>(path: &Path, roots: impl IntoIterator<Item = &'a Path>) -> Option<&'a Path> {
+    roots
+        .into_iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+}
 
-                    // Return only the non_existing files to be processed outside the lock
-                    non_existing
-                };
+/// `path` relative to the directory containing `current_file`, going up with `..` where
+/// the two diverge.
+fn path_relative_to_file(path: &Path, current_file: &Path) -> Option<PathBuf> {
+    pathdiff::diff_paths(path, current_file.parent()?)
+}
 
-                // Remove non-existing files from database (outside the lock)
-                for path in non_existing {
-                    if let Err(e) = WORKSPACE_DB.delete_recent_file(&path).await {
-                        log::error!(
-                            "Failed to delete non-existing file from database: {:?}, path: {:?}",
-                            e,
-                            path
-                        );
-                    }
-                }
+/// Copies `path` to the clipboard in the given form and reports what was copied in a toast.
+///
+/// The workspace root is looked up among the worktrees of `workspace` first, falling back to
+/// the most recent workspace on disk containing the file.
+fn copy_recent_file_path(
+    workspace: &Entity<Workspace>,
+    path: &Path,
+    form: CopiedPathForm,
+    cx: &mut App,
+) {
+    let path = expand_tilde(path);
+    let (worktree_roots, current_file) = {
+        let workspace = workspace.read(cx);
+        let project = workspace.project().read(cx);
+        let worktree_roots = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .collect::<Vec<_>>();
+        let current_file = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .and_then(|project_path| project.absolute_path(&project_path, cx));
+        (worktree_roots, current_file)
+    };
+    let workspace = workspace.downgrade();
+    cx.spawn(async move |cx| {
+        let copied = match form {
+            CopiedPathForm::Absolute => Ok(path.clone()),
+            CopiedPathForm::RelativeToCurrentFile => current_file
+                .as_deref()
+                .and_then(|current_file| path_relative_to_file(&path, current_file))
+                .ok_or("there is no file open in the active editor"),
+            CopiedPathForm::RelativeToWorkspaceRoot => {
+                let root = match containing_root(&path, worktree_roots.iter().map(PathBuf::as_path))
+                {
+                    Some(root) => Some(root.to_path_buf()),
+                    None => find_workspace_for_file(&path)
+                        .await
+                        .and_then(|(_, _, paths)| {
+                            let roots = paths.paths().iter().map(|root| expand_tilde(root));
+                            let roots = roots.collect::<Vec<_>>();
+                            containing_root(&path, roots.iter().map(PathBuf::as_path))
+                                .map(Path::to_path_buf)
+                        }),
+                };
+                root.and_then(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+                    .ok_or("the file isn't in any known workspace")
             }
-            Err(e) => {
-                log::error!("Failed to load recent files from database: {:?}", e);
+        };
+        let message = match &copied {
+            Ok(copied) => format!("Copied {} {}", form.label(), copied.display()),
+            Err(reason) => format!("Couldn't copy the {}: {reason}", form.label()),
+        };
+        cx.update(|cx| {
+            if let Ok(copied) = &copied {
+                cx.write_to_clipboard(ClipboardItem::new_string(
+                    copied.to_string_lossy().into_owned(),
+                ));
             }
-        }
-    })
-    .detach();
-
-    cx.on_action(|open_recent_file: &OpenRecentFile, cx| {
-        let create_new_window = open_recent_file.create_new_window;
-        with_active_or_new_workspace(cx, move |workspace, window, cx| {
-            let Some(recent_files) = workspace.active_modal::<RecentFiles>(cx) else {
-                RecentFiles::open(workspace, create_new_window, window, cx);
-                return;
-            };
-
-            recent_files.update(cx, |recent_files, cx| {
-                recent_files
-                    .picker
-                    .update(cx, |picker, cx| picker.cycle_selection(window, cx))
-            });
-        });
-    });
-
-    cx.on_action(|action: &OpenFileFromDirectory, cx| {
-        let directory = PathBuf::from(&action.directory);
-        with_active_or_new_workspace(cx, move |workspace, window, cx| {
-            let Some(picker) = workspace.active_modal::<DirectoryFilePicker>(cx) else {
-                DirectoryFilePicker::open(workspace, directory, window, cx);
-                return;
-            };
-
-            picker.update(cx, |picker, cx| {
-                picker
-                    .picker
-                    .update(cx, |picker, cx| picker.cycle_selection(window, cx))
-            });
-        });
-    });
-
-    cx.observe_new(|_workspace: &mut Workspace, window, cx| {
-        let Some(window) = window else { return };
-        cx.subscribe_in(
-            &cx.entity(),
-            window,
-            |workspace, _, event, _, cx| match event {
-                workspace::Event::ItemAdded { item } => {
-                    if let Some(project_path) = item.project_path(cx) {
-                        if let Some(abs_path) = workspace
-                            .project()
-                            .read(cx)
-                            .absolute_path(&project_path, cx)
-                        {
-                            add_recent_file(abs_path);
-                        }
-                    }
-                }
-                workspace::Event::ActiveItemChanged => {
-                    if let Some(active_item) = workspace.active_item(cx) {
-                        if let Some(project_path) = active_item.project_path(cx) {
-                            if let Some(abs_path) = workspace
-                                .project()
-                                .read(cx)
-                                .absolute_path(&project_path, cx)
-                            {
-                                add_recent_file(abs_path);
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            },
-        )
-        .detach();
+            workspace
+                .update(cx, |workspace, cx| {
+                    struct RecentFilePathCopied;
+                    workspace.show_toast(
+                        Toast::new(NotificationId::unique::<RecentFilePathCopied>(), message)
+                            .autohide(),
+                        cx,
+                    );
+                })
+                .ok();
+        })
+        .ok();
     })
     .detach();
+}
 
-    // Start periodic save task
-    let executor = cx.background_executor().clone();
-    cx.spawn(|_cx: &mut AsyncApp| async move {
-        loop {
-            // Wait for 5 seconds
-            executor.timer(std::time::Duration::from_secs(5)).await;
+/// Splits the leading and trailing `ext:<glob>` tokens off the query, returning the rest of
+/// the query and the lowercased extension globs, e.g. `ext:rs` or `ext:ts*`.
+fn split_extension_filters(query: &str) -> (&str, Vec<String>) {
+    fn extension_glob(token: &str) -> Option<String> {
+        let glob = token.strip_prefix("ext:")?.trim_start_matches('.');
+        (!glob.is_empty()).then(|| glob.to_lowercase())
+    }
 
-            // Get current recent files
-            let recent_files = {
-                let recent_files = RECENT_FILES.lock();
-                recent_files.clone()
-            };
+    let mut rest = query;
+    let mut globs = Vec::new();
+    loop {
+        let trimmed = rest.trim_start();
+        let (token, remainder) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let Some(glob) = extension_glob(token) else {
+            break;
+        };
+        globs.push(glob);
+        rest = remainder;
+    }
+    loop {
+        let trimmed = rest.trim_end();
+        let (remainder, token) = trimmed
+            .rsplit_once(char::is_whitespace)
+            .unwrap_or(("", trimmed));
+        let Some(glob) = extension_glob(token) else {
+            break;
+        };
+        globs.push(glob);
+        rest = remainder;
+    }
+    (rest.trim_start(), globs)
+}
 
-            // Save all recent files to database
-            if let Err(e) = WORKSPACE_DB.clear_recent_files().await {
-                log::error!("Failed to clear recent files from database: {:?}", e);
-                continue;
+/// Whether the extension of `path` matches any of the `ext:` globs, where `*` matches any
+/// run of characters and `?` a single one.
+fn matches_extension_globs(path: &str, globs: &[String]) -> bool {
+    fn matches_glob(pattern: &[u8], text: &[u8]) -> bool {
+        match (pattern.split_first(), text.split_first()) {
+            (None, _) => text.is_empty(),
+            (Some((b'*', pattern_rest)), _) => {
+                matches_glob(pattern_rest, text)
+                    || text
+                        .split_first()
+                        .is_some_and(|(_, text_rest)| matches_glob(pattern, text_rest))
             }
-
-            for path in recent_files {
-                if let Err(e) = WORKSPACE_DB.save_recent_file(&path).await {
-                    log::error!(
-                        "Failed to save recent file to database: {:?}, path: {:?}",
-                        e,
-                        path
-                    );
-                }
+            (Some((b'?', pattern_rest)), Some((_, text_rest))) => {
+                matches_glob(pattern_rest, text_rest)
             }
+            (Some((expected, pattern_rest)), Some((actual, text_rest))) => {
+                expected == actual && matches_glob(pattern_rest, text_rest)
+            }
+            (Some(_), None) => false,
         }
-    })
-    .detach();
-}
+    }
 
-struct RecentFiles {
-    picker: Entity<Picker<RecentFilesDelegate>>,
-    _subscription: Subscription,
+    let Some(extension) = Path::new(path).extension() else {
+        return false;
+    };
+    let extension = extension.to_string_lossy().to_lowercase();
+    globs
+        .iter()
+        .any(|glob| matches_glob(glob.as_bytes(), extension.as_bytes()))
 }
 
-impl ModalView for RecentFiles {}
-
-impl RecentFiles {
-    fn new(delegate: RecentFilesDelegate, window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let scroll_handle = UniformListScrollHandle::new();
-        let picker = cx.new(|cx| {
-            Picker::uniform_list(delegate, window, cx)
-                .max_height(None)
-                .track_scroll(scroll_handle.clone())
-                .show_scrollbar(true)
-        });
-        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
-        Self {
-            picker,
-            _subscription,
+/// Splits a trailing `:line` or `:line:column` suffix off the query, as accepted by most
+/// editors, returning the rest of the query and the zero-based position it refers to.
+fn split_position_suffix(query: &str) -> (&str, Option<Point>) {
+    fn parse_number(text: &str) -> Option<u32> {
+        if !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit()) {
+            text.parse().ok()
+        } else {
+            None
         }
     }
 
-    pub fn open(
-        workspace: &mut Workspace,
-        create_new_window: bool,
-        window: &mut Window,
-        cx: &mut Context<Workspace>,
-    ) {
-        let weak = cx.entity().downgrade();
-        workspace.toggle_modal(window, cx, |window, cx| {
-            let delegate = RecentFilesDelegate::new(weak, create_new_window);
-            Self::new(delegate, window, cx)
-        })
-    }
+    let Some((rest, last)) = query.trim_end().rsplit_once(':') else {
+        return (query, None);
+    };
+    let Some(last) = parse_number(last) else {
+        return (query, None);
+    };
+    let row_and_column = rest
+        .rsplit_once(':')
+        .and_then(|(name, row)| Some((name, parse_number(row)?)));
+    let (rest, row, column) = match row_and_column {
+        Some((name, row)) => (name, row, last),
+        None => (rest, last, 1),
+    };
+    let position = Point::new(row.saturating_sub(1), column.saturating_sub(1));
+    (rest, Some(position))
 }
 
-impl EventEmitter<DismissEvent> for RecentFiles {}
-
-impl Focusable for RecentFiles {
-    fn focus_handle(&self, cx: &App) -> FocusHandle {
-        self.picker.focus_handle(cx)
-    }
+/// Returns the recent files, most recently opened first. With `prune_missing`, the
+/// files that no longer exist are left out.
+pub fn recent_file_paths(prune_missing: bool) -> Vec<PathBuf> {
+    existing_recent_files(prune_missing)
+        .into_iter()
+        .map(|recent_file| recent_file.path)
+        .collect()
 }
 
-impl Render for RecentFiles {
-    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let viewport_size = window.viewport_size();
-        let modal_width = (viewport_size.width * 0.7).min(viewport_size.width);
-        let modal_height = (viewport_size.height * 0.7).min(viewport_size.height);
-
-        v_flex()
-            .key_context("RecentFiles")
-            .w(modal_width)
-            .h(modal_height)
-            .child(
-                v_flex()
-                    .flex_1()
-                    .overflow_hidden()
-                    .child(self.picker.clone()),
-            )
-            .on_mouse_down_out(cx.listener(|this, _, window, cx| {
-                this.picker.update(cx, |this, cx| {
-                    this.cancel(&Default::default(), window, cx);
-                })
-            }))
+fn existing_recent_files(prune_missing: bool) -> Vec<RecentFile> {
+    // Snapshot the list first, so that statting the files doesn't hold the lock.
+    let recent_files = RECENT_FILES.lock().clone();
+    if !prune_missing {
+        return recent_files;
     }
+    recent_files
+        .into_iter()
+        .filter(|recent_file| path_exists(&recent_file.path))
+        .collect()
 }
 
-struct RecentFilesDelegate {
-    workspace: WeakEntity<Workspace>,
-    files: Vec<PathBuf>,
-    matches: Vec<StringMatch>,
-    selected_match_index: usize,
+/// Opens a recent file, reopening the most recent workspace that contains it when that
+/// isn't the current one, and bumps it to the top of the recent files.
+pub fn open_recent_file(
+    workspace: Entity<Workspace>,
+    path: PathBuf,
     create_new_window: bool,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    open_recent_file_at(workspace, path, None, create_new_window, None, window, cx);
 }
 
-impl RecentFilesDelegate {
-    fn new(workspace: WeakEntity<Workspace>, create_new_window: bool) -> Self {
-        // Filter out non-existing files when creating the delegate
-        let files: Vec<PathBuf> = RECENT_FILES
-            .lock()
-            .iter()
-            .filter(|path| path_exists(path))
-            .cloned()
-            .collect();
-
-        Self {
-            workspace,
-            files,
-            matches: Vec::new(),
-            selected_match_index: 0,
-            create_new_window,
-        }
-    }
+/// Like [`open_recent_file`], additionally moving the cursor to the given zero-based
+/// position once the file is open. Positions past the end of the file are clamped.
+/// With a `split_direction`, a file of the current workspace opens in a new split.
+pub fn open_recent_file_at(
+    workspace: Entity<Workspace>,
+    path: PathBuf,
+    position: Option<Point>,
+    create_new_window: bool,
+    split_direction: Option<SplitDirection>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    open_recent_files_at(
+        workspace,
+        vec![path],
+        position,
+        create_new_window,
+        split_direction,
+        window,
+        cx,
+    );
 }
 
-impl EventEmitter<DismissEvent> for RecentFilesDelegate {}
-
-impl PickerDelegate for RecentFilesDelegate {
-    type ListItem = ListItem;
-
-    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
-        Arc::from("Search recent files...")
+/// Opens several recent files at once, reopening the workspaces containing them once
+/// for all of their files. The `position` only applies when opening a single file.
+fn open_recent_files_at(
+    workspace: Entity<Workspace>,
+    paths: Vec<PathBuf>,
+    position: Option<Point>,
+    create_new_window: bool,
+    split_direction: Option<SplitDirection>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    // Workspace events may lag or not fire when the file is already open in another
+    // pane, so bump it right away for the pickers to show it on top when reopened.
+    let project = workspace.read(cx).project().read(cx);
+    for path in paths.iter().rev() {
+        add_recent_file(path.clone(), project, cx);
     }
+    let position = position
+        .filter(|_| paths.len() == 1)
+        .zip(paths.first().cloned());
+    let allow_preview = paths.len() == 1;
+    let workspace_handle = workspace;
+    window
+        .spawn(cx, async move |cx| {
+            let mut files = Vec::with_capacity(paths.len());
+            for path in paths {
+                let found_workspace = find_workspace_for_file(&path).await;
+                files.push((path, found_workspace));
+            }
+            let groups = group_by_workspace(files, |a, b| a.0 == b.0);
+
+            // The first group is opened last, as only it may replace the current window,
+            // which the other groups are opened from.
+            for (ix, (found_workspace, paths)) in groups.into_iter().enumerate().rev() {
+                let open_task = workspace_handle.update_in(cx, |workspace, window, cx| {
+                    open_in_found_workspace(
+                        workspace,
+                        found_workspace,
+                        paths,
+                        create_new_window && ix == 0,
+                        split_direction,
+                        allow_preview,
+                        window,
+                        cx,
+                    )
+                })?;
+                open_task.await?;
+            }
 
-    fn match_count(&self) -> usize {
-        self.matches.len()
-    }
+            if let Some((position, path)) = position {
+                go_to_position_in_active_editor(&path, position, cx);
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+}
 
-    fn selected_index(&self) -> usize {
-        self.selected_match_index
+/// Groups `files` by the workspace found for them, keeping the order in which each
+/// workspace first appears. Files without a workspace form a group of their own.
+fn group_by_workspace<W>(
+    files: Vec<(PathBuf, Option<W>)>,
+    same_workspace: impl Fn(&W, &W) -> bool,
+) -> Vec<(Option<W>, Vec<PathBuf>)> {
+    let mut groups: Vec<(Option<W>, Vec<PathBuf>)> = Vec::new();
+    for (path, found_workspace) in files {
+        let group = groups.iter_mut().find(|(group_workspace, _)| {
+            match (group_workspace, &found_workspace) {
+                (Some(a), Some(b)) => same_workspace(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+        });
+        match group {
+            Some((_, paths)) => paths.push(path),
+            None => groups.push((found_workspace, vec![path])),
+        }
     }
+    groups
+}
 
-    fn set_selected_index(
-        &mut self,
-        ix: usize,
-        _window: &mut Window,
-        _cx: &mut Context<Picker<Self>>,
-    ) {
-        self.selected_match_index = ix;
+/// Opens `paths`, which all belong to `found_workspace`, reopening that workspace when
+/// it isn't the current one.
+fn open_in_found_workspace(
+    workspace: &mut Workspace,
+    found_workspace: Option<FoundWorkspace>,
+    paths: Vec<PathBuf>,
+    create_new_window: bool,
+    split_direction: Option<SplitDirection>,
+    allow_preview: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<anyhow::Result<()>> {
+    match found_workspace {
+        // We're already in the right workspace, just open the files
+        Some((workspace_id, _, _)) if workspace.database_id() == Some(workspace_id) => {
+            let open_tasks = paths
+                .into_iter()
+                .map(|path| {
+                    open_in_current_workspace(
+                        workspace,
+                        path,
+                        split_direction,
+                        allow_preview,
+                        window,
+                        cx,
+                    )
+                })
+                .collect::<Vec<_>>();
+            cx.spawn(async move |_, _| {
+                for open_task in open_tasks {
+                    open_task.await?;
+                }
+                anyhow::Ok(())
+            })
+        }
+        Some((_, SerializedWorkspaceLocation::Local, workspace_paths)) => {
+            // Open the workspace with all its paths, along with the specific files.
+            let mut paths_to_open = workspace_paths.paths().to_vec();
+            for path in paths {
+                if !paths_to_open.contains(&path) {
+                    paths_to_open.push(path);
+                }
+            }
+            workspace.open_workspace_for_paths(create_new_window, paths_to_open, window, cx)
+        }
+        // For remote workspaces, fall back to opening the files directly, as for files
+        // that no workspace contains.
+        Some((_, SerializedWorkspaceLocation::Remote(_), _)) | None => {
+            workspace.open_workspace_for_paths(create_new_window, paths, window, cx)
+        }
     }
+}
 
-    fn update_matches(
-        &mut self,
-        query: String,
-        _: &mut Window,
-        _cx: &mut Context<Picker<Self>>,
-    ) -> gpui::Task<()> {
-        let query = query.trim_start();
-        let smart_case = query.chars().any(|c| c.is_uppercase());
-        let candidates = self
-            .files
-            .iter()
-            .enumerate()
-            .map(|(id, path)| {
-                let path_str = path.compact().to_string_lossy().into_owned();
-                StringMatchCandidate::new(id, &path_str)
-            })
-            .collect::<Vec<_>>();
+fn open_in_current_workspace(
+    workspace: &mut Workspace,
+    path: PathBuf,
+    split_direction: Option<SplitDirection>,
+    allow_preview: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<anyhow::Result<()>> {
+    let split = split_direction.and_then(|direction| {
+        let project_path = workspace.project().read(cx).find_project_path(&path, cx)?;
+        Some((project_path, direction))
+    });
+    let preview_path = if allow_preview && RecentFilesSettings::get_global(cx).open_as_preview {
+        workspace.project().read(cx).find_project_path(&path, cx)
+    } else {
+        None
+    };
+    if let Some((project_path, direction)) = split {
+        let open_task =
+            workspace.split_path_preview(project_path, false, Some(direction), window, cx);
+        cx.spawn(async move |_, _| {
+            open_task.await?;
+            anyhow::Ok(())
+        })
+    } else if let Some(project_path) = preview_path {
+        let open_task =
+            workspace.open_path_preview(project_path, None, true, true, true, window, cx);
+        cx.spawn(async move |_, _| {
+            open_task.await?;
+            anyhow::Ok(())
+        })
+    } else {
+        workspace.open_workspace_for_paths(false, vec![path], window, cx)
+    }
+}
 
-        self.matches = smol::block_on(match_strings_order_insensitive(
-            candidates.as_slice(),
-            query,
-            smart_case,
-            100,
-            &Default::default(),
-        ));
-        self.matches.sort_unstable_by_key(|m| m.candidate_id);
+/// Moves the cursor of the active editor to `position`, provided that editor shows `path`.
+/// The file may have been opened in another window, so the active window is used rather
+/// than the one the picker was opened from.
+fn go_to_position_in_active_editor(path: &Path, position: Point, cx: &mut AsyncWindowContext) {
+    let Some(workspace) = cx
+        .update(|_, cx| cx.active_window())
+        .ok()
+        .flatten()
+        .and_then(|window| window.downcast::<Workspace>())
+    else {
+        return;
+    };
+    workspace
+        .update(cx, |workspace, window, cx| {
+            let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+                return;
+            };
+            editor.update(cx, |editor, cx| {
+                let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+                    return;
+                };
+                let buffer = buffer.read(cx);
+                let shows_path = buffer
+                    .file()
+                    .and_then(|file| file.as_local())
+                    .is_some_and(|file| file.abs_path(cx) == path);
+                if !shows_path {
+                    return;
+                }
+                let position = buffer.clip_point(position, Bias::Left);
+                editor.go_to_singleton_buffer_point(position, window, cx);
+            });
+        })
+        .log_err();
+}
 
-        self.selected_match_index = 0;
+pub fn init(cx: &mut App) {
+    // Load recent files from database on startup
+    let generation = RECENT_FILES_GENERATION.load(atomic::Ordering::Acquire);
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        let (max_entries, prune_missing, case_insensitive_paths, private_files) = cx.update(|cx| {
+            let settings = RecentFilesSettings::get_global(cx);
+            let private_files =
+                (!settings.track_private_files).then(|| settings.private_files.clone());
+            (
+                settings.max_entries,
+                settings.prune_missing,
+                settings.case_insensitive_paths,
+                private_files,
+            )
+        });
+        match WORKSPACE_DB.get_recent_files(max_entries).await {
+            Ok(files) => {
+                let mut files = files
+                    .into_iter()
+                    .map(|(path, last_opened)| RecentFile::from_unix_seconds(path, last_opened))
+                    .collect::<Vec<_>>();
+                // Statting thousands of files, possibly on network mounts, is kept off the
+                // main thread. The picker filters missing files on its own until this is done.
+                let (existing, non_existing, private, deduplicated) = cx
+                    .background_spawn(async move {
+                        // Older versions recorded files by the path they were opened with.
+                        let deduplicated = dedup_by_canonical_path(
+                            &mut files,
+                            canonical_path,
+                            case_insensitive_paths,
+                        );
+                        // Files recorded before they became private, or while they were
+                        // tracked, are dropped.
+                        let private = private_files
+                            .map(|private_files| remove_private_files(&mut files, &private_files))
+                            .unwrap_or_default();
+                        let (existing, non_existing): (Vec<_>, Vec<_>) = if prune_missing {
+                            files
+                                .into_iter()
+                                .partition(|recent_file| path_exists(&recent_file.path))
+                        } else {
+                            (files, Vec::new())
+                        };
+                        (existing, non_existing, private, deduplicated)
+                    })
+                    .await;
+                if !non_existing.is_empty() {
+                    log::info!("Pruned {} missing recent files", non_existing.len());
+                }
+                if !private.is_empty() {
+                    log::info!("Removed {} private recent files", private.len());
+                }
 
-        Task::ready(())
-    }
+                {
+                    let mut recent_files = RECENT_FILES.lock();
+                    if RECENT_FILES_GENERATION.load(atomic::Ordering::Acquire) != generation {
+                        return;
+                    }
+                    recent_files.clear();
+                    recent_files.extend(existing);
+                }
+                // Rewriting the whole list replaces the entries stored under other paths.
+                if deduplicated {
+                    mark_recent_files_dirty();
+                }
 
-    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
-        if let Some(hit) = self.matches.get(self.selected_index()) {
-            let path = self.files[hit.candidate_id].clone();
-            let create_new_window = if self.create_new_window {
-                !secondary
-            } else {
-                secondary
-            };
+                let max_entries_per_directory =
+                    cx.update(|cx| RecentFilesSettings::get_global(cx).max_entries_per_directory);
+                let pruned = prune_recent_files(max_entries_per_directory);
 
-            if let Some(workspace) = self.workspace.upgrade() {
-                // Try to find a recent workspace that contains this file
-                let workspace_handle = workspace;
-                cx.spawn_in(window, async move |_, cx| {
-                    if let Some((workspace_id, location, _workspace_paths)) =
-                        find_workspace_for_file(&path).await
-                    {
-                        // Found a workspace that contains this file, open that workspace
-                        workspace_handle.update_in(cx, |workspace, window, cx| {
-                            // Check if we're already in the correct workspace
-                            if workspace.database_id() == Some(workspace_id) {
-                                // We're already in the right workspace, just open the file
-                                workspace
-                                    .open_workspace_for_paths(false, vec![path], window, cx)
-                                    .detach_and_log_err(cx);
-                            } else {
-                                // Open the workspace that contains this file
-                                match location {
-                                    SerializedWorkspaceLocation::Local => {
-                                        // We need to open the workspace with all its paths, but then
-                                        // also open the specific file. First get workspace paths.
-                                        let workspace_paths = _workspace_paths.paths().to_vec();
-
-                                        // Create a combined list: workspace paths + the specific file
-                                        let mut paths_to_open = workspace_paths;
-                                        if !paths_to_open.contains(&path) {
-                                            paths_to_open.push(path);
-                                        }
-
-                                        workspace
-                                            .open_workspace_for_paths(
-                                                create_new_window,
-                                                paths_to_open,
-                                                window,
-                                                cx,
-                                            )
-                                            .detach_and_log_err(cx);
-                                    }
-                                    SerializedWorkspaceLocation::Remote(_) => {
-                                        // For remote workspaces, fall back to opening the file directly
-                                        workspace
-                                            .open_workspace_for_paths(
-                                                create_new_window,
-                                                vec![path],
-                                                window,
-                                                cx,
-                                            )
-                                            .detach_and_log_err(cx);
-                                    }
-                                }
-                            }
-                        })
-                    } else {
-                        // No workspace found, open the file standalone
-                        workspace_handle.update_in(cx, |workspace, window, cx| {
-                            workspace
-                                .open_workspace_for_paths(create_new_window, vec![path], window, cx)
-                                .detach_and_log_err(cx);
-                        })
+                // Remove non-existing, private and pruned files from database (outside the lock)
+                let removed = non_existing.into_iter().chain(private).chain(pruned);
+                for RecentFile { path, .. } in removed {
+                    if let Err(e) = WORKSPACE_DB.delete_recent_file(&path).await {
+                        log::error!(
+                            "Failed to delete non-existing file from database: {:?}, path: {:?}",
+                            e,
+                            path
+                        );
                     }
-                })
-                .detach_and_log_err(cx);
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to load recent files from database: {:?}", e);
             }
         }
-        cx.emit(DismissEvent);
-    }
+    })
+    .detach();
 
-    fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
+    cx.on_action(|open_recent_file: &OpenRecentFile, cx| {
+        let create_new_window = open_recent_file.create_new_window;
+        let current_workspace_only = open_recent_file.current_workspace_only;
+        with_active_or_new_workspace(cx, move |workspace, window, cx| {
+            let Some(recent_files) = workspace.active_modal::<RecentFiles>(cx) else {
+                RecentFiles::open(
+                    workspace,
+                    create_new_window,
+                    current_workspace_only,
+                    window,
+                    cx,
+                );
+                return;
+            };
 
-    fn render_match(
-        &self,
-        ix: usize,
-        selected: bool,
-        window: &mut Window,
-        cx: &mut Context<Picker<Self>>,
-    ) -> Option<Self::ListItem> {
-        let hit = self.matches.get(ix)?;
-        let path = self.files.get(hit.candidate_id)?;
+            recent_files.update(cx, |recent_files, cx| {
+                recent_files
+                    .picker
+                    .update(cx, |picker, cx| picker.cycle_selection(window, cx))
+            });
+        });
+    });
 
-        let path = path.compact();
-        let path_string = path.to_string_lossy();
-        let file_name = path
-            .file_name()
-            .map(|n| n.to_string_lossy())
-            .unwrap_or_default();
+    cx.on_action(|action: &OpenFileFromDirectory, cx| {
+        let directory = PathBuf::from(&action.directory);
+        with_active_or_new_workspace(cx, move |workspace, window, cx| {
+            let Some(picker) = workspace.active_modal::<DirectoryFilePicker>(cx) else {
+                DirectoryFilePicker::open(workspace, directory, window, cx);
+                return;
+            };
 
-        let file_name_start = path_string.len().saturating_sub(file_name.len());
-        let mut dir_name = path_string[0..file_name_start].to_string();
+            picker.update(cx, |picker, cx| {
+                picker
+                    .picker
+                    .update(cx, |picker, cx| picker.cycle_selection(window, cx))
+            });
+        });
+    });
 
-        let file_name_highlights: Vec<usize> = hit
-            .positions
-            .iter()
-            .filter(|&&i| i >= file_name_start)
-            .map(|&i| i - file_name_start)
-            .collect();
+    cx.on_action(|_: &OpenRecentlyClosedBuffers, cx| {
+        with_active_or_new_workspace(cx, move |workspace, window, cx| {
+            let Some(recently_closed) = workspace.active_modal::<RecentlyClosedBuffers>(cx) else {
+                RecentlyClosedBuffers::open(workspace, window, cx);
+                return;
+            };
 
-        let mut dir_highlights: Vec<usize> = hit
-            .positions
-            .iter()
-            .filter(|&&i| i < file_name_start)
-            .copied()
-            .collect();
-
-        if dir_name.is_ascii() {
-            let max_width = rems(48.).to_pixels(window.rem_size());
-            let (normal_em, small_em) = {
-                let style = window.text_style();
-                let font_id = window.text_system().resolve_font(&style.font());
-                let font_size = ui::TextSize::Default.rems(cx).to_pixels(window.rem_size());
-                let normal = cx
-                    .text_system()
-                    .em_width(font_id, font_size)
-                    .unwrap_or(px(16.));
-                let font_size = ui::TextSize::Small.rems(cx).to_pixels(window.rem_size());
-                let small = cx
-                    .text_system()
-                    .em_width(font_id, font_size)
-                    .unwrap_or(px(10.));
-                (normal, small)
-            };
-            let budget = full_path_budget(&file_name, normal_em, small_em, max_width);
+            recently_closed.update(cx, |recently_closed, cx| {
+                recently_closed
+                    .picker
+                    .update(cx, |picker, cx| picker.cycle_selection(window, cx))
+            });
+        });
+    });
 
-            if budget > 0 && dir_name.len() > budget {
-                let components = PathComponentSlice::new(&dir_name);
-                if let Some(elided_range) = components.elision_range(budget - 1, &dir_highlights) {
-                    let elided_len = elided_range.end - elided_range.start;
-                    let placeholder = "…";
-                    dir_highlights.retain_mut(|mat| {
-                        if *mat >= elided_range.end {
-                            *mat -= elided_len;
-                            *mat += placeholder.len();
-                        } else if *mat >= elided_range.start {
-                            return false;
+    cx.observe_new(|workspace: &mut Workspace, window, cx| {
+        workspace.register_action(|workspace, _: &ClearRecentFiles, window, cx| {
+            clear_recent_files(workspace, window, cx);
+        });
+        let Some(window) = window else { return };
+        // `ItemRemoved` only carries the item id, so remember the path of every open item.
+        let mut open_item_paths: HashMap<EntityId, PathBuf> = HashMap::default();
+        cx.subscribe_in(
+            &cx.entity(),
+            window,
+            move |workspace, _, event, _, cx| match event {
+                workspace::Event::ItemAdded { item } => {
+                    if let Some(project_path) = item.project_path(cx) {
+                        let project = workspace.project().read(cx);
+                        if let Some(abs_path) = project.absolute_path(&project_path, cx) {
+                            forget_recently_closed_buffer(&abs_path);
+                            open_item_paths.insert(item.item_id(), abs_path.clone());
+                            add_recent_file(abs_path, project, cx);
                         }
-                        true
-                    });
-                    dir_name.replace_range(elided_range, placeholder);
+                    }
+                }
+                workspace::Event::ActiveItemChanged => {
+                    if let Some(active_item) = workspace.active_item(cx) {
+                        if let Some(project_path) = active_item.project_path(cx) {
+                            let project = workspace.project().read(cx);
+                            if let Some(abs_path) = project.absolute_path(&project_path, cx) {
+                                forget_recently_closed_buffer(&abs_path);
+                                open_item_paths.insert(active_item.item_id(), abs_path.clone());
+                                add_recent_file(abs_path, project, cx);
+                            }
+                        }
+                    }
+                }
+                workspace::Event::ItemRemoved { item_id } => {
+                    if let Some(abs_path) = open_item_paths.remove(item_id) {
+                        // The same file may still be open in another pane.
+                        if !open_item_paths.values().any(|path| path == &abs_path) {
+                            add_recently_closed_buffer(abs_path);
+                        }
+                    }
                 }
+                _ => {}
+            },
+        )
+        .detach();
+    })
+    .detach();
+
+    // Start periodic save task
+    let executor = cx.background_executor().clone();
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        loop {
+            // Wait for 5 seconds
+            executor.timer(std::time::Duration::from_secs(5)).await;
+
+            let max_entries_per_directory =
+                cx.update(|cx| RecentFilesSettings::get_global(cx).max_entries_per_directory);
+            prune_recent_files(max_entries_per_directory);
+
+            if !RECENT_FILES_DIRTY.swap(false, atomic::Ordering::AcqRel) {
+                continue;
+            }
+
+            // Get current recent files
+            let recent_files = RECENT_FILES
+                .lock()
+                .iter()
+                .map(|recent_file| {
+                    (
+                        recent_file.path.clone(),
+                        recent_file.last_opened_unix_seconds(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            // Replace the saved recent files in one transaction, so a crash midway keeps
+            // the previous list rather than losing it.
+            if let Err(e) = WORKSPACE_DB.replace_recent_files(&recent_files).await {
+                log::error!("Failed to save recent files to database: {:?}", e);
+                mark_recent_files_dirty();
             }
         }
+    })
+    .detach();
+}
 
-        let file_icon =
-            FileIcons::get_icon(&path, cx).map(|icon| Icon::from_path(icon).color(Color::Muted));
+/// Image files larger than this are not decoded for thumbnails and keep their file icon.
+const MAX_THUMBNAIL_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Returns the format to decode a thumbnail with, or `None` when the file is not a
+/// raster image. SVGs are skipped since they render through the icon pipeline.
+fn thumbnail_format(path: &Path) -> Option<ImageFormat> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some(ImageFormat::Png),
+        "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+        "webp" => Some(ImageFormat::Webp),
+        "gif" => Some(ImageFormat::Gif),
+        "bmp" => Some(ImageFormat::Bmp),
+        "tif" | "tiff" => Some(ImageFormat::Tiff),
+        "ico" => Some(ImageFormat::Ico),
+        _ => None,
+    }
+}
 
-        Some(
-            ListItem::new(ix)
-                .spacing(ListItemSpacing::Sparse)
-                .toggle_state(selected)
-                .start_slot::<Icon>(file_icon)
-                .inset(true)
-                .child(
-                    h_flex()
-                        .gap_2()
-                        .py_px()
-                        .child(HighlightedLabel::new(
-                            file_name.to_string(),
-                            file_name_highlights,
-                        ))
-                        .child(
-                            HighlightedLabel::new(dir_name, dir_highlights)
-                                .size(LabelSize::Small)
-                                .color(Color::Muted),
-                        ),
-                ),
-        )
+/// How long the selection has to rest on a file before it is loaded into the preview, so
+/// moving through the list doesn't load every file passed along the way.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Files larger than this are not loaded into the preview.
+const MAX_PREVIEW_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// How many lines of the selected file the preview shows.
+const MAX_PREVIEW_LINES: usize = 50;
+
+/// The length of the first `max_lines` lines of `text`, including their line endings.
+fn preview_len(text: &str, max_lines: usize) -> usize {
+    text.match_indices('\n')
+        .nth(max_lines.saturating_sub(1))
+        .map_or(text.len(), |(ix, _)| ix + 1)
+}
+
+/// What the preview beside the list shows for the selected file.
+enum FilePreview {
+    Editor(Entity<Editor>),
+    /// Why the file isn't shown.
+    Placeholder(SharedString),
+}
+
+/// Loads the text of `path` for the preview, or the message shown in its place.
+async fn load_preview_text(fs: &dyn Fs, path: &Path) -> Result<String, SharedString> {
+    let path = expand_tilde(path);
+    match fs.metadata(&path).await {
+        Ok(Some(metadata)) if metadata.is_dir => {
+            return Err("Directories can't be previewed".into());
+        }
+        Ok(Some(metadata)) if metadata.len > MAX_PREVIEW_FILE_SIZE => {
+            return Err("File too large to preview".into());
+        }
+        Ok(Some(_)) => {}
+        Ok(None) | Err(_) => return Err("File not found".into()),
     }
+    // Loading fails for files that aren't valid UTF-8, which are mostly binary ones.
+    let mut text = fs
+        .load(&path)
+        .await
+        .map_err(|_| SharedString::from("File can't be previewed"))?;
+    text.truncate(preview_len(&text, MAX_PREVIEW_LINES));
+    Ok(text)
 }
 
-fn full_path_budget(
-    file_name: &str,
-    normal_em: Pixels,
-    small_em: Pixels,
-    max_width: Pixels,
-) -> usize {
-    (((max_width / 0.8) - (file_name.len() as f32) * normal_em) / small_em) as usize
+async fn load_thumbnail(fs: &dyn Fs, path: &Path, format: ImageFormat) -> Option<Arc<Image>> {
+    let metadata = fs.metadata(path).await.ok()??;
+    if metadata.is_dir || metadata.len > MAX_THUMBNAIL_FILE_SIZE {
+        return None;
+    }
+    let bytes = fs.load_bytes(path).await.ok()?;
+    Some(Arc::new(Image::from_bytes(format, bytes)))
 }
 
-struct DirectoryFilePicker {
-    picker: Entity<Picker<DirectoryFileDelegate>>,
+struct RecentFiles {
+    picker: Entity<Picker<RecentFilesDelegate>>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+    /// Whether the preview is shown beside the list, from `recent_files.show_preview`.
+    show_preview: bool,
+    /// The file the preview shows, or is about to show once loaded.
+    preview_path: Option<PathBuf>,
+    /// Built from the file's text rather than opened in the project, so previewing a file
+    /// doesn't record it as opened.
+    preview: Option<FilePreview>,
     _subscription: Subscription,
+    _picker_observation: Option<Subscription>,
+    _preview_task: Option<Task<()>>,
+    _load_thumbnails: Task<()>,
+    _load_workspace_names: Task<()>,
 }
 
-impl ModalView for DirectoryFilePicker {}
+impl ModalView for RecentFiles {}
 
-impl DirectoryFilePicker {
-    fn new(delegate: DirectoryFileDelegate, window: &mut Window, cx: &mut Context<Self>) -> Self {
+impl RecentFiles {
+    fn new(
+        delegate: RecentFilesDelegate,
+        fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
+        show_preview: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let image_files = delegate
+            .files
+            .iter()
+            .filter_map(|path| Some((path.clone(), thumbnail_format(path)?)))
+            .collect::<Vec<_>>();
+        let files = delegate.files.clone();
         let scroll_handle = UniformListScrollHandle::new();
         let picker = cx.new(|cx| {
             Picker::uniform_list(delegate, window, cx)
+                .modal(!show_preview)
                 .max_height(None)
                 .track_scroll(scroll_handle.clone())
                 .show_scrollbar(true)
         });
         let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        // The picker notifies whenever its selection or matches change.
+        let _picker_observation = show_preview.then(|| {
+            cx.observe_in(&picker, window, |this, picker, window, cx| {
+                let delegate = &picker.read(cx).delegate;
+                let path = delegate
+                    .path_at_row(delegate.selected_match_index)
+                    .map(Path::to_path_buf);
+                this.schedule_preview_update(path, window, cx);
+            })
+        });
+        let _load_thumbnails =
+            Self::load_thumbnails(picker.downgrade(), fs.clone(), image_files, cx);
+        let _load_workspace_names = Self::load_workspace_names(picker.downgrade(), files, cx);
         Self {
             picker,
+            fs,
+            languages,
+            show_preview,
+            preview_path: None,
+            preview: None,
             _subscription,
+            _picker_observation,
+            _preview_task: None,
+            _load_thumbnails,
+            _load_workspace_names,
+        }
+    }
+
+    fn schedule_preview_update(
+        &mut self,
+        path: Option<PathBuf>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.preview_path == path {
+            return;
+        }
+        self.preview_path = path.clone();
+        let Some(path) = path else {
+            self.preview = None;
+            self._preview_task = None;
+            cx.notify();
+            return;
+        };
+
+        let fs = self.fs.clone();
+        let languages = self.languages.clone();
+        self._preview_task = Some(cx.spawn_in(window, async move |this, cx| {
+            cx.background_executor().timer(PREVIEW_DEBOUNCE).await;
+            let text = load_preview_text(fs.as_ref(), &path).await;
+            let language = match &text {
+                Ok(_) => languages.load_language_for_file_path(&path).await.ok(),
+                Err(_) => None,
+            };
+            this.update_in(cx, |this, window, cx| {
+                this.preview = Some(match text {
+                    Ok(text) => {
+                        FilePreview::Editor(Self::preview_editor(text, language, window, cx))
+                    }
+                    Err(message) => FilePreview::Placeholder(message),
+                });
+                cx.notify();
+            })
+            .log_err();
+        }));
+    }
+
+    fn preview_editor(
+        text: String,
+        language: Option<Arc<Language>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Editor> {
+        let buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            buffer.set_language(language, cx);
+            buffer
+        });
+        cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer, None, window, cx);
+            editor.set_read_only(true);
+            editor.set_show_gutter(false, cx);
+            editor.set_soft_wrap_mode(SoftWrap::EditorWidth, cx);
+            editor
+        })
+    }
+
+    fn render_preview(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .h_full()
+            .overflow_hidden()
+            .bg(cx.theme().colors().elevated_surface_background)
+            .map(|this| match &self.preview {
+                Some(FilePreview::Editor(editor)) => this.child(editor.clone()),
+                Some(FilePreview::Placeholder(message)) => this.child(
+                    div()
+                        .size_full()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .child(Label::new(message.clone()).color(Color::Muted)),
+                ),
+                None => this,
+            })
+    }
+
+    /// Looks up the workspace each file belongs to once, off the main thread, so rendering
+    /// the rows only reads the result.
+    fn load_workspace_names(
+        picker: WeakEntity<Picker<RecentFilesDelegate>>,
+        files: Vec<PathBuf>,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        cx.spawn(async move |_, cx| {
+            let Some(workspaces) = WORKSPACE_DB.recent_workspaces_on_disk().await.log_err() else {
+                return;
+            };
+            let workspace_roots = workspaces
+                .into_iter()
+                .filter(|(_, location, _)| matches!(location, SerializedWorkspaceLocation::Local))
+                .flat_map(|(_, _, paths)| paths.paths().to_vec())
+                .collect::<Vec<_>>();
+            let workspace_names = cx
+                .background_spawn(
+                    async move { workspace_names_for_files(&files, &workspace_roots) },
+                )
+                .await;
+            picker
+                .update(cx, |picker, cx| {
+                    picker.delegate.workspace_names = workspace_names;
+                    cx.notify();
+                })
+                .ok();
+        })
+    }
+
+    fn load_thumbnails(
+        picker: WeakEntity<Picker<RecentFilesDelegate>>,
+        fs: Arc<dyn Fs>,
+        image_files: Vec<(PathBuf, ImageFormat)>,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        if image_files.is_empty() {
+            return Task::ready(());
         }
+        cx.spawn(async move |_, cx| {
+            for (path, format) in image_files {
+                let Some(thumbnail) = load_thumbnail(fs.as_ref(), &path, format).await else {
+                    continue;
+                };
+                let updated = picker.update(cx, |picker, cx| {
+                    picker.delegate.thumbnails.insert(path, thumbnail);
+                    cx.notify();
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    fn copy_selected_path(&mut self, form: CopiedPathForm, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if let Some(path) = picker.delegate.selected_path()
+                && let Some(workspace) = picker.delegate.workspace.upgrade()
+            {
+                copy_recent_file_path(&workspace, path, form, cx);
+                cx.emit(DismissEvent);
+            }
+        });
     }
 
     pub fn open(
         workspace: &mut Workspace,
-        directory: PathBuf,
+        create_new_window: bool,
+        current_workspace_only: bool,
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) {
         let weak = cx.entity().downgrade();
+        let fs = workspace.app_state().fs.clone();
+        let languages = workspace.app_state().languages.clone();
+        let settings = RecentFilesSettings::get_global(cx);
+        // Hidden missing files are found in the background rather than before opening.
+        let prune_missing = settings.prune_missing && !settings.hide_missing;
+        let show_preview = settings.show_preview;
+        let git_statuses = git_statuses_by_abs_path(workspace.project(), cx);
+        let worktree_roots = workspace
+            .project()
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .collect();
         workspace.toggle_modal(window, cx, |window, cx| {
-            let delegate = DirectoryFileDelegate::new(weak, directory);
-            Self::new(delegate, window, cx)
+            let mut delegate =
+                RecentFilesDelegate::new(weak, create_new_window, prune_missing, git_statuses);
+            delegate.worktree_roots = Arc::new(worktree_roots);
+            delegate.current_workspace_only = current_workspace_only;
+            Self::new(delegate, fs, languages, show_preview, window, cx)
         })
     }
+
+    fn toggle_current_workspace_only(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.current_workspace_only = !picker.delegate.current_workspace_only;
+            picker.refresh_placeholder(window, cx);
+            picker.refresh(window, cx);
+        });
+    }
 }
 
-impl EventEmitter<DismissEvent> for DirectoryFilePicker {}
+impl EventEmitter<DismissEvent> for RecentFiles {}
 
-impl Focusable for DirectoryFilePicker {
+impl Focusable for RecentFiles {
     fn focus_handle(&self, cx: &App) -> FocusHandle {
         self.picker.focus_handle(cx)
     }
 }
 
-impl Render for DirectoryFilePicker {
+impl Render for RecentFiles {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let viewport_size = window.viewport_size();
         let modal_width = (viewport_size.width * 0.7).min(viewport_size.width);
         let modal_height = (viewport_size.height * 0.7).min(viewport_size.height);
 
         v_flex()
-            .key_context("DirectoryFilePicker")
+            .key_context("RecentFiles")
             .w(modal_width)
             .h(modal_height)
-            .child(
-                v_flex()
-                    .flex_1()
-                    .overflow_hidden()
-                    .child(self.picker.clone()),
+            .on_action(cx.listener(|this, _: &CopyAbsolutePath, _, cx| {
+                this.copy_selected_path(CopiedPathForm::Absolute, cx)
+            }))
+            .on_action(
+                cx.listener(|this, _: &CopyPathRelativeToWorkspaceRoot, _, cx| {
+                    this.copy_selected_path(CopiedPathForm::RelativeToWorkspaceRoot, cx)
+                }),
+            )
+            .on_action(
+                cx.listener(|this, _: &CopyPathRelativeToCurrentFile, _, cx| {
+                    this.copy_selected_path(CopiedPathForm::RelativeToCurrentFile, cx)
+                }),
             )
+            .on_action(cx.listener(|this, _: &ToggleChecked, _, cx| {
+                this.picker
+                    .update(cx, |picker, cx| picker.delegate.toggle_checked(cx))
+            }))
+            .on_action(
+                cx.listener(|this, _: &ToggleCurrentWorkspaceOnly, window, cx| {
+                    this.toggle_current_workspace_only(window, cx)
+                }),
+            )
+            .on_action(cx.listener(|this, _: &OpenInSplit, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker
+                        .delegate
+                        .open_selected(false, Some(SplitDirection::Right), window, cx)
+                })
+            }))
+            .map(|this| {
+                if self.show_preview {
+                    this.child(
+                        h_flex()
+                            .elevation_3(cx)
+                            .size_full()
+                            .overflow_hidden()
+                            .child(
+                                v_flex()
+                                    .w_1_2()
+                                    .h_full()
+                                    .overflow_hidden()
+                                    .border_r_1()
+                                    .border_color(cx.theme().colors().border)
+                                    .child(self.picker.clone()),
+                            )
+                            .child(self.render_preview(cx)),
+                    )
+                } else {
+                    this.child(
+                        v_flex()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(self.picker.clone()),
+                    )
+                }
+            })
             .on_mouse_down_out(cx.listener(|this, _, window, cx| {
                 this.picker.update(cx, |this, cx| {
                     this.cancel(&Default::default(), window, cx);
@@ -794,57 +1822,389 @@ impl Render for DirectoryFilePicker {
     }
 }
 
-struct DirectoryFileDelegate {
+/// Sections the recent files are grouped into while the query is empty, by when they
+/// were last opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RecencySection {
+    Today,
+    Yesterday,
+    ThisWeek,
+    Earlier,
+}
+
+impl RecencySection {
+    fn for_time(today: NaiveDate, last_opened: SystemTime) -> Self {
+        Self::from_dates(today, DateTime::<Local>::from(last_opened).date_naive())
+    }
+
+    fn from_dates(today: NaiveDate, date: NaiveDate) -> Self {
+        if date == today {
+            RecencySection::Today
+        } else if date == today - TimeDelta::days(1) {
+            RecencySection::Yesterday
+        } else if date.iso_week() == today.iso_week() {
+            RecencySection::ThisWeek
+        } else {
+            RecencySection::Earlier
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            RecencySection::Today => "Today",
+            RecencySection::Yesterday => "Yesterday",
+            RecencySection::ThisWeek => "This Week",
+            RecencySection::Earlier => "Earlier",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RecentFilesRow {
+    /// A section header, which can't be selected.
+    Header(RecencySection),
+    /// The match at this index in `matches`.
+    Match(usize),
+}
+
+impl RecentFilesRow {
+    fn is_selectable(&self) -> bool {
+        matches!(self, RecentFilesRow::Match(_))
+    }
+}
+
+/// Lays out the matches in order, starting a new section whenever the section of the next
+/// match differs from the previous one.
+/// The selectable row closest to `ix`, preferring the ones after it, so the selection
+/// stays in place when the row it was on is removed.
+fn nearest_selectable_row(rows: &[RecentFilesRow], ix: usize) -> Option<usize> {
+    let ix = ix.min(rows.len().checked_sub(1)?);
+    (ix..rows.len())
+        .chain((0..ix).rev())
+        .find(|&ix| rows[ix].is_selectable())
+}
+
+fn rows_with_sections(sections: impl IntoIterator<Item = RecencySection>) -> Vec<RecentFilesRow> {
+    let mut rows = Vec::new();
+    let mut current_section = None;
+    for (match_index, section) in sections.into_iter().enumerate() {
+        if current_section != Some(section) {
+            rows.push(RecentFilesRow::Header(section));
+            current_section = Some(section);
+        }
+        rows.push(RecentFilesRow::Match(match_index));
+    }
+    rows
+}
+
+struct RecentFilesDelegate {
     workspace: WeakEntity<Workspace>,
-    directory: PathBuf,
     files: Vec<PathBuf>,
+    /// The compacted paths of `files`, matched against the query off the main thread.
+    candidates: Arc<Vec<StringMatchCandidate>>,
+    /// Set when a newer query supersedes the search in flight.
+    search_cancelled: Option<Arc<AtomicBool>>,
+    /// Whether only the files within `worktree_roots` are matched.
+    current_workspace_only: bool,
+    /// The roots of the worktrees of the workspace the picker was opened from.
+    worktree_roots: Arc<Vec<PathBuf>>,
+    /// Whether each of `candidates` lies within `worktree_roots`, computed off the main
+    /// thread the first time `current_workspace_only` is enabled.
+    within_worktree_roots: Option<Arc<Vec<bool>>>,
+    /// Whether each of `candidates` still exists, statted once when `hide_missing` is set.
+    existing_files: Option<Arc<Vec<bool>>>,
     matches: Vec<StringMatch>,
+    /// Index of the first of `matches` found by the fuzzy fallback rather than by matching
+    /// every word of the query.
+    first_fuzzy_match: usize,
+    /// The rows of the picker: the matches, preceded by section headers while the query is empty.
+    rows: Vec<RecentFilesRow>,
     selected_match_index: usize,
+    /// Whether the next matches select the first row, rather than the one nearest to the
+    /// current selection.
+    reset_selected_match_index: bool,
+    create_new_window: bool,
+    /// Width in characters of the file name column when `recent_files.align_paths` is enabled.
+    file_name_column_len: usize,
+    /// Thumbnails of the image files in `files`, filled in as they finish loading.
+    thumbnails: HashMap<PathBuf, Arc<Image>>,
+    /// When each of `files` was last opened, shown by `recent_files.debug_scores`.
+    last_opened: HashMap<PathBuf, SystemTime>,
+    /// The git statuses of the changed files in the workspace's project when the picker opened.
+    git_statuses: HashMap<PathBuf, FileStatus>,
+    /// The name of the recent workspace each of `files` belongs to, filled in once loaded.
+    workspace_names: HashMap<PathBuf, SharedString>,
+    /// Where to move the cursor in the confirmed file, from a `:line` or `:line:column`
+    /// suffix of the query.
+    position: Option<Point>,
+    /// The files to open together on confirm, in the order they were checked. Kept by
+    /// path, so they stay checked while the query changes.
+    checked: Vec<PathBuf>,
 }
 
-impl DirectoryFileDelegate {
-    fn new(workspace: WeakEntity<Workspace>, directory: PathBuf) -> Self {
-        let expanded_dir = expand_tilde(&directory);
-        let files = Self::list_files(&expanded_dir);
+impl RecentFilesDelegate {
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        create_new_window: bool,
+        prune_missing: bool,
+        git_statuses: HashMap<PathBuf, FileStatus>,
+    ) -> Self {
+        let recent_files = existing_recent_files(prune_missing);
+        let files: Vec<PathBuf> = recent_files
+            .iter()
+            .map(|recent_file| recent_file.path.clone())
+            .collect();
         Self {
             workspace,
-            directory: expanded_dir,
+            candidates: Arc::new(match_candidates(&files)),
             files,
+            search_cancelled: None,
+            current_workspace_only: false,
+            worktree_roots: Arc::default(),
+            within_worktree_roots: None,
+            existing_files: None,
             matches: Vec::new(),
+            first_fuzzy_match: 0,
+            rows: Vec::new(),
             selected_match_index: 0,
+            reset_selected_match_index: true,
+            create_new_window,
+            file_name_column_len: 0,
+            thumbnails: HashMap::default(),
+            last_opened: recent_files
+                .into_iter()
+                .map(|recent_file| (recent_file.path, recent_file.last_opened))
+                .collect(),
+            git_statuses,
+            workspace_names: HashMap::default(),
+            position: None,
+            checked: Vec::new(),
         }
     }
 
-    fn list_files(directory: &Path) -> Vec<PathBuf> {
-        let output = smol::block_on(
-            smol::process::Command::new("rg")
-                .args(["--files"])
-                .current_dir(directory)
-                .output(),
-        );
-
-        match output {
-            Ok(output) if output.status.success() => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                stdout.lines().map(|line| directory.join(line)).collect()
-            }
-            _ => Vec::new(),
-        }
+    /// Empties the list after the recent files were cleared.
+    fn clear_files(&mut self) {
+        self.files.clear();
+        self.candidates = Arc::default();
+        self.within_worktree_roots = None;
+        self.existing_files = None;
+        self.checked.clear();
+        self.last_opened.clear();
+        self.thumbnails.clear();
+        self.workspace_names.clear();
+        self.matches.clear();
+        self.rows.clear();
+        self.first_fuzzy_match = 0;
+        self.selected_match_index = 0;
     }
-}
 
-impl EventEmitter<DismissEvent> for DirectoryFileDelegate {}
-
-impl PickerDelegate for DirectoryFileDelegate {
-    type ListItem = ListItem;
+    fn path_at_row(&self, ix: usize) -> Option<&Path> {
+        let RecentFilesRow::Match(match_index) = self.rows.get(ix)? else {
+            return None;
+        };
+        let hit = self.matches.get(*match_index)?;
+        self.files.get(hit.candidate_id).map(PathBuf::as_path)
+    }
 
-    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
-        let display_path = self.directory.compact();
-        Arc::from(format!("Open file from {}…", display_path.display()))
+    fn delete_recent_file(
+        &mut self,
+        ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(path) = self.path_at_row(ix).map(Path::to_path_buf) else {
+            return;
+        };
+        if let Some(index) = self.files.iter().position(|file| file == &path) {
+            self.files.remove(index);
+            if let Some(existing_files) = &mut self.existing_files {
+                Arc::make_mut(existing_files).remove(index);
+            }
+            self.remove_candidate_from_matches(index, ix);
+        }
+        self.checked.retain(|file| file != &path);
+        self.candidates = Arc::new(match_candidates(&self.files));
+        self.within_worktree_roots = None;
+        self.last_opened.remove(&path);
+        self.thumbnails.remove(&path);
+        cx.spawn_in(window, async move |picker, cx| {
+            delete_recent_file(path).await;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.selected_match_index = ix;
+                picker.delegate.reset_selected_match_index = false;
+                picker.update_matches(picker.query(cx), window, cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Drops the match of the file that was at `candidate_id` in `files`, and points the
+    /// other matches at where their files moved, so the rows keep showing the right files
+    /// until the matches are updated. The selection stays on row `selected_row`.
+    fn remove_candidate_from_matches(&mut self, candidate_id: usize, selected_row: usize) {
+        // Only the rows of an empty query have section headers.
+        let query_is_empty = self.rows.iter().any(|row| !row.is_selectable());
+        let mut matches = mem::take(&mut self.matches);
+        if let Some(position) = matches
+            .iter()
+            .position(|hit| hit.candidate_id == candidate_id)
+        {
+            matches.remove(position);
+            if position < self.first_fuzzy_match {
+                self.first_fuzzy_match -= 1;
+            }
+        }
+        for hit in &mut matches {
+            if hit.candidate_id > candidate_id {
+                hit.candidate_id -= 1;
+            }
+        }
+        self.selected_match_index = selected_row;
+        self.reset_selected_match_index = false;
+        self.set_matches(matches, self.first_fuzzy_match, query_is_empty);
+    }
+
+    fn open_selected(
+        &mut self,
+        secondary: bool,
+        split_direction: Option<SplitDirection>,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let paths = paths_to_open(&self.checked, self.selected_path());
+        if !paths.is_empty() {
+            let create_new_window = if self.create_new_window {
+                !secondary
+            } else {
+                secondary
+            };
+
+            if let Some(workspace) = self.workspace.upgrade() {
+                open_recent_files_at(
+                    workspace,
+                    paths,
+                    self.position,
+                    create_new_window,
+                    split_direction,
+                    window,
+                    cx,
+                );
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn toggle_checked(&mut self, cx: &mut Context<Picker<Self>>) {
+        let Some(path) = self.selected_path().map(Path::to_path_buf) else {
+            return;
+        };
+        if let Some(ix) = self.checked.iter().position(|checked| checked == &path) {
+            self.checked.remove(ix);
+        } else {
+            self.checked.push(path);
+        }
+        cx.notify();
+    }
+
+    fn selected_path(&self) -> Option<&Path> {
+        self.path_at_row(self.selected_match_index)
+    }
+
+    fn set_matches(
+        &mut self,
+        matches: Vec<StringMatch>,
+        first_fuzzy_match: usize,
+        query_is_empty: bool,
+    ) {
+        self.matches = matches;
+        self.first_fuzzy_match = first_fuzzy_match;
+        self.file_name_column_len = file_name_column_len(
+            self.matches
+                .iter()
+                .filter_map(|hit| self.files.get(hit.candidate_id)),
+        );
+
+        self.rows = if query_is_empty {
+            let today = Local::now().date_naive();
+            rows_with_sections(self.matches.iter().map(|hit| {
+                self.files
+                    .get(hit.candidate_id)
+                    .and_then(|path| self.last_opened.get(path))
+                    .map_or(RecencySection::Earlier, |last_opened| {
+                        RecencySection::for_time(today, *last_opened)
+                    })
+            }))
+        } else {
+            (0..self.matches.len()).map(RecentFilesRow::Match).collect()
+        };
+        let selected_match_index = if self.reset_selected_match_index {
+            self.rows.iter().position(RecentFilesRow::is_selectable)
+        } else {
+            self.reset_selected_match_index = true;
+            nearest_selectable_row(&self.rows, self.selected_match_index)
+        };
+        self.selected_match_index = selected_match_index.unwrap_or(0);
+    }
+}
+
+/// The checked files, or otherwise the selected one.
+fn paths_to_open(checked: &[PathBuf], selected: Option<&Path>) -> Vec<PathBuf> {
+    if checked.is_empty() {
+        selected.map(Path::to_path_buf).into_iter().collect()
+    } else {
+        checked.to_vec()
+    }
+}
+
+/// Whether each of `candidates` lies within one of `roots`.
+fn candidates_within_roots(candidates: &[StringMatchCandidate], roots: &[PathBuf]) -> Vec<bool> {
+    let canonical_roots = roots
+        .iter()
+        .map(|root| canonical_path(root))
+        .collect::<Vec<_>>();
+    candidates
+        .iter()
+        .map(|candidate| is_within_roots(Path::new(&candidate.string), &canonical_roots))
+        .collect()
+}
+
+/// Whether the file of each candidate still exists. They are statted in one batch on a
+/// background thread, so typing doesn't wait on the file system.
+fn candidates_exist(candidates: &[StringMatchCandidate]) -> Vec<bool> {
+    candidates
+        .iter()
+        .map(|candidate| path_exists(Path::new(&candidate.string)))
+        .collect()
+}
+
+/// The compacted paths of `files`, identified by their index.
+fn match_candidates(files: &[PathBuf]) -> Vec<StringMatchCandidate> {
+    files
+        .iter()
+        .enumerate()
+        .map(|(id, path)| StringMatchCandidate::new(id, &path.compact().to_string_lossy()))
+        .collect()
+}
+
+impl EventEmitter<DismissEvent> for RecentFilesDelegate {}
+
+impl PickerDelegate for RecentFilesDelegate {
+    type ListItem = AnyElement;
+
+    fn placeholder_text(&self, window: &mut Window, cx: &mut App) -> Arc<str> {
+        let scope = if self.current_workspace_only {
+            "Search recent files in this workspace"
+        } else {
+            "Search recent files"
+        };
+        match ui::text_for_action(&OpenInSplit, window, cx) {
+            Some(keystrokes) => format!("{scope} ({keystrokes} opens in a split)...").into(),
+            None => format!("{scope}...").into(),
+        }
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        self.rows.len()
     }
 
     fn selected_index(&self) -> usize {
@@ -860,61 +2220,141 @@ impl PickerDelegate for DirectoryFileDelegate {
         self.selected_match_index = ix;
     }
 
+    fn can_select(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) -> bool {
+        self.rows.get(ix).is_some_and(RecentFilesRow::is_selectable)
+    }
+
     fn update_matches(
         &mut self,
         query: String,
-        _: &mut Window,
-        _cx: &mut Context<Picker<Self>>,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
     ) -> gpui::Task<()> {
-        let query = query.trim_start();
+        let (query, extension_globs) = split_extension_filters(query.trim_start());
+        let (query, position) = split_position_suffix(query);
+        self.position = position;
+        let query = query.to_string();
         let smart_case = query.chars().any(|c| c.is_uppercase());
-        let candidates = self
-            .files
-            .iter()
-            .enumerate()
-            .map(|(id, path)| {
-                let path_str = path
-                    .strip_prefix(&self.directory)
-                    .unwrap_or(path)
-                    .to_string_lossy()
-                    .into_owned();
-                StringMatchCandidate::new(id, &path_str)
-            })
-            .collect::<Vec<_>>();
-
-        self.matches = smol::block_on(match_strings_order_insensitive(
-            candidates.as_slice(),
-            query,
-            smart_case,
-            100,
-            &Default::default(),
-        ));
-        self.matches.sort_unstable_by_key(|m| m.candidate_id);
-
-        self.selected_match_index = 0;
-
-        Task::ready(())
-    }
-
-    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
-        if let Some(hit) = self.matches.get(self.selected_index()) {
-            let path = self.files[hit.candidate_id].clone();
 
-            if let Some(workspace) = self.workspace.upgrade() {
-                workspace.update(cx, |workspace, cx| {
-                    workspace
-                        .open_paths(
-                            vec![path],
-                            workspace::OpenOptions::default(),
-                            None,
-                            window,
-                            cx,
-                        )
-                        .detach();
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        if let Some(previous_cancel_flag) = self.search_cancelled.replace(cancel_flag.clone()) {
+            previous_cancel_flag.store(true, atomic::Ordering::Relaxed);
+        }
+        let candidates = self.candidates.clone();
+        let worktree_roots = self
+            .current_workspace_only
+            .then(|| self.worktree_roots.clone());
+        let within_worktree_roots = self.within_worktree_roots.clone();
+        let settings = RecentFilesSettings::get_global(cx);
+        let fuzzy_fallback = settings.fuzzy_fallback;
+        let match_mode = settings.match_mode;
+        let file_name_boost = settings.file_name_boost;
+        let hide_missing = settings.hide_missing;
+        let prune_missing = settings.prune_missing;
+        let existing_files = self.existing_files.clone();
+        let executor = cx.background_executor().clone();
+        let matches = cx.background_spawn({
+            let query = query.clone();
+            let cancel_flag = cancel_flag.clone();
+            async move {
+                let within_worktree_roots = worktree_roots.map(|roots| {
+                    within_worktree_roots
+                        .unwrap_or_else(|| Arc::new(candidates_within_roots(&candidates, &roots)))
                 });
+                let existing_files = hide_missing.then(|| {
+                    existing_files.unwrap_or_else(|| Arc::new(candidates_exist(&candidates)))
+                });
+                let filtered_candidates;
+                let candidates = if within_worktree_roots.is_some()
+                    || existing_files.is_some()
+                    || !extension_globs.is_empty()
+                {
+                    filtered_candidates = candidates
+                        .iter()
+                        .filter(|candidate| {
+                            within_worktree_roots
+                                .as_ref()
+                                .is_none_or(|within_worktree_roots| {
+                                    within_worktree_roots[candidate.id]
+                                })
+                                && existing_files
+                                    .as_ref()
+                                    .is_none_or(|existing_files| existing_files[candidate.id])
+                                && (extension_globs.is_empty()
+                                    || matches_extension_globs(&candidate.string, &extension_globs))
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    filtered_candidates.as_slice()
+                } else {
+                    candidates.as_slice()
+                };
+                let (matches, first_fuzzy_match) = match_recent_files(
+                    candidates,
+                    &query,
+                    smart_case,
+                    match_mode,
+                    fuzzy_fallback,
+                    file_name_boost,
+                    100,
+                    &cancel_flag,
+                    executor,
+                )
+                .await;
+                (
+                    matches,
+                    first_fuzzy_match,
+                    within_worktree_roots,
+                    existing_files,
+                )
             }
-        }
-        cx.emit(DismissEvent);
+        });
+        cx.spawn_in(window, async move |picker, cx| {
+            let (matches, first_fuzzy_match, within_worktree_roots, existing_files) = matches.await;
+            // A newer query started while this one was matching.
+            if cancel_flag.load(atomic::Ordering::Relaxed) {
+                return;
+            }
+            picker
+                .update(cx, |picker, cx| {
+                    if within_worktree_roots.is_some() {
+                        picker.delegate.within_worktree_roots = within_worktree_roots;
+                    }
+                    if let Some(existing_files) = existing_files
+                        && picker.delegate.existing_files.is_none()
+                    {
+                        if prune_missing {
+                            let missing_files = picker
+                                .delegate
+                                .files
+                                .iter()
+                                .zip(existing_files.iter())
+                                .filter(|(_, exists)| !**exists)
+                                .map(|(path, _)| path.clone())
+                                .collect::<Vec<_>>();
+                            if !missing_files.is_empty() {
+                                log::info!("Pruned {} missing recent files", missing_files.len());
+                                cx.background_spawn(async move {
+                                    for path in missing_files {
+                                        delete_recent_file(path).await;
+                                    }
+                                })
+                                .detach();
+                            }
+                        }
+                        picker.delegate.existing_files = Some(existing_files);
+                    }
+                    picker
+                        .delegate
+                        .set_matches(matches, first_fuzzy_match, query.is_empty());
+                    cx.notify();
+                })
+                .ok();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.open_selected(secondary, None, window, cx);
     }
 
     fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
@@ -923,21 +2363,65 @@ impl PickerDelegate for DirectoryFileDelegate {
         &self,
         ix: usize,
         selected: bool,
-        _window: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> Option<Self::ListItem> {
-        let hit = self.matches.get(ix)?;
+        let hit = match self.rows.get(ix)? {
+            RecentFilesRow::Header(section) => {
+                return Some(
+                    ListSubHeader::new(section.label())
+                        .inset(true)
+                        .into_any_element(),
+                );
+            }
+            RecentFilesRow::Match(match_index) => self.matches.get(*match_index)?,
+        };
         let path = self.files.get(hit.candidate_id)?;
+        let thumbnail = self.thumbnails.get(path).cloned();
+        let is_checked = self.checked.contains(path);
+        let git_status_color = self
+            .git_statuses
+            .get(path)
+            .and_then(|status| git_status_color(*status));
+        let debug_label = RecentFilesSettings::get_global(cx).debug_scores.then(|| {
+            let age = self
+                .last_opened
+                .get(path)
+                .and_then(|last_opened| SystemTime::now().duration_since(*last_opened).ok());
+            Label::new(debug_score_label(hit.candidate_id + 1, hit.score, age))
+                .size(LabelSize::XSmall)
+                .color(Color::Muted)
+        });
+        let is_fuzzy_match = matches!(
+            self.rows.get(ix),
+            Some(RecentFilesRow::Match(match_index)) if *match_index >= self.first_fuzzy_match
+        );
+        let end_label = debug_label.or_else(|| {
+            is_fuzzy_match.then(|| {
+                Label::new("fuzzy")
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted)
+            })
+        });
+        let workspace_label = self.workspace_names.get(path).map(|name| {
+            Label::new(name.clone())
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+        });
+        let end_labels = h_flex()
+            .gap_1()
+            .children(workspace_label)
+            .children(end_label);
 
-        let relative_path = path.strip_prefix(&self.directory).unwrap_or(path);
-        let path_string = relative_path.to_string_lossy();
+        let path = path.compact();
+        let path_string = path.to_string_lossy();
         let file_name = path
             .file_name()
             .map(|n| n.to_string_lossy())
             .unwrap_or_default();
 
         let file_name_start = path_string.len().saturating_sub(file_name.len());
-        let dir_name = path_string[0..file_name_start].to_string();
+        let mut dir_name = path_string[0..file_name_start].to_string();
 
         let file_name_highlights: Vec<usize> = hit
             .positions
@@ -945,265 +2429,2204 @@ impl PickerDelegate for DirectoryFileDelegate {
             .filter(|&&i| i >= file_name_start)
             .map(|&i| i - file_name_start)
             .collect();
+        let (file_name, file_name_highlights) =
+            truncate_middle(&file_name, MAX_FILE_NAME_LABEL_LEN, file_name_highlights);
 
-        let dir_highlights: Vec<usize> = hit
+        let mut dir_highlights: Vec<usize> = hit
             .positions
             .iter()
             .filter(|&&i| i < file_name_start)
             .copied()
             .collect();
 
-        let file_icon =
-            FileIcons::get_icon(path, cx).map(|icon| Icon::from_path(icon).color(Color::Muted));
+        let (normal_em, small_em) = {
+            let style = window.text_style();
+            let font_id = window.text_system().resolve_font(&style.font());
+            let font_size = ui::TextSize::Default.rems(cx).to_pixels(window.rem_size());
+            let normal = cx
+                .text_system()
+                .em_width(font_id, font_size)
+                .unwrap_or(px(16.));
+            let font_size = ui::TextSize::Small.rems(cx).to_pixels(window.rem_size());
+            let small = cx
+                .text_system()
+                .em_width(font_id, font_size)
+                .unwrap_or(px(10.));
+            (normal, small)
+        };
+        let align_paths = RecentFilesSettings::get_global(cx).align_paths;
+        let file_name_len = if align_paths {
+            file_name.chars().count().max(self.file_name_column_len)
+        } else {
+            file_name.chars().count()
+        };
+
+        if dir_name.is_ascii() {
+            let max_width = rems(48.).to_pixels(window.rem_size());
+            let budget = full_path_budget(file_name_len, normal_em, small_em, max_width);
+
+            if budget > 0 && dir_name.len() > budget {
+                let components = PathComponentSlice::new(&dir_name);
+                if let Some(elided_range) = components.elision_range(budget - 1, &dir_highlights) {
+                    let elided_len = elided_range.end - elided_range.start;
+                    let placeholder = "…";
+                    dir_highlights.retain_mut(|mat| {
+                        if *mat >= elided_range.end {
+                            *mat -= elided_len;
+                            *mat += placeholder.len();
+                        } else if *mat >= elided_range.start {
+                            return false;
+                        }
+                        true
+                    });
+                    dir_name.replace_range(elided_range, placeholder);
+                }
+            }
+        }
+
+        let file_icon = FileIcons::get_icon(&path, cx);
+        let start_slot = match thumbnail {
+            Some(thumbnail) => Some(
+                img(thumbnail)
+                    .size(IconSize::Medium.rems())
+                    .object_fit(ObjectFit::Contain)
+                    .rounded_xs()
+                    .with_fallback(move || match file_icon.clone() {
+                        Some(icon) => Icon::from_path(icon).color(Color::Muted).into_any_element(),
+                        None => gpui::Empty.into_any_element(),
+                    })
+                    .into_any_element(),
+            ),
+            None => {
+                file_icon.map(|icon| Icon::from_path(icon).color(Color::Muted).into_any_element())
+            }
+        };
+        let start_slot = match git_status_color {
+            Some(color) => Some(
+                h_flex()
+                    .gap_1()
+                    .children(start_slot)
+                    .child(Indicator::dot().color(color))
+                    .into_any_element(),
+            ),
+            None => start_slot,
+        };
+        let start_slot = if is_checked {
+            Some(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Accent),
+                    )
+                    .children(start_slot)
+                    .into_any_element(),
+            )
+        } else {
+            start_slot
+        };
+        let file_name_label = HighlightedLabel::new(file_name, file_name_highlights)
+            .when_some(git_status_color, |label, color| label.color(color));
+        let dir_label = HighlightedLabel::new(dir_name, dir_highlights)
+            .size(LabelSize::Small)
+            .color(Color::Muted);
+        let delete_button = IconButton::new("delete", IconName::Close)
+            .icon_size(IconSize::Small)
+            .tooltip(Tooltip::text("Remove from Recent Files"))
+            .on_click(cx.listener(move |this, _event, window, cx| {
+                cx.stop_propagation();
+                window.prevent_default();
+
+                this.delegate.delete_recent_file(ix, window, cx)
+            }));
 
         Some(
             ListItem::new(ix)
                 .spacing(ListItemSpacing::Sparse)
                 .toggle_state(selected)
-                .start_slot::<Icon>(file_icon)
+                .start_slot(start_slot)
+                .map(|item| {
+                    if selected {
+                        item.end_slot(end_labels.child(delete_button))
+                    } else {
+                        item.end_slot(end_labels).end_hover_slot(delete_button)
+                    }
+                })
                 .inset(true)
-                .child(
+                .child(if align_paths {
                     h_flex()
+                        .w_full()
                         .gap_2()
                         .py_px()
-                        .child(HighlightedLabel::new(
-                            file_name.to_string(),
-                            file_name_highlights,
-                        ))
                         .child(
-                            HighlightedLabel::new(dir_name, dir_highlights)
-                                .size(LabelSize::Small)
-                                .color(Color::Muted),
-                        ),
-                ),
+                            div()
+                                .flex_none()
+                                .min_w(normal_em * self.file_name_column_len as f32)
+                                .child(file_name_label),
+                        )
+                        .child(h_flex().flex_1().min_w_0().justify_end().child(dir_label))
+                } else {
+                    h_flex()
+                        .gap_2()
+                        .py_px()
+                        .child(file_name_label)
+                        .child(dir_label)
+                })
+                .into_any_element(),
         )
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct PathComponentSlice<'a> {
-    path: Cow<'a, Path>,
-    path_str: Cow<'a, str>,
-    component_ranges: Vec<(Component<'a>, Range<usize>)>,
+/// Text of the `recent_files.debug_scores` overlay for the entry at `rank` in
+/// recency order.
+fn debug_score_label(rank: usize, score: f64, age: Option<Duration>) -> String {
+    let age = match age {
+        Some(age) => format!("opened {} ago", format_age(age)),
+        None => "age unknown".to_string(),
+    };
+    format!("debug: #{rank} · score {score:.2} · {age}")
 }
 
-impl<'a> PathComponentSlice<'a> {
-    fn new(path: &'a str) -> Self {
-        let trimmed_path = Path::new(path).components().as_path().as_os_str();
-        let mut component_ranges = Vec::new();
-        let mut components = Path::new(trimmed_path).components();
-        let len = trimmed_path.as_encoded_bytes().len();
-        let mut pos = 0;
-        while let Some(component) = components.next() {
-            component_ranges.push((component, pos..0));
-            pos = len - components.as_path().as_os_str().as_encoded_bytes().len();
-        }
-        for ((_, range), ancestor) in component_ranges
-            .iter_mut()
-            .rev()
-            .zip(Path::new(trimmed_path).ancestors())
-        {
-            range.end = ancestor.as_os_str().as_encoded_bytes().len();
-        }
-        Self {
-            path: Cow::Borrowed(Path::new(path)),
-            path_str: Cow::Borrowed(path),
-            component_ranges,
-        }
+fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Longest file name among `paths` in characters, capped so that a single long
+/// name doesn't push every directory out of view.
+fn file_name_column_len<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> usize {
+    paths
+        .into_iter()
+        .filter_map(|path| path.file_name())
+        .map(|file_name| file_name.to_string_lossy().chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_ALIGNED_FILE_NAME_LEN)
+}
+
+fn full_path_budget(
+    file_name_len: usize,
+    normal_em: Pixels,
+    small_em: Pixels,
+    max_width: Pixels,
+) -> usize {
+    let remaining_width = (max_width / 0.8) - (file_name_len as f32) * normal_em;
+    let budget = (remaining_width / small_em).max(0.);
+    (budget as usize).max(MIN_DIR_NAME_BUDGET)
+}
+
+/// Elides the middle of `text` when it is longer than `max_chars`, keeping the
+/// highlight byte offsets that fall into the visible prefix and suffix.
+fn truncate_middle(text: &str, max_chars: usize, highlights: Vec<usize>) -> (String, Vec<usize>) {
+    let char_count = text.chars().count();
+    if char_count <= max_chars {
+        return (text.to_string(), highlights);
+    }
+
+    let placeholder = "…";
+    let visible_chars = max_chars.saturating_sub(1);
+    let suffix_chars = visible_chars / 2;
+    let prefix_chars = visible_chars - suffix_chars;
+    let prefix_end = text
+        .char_indices()
+        .nth(prefix_chars)
+        .map_or(text.len(), |(ix, _)| ix);
+    let suffix_start = text
+        .char_indices()
+        .nth(char_count - suffix_chars)
+        .map_or(text.len(), |(ix, _)| ix);
+
+    let truncated = format!(
+        "{}{placeholder}{}",
+        &text[..prefix_end],
+        &text[suffix_start..]
+    );
+    let highlights = highlights
+        .into_iter()
+        .filter_map(|ix| {
+            if ix < prefix_end {
+                Some(ix)
+            } else if ix >= suffix_start {
+                Some(ix - suffix_start + prefix_end + placeholder.len())
+            } else {
+                None
+            }
+        })
+        .collect();
+    (truncated, highlights)
+}
+
+struct RecentlyClosedBuffers {
+    picker: Entity<Picker<RecentlyClosedBuffersDelegate>>,
+    _subscription: Subscription,
+}
+
+impl ModalView for RecentlyClosedBuffers {}
+
+impl RecentlyClosedBuffers {
+    fn new(
+        delegate: RecentlyClosedBuffersDelegate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+
+    pub fn open(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let weak = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let delegate = RecentlyClosedBuffersDelegate::new(weak);
+            Self::new(delegate, window, cx)
+        })
+    }
+}
+
+impl EventEmitter<DismissEvent> for RecentlyClosedBuffers {}
+
+impl Focusable for RecentlyClosedBuffers {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for RecentlyClosedBuffers {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RecentlyClosedBuffers")
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|this, _, window, cx| {
+                this.picker.update(cx, |this, cx| {
+                    this.cancel(&Default::default(), window, cx);
+                })
+            }))
+    }
+}
+
+struct RecentlyClosedBuffersDelegate {
+    workspace: WeakEntity<Workspace>,
+    files: Vec<PathBuf>,
+    matches: Vec<StringMatch>,
+    selected_match_index: usize,
+}
+
+impl RecentlyClosedBuffersDelegate {
+    fn new(workspace: WeakEntity<Workspace>) -> Self {
+        Self {
+            workspace,
+            files: RECENTLY_CLOSED_BUFFERS.lock().clone(),
+            matches: Vec::new(),
+            selected_match_index: 0,
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for RecentlyClosedBuffersDelegate {}
+
+impl PickerDelegate for RecentlyClosedBuffersDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
+        Arc::from("Reopen a closed buffer...")
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No buffers closed in this session".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_match_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_match_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let query = query.trim_start();
+        let smart_case = query.chars().any(|c| c.is_uppercase());
+        let candidates = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(id, path)| {
+                let path_str = path.compact().to_string_lossy().into_owned();
+                StringMatchCandidate::new(id, &path_str)
+            })
+            .collect::<Vec<_>>();
+
+        let options = WordMatchOptions {
+            smart_case,
+            max_results: MAX_RECENTLY_CLOSED_BUFFERS,
+            ..WordMatchOptions::default()
+        };
+        self.matches = smol::block_on(match_strings_order_insensitive(
+            candidates.as_slice(),
+            query,
+            &options,
+            &Default::default(),
+            cx.background_executor().clone(),
+        ));
+        // Keep close order rather than score order.
+        self.matches.sort_unstable_by_key(|m| m.candidate_id);
+
+        self.selected_match_index = 0;
+
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(hit) = self.matches.get(self.selected_index()) {
+            let path = self.files[hit.candidate_id].clone();
+
+            if let Some(workspace) = self.workspace.upgrade() {
+                workspace.update(cx, |workspace, cx| {
+                    workspace
+                        .open_paths(
+                            vec![path],
+                            workspace::OpenOptions::default(),
+                            None,
+                            window,
+                            cx,
+                        )
+                        .detach();
+                });
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let path = self.files.get(hit.candidate_id)?;
+
+        let compact_path = path.compact();
+        let path_string = compact_path.to_string_lossy();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+
+        let file_name_start = path_string.len().saturating_sub(file_name.len());
+        let dir_name = path_string[0..file_name_start].to_string();
+
+        let file_name_highlights: Vec<usize> = hit
+            .positions
+            .iter()
+            .filter(|&&i| i >= file_name_start)
+            .map(|&i| i - file_name_start)
+            .collect();
+
+        let dir_highlights: Vec<usize> = hit
+            .positions
+            .iter()
+            .filter(|&&i| i < file_name_start)
+            .copied()
+            .collect();
+
+        let file_icon =
+            FileIcons::get_icon(path, cx).map(|icon| Icon::from_path(icon).color(Color::Muted));
+
+        Some(
+            ListItem::new(ix)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot::<Icon>(file_icon)
+                .inset(true)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .py_px()
+                        .child(HighlightedLabel::new(
+                            file_name.to_string(),
+                            file_name_highlights,
+                        ))
+                        .child(
+                            HighlightedLabel::new(dir_name, dir_highlights)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+struct DirectoryFilePicker {
+    picker: Entity<Picker<DirectoryFileDelegate>>,
+    _subscription: Subscription,
+}
+
+impl ModalView for DirectoryFilePicker {}
+
+impl DirectoryFilePicker {
+    fn new(delegate: DirectoryFileDelegate, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let scroll_handle = UniformListScrollHandle::new();
+        let picker = cx.new(|cx| {
+            Picker::uniform_list(delegate, window, cx)
+                .max_height(None)
+                .track_scroll(scroll_handle.clone())
+                .show_scrollbar(true)
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+
+    pub fn open(
+        workspace: &mut Workspace,
+        directory: PathBuf,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let weak = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let delegate = DirectoryFileDelegate::new(weak, directory);
+            Self::new(delegate, window, cx)
+        })
+    }
+}
+
+impl EventEmitter<DismissEvent> for DirectoryFilePicker {}
+
+impl Focusable for DirectoryFilePicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for DirectoryFilePicker {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let viewport_size = window.viewport_size();
+        let modal_width = (viewport_size.width * 0.7).min(viewport_size.width);
+        let modal_height = (viewport_size.height * 0.7).min(viewport_size.height);
+
+        v_flex()
+            .key_context("DirectoryFilePicker")
+            .w(modal_width)
+            .h(modal_height)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(self.picker.clone()),
+            )
+            .on_mouse_down_out(cx.listener(|this, _, window, cx| {
+                this.picker.update(cx, |this, cx| {
+                    this.cancel(&Default::default(), window, cx);
+                })
+            }))
+    }
+}
+
+struct DirectoryFileDelegate {
+    workspace: WeakEntity<Workspace>,
+    directory: PathBuf,
+    files: Vec<PathBuf>,
+    matches: Vec<StringMatch>,
+    selected_match_index: usize,
+}
+
+impl DirectoryFileDelegate {
+    fn new(workspace: WeakEntity<Workspace>, directory: PathBuf) -> Self {
+        let expanded_dir = expand_tilde(&directory);
+        let files = Self::list_files(&expanded_dir);
+        Self {
+            workspace,
+            directory: expanded_dir,
+            files,
+            matches: Vec::new(),
+            selected_match_index: 0,
+        }
+    }
+
+    fn list_files(directory: &Path) -> Vec<PathBuf> {
+        let output = smol::block_on(
+            smol::process::Command::new("rg")
+                .args(["--files"])
+                .current_dir(directory)
+                .output(),
+        );
+
+        match output {
+            Ok(output) if output.status.success() => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                stdout.lines().map(|line| directory.join(line)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for DirectoryFileDelegate {}
+
+impl PickerDelegate for DirectoryFileDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
+        let display_path = self.directory.compact();
+        Arc::from(format!("Open file from {}…", display_path.display()))
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_match_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_match_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let query = query.trim_start();
+        let smart_case = query.chars().any(|c| c.is_uppercase());
+        let candidates = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(id, path)| {
+                let path_str = path
+                    .strip_prefix(&self.directory)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .into_owned();
+                StringMatchCandidate::new(id, &path_str)
+            })
+            .collect::<Vec<_>>();
+
+        let options = WordMatchOptions {
+            smart_case,
+            max_results: 100,
+            ..WordMatchOptions::default()
+        };
+        self.matches = smol::block_on(match_strings_order_insensitive(
+            candidates.as_slice(),
+            query,
+            &options,
+            &Default::default(),
+            cx.background_executor().clone(),
+        ));
+        self.matches.sort_unstable_by_key(|m| m.candidate_id);
+
+        self.selected_match_index = 0;
+
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(hit) = self.matches.get(self.selected_index()) {
+            let path = self.files[hit.candidate_id].clone();
+
+            if let Some(workspace) = self.workspace.upgrade() {
+                workspace.update(cx, |workspace, cx| {
+                    workspace
+                        .open_paths(
+                            vec![path],
+                            workspace::OpenOptions::default(),
+                            None,
+                            window,
+                            cx,
+                        )
+                        .detach();
+                });
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let path = self.files.get(hit.candidate_id)?;
+
+        let relative_path = path.strip_prefix(&self.directory).unwrap_or(path);
+        let path_string = relative_path.to_string_lossy();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+
+        let file_name_start = path_string.len().saturating_sub(file_name.len());
+        let dir_name = path_string[0..file_name_start].to_string();
+
+        let file_name_highlights: Vec<usize> = hit
+            .positions
+            .iter()
+            .filter(|&&i| i >= file_name_start)
+            .map(|&i| i - file_name_start)
+            .collect();
+
+        let dir_highlights: Vec<usize> = hit
+            .positions
+            .iter()
+            .filter(|&&i| i < file_name_start)
+            .copied()
+            .collect();
+
+        let file_icon =
+            FileIcons::get_icon(path, cx).map(|icon| Icon::from_path(icon).color(Color::Muted));
+
+        Some(
+            ListItem::new(ix)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot::<Icon>(file_icon)
+                .inset(true)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .py_px()
+                        .child(HighlightedLabel::new(
+                            file_name.to_string(),
+                            file_name_highlights,
+                        ))
+                        .child(
+                            HighlightedLabel::new(dir_name, dir_highlights)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PathComponentSlice<'a> {
+    path: Cow<'a, Path>,
+    path_str: Cow<'a, str>,
+    component_ranges: Vec<(Component<'a>, Range<usize>)>,
+}
+
+impl<'a> PathComponentSlice<'a> {
+    fn new(path: &'a str) -> Self {
+        let trimmed_path = Path::new(path).components().as_path().as_os_str();
+        let mut component_ranges = Vec::new();
+        let mut components = Path::new(trimmed_path).components();
+        let len = trimmed_path.as_encoded_bytes().len();
+        let mut pos = 0;
+        while let Some(component) = components.next() {
+            component_ranges.push((component, pos..0));
+            pos = len - components.as_path().as_os_str().as_encoded_bytes().len();
+        }
+        for ((_, range), ancestor) in component_ranges
+            .iter_mut()
+            .rev()
+            .zip(Path::new(trimmed_path).ancestors())
+        {
+            range.end = ancestor.as_os_str().as_encoded_bytes().len();
+        }
+        Self {
+            path: Cow::Borrowed(Path::new(path)),
+            path_str: Cow::Borrowed(path),
+            component_ranges,
+        }
+    }
+
+    fn elision_range(&self, budget: usize, matches: &[usize]) -> Option<Range<usize>> {
+        let eligible_range = {
+            assert!(matches.windows(2).all(|w| w[0] <= w[1]));
+            let mut matches = matches.iter().copied().peekable();
+            let mut longest: Option<Range<usize>> = None;
+            let mut cur = 0..0;
+            let mut seen_normal = false;
+            for (i, (component, range)) in self.component_ranges.iter().enumerate() {
+                let is_normal = matches!(component, Component::Normal(_));
+                let is_first_normal = is_normal && !seen_normal;
+                seen_normal |= is_normal;
+                let is_last = i == self.component_ranges.len() - 1;
+                let contains_match = matches.peek().is_some_and(|mat| range.contains(mat));
+                if contains_match {
+                    matches.next();
+                }
+                if is_first_normal || is_last || !is_normal || contains_match {
+                    if longest
+                        .as_ref()
+                        .is_none_or(|old| old.end - old.start <= cur.end - cur.start)
+                    {
+                        longest = Some(cur);
+                    }
+                    cur = i + 1..i + 1;
+                } else {
+                    cur.end = i + 1;
+                }
+            }
+            if longest
+                .as_ref()
+                .is_none_or(|old| old.end - old.start <= cur.end - cur.start)
+            {
+                longest = Some(cur);
+            }
+            longest
+        };
+
+        let eligible_range = eligible_range?;
+        assert!(eligible_range.start <= eligible_range.end);
+        if eligible_range.is_empty() {
+            return None;
+        }
+
+        let elided_range: Range<usize> = {
+            let byte_range = self.component_ranges[eligible_range.start].1.start
+                ..self.component_ranges[eligible_range.end - 1].1.end;
+            let midpoint = self.path_str.len() / 2;
+            let distance_from_start = byte_range.start.abs_diff(midpoint);
+            let distance_from_end = byte_range.end.abs_diff(midpoint);
+            let pick_from_end = distance_from_start > distance_from_end;
+            let mut len_with_elision = self.path_str.len();
+            let mut i = eligible_range.start;
+            while i < eligible_range.end {
+                let x = if pick_from_end {
+                    eligible_range.end - i + eligible_range.start - 1
+                } else {
+                    i
+                };
+                len_with_elision -= self.component_ranges[x]
+                    .0
+                    .as_os_str()
+                    .as_encoded_bytes()
+                    .len()
+                    + 1;
+                if len_with_elision <= budget {
+                    break;
+                }
+                i += 1;
+            }
+            if len_with_elision > budget {
+                return None;
+            } else if pick_from_end {
+                let x = eligible_range.end - i + eligible_range.start - 1;
+                x..eligible_range.end
+            } else {
+                let x = i;
+                eligible_range.start..x + 1
+            }
+        };
+
+        let byte_range = self.component_ranges[elided_range.start].1.start
+            ..self.component_ranges[elided_range.end - 1].1.end;
+        Some(byte_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_workspace_path_matching() {
+        // Test the core logic of finding the deepest workspace path
+        let workspace_paths = vec![
+            PathBuf::from("/Users/dima/Developer"),
+            PathBuf::from("/Users/dima/Developer/zed"),
+            PathBuf::from("/Users/dima/Developer/zed/docs"),
+        ];
+
+        let file_path = PathBuf::from("/Users/dima/Developer/zed/docs/src/configuring-zed.md");
+
+        // Find the deepest matching path
+        let mut best_match: Option<(PathBuf, usize)> = None;
+        for workspace_path in &workspace_paths {
+            if file_path.starts_with(workspace_path) {
+                let depth = workspace_path.components().count();
+                if best_match
+                    .as_ref()
+                    .map_or(true, |(_, best_depth)| depth > *best_depth)
+                {
+                    best_match = Some((workspace_path.clone(), depth));
+                }
+            }
+        }
+
+        // Should match the deepest path: /Users/dima/Developer/zed/docs
+        assert_eq!(
+            best_match.unwrap().0,
+            PathBuf::from("/Users/dima/Developer/zed/docs")
+        );
+    }
+
+    #[test]
+    fn test_workspace_path_matching_prefers_most_recent() {
+        // Test that we prefer the most recent workspace that contains the file
+        // Simulating the order that recent_workspaces_on_disk() would return
+        let workspace_paths_in_recency_order = vec![
+            PathBuf::from("/Users/dima/Developer/zed"), // Most recent
+            PathBuf::from("/Users/dima/Developer/zed/docs"), // Less recent
+        ];
+
+        let file_path = PathBuf::from("/Users/dima/Developer/zed/docs/src/configuring-zed.md");
+
+        // Find the first (most recent) matching workspace
+        let mut first_match: Option<PathBuf> = None;
+        for workspace_path in &workspace_paths_in_recency_order {
+            if file_path.starts_with(workspace_path) {
+                first_match = Some(workspace_path.clone());
+                break; // Take the first match (most recent)
+            }
+        }
+
+        // Should match the most recent workspace that contains the file: /Users/dima/Developer/zed
+        assert_eq!(
+            first_match.unwrap(),
+            PathBuf::from("/Users/dima/Developer/zed")
+        );
+    }
+
+    #[test]
+    fn test_tilde_expansion() {
+        use super::expand_tilde;
+
+        // Test tilde expansion
+        let tilde_path = PathBuf::from("~/Developer/zed");
+        let expanded = expand_tilde(&tilde_path);
+
+        // Should expand to absolute path
+        assert!(expanded.is_absolute());
+        assert!(expanded.to_string_lossy().contains("Developer/zed"));
+        assert!(!expanded.to_string_lossy().starts_with("~"));
+
+        // Test non-tilde path remains unchanged
+        let abs_path = PathBuf::from("/Users/test/project");
+        let unchanged = expand_tilde(&abs_path);
+        assert_eq!(unchanged, abs_path);
+    }
+
+    #[test]
+    fn test_tilde_expansion_with_windows_home_variables() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+
+        let home = home_dir_from_env(env(&[("USERPROFILE", r"C:\Users\dima")]));
+        assert_eq!(home, Some(PathBuf::from(r"C:\Users\dima")));
+        let home = home_dir_from_env(env(&[
+            ("HOME", ""),
+            ("HOMEDRIVE", "D:"),
+            ("HOMEPATH", r"\Users\dima"),
+        ]));
+        assert_eq!(home, Some(PathBuf::from(r"D:\Users\dima")));
+        // `HOME` wins when it's set.
+        let home = home_dir_from_env(env(&[("HOME", "/home/dima"), ("USERPROFILE", "C:")]));
+        assert_eq!(home, Some(PathBuf::from("/home/dima")));
+        assert_eq!(home_dir_from_env(env(&[("HOMEDRIVE", "D:")])), None);
+
+        let home = Path::new("/home/dima");
+        assert_eq!(
+            expand_tilde_with_home(Path::new("~/Developer/zed"), Some(home)),
+            home.join("Developer/zed")
+        );
+        assert_eq!(
+            expand_tilde_with_home(Path::new(r"~\Developer\zed"), Some(home)),
+            home.join(r"Developer\zed")
+        );
+        assert_eq!(expand_tilde_with_home(Path::new("~"), Some(home)), home);
+        assert_eq!(
+            expand_tilde_with_home(Path::new("~dima/zed"), Some(home)),
+            Path::new("~dima/zed")
+        );
+        assert_eq!(
+            expand_tilde_with_home(Path::new("~/zed"), None),
+            Path::new("~/zed")
+        );
+    }
+
+    #[test]
+    fn test_workspace_matching_with_tilde_paths() {
+        use super::expand_tilde;
+
+        // Simulate workspace paths (could be absolute)
+        let workspace_paths = vec![PathBuf::from("/Users/dima/Developer/zed")];
+
+        // Simulate file path with tilde (as it appears in recent files)
+        let file_path_with_tilde = PathBuf::from("~/Developer/zed/docs/src/configuring-zed.md");
+        let expanded_file_path = expand_tilde(&file_path_with_tilde);
+
+        // Find matching workspace
+        let mut match_found = false;
+        for workspace_path in &workspace_paths {
+            let expanded_workspace = expand_tilde(workspace_path);
+            if expanded_file_path.starts_with(&expanded_workspace) {
+                match_found = true;
+                break;
+            }
+        }
+
+        assert!(
+            match_found,
+            "Should find workspace match after tilde expansion"
+        );
+    }
+
+    #[test]
+    fn test_is_within_roots() {
+        use super::{expand_tilde, is_within_roots};
+
+        let roots = [
+            PathBuf::from("/nonexistent/zed"),
+            expand_tilde(Path::new("~/nonexistent/notes")),
+        ];
+        assert!(is_within_roots(
+            Path::new("/nonexistent/zed/src/main.rs"),
+            &roots
+        ));
+        assert!(is_within_roots(
+            Path::new("~/nonexistent/notes/todo.md"),
+            &roots
+        ));
+        // Roots only contain the paths below them, not siblings sharing their prefix.
+        assert!(!is_within_roots(
+            Path::new("/nonexistent/zed2/src/main.rs"),
+            &roots
+        ));
+        assert!(!is_within_roots(Path::new("/nonexistent/other.rs"), &roots));
+        assert!(!is_within_roots(Path::new("/nonexistent/zed/a.rs"), &[]));
+    }
+
+    #[test]
+    fn test_candidates_exist() {
+        use super::{candidates_exist, match_candidates};
+
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let candidates = match_candidates(&[
+            crate_dir.join("Cargo.toml"),
+            crate_dir.join("src/removed.rs"),
+            crate_dir.join("src/lib.rs"),
+        ]);
+        assert_eq!(candidates_exist(&candidates), [true, false, true]);
+    }
+
+    #[test]
+    fn test_workspace_names_for_files() {
+        use super::workspace_names_for_files;
+
+        let files = [
+            PathBuf::from("/nonexistent/zed/crates/main.rs"),
+            PathBuf::from("/nonexistent/zed/docs/README.md"),
+            PathBuf::from("/nonexistent/notes/todo.md"),
+            PathBuf::from("/nonexistent/scratch.txt"),
+        ];
+        // The docs are also a workspace of their own, opened more recently than zed.
+        let workspace_roots = [
+            PathBuf::from("/nonexistent/zed/docs"),
+            PathBuf::from("/nonexistent/zed"),
+            PathBuf::from("/nonexistent/notes"),
+        ];
+        let names = workspace_names_for_files(&files, &workspace_roots);
+        assert_eq!(names.get(&files[0]).map(|name| name.as_ref()), Some("zed"));
+        assert_eq!(names.get(&files[1]).map(|name| name.as_ref()), Some("docs"));
+        assert_eq!(
+            names.get(&files[2]).map(|name| name.as_ref()),
+            Some("notes")
+        );
+        assert_eq!(names.get(&files[3]), None);
+    }
+
+    #[test]
+    fn test_group_by_workspace_with_mixed_workspaces() {
+        use super::group_by_workspace;
+
+        let files = vec![
+            (PathBuf::from("/zed/a.rs"), Some("zed")),
+            (PathBuf::from("/notes/todo.md"), Some("notes")),
+            (PathBuf::from("/tmp/scratch.txt"), None),
+            (PathBuf::from("/zed/b.rs"), Some("zed")),
+            (PathBuf::from("/tmp/other.txt"), None),
+        ];
+        assert_eq!(
+            group_by_workspace(files, |a, b| a == b),
+            [
+                (
+                    Some("zed"),
+                    vec![PathBuf::from("/zed/a.rs"), PathBuf::from("/zed/b.rs")]
+                ),
+                (Some("notes"), vec![PathBuf::from("/notes/todo.md")]),
+                (
+                    None,
+                    vec![
+                        PathBuf::from("/tmp/scratch.txt"),
+                        PathBuf::from("/tmp/other.txt")
+                    ]
+                ),
+            ]
+        );
+        assert!(group_by_workspace::<&str>(Vec::new(), |a, b| a == b).is_empty());
+    }
+
+    #[test]
+    fn test_paths_to_open_fall_back_to_selected() {
+        use super::paths_to_open;
+
+        let selected = Path::new("/zed/selected.rs");
+        assert_eq!(
+            paths_to_open(&[], Some(selected)),
+            [PathBuf::from("/zed/selected.rs")]
+        );
+        assert!(paths_to_open(&[], None).is_empty());
+        // Checked files are opened instead of the selected one, in the order they were checked.
+        let checked = [PathBuf::from("/zed/b.rs"), PathBuf::from("/zed/a.rs")];
+        assert_eq!(paths_to_open(&checked, Some(selected)), checked);
+        assert_eq!(paths_to_open(&checked, None), checked);
+    }
+
+    #[test]
+    fn test_prune_per_directory_caps_skewed_directories() {
+        use super::{RecentFile, prune_per_directory};
+        use std::time::{Duration, SystemTime};
+
+        let now = SystemTime::now();
+        let two_days_ago = now - Duration::from_secs(2 * 24 * 60 * 60);
+        let recent_file = |path: &str, last_opened: SystemTime| RecentFile {
+            path: PathBuf::from(path),
+            last_opened,
+        };
+
+        let mut recent_files = Vec::new();
+        recent_files.push(recent_file("/project/src/main.rs", two_days_ago));
+        for index in 0..10 {
+            recent_files.push(recent_file(
+                &format!("/project/generated/file_{index}.rs"),
+                two_days_ago,
+            ));
+        }
+        recent_files.push(recent_file("/project/README.md", two_days_ago));
+
+        let pruned = prune_per_directory(&mut recent_files, 3, now);
+
+        assert_eq!(pruned.len(), 7);
+        assert!(
+            pruned
+                .iter()
+                .all(|file| file.path.starts_with("/project/generated"))
+        );
+        assert_eq!(
+            recent_files
+                .iter()
+                .map(|file| file.path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>(),
+            vec![
+                "/project/src/main.rs",
+                "/project/generated/file_0.rs",
+                "/project/generated/file_1.rs",
+                "/project/generated/file_2.rs",
+                "/project/README.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prune_per_directory_keeps_protected_window() {
+        use super::{RecentFile, prune_per_directory};
+        use std::time::{Duration, SystemTime};
+
+        let now = SystemTime::now();
+        let one_hour_ago = now - Duration::from_secs(60 * 60);
+        let two_days_ago = now - Duration::from_secs(2 * 24 * 60 * 60);
+
+        let mut recent_files = (0..5)
+            .map(|index| RecentFile {
+                path: PathBuf::from(format!("/project/generated/new_{index}.rs")),
+                last_opened: one_hour_ago,
+            })
+            .chain((0..5).map(|index| RecentFile {
+                path: PathBuf::from(format!("/project/generated/old_{index}.rs")),
+                last_opened: two_days_ago,
+            }))
+            .collect::<Vec<_>>();
+
+        let pruned = prune_per_directory(&mut recent_files, 2, now);
+
+        // Everything opened within the last 24 hours survives, even beyond the cap.
+        assert_eq!(recent_files.len(), 5);
+        assert!(
+            recent_files
+                .iter()
+                .all(|file| file.last_opened == one_hour_ago)
+        );
+        assert_eq!(pruned.len(), 5);
+        assert!(pruned.iter().all(|file| file.last_opened == two_days_ago));
+    }
+
+    #[test]
+    fn test_file_name_column_len() {
+        let paths = [
+            PathBuf::from("/project/src/lib.rs"),
+            PathBuf::from("/project/src/übersicht.rs"),
+            PathBuf::from("/project/Cargo.toml"),
+        ];
+        assert_eq!(file_name_column_len(&paths), "übersicht.rs".chars().count());
+        assert_eq!(file_name_column_len(&Vec::<PathBuf>::new()), 0);
+
+        let long_name = PathBuf::from(format!("/project/{}.rs", "a".repeat(100)));
+        assert_eq!(
+            file_name_column_len([&long_name]),
+            MAX_ALIGNED_FILE_NAME_LEN
+        );
+    }
+
+    #[test]
+    fn test_dedup_by_canonical_path() {
+        use super::{dedup_by_canonical_path, expand_tilde_with_home};
+
+        let now = SystemTime::now();
+        let recent_file = |path: &str, age: u64| RecentFile {
+            path: PathBuf::from(path),
+            last_opened: now - Duration::from_secs(age),
+        };
+        let canonicalize = |path: &Path| {
+            let path = expand_tilde_with_home(path, Some(Path::new("/home/user")));
+            // `/home/user/link` is a symlink to `/home/user/real`.
+            match path.strip_prefix("/home/user/link") {
+                Ok(rest) => Path::new("/home/user/real").join(rest),
+                Err(_) => path,
+            }
+        };
+
+        let mut recent_files = vec![
+            recent_file("~/foo", 0),
+            recent_file("/home/user/foo", 10),
+            recent_file("/home/user/link/bar.rs", 20),
+            recent_file("/home/user/real/bar.rs", 30),
+            recent_file("/tmp/baz.rs", 40),
+        ];
+        assert!(dedup_by_canonical_path(
+            &mut recent_files,
+            canonicalize,
+            false
+        ));
+        assert_eq!(
+            recent_files
+                .iter()
+                .map(|recent_file| (recent_file.path.clone(), recent_file.last_opened))
+                .collect::<Vec<_>>(),
+            [
+                (PathBuf::from("/home/user/foo"), now),
+                (
+                    PathBuf::from("/home/user/real/bar.rs"),
+                    now - Duration::from_secs(20)
+                ),
+                (PathBuf::from("/tmp/baz.rs"), now - Duration::from_secs(40)),
+            ]
+        );
+
+        // Already resolved and unique entries are left alone.
+        assert!(!dedup_by_canonical_path(
+            &mut recent_files,
+            canonicalize,
+            false
+        ));
+        assert_eq!(recent_files.len(), 3);
+    }
+
+    #[test]
+    fn test_bump_recent_file_moves_confirmed_file_to_top() {
+        let now = SystemTime::now();
+        let earlier = now - Duration::from_secs(60);
+        let recent_file = |path: &str| RecentFile {
+            path: PathBuf::from(path),
+            last_opened: earlier,
+        };
+        let mut recent_files = vec![
+            recent_file("/project/a.rs"),
+            recent_file("/project/b.rs"),
+            recent_file("/project/c.rs"),
+        ];
+
+        assert!(bump_recent_file(
+            &mut recent_files,
+            Path::new("/project/c.rs"),
+            now,
+            DEFAULT_MAX_ENTRIES,
+            false
+        ));
+        let order = |recent_files: &[RecentFile]| {
+            recent_files
+                .iter()
+                .map(|recent_file| recent_file.path.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            order(&recent_files),
+            [
+                PathBuf::from("/project/c.rs"),
+                PathBuf::from("/project/a.rs"),
+                PathBuf::from("/project/b.rs"),
+            ]
+        );
+
+        // The workspace event for the same file arriving afterwards keeps the order.
+        let later = now + Duration::from_secs(1);
+        assert!(!bump_recent_file(
+            &mut recent_files,
+            Path::new("/project/c.rs"),
+            later,
+            DEFAULT_MAX_ENTRIES,
+            false
+        ));
+        assert_eq!(order(&recent_files)[0], PathBuf::from("/project/c.rs"));
+        assert_eq!(recent_files.len(), 3);
+        assert_eq!(recent_files[0].last_opened, later);
+
+        // Lowering the limit drops the least recent files on the next bump, even when
+        // the bumped file already was the most recent one.
+        assert!(!bump_recent_file(
+            &mut recent_files,
+            Path::new("/project/c.rs"),
+            later,
+            2,
+            false
+        ));
+        assert_eq!(
+            order(&recent_files),
+            [
+                PathBuf::from("/project/c.rs"),
+                PathBuf::from("/project/a.rs")
+            ]
+        );
+        assert!(bump_recent_file(
+            &mut recent_files,
+            Path::new("/project/d.rs"),
+            later,
+            1,
+            false
+        ));
+        assert_eq!(order(&recent_files), [PathBuf::from("/project/d.rs")]);
+    }
+
+    #[test]
+    fn test_bump_recent_file_dedups_case_variants() {
+        let now = SystemTime::now();
+        let later = now + Duration::from_secs(1);
+        let paths = |recent_files: &[RecentFile]| {
+            recent_files
+                .iter()
+                .map(|recent_file| recent_file.path.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut recent_files = Vec::new();
+        bump_recent_file(
+            &mut recent_files,
+            Path::new("/Foo/Bar.rs"),
+            now,
+            DEFAULT_MAX_ENTRIES,
+            true,
+        );
+        bump_recent_file(
+            &mut recent_files,
+            Path::new("/foo/bar.rs"),
+            later,
+            DEFAULT_MAX_ENTRIES,
+            true,
+        );
+        assert_eq!(paths(&recent_files), [PathBuf::from("/foo/bar.rs")]);
+        assert_eq!(recent_files[0].last_opened, later);
+
+        // A case variant further down the list is replaced too.
+        bump_recent_file(
+            &mut recent_files,
+            Path::new("/foo/baz.rs"),
+            later,
+            DEFAULT_MAX_ENTRIES,
+            true,
+        );
+        assert!(bump_recent_file(
+            &mut recent_files,
+            Path::new("/FOO/BAR.rs"),
+            later,
+            DEFAULT_MAX_ENTRIES,
+            true
+        ));
+        assert_eq!(
+            paths(&recent_files),
+            [PathBuf::from("/FOO/BAR.rs"), PathBuf::from("/foo/baz.rs")]
+        );
+
+        // Case-sensitive filesystems keep them apart.
+        let mut recent_files = Vec::new();
+        bump_recent_file(
+            &mut recent_files,
+            Path::new("/Foo/Bar.rs"),
+            now,
+            DEFAULT_MAX_ENTRIES,
+            false,
+        );
+        bump_recent_file(
+            &mut recent_files,
+            Path::new("/foo/bar.rs"),
+            later,
+            DEFAULT_MAX_ENTRIES,
+            false,
+        );
+        assert_eq!(
+            paths(&recent_files),
+            [PathBuf::from("/foo/bar.rs"), PathBuf::from("/Foo/Bar.rs")]
+        );
+
+        // Loading the stored list collapses case variants the same way.
+        let mut recent_files = vec![
+            RecentFile {
+                path: PathBuf::from("/foo/bar.rs"),
+                last_opened: later,
+            },
+            RecentFile {
+                path: PathBuf::from("/Foo/Bar.rs"),
+                last_opened: now,
+            },
+        ];
+        assert!(dedup_by_canonical_path(
+            &mut recent_files,
+            Path::to_path_buf,
+            true
+        ));
+        assert_eq!(paths(&recent_files), [PathBuf::from("/foo/bar.rs")]);
+    }
+
+    /// Held by the tests that replace the global list of recent files, as tests run in
+    /// parallel.
+    static RECENT_FILES_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_remove_recent_file() {
+        let _lock = RECENT_FILES_TEST_LOCK.lock();
+        *RECENT_FILES.lock() = vec![
+            RecentFile::from_unix_seconds(PathBuf::from("/project/a.rs"), 2),
+            RecentFile::from_unix_seconds(PathBuf::from("/project/b.rs"), 1),
+        ];
+        RECENT_FILES_DIRTY.store(false, atomic::Ordering::Release);
+
+        assert!(!remove(Path::new("/project/c.rs")));
+        assert!(!RECENT_FILES_DIRTY.load(atomic::Ordering::Acquire));
+
+        assert!(remove(Path::new("/project/a.rs")));
+        assert_eq!(recent_files(), [PathBuf::from("/project/b.rs")]);
+        assert!(RECENT_FILES_DIRTY.load(atomic::Ordering::Acquire));
+    }
+
+    /// Sets up a workspace over `/work` with `files` in it, which are also the recent files,
+    /// all last opened a minute ago.
+    async fn init_picker_test<'a>(
+        files: &[&str],
+        cx: &'a mut gpui::TestAppContext,
+    ) -> (Entity<Workspace>, &'a mut gpui::VisualTestContext) {
+        cx.update(|cx| {
+            workspace::AppState::test(cx);
+            editor::init(cx);
+            cx.update_global::<settings::SettingsStore, _>(|store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.recent_files.get_or_insert_default().prune_missing = Some(false);
+                });
+            });
+        });
+        let fs = project::FakeFs::new(cx.executor());
+        for path in files {
+            fs.insert_file(path, Vec::new()).await;
+        }
+        let project = Project::test(fs, [Path::new("/work")], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+
+        let earlier = SystemTime::now() - Duration::from_secs(60);
+        *RECENT_FILES.lock() = files
+            .iter()
+            .map(|path| RecentFile {
+                path: PathBuf::from(path),
+                last_opened: earlier,
+            })
+            .collect();
+        (workspace, cx)
+    }
+
+    fn open_picker(
+        workspace: &Entity<Workspace>,
+        cx: &mut gpui::VisualTestContext,
+    ) -> Entity<Picker<RecentFilesDelegate>> {
+        let picker = workspace.update_in(cx, |workspace, window, cx| {
+            let delegate =
+                RecentFilesDelegate::new(workspace.weak_handle(), false, false, HashMap::default());
+            cx.new(|cx| Picker::uniform_list(delegate, window, cx))
+        });
+        cx.run_until_parked();
+        picker
+    }
+
+    fn listed_files(
+        picker: &Entity<Picker<RecentFilesDelegate>>,
+        cx: &gpui::VisualTestContext,
+    ) -> Vec<PathBuf> {
+        cx.read(|cx| {
+            let delegate = &picker.read(cx).delegate;
+            (0..delegate.rows.len())
+                .filter_map(|ix| delegate.path_at_row(ix).map(Path::to_path_buf))
+                .collect()
+        })
+    }
+
+    fn row_of(delegate: &RecentFilesDelegate, path: &str) -> usize {
+        (0..delegate.rows.len())
+            .find(|&ix| delegate.path_at_row(ix) == Some(Path::new(path)))
+            .unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_confirmed_file_is_listed_first_when_reopening(cx: &mut gpui::TestAppContext) {
+        let _lock = RECENT_FILES_TEST_LOCK.lock();
+        let (workspace, cx) =
+            init_picker_test(&["/work/a.rs", "/work/b.rs", "/work/c.rs"], cx).await;
+
+        let picker = open_picker(&workspace, cx);
+        assert_eq!(
+            listed_files(&picker, cx),
+            [
+                PathBuf::from("/work/a.rs"),
+                PathBuf::from("/work/b.rs"),
+                PathBuf::from("/work/c.rs"),
+            ]
+        );
+        picker.update_in(cx, |picker, window, cx| {
+            picker.delegate.selected_match_index = row_of(&picker.delegate, "/work/c.rs");
+            picker.delegate.confirm(false, window, cx);
+        });
+        cx.run_until_parked();
+
+        // A picker built afterwards lists the confirmed file first, without waiting for the
+        // workspace to report it as opened.
+        let picker = open_picker(&workspace, cx);
+        assert_eq!(
+            listed_files(&picker, cx),
+            [
+                PathBuf::from("/work/c.rs"),
+                PathBuf::from("/work/a.rs"),
+                PathBuf::from("/work/b.rs"),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_confirming_right_after_a_delete(cx: &mut gpui::TestAppContext) {
+        let _lock = RECENT_FILES_TEST_LOCK.lock();
+        let (workspace, cx) =
+            init_picker_test(&["/work/a.rs", "/work/b.rs", "/work/c.rs"], cx).await;
+        let picker = open_picker(&workspace, cx);
+
+        // Until the deletion is saved, the remaining rows keep showing their own files.
+        picker.update_in(cx, |picker, window, cx| {
+            let row = row_of(&picker.delegate, "/work/a.rs");
+            picker.delegate.delete_recent_file(row, window, cx);
+            assert_eq!(
+                picker.delegate.selected_path(),
+                Some(Path::new("/work/b.rs"))
+            );
+        });
+        assert_eq!(
+            listed_files(&picker, cx),
+            [PathBuf::from("/work/b.rs"), PathBuf::from("/work/c.rs")]
+        );
+        picker.update_in(cx, |picker, window, cx| {
+            picker.delegate.confirm(false, window, cx)
+        });
+        cx.run_until_parked();
+        let opened_path = workspace.read_with(cx, |workspace, cx| {
+            let item = workspace.active_item(cx).unwrap();
+            item.project_path(cx).unwrap().path
+        });
+        assert_eq!(opened_path.as_unix_str(), "b.rs");
+
+        // Deleting the last file doesn't leave rows pointing past the end of the list.
+        let picker = open_picker(&workspace, cx);
+        picker.update_in(cx, |picker, window, cx| {
+            let row = row_of(&picker.delegate, "/work/c.rs");
+            picker.delegate.delete_recent_file(row, window, cx);
+        });
+        assert!(!listed_files(&picker, cx).contains(&PathBuf::from("/work/c.rs")));
+        cx.run_until_parked();
+    }
+
+    #[test]
+    fn test_remove_private_files() {
+        let private_files = PathMatcher::new(
+            ["**/.env*", "**/*.pem", "**/*.key", "**/secrets.yml"],
+            PathStyle::local(),
+        )
+        .unwrap();
+        let mut recent_files = [
+            "/project/src/main.rs",
+            "/project/.env",
+            "/project/.env.local",
+            "/project/environment.rs",
+            "/project/certs/server.pem",
+            "/project/config/secrets.yml",
+            "/project/config/secrets.yml.example",
+            "/project/src/key.rs",
+            "/home/user/.ssh/id.key",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(ix, path)| RecentFile::from_unix_seconds(PathBuf::from(path), ix as i64))
+        .collect::<Vec<_>>();
+
+        let removed = remove_private_files(&mut recent_files, &private_files);
+        let paths = |recent_files: &[RecentFile]| {
+            recent_files
+                .iter()
+                .map(|recent_file| recent_file.path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(&recent_files),
+            [
+                "/project/src/main.rs",
+                "/project/environment.rs",
+                "/project/config/secrets.yml.example",
+                "/project/src/key.rs",
+            ]
+        );
+        assert_eq!(
+            paths(&removed),
+            [
+                "/project/.env",
+                "/project/.env.local",
+                "/project/certs/server.pem",
+                "/project/config/secrets.yml",
+                "/home/user/.ssh/id.key",
+            ]
+        );
+
+        // Without patterns, nothing is private.
+        let removed = remove_private_files(&mut recent_files, &PathMatcher::default());
+        assert!(removed.is_empty());
+        assert_eq!(recent_files.len(), 4);
+    }
+
+    #[test]
+    fn test_thumbnail_format() {
+        assert_eq!(
+            thumbnail_format(Path::new("/project/logo.png")),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            thumbnail_format(Path::new("/project/photo.JPG")),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(thumbnail_format(Path::new("/project/icon.svg")), None);
+        assert_eq!(thumbnail_format(Path::new("/project/main.rs")), None);
+        assert_eq!(thumbnail_format(Path::new("/project/Makefile")), None);
+    }
+
+    #[test]
+    fn test_copied_path_forms() {
+        use super::{containing_root, path_relative_to_file};
+        use std::path::Path;
+
+        let roots = [Path::new("/work"), Path::new("/work/vendor/lib")];
+        assert_eq!(
+            containing_root(Path::new("/work/vendor/lib/src/a.rs"), roots),
+            Some(Path::new("/work/vendor/lib"))
+        );
+        assert_eq!(
+            containing_root(Path::new("/work/src/a.rs"), roots),
+            Some(Path::new("/work"))
+        );
+        assert_eq!(containing_root(Path::new("/workspace/a.rs"), roots), None);
+
+        assert_eq!(
+            path_relative_to_file(Path::new("/work/src/a.rs"), Path::new("/work/src/b.rs")),
+            Some(PathBuf::from("a.rs"))
+        );
+        assert_eq!(
+            path_relative_to_file(
+                Path::new("/work/docs/guide.md"),
+                Path::new("/work/src/util/b.rs")
+            ),
+            Some(PathBuf::from("../../docs/guide.md"))
+        );
+    }
+
+    #[gpui::test]
+    async fn test_load_preview_text(cx: &mut gpui::TestAppContext) {
+        use super::{MAX_PREVIEW_FILE_SIZE, MAX_PREVIEW_LINES, load_preview_text, preview_len};
+        use project::FakeFs;
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file("/work/main.rs", b"fn main() {}\n".to_vec())
+            .await;
+        fs.insert_file(
+            "/work/huge.log",
+            vec![b'x'; MAX_PREVIEW_FILE_SIZE as usize + 1],
+        )
+        .await;
+        fs.insert_file("/work/image.png", vec![0x89, 0xff, 0xfe, 0x00])
+            .await;
+        let long_text = (1..=MAX_PREVIEW_LINES + 10)
+            .map(|line| format!("line {line}\n"))
+            .collect::<String>();
+        fs.insert_file("/work/long.txt", long_text.clone().into_bytes())
+            .await;
+
+        assert_eq!(
+            load_preview_text(fs.as_ref(), Path::new("/work/main.rs")).await,
+            Ok("fn main() {}\n".to_string())
+        );
+        assert_eq!(
+            load_preview_text(fs.as_ref(), Path::new("/work/huge.log")).await,
+            Err("File too large to preview".into())
+        );
+        assert_eq!(
+            load_preview_text(fs.as_ref(), Path::new("/work/image.png")).await,
+            Err("File can't be previewed".into())
+        );
+        assert_eq!(
+            load_preview_text(fs.as_ref(), Path::new("/work/removed.rs")).await,
+            Err("File not found".into())
+        );
+
+        // Only the first lines are loaded into the preview.
+        let preview = load_preview_text(fs.as_ref(), Path::new("/work/long.txt"))
+            .await
+            .unwrap();
+        assert_eq!(preview.lines().count(), MAX_PREVIEW_LINES);
+        assert!(long_text.starts_with(&preview));
+        assert_eq!(preview_len("a\nb", 5), 3);
+        assert_eq!(preview_len("a\nb\nc", 2), 4);
     }
 
-    fn elision_range(&self, budget: usize, matches: &[usize]) -> Option<Range<usize>> {
-        let eligible_range = {
-            assert!(matches.windows(2).all(|w| w[0] <= w[1]));
-            let mut matches = matches.iter().copied().peekable();
-            let mut longest: Option<Range<usize>> = None;
-            let mut cur = 0..0;
-            let mut seen_normal = false;
-            for (i, (component, range)) in self.component_ranges.iter().enumerate() {
-                let is_normal = matches!(component, Component::Normal(_));
-                let is_first_normal = is_normal && !seen_normal;
-                seen_normal |= is_normal;
-                let is_last = i == self.component_ranges.len() - 1;
-                let contains_match = matches.peek().is_some_and(|mat| range.contains(mat));
-                if contains_match {
-                    matches.next();
-                }
-                if is_first_normal || is_last || !is_normal || contains_match {
-                    if longest
-                        .as_ref()
-                        .is_none_or(|old| old.end - old.start <= cur.end - cur.start)
-                    {
-                        longest = Some(cur);
-                    }
-                    cur = i + 1..i + 1;
-                } else {
-                    cur.end = i + 1;
-                }
-            }
-            if longest
-                .as_ref()
-                .is_none_or(|old| old.end - old.start <= cur.end - cur.start)
-            {
-                longest = Some(cur);
+    #[gpui::test]
+    async fn test_fuzzy_fallback_ranks_below_word_matches(cx: &mut gpui::TestAppContext) {
+        use super::match_recent_files;
+        use fuzzy::StringMatchCandidate;
+        use std::sync::atomic::AtomicBool;
+
+        let candidates = [
+            "crates/recent_files/src/lib.rs",
+            "crates/recent_projects/src/lib.rs",
+            "docs/src/recent.md",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, path)| StringMatchCandidate::new(id, path))
+        .collect::<Vec<_>>();
+        let cancel_flag = AtomicBool::new(false);
+        let (candidates, cancel_flag, executor) = (&candidates, &cancel_flag, cx.executor());
+        let search = move |query: &'static str, fuzzy_fallback: bool| {
+            let executor = executor.clone();
+            async move {
+                match_recent_files(
+                    candidates,
+                    query,
+                    false,
+                    WordMatchMode::Substring,
+                    fuzzy_fallback,
+                    DEFAULT_FILE_NAME_BOOST,
+                    10,
+                    cancel_flag,
+                    executor,
+                )
+                .await
             }
-            longest
         };
 
-        let eligible_range = eligible_range?;
-        assert!(eligible_range.start <= eligible_range.end);
-        if eligible_range.is_empty() {
-            return None;
+        // Word matches are found without falling back to fuzzy matching.
+        let (matches, first_fuzzy_match) = search("lib recent", true).await;
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1]);
+        assert_eq!(first_fuzzy_match, 2);
+
+        // An abbreviation only matches fuzzily, and all of its matches are tagged as such.
+        let (matches, first_fuzzy_match) = search("rcfls", true).await;
+        assert_eq!(first_fuzzy_match, 0);
+        assert_eq!(matches.first().map(|m| m.candidate_id), Some(0));
+        for hit in &matches {
+            let highlighted = hit
+                .positions
+                .iter()
+                .map(|&position| hit.string[position..].chars().next().unwrap())
+                .collect::<String>();
+            assert_eq!(highlighted, "rcfls", "highlights of {}", hit.string);
         }
 
-        let elided_range: Range<usize> = {
-            let byte_range = self.component_ranges[eligible_range.start].1.start
-                ..self.component_ranges[eligible_range.end - 1].1.end;
-            let midpoint = self.path_str.len() / 2;
-            let distance_from_start = byte_range.start.abs_diff(midpoint);
-            let distance_from_end = byte_range.end.abs_diff(midpoint);
-            let pick_from_end = distance_from_start > distance_from_end;
-            let mut len_with_elision = self.path_str.len();
-            let mut i = eligible_range.start;
-            while i < eligible_range.end {
-                let x = if pick_from_end {
-                    eligible_range.end - i + eligible_range.start - 1
-                } else {
-                    i
-                };
-                len_with_elision -= self.component_ranges[x]
-                    .0
-                    .as_os_str()
-                    .as_encoded_bytes()
-                    .len()
-                    + 1;
-                if len_with_elision <= budget {
-                    break;
-                }
-                i += 1;
-            }
-            if len_with_elision > budget {
-                return None;
-            } else if pick_from_end {
-                let x = eligible_range.end - i + eligible_range.start - 1;
-                x..eligible_range.end
-            } else {
-                let x = i;
-                eligible_range.start..x + 1
-            }
+        // Without the fallback, the abbreviation matches nothing.
+        let (matches, _) = search("rcfls", false).await;
+        assert!(matches.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_equal_word_matches_rank_by_recency(cx: &mut gpui::TestAppContext) {
+        use super::{match_recent_files, recency_decay};
+        use fuzzy::StringMatchCandidate;
+        use std::sync::atomic::AtomicBool;
+
+        // Most recently opened first.
+        let candidates = [
+            "/work/api/src/main.rs",
+            "/main.rs",
+            "/work/cli/src/main.rs",
+            "/work/web/src/main.rs",
+            "/work/old/src/main.rs",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, path)| StringMatchCandidate::new(id, path))
+        .collect::<Vec<_>>();
+        let cancel_flag = AtomicBool::new(false);
+        let (candidates, cancel_flag, executor) = (&candidates, &cancel_flag, cx.executor());
+        let search = move |query: &'static str, max_results: usize| {
+            match_recent_files(
+                candidates,
+                query,
+                false,
+                WordMatchMode::Substring,
+                false,
+                DEFAULT_FILE_NAME_BOOST,
+                max_results,
+                cancel_flag,
+                executor.clone(),
+            )
         };
 
-        let byte_range = self.component_ranges[elided_range.start].1.start
-            ..self.component_ranges[elided_range.end - 1].1.end;
-        Some(byte_range)
+        // The equally good matches in `/work` follow recency, while the much better match
+        // of `/main.rs` still outranks a slightly more recent file.
+        let (matches, _) = search("main", 10).await;
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [1, 0, 2, 3, 4]);
+        assert!(matches[1].score > matches[2].score);
+
+        // Truncating keeps the most recent of the equally good matches.
+        let (matches, _) = search("src main", 2).await;
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [0, 2]);
+
+        // An empty query keeps every file, in recency order.
+        let (matches, _) = search("", 2).await;
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1, 2, 3, 4]);
+
+        assert_eq!(recency_decay(0), 1.);
+        assert!((recency_decay(20) - 0.5).abs() < f64::EPSILON);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::path::PathBuf;
+    #[gpui::test]
+    async fn test_equal_scores_prefer_the_more_recent_file(cx: &mut gpui::TestAppContext) {
+        use fuzzy::StringMatchCandidate;
+        use std::sync::atomic::AtomicBool;
 
-    #[test]
-    fn test_workspace_path_matching() {
-        // Test the core logic of finding the deepest workspace path
-        let workspace_paths = vec![
-            PathBuf::from("/Users/dima/Developer"),
-            PathBuf::from("/Users/dima/Developer/zed"),
-            PathBuf::from("/Users/dima/Developer/zed/docs"),
+        // Both paths score the same for the query, and the more recent one comes last.
+        let candidates = [
+            StringMatchCandidate::new(1, "/old/main.rs"),
+            StringMatchCandidate::new(0, "/new/main.rs"),
         ];
+        let cancel_flag = AtomicBool::new(false);
+        let search = |max_results| {
+            let options = WordMatchOptions {
+                max_results,
+                ..WordMatchOptions::default()
+            };
+            let executor = cx.executor();
+            let (candidates, cancel_flag) = (&candidates, &cancel_flag);
+            async move {
+                match_strings_order_insensitive(candidates, "main", &options, cancel_flag, executor)
+                    .await
+            }
+        };
 
-        let file_path = PathBuf::from("/Users/dima/Developer/zed/docs/src/configuring-zed.md");
+        let matches = search(10).await;
+        assert_eq!(matches[0].score, matches[1].score);
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1]);
 
-        // Find the deepest matching path
-        let mut best_match: Option<(PathBuf, usize)> = None;
-        for workspace_path in &workspace_paths {
-            if file_path.starts_with(workspace_path) {
-                let depth = workspace_path.components().count();
-                if best_match
-                    .as_ref()
-                    .map_or(true, |(_, best_depth)| depth > *best_depth)
-                {
-                    best_match = Some((workspace_path.clone(), depth));
-                }
+        let matches = search(1).await;
+        assert_eq!(matches[0].string, "/new/main.rs");
+    }
+
+    #[gpui::test]
+    async fn test_file_name_matches_rank_above_directory_matches(cx: &mut gpui::TestAppContext) {
+        use super::{DEFAULT_FILE_NAME_BOOST, match_recent_files};
+        use fuzzy::StringMatchCandidate;
+        use std::sync::atomic::AtomicBool;
+
+        let candidates = [
+            "/main-service/src/lib.rs",
+            "/work/project/src/main.rs",
+            "/a/main.rs",
+            "/main/notes.md",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, path)| StringMatchCandidate::new(id, path))
+        .collect::<Vec<_>>();
+        let cancel_flag = AtomicBool::new(false);
+        let (candidates, cancel_flag, executor) = (&candidates, &cancel_flag, cx.executor());
+        let search = move |query: &'static str, file_name_boost: f64| {
+            let executor = executor.clone();
+            async move {
+                let (matches, _) = match_recent_files(
+                    candidates,
+                    query,
+                    false,
+                    WordMatchMode::Substring,
+                    false,
+                    file_name_boost,
+                    10,
+                    cancel_flag,
+                    executor,
+                )
+                .await;
+                matches
             }
-        }
+        };
 
-        // Should match the deepest path: /Users/dima/Developer/zed/docs
+        // Files named after the query come first, however deep, despite being less recent.
+        let matches = search("main", DEFAULT_FILE_NAME_BOOST).await;
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [2, 1, 3, 0]);
+        assert_eq!(matches[0].positions, [3, 4, 5, 6]);
+
+        let matches = search("main.rs", DEFAULT_FILE_NAME_BOOST).await;
+        let ids = matches.iter().map(|m| m.candidate_id).collect::<Vec<_>>();
+        assert_eq!(ids, [2, 1]);
+
+        // The boost scales the score of words found in the file name.
+        let boosted = search("main", 3.).await;
+        let unboosted = search("main", 1.).await;
+        let score_of = |matches: &[StringMatch], id| {
+            matches.iter().find(|m| m.candidate_id == id).unwrap().score
+        };
+        assert!((score_of(&boosted, 2) - 3. * score_of(&unboosted, 2)).abs() < 1e-12);
+        assert_eq!(score_of(&boosted, 0), score_of(&unboosted, 0));
+    }
+
+    #[test]
+    fn test_git_status_color() {
+        use super::git_status_color;
+        use git::status::{
+            FileStatus, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
+        };
+
+        let tracked = |index_status, worktree_status| {
+            FileStatus::Tracked(TrackedStatus {
+                index_status,
+                worktree_status,
+            })
+        };
         assert_eq!(
-            best_match.unwrap().0,
-            PathBuf::from("/Users/dima/Developer/zed/docs")
+            git_status_color(tracked(StatusCode::Unmodified, StatusCode::Modified)),
+            Some(Color::Modified)
+        );
+        assert_eq!(
+            git_status_color(tracked(StatusCode::Added, StatusCode::Unmodified)),
+            Some(Color::Created)
+        );
+        assert_eq!(
+            git_status_color(FileStatus::Untracked),
+            Some(Color::Created)
+        );
+        assert_eq!(
+            git_status_color(FileStatus::Unmerged(UnmergedStatus {
+                first_head: UnmergedStatusCode::Updated,
+                second_head: UnmergedStatusCode::Updated,
+            })),
+            Some(Color::Conflict)
+        );
+        assert_eq!(
+            git_status_color(tracked(StatusCode::Unmodified, StatusCode::Unmodified)),
+            None
         );
+        assert_eq!(git_status_color(FileStatus::Ignored), None);
     }
 
     #[test]
-    fn test_workspace_path_matching_prefers_most_recent() {
-        // Test that we prefer the most recent workspace that contains the file
-        // Simulating the order that recent_workspaces_on_disk() would return
-        let workspace_paths_in_recency_order = vec![
-            PathBuf::from("/Users/dima/Developer/zed"), // Most recent
-            PathBuf::from("/Users/dima/Developer/zed/docs"), // Less recent
-        ];
-
-        let file_path = PathBuf::from("/Users/dima/Developer/zed/docs/src/configuring-zed.md");
+    fn test_debug_score_label() {
+        use super::{debug_score_label, format_age};
+        use std::time::Duration;
+
+        assert_eq!(format_age(Duration::from_secs(42)), "42s");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 + 30)), "5m");
+        assert_eq!(format_age(Duration::from_secs(3 * 3600)), "3h");
+        assert_eq!(format_age(Duration::from_secs(9 * 86400)), "9d");
+        assert_eq!(
+            debug_score_label(3, 0.4251, Some(Duration::from_secs(120))),
+            "debug: #3 · score 0.43 · opened 2m ago"
+        );
+        assert_eq!(
+            debug_score_label(1, 0., None),
+            "debug: #1 · score 0.00 · age unknown"
+        );
+    }
 
-        // Find the first (most recent) matching workspace
-        let mut first_match: Option<PathBuf> = None;
-        for workspace_path in &workspace_paths_in_recency_order {
-            if file_path.starts_with(workspace_path) {
-                first_match = Some(workspace_path.clone());
-                break; // Take the first match (most recent)
-            }
-        }
+    #[test]
+    fn test_split_position_suffix() {
+        use super::split_position_suffix;
+        use text::Point;
 
-        // Should match the most recent workspace that contains the file: /Users/dima/Developer/zed
+        assert_eq!(split_position_suffix("config"), ("config", None));
         assert_eq!(
-            first_match.unwrap(),
-            PathBuf::from("/Users/dima/Developer/zed")
+            split_position_suffix("config:42"),
+            ("config", Some(Point::new(41, 0)))
+        );
+        assert_eq!(
+            split_position_suffix("config:42:7 "),
+            ("config", Some(Point::new(41, 6)))
+        );
+        assert_eq!(
+            split_position_suffix("zed main.rs:3"),
+            ("zed main.rs", Some(Point::new(2, 0)))
+        );
+        assert_eq!(
+            split_position_suffix("config:0"),
+            ("config", Some(Point::zero()))
+        );
+        assert_eq!(split_position_suffix("config:"), ("config:", None));
+        assert_eq!(split_position_suffix("config:4x"), ("config:4x", None));
+        assert_eq!(
+            split_position_suffix("notes:draft:3"),
+            ("notes:draft", Some(Point::new(2, 0)))
         );
     }
 
     #[test]
-    fn test_tilde_expansion() {
-        use super::expand_tilde;
+    fn test_split_extension_filters() {
+        use super::{matches_extension_globs, split_extension_filters};
 
-        // Test tilde expansion
-        let tilde_path = PathBuf::from("~/Developer/zed");
-        let expanded = expand_tilde(&tilde_path);
+        assert_eq!(
+            split_extension_filters("main"),
+            ("main", Vec::<String>::new())
+        );
+        assert_eq!(
+            split_extension_filters("ext:rs main"),
+            ("main", vec!["rs".to_string()])
+        );
+        assert_eq!(
+            split_extension_filters("editor ext:.TS ext:tsx"),
+            ("editor", vec!["tsx".to_string(), "ts".to_string()])
+        );
+        assert_eq!(
+            split_extension_filters("ext:md"),
+            ("", vec!["md".to_string()])
+        );
+        // Only leading and trailing tokens are filters, and a bare `ext:` is a query.
+        assert_eq!(
+            split_extension_filters("main ext:rs lib"),
+            ("main ext:rs lib", Vec::<String>::new())
+        );
+        assert_eq!(
+            split_extension_filters("ext:"),
+            ("ext:", Vec::<String>::new())
+        );
 
-        // Should expand to absolute path
-        assert!(expanded.is_absolute());
-        assert!(expanded.to_string_lossy().contains("Developer/zed"));
-        assert!(!expanded.to_string_lossy().starts_with("~"));
+        let globs = ["rs".to_string(), "ts*".to_string(), "?s".to_string()];
+        assert!(matches_extension_globs("~/zed/main.rs", &globs));
+        assert!(matches_extension_globs("~/web/App.TSX", &globs));
+        assert!(matches_extension_globs("~/web/index.js", &globs));
+        assert!(!matches_extension_globs("~/notes/todo.md", &globs));
+        assert!(!matches_extension_globs("~/zed/Makefile", &globs));
+    }
 
-        // Test non-tilde path remains unchanged
-        let abs_path = PathBuf::from("/Users/test/project");
-        let unchanged = expand_tilde(&abs_path);
-        assert_eq!(unchanged, abs_path);
+    #[test]
+    fn test_full_path_budget_with_extreme_file_names() {
+        use super::{MIN_DIR_NAME_BUDGET, full_path_budget};
+        use gpui::px;
+
+        let budget = |file_name_len| full_path_budget(file_name_len, px(8.), px(6.), px(768.));
+        assert_eq!(budget(0), 160);
+        assert!(budget(10) > MIN_DIR_NAME_BUDGET);
+        assert_eq!(budget(150), MIN_DIR_NAME_BUDGET);
+        assert_eq!(budget(usize::MAX), MIN_DIR_NAME_BUDGET);
+        assert_eq!(
+            full_path_budget(10, px(8.), px(6.), px(0.)),
+            MIN_DIR_NAME_BUDGET
+        );
     }
 
     #[test]
-    fn test_workspace_matching_with_tilde_paths() {
-        use super::expand_tilde;
+    fn test_truncate_middle_remaps_highlights() {
+        use super::truncate_middle;
 
-        // Simulate workspace paths (could be absolute)
-        let workspace_paths = vec![PathBuf::from("/Users/dima/Developer/zed")];
+        assert_eq!(
+            truncate_middle("main.rs", 10, vec![0, 5]),
+            ("main.rs".to_string(), vec![0, 5])
+        );
 
-        // Simulate file path with tilde (as it appears in recent files)
-        let file_path_with_tilde = PathBuf::from("~/Developer/zed/docs/src/configuring-zed.md");
-        let expanded_file_path = expand_tilde(&file_path_with_tilde);
+        let (truncated, highlights) = truncate_middle("abcdefghijklmnop.rs", 9, vec![0, 3, 8, 17]);
+        assert_eq!(truncated, "abcd…p.rs");
+        assert_eq!(highlights, vec![0, 3, "abcd…p.".len()]);
 
-        // Find matching workspace
-        let mut match_found = false;
-        for workspace_path in &workspace_paths {
-            let expanded_workspace = expand_tilde(workspace_path);
-            if expanded_file_path.starts_with(&expanded_workspace) {
-                match_found = true;
-                break;
-            }
+        let (truncated, highlights) = truncate_middle("ääääääääää.rs", 7, vec![2, 12, 21]);
+        assert_eq!(truncated, "äää….rs");
+        assert_eq!(highlights, vec![2, "äää…".len() + 1]);
+        for ix in highlights {
+            assert!(truncated.is_char_boundary(ix));
         }
+    }
 
-        assert!(
-            match_found,
-            "Should find workspace match after tilde expansion"
+    #[test]
+    fn test_recency_section_boundaries() {
+        use super::{RecencySection, SystemTime};
+        use chrono::{Local, NaiveDate, TimeZone as _};
+
+        // A Wednesday.
+        let today = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let at = |date: NaiveDate, hour: u32, minute: u32, second: u32| {
+            let time = date.and_hms_opt(hour, minute, second).unwrap();
+            SystemTime::from(Local.from_local_datetime(&time).single().unwrap())
+        };
+        let day = |day: u32| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+
+        assert_eq!(
+            RecencySection::for_time(today, at(day(15), 0, 0, 1)),
+            RecencySection::Today
+        );
+        assert_eq!(
+            RecencySection::for_time(today, at(day(14), 23, 59, 59)),
+            RecencySection::Yesterday
+        );
+        assert_eq!(
+            RecencySection::for_time(today, at(day(14), 0, 0, 1)),
+            RecencySection::Yesterday
+        );
+        assert_eq!(
+            RecencySection::for_time(today, at(day(13), 23, 59, 59)),
+            RecencySection::ThisWeek
+        );
+        assert_eq!(
+            RecencySection::for_time(today, at(day(12), 23, 59, 59)),
+            RecencySection::Earlier
+        );
+
+        // On a Monday, yesterday belongs to the previous week but is still "Yesterday".
+        assert_eq!(
+            RecencySection::from_dates(day(13), day(12)),
+            RecencySection::Yesterday
+        );
+        assert_eq!(
+            RecencySection::from_dates(day(13), day(11)),
+            RecencySection::Earlier
+        );
+    }
+
+    #[test]
+    fn test_rows_with_sections_skip_headers() {
+        use super::{RecencySection::*, RecentFilesRow::*, rows_with_sections};
+
+        let rows = rows_with_sections([Today, Today, Yesterday, Earlier, Earlier]);
+        assert_eq!(
+            rows,
+            [
+                Header(Today),
+                Match(0),
+                Match(1),
+                Header(Yesterday),
+                Match(2),
+                Header(Earlier),
+                Match(3),
+                Match(4),
+            ]
         );
+        assert_eq!(rows.iter().position(|row| row.is_selectable()), Some(1));
+        assert_eq!(
+            rows.iter()
+                .filter(|row| row.is_selectable())
+                .collect::<Vec<_>>(),
+            [&Match(0), &Match(1), &Match(2), &Match(3), &Match(4)]
+        );
+
+        let rows = rows_with_sections([Earlier]);
+        assert_eq!(rows, [Header(Earlier), Match(0)]);
+        assert!(rows_with_sections([]).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_selectable_row_after_deletion() {
+        use super::{RecencySection::*, nearest_selectable_row, rows_with_sections};
+
+        // Deleting the last file of today moves the selection past the next header.
+        let rows = rows_with_sections([Today, Yesterday]);
+        assert_eq!(nearest_selectable_row(&rows, 2), Some(3));
+        // Deleting the very last file selects the one before it.
+        let rows = rows_with_sections([Today, Today]);
+        assert_eq!(nearest_selectable_row(&rows, 3), Some(2));
+        assert_eq!(nearest_selectable_row(&rows, 1), Some(1));
+        assert_eq!(nearest_selectable_row(&[], 0), None);
+    }
+
+    #[gpui::test]
+    async fn test_clearing_files_while_the_preview_is_shown(cx: &mut gpui::TestAppContext) {
+        let _lock = RECENT_FILES_TEST_LOCK.lock();
+        let (workspace, cx) = init_picker_test(&["/work/a.rs", "/work/b.rs"], cx).await;
+        let recent_files = workspace.update_in(cx, |workspace, window, cx| {
+            let delegate =
+                RecentFilesDelegate::new(workspace.weak_handle(), false, false, HashMap::default());
+            let fs = workspace.app_state().fs.clone();
+            let languages = workspace.app_state().languages.clone();
+            cx.new(|cx| RecentFiles::new(delegate, fs, languages, true, window, cx))
+        });
+        cx.run_until_parked();
+        let picker = recent_files.read_with(cx, |recent_files, _| recent_files.picker.clone());
+        assert_eq!(listed_files(&picker, cx).len(), 2);
+
+        picker.update_in(cx, |picker, window, cx| {
+            picker.delegate.clear_files();
+            picker.refresh(window, cx);
+        });
+        cx.run_until_parked();
+        assert!(listed_files(&picker, cx).is_empty());
+        recent_files.read_with(cx, |recent_files, _| {
+            assert_eq!(recent_files.preview_path, None);
+        });
     }
 }
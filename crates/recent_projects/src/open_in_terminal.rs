@@ -0,0 +1,192 @@
+use std::{path::PathBuf, sync::Arc};
+
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    Action, App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task,
+    Window,
+};
+use picker::{Picker, PickerDelegate};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::{ResultExt, paths::PathExt};
+use workspace::{ModalView, OpenTerminal, Workspace};
+
+/// Opens a local terminal in the current window with `working_directory` as its cwd.
+pub(crate) fn open_terminal_at(working_directory: PathBuf, window: &mut Window, cx: &mut App) {
+    window.dispatch_action(
+        OpenTerminal {
+            working_directory,
+            local: true,
+        }
+        .boxed_clone(),
+        cx,
+    );
+}
+
+/// Lets the user choose which root of a multi-root project to open a terminal in.
+pub(crate) struct TerminalRootPicker {
+    picker: Entity<Picker<TerminalRootPickerDelegate>>,
+    _subscription: Subscription,
+}
+
+impl ModalView for TerminalRootPicker {}
+
+impl EventEmitter<DismissEvent> for TerminalRootPicker {}
+
+impl Focusable for TerminalRootPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for TerminalRootPicker {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TerminalRootPicker")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl TerminalRootPicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        roots: Vec<PathBuf>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let delegate = TerminalRootPickerDelegate::new(roots);
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+            Self {
+                picker,
+                _subscription,
+            }
+        });
+    }
+}
+
+pub(crate) struct TerminalRootPickerDelegate {
+    roots: Vec<PathBuf>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl TerminalRootPickerDelegate {
+    fn new(roots: Vec<PathBuf>) -> Self {
+        Self {
+            roots,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for TerminalRootPickerDelegate {}
+
+impl PickerDelegate for TerminalRootPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
+        "Open a terminal in…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background_executor = cx.background_executor().clone();
+        let candidates = self
+            .roots
+            .iter()
+            .enumerate()
+            .map(|(id, root)| StringMatchCandidate::new(id, &root.compact().to_string_lossy()))
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background_executor,
+                )
+                .await
+            };
+
+            picker
+                .update(cx, |picker, _| {
+                    picker.delegate.matches = matches;
+                    picker.delegate.selected_index = picker
+                        .delegate
+                        .selected_index
+                        .min(picker.delegate.matches.len().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(root) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|hit| self.roots.get(hit.candidate_id))
+        else {
+            return;
+        };
+        open_terminal_at(root.clone(), window, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Terminal).color(Color::Muted))
+                .child(HighlightedLabel::new(
+                    hit.string.clone(),
+                    hit.positions.clone(),
+                )),
+        )
+    }
+}
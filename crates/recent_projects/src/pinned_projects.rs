@@ -0,0 +1,86 @@
+use collections::HashSet;
+use db::kvp::KEY_VALUE_STORE;
+use util::ResultExt as _;
+use workspace::WorkspaceId;
+
+const PINNED_KEY: &str = "recent_project_pinned";
+
+fn pinned_key(workspace_id: WorkspaceId) -> String {
+    format!("{PINNED_KEY}_{}", i64::from(workspace_id))
+}
+
+/// The recent projects pinned to the top of the picker, whatever the query scores them.
+#[derive(Default)]
+pub(crate) struct PinnedProjects {
+    pinned: HashSet<WorkspaceId>,
+}
+
+impl PinnedProjects {
+    /// Reads which of the given workspaces are pinned.
+    pub(crate) fn load(workspace_ids: impl IntoIterator<Item = WorkspaceId>) -> Self {
+        let pinned = workspace_ids
+            .into_iter()
+            .filter(|workspace_id| {
+                KEY_VALUE_STORE
+                    .read_kvp(&pinned_key(*workspace_id))
+                    .log_err()
+                    .flatten()
+                    .is_some()
+            })
+            .collect();
+        Self { pinned }
+    }
+
+    pub(crate) fn is_pinned(&self, workspace_id: WorkspaceId) -> bool {
+        self.pinned.contains(&workspace_id)
+    }
+
+    /// Pins the workspace, or unpins it when it was pinned, returning whether it's pinned
+    /// now. Use [`save_pinned_project`] to keep the change for the next time.
+    pub(crate) fn toggle(&mut self, workspace_id: WorkspaceId) -> bool {
+        if self.pinned.remove(&workspace_id) {
+            false
+        } else {
+            self.pinned.insert(workspace_id);
+            true
+        }
+    }
+
+    /// Forgets the pin of a workspace that was deleted.
+    pub(crate) fn remove(&mut self, workspace_id: WorkspaceId) {
+        self.pinned.remove(&workspace_id);
+    }
+}
+
+pub(crate) async fn save_pinned_project(workspace_id: WorkspaceId, pinned: bool) {
+    let key = pinned_key(workspace_id);
+    if pinned {
+        KEY_VALUE_STORE
+            .write_kvp(key, "true".to_string())
+            .await
+            .log_err();
+    } else {
+        KEY_VALUE_STORE.delete_kvp(key).await.log_err();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_pinned_project() {
+        let workspace_id = WorkspaceId::default();
+        let mut pinned_projects = PinnedProjects::default();
+        assert!(!pinned_projects.is_pinned(workspace_id));
+
+        assert!(pinned_projects.toggle(workspace_id));
+        assert!(pinned_projects.is_pinned(workspace_id));
+        assert!(!pinned_projects.toggle(workspace_id));
+        assert!(!pinned_projects.is_pinned(workspace_id));
+
+        pinned_projects.toggle(workspace_id);
+        pinned_projects.remove(workspace_id);
+        assert!(!pinned_projects.is_pinned(workspace_id));
+    }
+}
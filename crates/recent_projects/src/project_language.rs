@@ -0,0 +1,176 @@
+use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
+use futures::StreamExt as _;
+use gpui::SharedString;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use util::ResultExt as _;
+use workspace::{PathList, SerializedWorkspaceLocation, WorkspaceId};
+
+const PROJECT_LANGUAGE_KEY: &str = "recent_project_language";
+
+/// How long a detected language is trusted before the project is scanned again.
+const STALE_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Marker files identifying a project's primary language, checked in order so that
+/// more specific markers win (e.g. `tsconfig.json` over `package.json`).
+const LANGUAGE_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("go.mod", "Go"),
+    ("tsconfig.json", "TypeScript"),
+    ("deno.json", "TypeScript"),
+    ("package.json", "JavaScript"),
+    ("pyproject.toml", "Python"),
+    ("setup.py", "Python"),
+    ("requirements.txt", "Python"),
+    ("Gemfile", "Ruby"),
+    ("mix.exs", "Elixir"),
+    ("composer.json", "PHP"),
+    ("build.gradle.kts", "Kotlin"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("Package.swift", "Swift"),
+    ("pubspec.yaml", "Dart"),
+    ("build.zig", "Zig"),
+    ("stack.yaml", "Haskell"),
+    ("dune-project", "OCaml"),
+    ("CMakeLists.txt", "C++"),
+    ("flake.nix", "Nix"),
+];
+
+#[derive(Serialize, Deserialize)]
+struct CachedProjectLanguage {
+    language: Option<String>,
+    detected_at: u64,
+}
+
+/// Returns the language of the highest-priority marker among `file_names`.
+pub(crate) fn language_for_marker_files<'a>(
+    file_names: impl IntoIterator<Item = &'a str>,
+) -> Option<&'static str> {
+    let file_names = file_names.into_iter().collect::<HashSet<_>>();
+    LANGUAGE_MARKERS
+        .iter()
+        .find(|(marker, _)| file_names.contains(marker))
+        .map(|(_, language)| *language)
+}
+
+fn cache_key(workspace_id: WorkspaceId) -> String {
+    format!("{PROJECT_LANGUAGE_KEY}_{}", i64::from(workspace_id))
+}
+
+fn now_unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Reads the cached languages of the given workspaces, returning them along with the
+/// local workspaces whose cache entry is missing or stale. Remote workspaces are skipped.
+pub(crate) fn cached_project_languages(
+    workspaces: &[(WorkspaceId, SerializedWorkspaceLocation, PathList)],
+) -> (
+    HashMap<WorkspaceId, SharedString>,
+    Vec<(WorkspaceId, Vec<PathBuf>)>,
+) {
+    let now = now_unix_seconds();
+    let mut languages = HashMap::default();
+    let mut stale = Vec::new();
+    for (workspace_id, location, paths) in workspaces {
+        if !matches!(location, SerializedWorkspaceLocation::Local) {
+            continue;
+        }
+        let cached = KEY_VALUE_STORE
+            .read_kvp(&cache_key(*workspace_id))
+            .log_err()
+            .flatten()
+            .and_then(|value| serde_json::from_str::<CachedProjectLanguage>(&value).log_err());
+        if let Some(cached) = &cached
+            && let Some(language) = &cached.language
+        {
+            languages.insert(*workspace_id, SharedString::from(language.clone()));
+        }
+        let is_fresh = cached
+            .is_some_and(|cached| now.saturating_sub(cached.detected_at) < STALE_AFTER.as_secs());
+        if !is_fresh {
+            stale.push((*workspace_id, paths.paths().to_vec()));
+        }
+    }
+    (languages, stale)
+}
+
+async fn detect_project_language(paths: &[PathBuf]) -> Option<&'static str> {
+    let mut file_names = Vec::new();
+    for path in paths {
+        let Some(mut entries) = smol::fs::read_dir(path).await.ok() else {
+            continue;
+        };
+        while let Some(entry) = entries.next().await {
+            if let Some(entry) = entry.log_err() {
+                file_names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+    }
+    language_for_marker_files(file_names.iter().map(String::as_str))
+}
+
+/// Scans the given workspaces for marker files and caches the detected languages,
+/// so that they show up the next time the recent projects picker is opened.
+pub(crate) async fn refresh_project_languages(workspaces: Vec<(WorkspaceId, Vec<PathBuf>)>) {
+    let detected_at = now_unix_seconds();
+    for (workspace_id, paths) in workspaces {
+        let language = detect_project_language(&paths).await;
+        let Some(value) = serde_json::to_string(&CachedProjectLanguage {
+            language: language.map(str::to_string),
+            detected_at,
+        })
+        .log_err() else {
+            continue;
+        };
+        KEY_VALUE_STORE
+            .write_kvp(cache_key(workspace_id), value)
+            .await
+            .log_err();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_for_marker_files() {
+        assert_eq!(
+            language_for_marker_files(["README.md", "Cargo.toml", "src"]),
+            Some("Rust")
+        );
+        assert_eq!(language_for_marker_files(["go.mod", "main.go"]), Some("Go"));
+        assert_eq!(language_for_marker_files(["README.md", "LICENSE"]), None);
+        assert_eq!(language_for_marker_files([]), None);
+    }
+
+    #[test]
+    fn test_language_markers_are_prioritized() {
+        assert_eq!(
+            language_for_marker_files(["package.json", "tsconfig.json"]),
+            Some("TypeScript")
+        );
+        assert_eq!(
+            language_for_marker_files(["package.json", "Cargo.toml"]),
+            Some("Rust")
+        );
+        assert_eq!(
+            language_for_marker_files(["build.gradle", "build.gradle.kts"]),
+            Some("Kotlin")
+        );
+    }
+
+    #[test]
+    fn test_language_markers_are_unique() {
+        let mut seen = HashSet::default();
+        for (marker, _) in LANGUAGE_MARKERS {
+            assert!(seen.insert(*marker), "duplicate marker {marker}");
+        }
+    }
+}
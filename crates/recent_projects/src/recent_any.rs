@@ -0,0 +1,364 @@
+use std::{path::PathBuf, sync::Arc};
+
+use fuzzy::{
+    StringMatch, StringMatchCandidate,
+    word_match::{WordMatchOptions, match_strings_order_insensitive},
+};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task,
+    WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use recent_files::RecentFilesSettings;
+use remote::RemoteConnectionOptions;
+use settings::Settings as _;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::{ResultExt, paths::PathExt};
+use workspace::{
+    ModalView, PathList, SerializedWorkspaceLocation, WORKSPACE_DB, Workspace, WorkspaceId,
+};
+
+use crate::open_recent_project;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RecentKind {
+    File,
+    Project,
+}
+
+enum RecentEntry {
+    File(PathBuf),
+    Project {
+        location: SerializedWorkspaceLocation,
+        paths: PathList,
+    },
+}
+
+impl RecentEntry {
+    fn kind(&self) -> RecentKind {
+        match self {
+            RecentEntry::File(_) => RecentKind::File,
+            RecentEntry::Project { .. } => RecentKind::Project,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            RecentEntry::File(path) => path.compact().to_string_lossy().into_owned(),
+            RecentEntry::Project { paths, .. } => paths
+                .ordered_paths()
+                .map(|path| path.compact().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    fn icon(&self) -> IconName {
+        match self {
+            RecentEntry::File(_) => IconName::File,
+            RecentEntry::Project { location, .. } => match location {
+                SerializedWorkspaceLocation::Local => IconName::Screen,
+                SerializedWorkspaceLocation::Remote(options) => match options {
+                    RemoteConnectionOptions::Ssh { .. } => IconName::Server,
+                    RemoteConnectionOptions::Wsl { .. } => IconName::Linux,
+                    RemoteConnectionOptions::Docker(_) => IconName::Box,
+                    #[cfg(any(test, feature = "test-support"))]
+                    RemoteConnectionOptions::Mock(_) => IconName::Server,
+                },
+            },
+        }
+    }
+}
+
+/// Splits a `file:` or `project:` prefix off the query, which restricts the
+/// matches to that kind of entry.
+fn parse_kind_filter(query: &str) -> (Option<RecentKind>, &str) {
+    let query = query.trim_start();
+    if let Some(rest) = query.strip_prefix("file:") {
+        (Some(RecentKind::File), rest.trim_start())
+    } else if let Some(rest) = query.strip_prefix("project:") {
+        (Some(RecentKind::Project), rest.trim_start())
+    } else {
+        (None, query)
+    }
+}
+
+/// Alternates between the items of both lists, keeping each list's own order.
+fn interleave<T>(first: Vec<T>, second: Vec<T>) -> Vec<T> {
+    let mut result = Vec::with_capacity(first.len() + second.len());
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (first_item, second_item) => {
+                result.extend(first_item);
+                result.extend(second_item);
+            }
+        }
+    }
+    result
+}
+
+/// A picker over both recent projects and recent files.
+pub struct RecentAny {
+    pub picker: Entity<Picker<RecentAnyDelegate>>,
+    _load_task: Task<()>,
+    _subscription: Subscription,
+}
+
+impl ModalView for RecentAny {}
+
+impl EventEmitter<DismissEvent> for RecentAny {}
+
+impl Focusable for RecentAny {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for RecentAny {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RecentAny")
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|this, _, window, cx| {
+                this.picker.update(cx, |this, cx| {
+                    this.cancel(&Default::default(), window, cx);
+                })
+            }))
+    }
+}
+
+impl RecentAny {
+    pub fn open(
+        workspace: &mut Workspace,
+        create_new_window: bool,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let weak = cx.entity().downgrade();
+        let current_workspace_id = workspace.database_id();
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let delegate = RecentAnyDelegate::new(weak, create_new_window);
+            Self::new(delegate, current_workspace_id, window, cx)
+        })
+    }
+
+    fn new(
+        delegate: RecentAnyDelegate,
+        current_workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        let prune_missing = RecentFilesSettings::get_global(cx).prune_missing;
+        let _load_task = cx.spawn_in(window, async move |this, cx| {
+            let projects = WORKSPACE_DB
+                .recent_workspaces_on_disk()
+                .await
+                .log_err()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(workspace_id, _, _)| Some(*workspace_id) != current_workspace_id)
+                .map(|(_, location, paths)| RecentEntry::Project { location, paths })
+                .collect::<Vec<_>>();
+            let files = cx
+                .background_spawn(async move { recent_files::recent_file_paths(prune_missing) })
+                .await
+                .into_iter()
+                .map(RecentEntry::File)
+                .collect::<Vec<_>>();
+            this.update_in(cx, move |this, window, cx| {
+                this.picker.update(cx, move |picker, cx| {
+                    picker.delegate.entries = interleave(projects, files);
+                    picker.update_matches(picker.query(cx), window, cx)
+                })
+            })
+            .ok();
+        });
+        Self {
+            picker,
+            _load_task,
+            _subscription,
+        }
+    }
+}
+
+pub struct RecentAnyDelegate {
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<RecentEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    create_new_window: bool,
+}
+
+impl RecentAnyDelegate {
+    fn new(workspace: WeakEntity<Workspace>, create_new_window: bool) -> Self {
+        Self {
+            workspace,
+            entries: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            create_new_window,
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for RecentAnyDelegate {}
+
+impl PickerDelegate for RecentAnyDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut Window, _: &mut App) -> Arc<str> {
+        "Search recent files and projects (filter with file: or project:)...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let (kind, query) = parse_kind_filter(&query);
+        let smart_case = query.chars().any(|c| c.is_uppercase());
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| kind.is_none_or(|kind| entry.kind() == kind))
+            .map(|(id, entry)| StringMatchCandidate::new(id, &entry.label()))
+            .collect::<Vec<_>>();
+        let options = WordMatchOptions {
+            smart_case,
+            max_results: 100,
+            ..WordMatchOptions::default()
+        };
+        self.matches = smol::block_on(match_strings_order_insensitive(
+            candidates.as_slice(),
+            query,
+            &options,
+            &Default::default(),
+            cx.background_executor().clone(),
+        ));
+        self.selected_index = 0;
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some((entry, workspace)) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|hit| self.entries.get(hit.candidate_id))
+            .zip(self.workspace.upgrade())
+        else {
+            return;
+        };
+        match entry {
+            RecentEntry::File(path) => {
+                let create_new_window = self.create_new_window != secondary;
+                recent_files::open_recent_file(
+                    workspace,
+                    path.clone(),
+                    create_new_window,
+                    window,
+                    cx,
+                );
+            }
+            RecentEntry::Project { location, paths } => {
+                let replace_current_window = self.create_new_window == secondary;
+                workspace.update(cx, |workspace, cx| {
+                    open_recent_project(
+                        workspace,
+                        location.clone(),
+                        paths,
+                        replace_current_window,
+                        window,
+                        cx,
+                    );
+                });
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, _: &mut Context<Picker<Self>>) {}
+
+    fn no_matches_text(&self, _: &mut Window, _: &mut App) -> Option<SharedString> {
+        let text = if self.entries.is_empty() {
+            "Recently opened files and projects will show up here"
+        } else {
+            "No matches"
+        };
+        Some(text.into())
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let entry = self.entries.get(hit.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(entry.icon()).color(Color::Muted))
+                .child(HighlightedLabel::new(
+                    hit.string.clone(),
+                    hit.positions.clone(),
+                )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kind_filter() {
+        assert_eq!(parse_kind_filter("main.rs"), (None, "main.rs"));
+        assert_eq!(
+            parse_kind_filter("file: main.rs"),
+            (Some(RecentKind::File), "main.rs")
+        );
+        assert_eq!(
+            parse_kind_filter("project:zed"),
+            (Some(RecentKind::Project), "zed")
+        );
+        assert_eq!(
+            parse_kind_filter("project:"),
+            (Some(RecentKind::Project), "")
+        );
+        assert_eq!(
+            parse_kind_filter("my file: notes"),
+            (None, "my file: notes")
+        );
+    }
+
+    #[test]
+    fn test_interleave() {
+        assert_eq!(interleave(vec![1, 3, 5, 7], vec![2, 4]), [1, 2, 3, 4, 5, 7]);
+        assert_eq!(interleave(vec![], vec![2, 4]), [2, 4]);
+        assert_eq!(interleave(Vec::<i32>::new(), vec![]), Vec::<i32>::new());
+    }
+}
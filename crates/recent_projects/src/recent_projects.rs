@@ -1,8 +1,14 @@
 mod dev_container_suggest;
 pub mod disconnected_overlay;
+mod open_in_terminal;
+mod pinned_projects;
+mod project_language;
+mod recent_any;
 mod remote_connections;
 mod remote_servers;
 mod ssh_config;
+mod window_preference;
+mod zoxide;
 
 use std::path::PathBuf;
 
@@ -13,152 +19,114 @@ use remote::RemoteConnectionOptions;
 pub use remote_connection::{RemoteConnectionModal, connect};
 pub use remote_connections::open_remote_project;
 
+use collections::{HashMap, HashSet};
 use disconnected_overlay::DisconnectedOverlay;
-use fuzzy::{StringMatch, StringMatchCandidate};
+use fuzzy::{
+    StringMatch, StringMatchCandidate,
+    word_match::{WordMatchOptions, match_strings_order_insensitive},
+};
 use gpui::{
     Action, AnyElement, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
-    Subscription, Task, WeakEntity, Window,
+    Global, Subscription, Task, WeakEntity, Window, actions,
 };
+use open_in_terminal::{TerminalRootPicker, open_terminal_at};
 use picker::{
     Picker, PickerDelegate,
     highlighted_match_with_paths::{HighlightedMatch, HighlightedMatchWithPaths},
 };
+use pinned_projects::{PinnedProjects, save_pinned_project};
+use recent_any::RecentAny;
 pub use remote_connections::RemoteSettings;
 pub use remote_servers::RemoteServerProjects;
-use settings::Settings;
-use std::{path::Path, sync::Arc};
-use ui::{KeyBinding, ListItem, ListItemSpacing, Tooltip, prelude::*, tooltip_container};
+use settings::{RecentProjectsGroupBy, RecentProjectsOnDirty, RegisterSetting, Settings};
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{self, AtomicBool},
+    },
+    time::{Duration, Instant},
+};
+use ui::{
+    KeyBinding, ListItem, ListItemSpacing, ListSubHeader, Tooltip, prelude::*, tooltip_container,
+};
 use util::{ResultExt, paths::PathExt};
+use window_preference::{WindowPreferences, save_window_preference};
 use workspace::{
     CloseIntent, HistoryManager, ModalView, OpenOptions, PathList, SerializedWorkspaceLocation,
     WORKSPACE_DB, Workspace, WorkspaceId, notifications::DetachAndPromptErr,
     with_active_or_new_workspace,
 };
-use zed_actions::{OpenDevContainer, OpenRecent, OpenRecentZoxide, OpenRemote};
-
-/// Match strings with order-insensitive word matching.
-/// Splits the query into words and ensures all words match somewhere in the candidate,
-/// regardless of order.
-async fn match_strings_order_insensitive<T>(
-    candidates: &[T],
-    query: &str,
-    smart_case: bool,
-    max_results: usize,
-    cancel_flag: &std::sync::atomic::AtomicBool,
-) -> Vec<StringMatch>
-where
-    T: std::borrow::Borrow<StringMatchCandidate> + Sync,
-{
-    if candidates.is_empty() || max_results == 0 {
-        return Default::default();
-    }
-
-    if query.is_empty() {
-        return candidates
-            .iter()
-            .map(|candidate| StringMatch {
-                candidate_id: candidate.borrow().id,
-                score: 0.,
-                positions: Default::default(),
-                string: candidate.borrow().string.clone(),
-            })
-            .collect();
-    }
-
-    // Split query into words and remove empty ones
-    let words: Vec<&str> = if query.trim().contains(' ') {
-        query.split_whitespace().collect()
-    } else {
-        // For single words, treat the whole query as one word
-        vec![query.trim()]
-    };
-
-    if words.is_empty() {
-        return Vec::new();
-    }
-
-    let mut results = Vec::new();
+use zed_actions::{OpenDevContainer, OpenRecent, OpenRecentAny, OpenRecentZoxide, OpenRemote};
+use zoxide::{Zoxide, ZoxideError};
+
+actions!(
+    recent_projects,
+    [
+        /// Reloads the entries of the open recent projects or zoxide picker, keeping the query.
+        RefreshPickerResults,
+        /// Opens a terminal at the root of the selected local project without opening the project.
+        OpenInTerminal,
+        /// Deletes the selected project from the recent projects.
+        DeleteSelected,
+        /// Shows only the zoxide directories that are git repositories, or all of them again.
+        ToggleGitRepositoriesOnly
+    ]
+);
+
+#[derive(Clone, RegisterSetting)]
+pub struct RecentProjectsSettings {
+    pub sticky_selection: bool,
+    pub show_language_badge: bool,
+    pub zoxide_binary_path: Option<PathBuf>,
+    pub on_dirty: RecentProjectsOnDirty,
+    pub group_by: RecentProjectsGroupBy,
+    pub confirm_delete: bool,
+}
 
-    for candidate in candidates {
-        if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
-            break;
+impl Settings for RecentProjectsSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let recent_projects = content.recent_projects.clone().unwrap_or_default();
+        Self {
+            sticky_selection: recent_projects.sticky_selection.unwrap_or(false),
+            show_language_badge: recent_projects.show_language_badge.unwrap_or(false),
+            zoxide_binary_path: recent_projects
+                .zoxide_binary_path
+                .filter(|path| !path.is_empty())
+                .map(|path| PathBuf::from(shellexpand::tilde(&path).as_ref())),
+            on_dirty: recent_projects.on_dirty.unwrap_or_default(),
+            group_by: recent_projects.group_by.unwrap_or_default(),
+            confirm_delete: recent_projects.confirm_delete.unwrap_or(false),
         }
+    }
+}
 
-        let candidate_borrowed = candidate.borrow();
-        let candidate_string = &candidate_borrowed.string;
-        let candidate_lower = candidate_string.to_lowercase();
-
-        // Check if all words are present in the candidate (case-insensitive)
-        let mut all_words_match = true;
-        let mut total_score = 0.0;
-        let mut all_positions = Vec::new();
+/// The most projects the recent projects picker lists for a query.
+const MAX_MATCHES: usize = 100;
 
-        for word in &words {
-            let word_lower = if smart_case {
-                word.to_string()
-            } else {
-                word.to_lowercase()
-            };
+/// How many zoxide directories are checked for being git repositories at once.
+const GIT_CHECK_CONCURRENCY: usize = 16;
 
-            let found_match = if smart_case {
-                candidate_string.contains(word)
-            } else {
-                candidate_lower.contains(&word_lower)
-            };
+/// How often opening the recent projects picker looks for duplicate entries to merge.
+const MERGE_DUPLICATE_WORKSPACES_INTERVAL: Duration = Duration::from_secs(10 * 60);
 
-            if found_match {
-                if let Some(byte_pos) = if smart_case {
-                    candidate_string.find(word)
-                } else {
-                    candidate_lower.find(&word_lower)
-                } {
-                    // Calculate a simple score based on position and word length
-                    let word_score = 1.0 / (byte_pos as f64 + 1.0)
-                        * (word.len() as f64 / candidate_string.len() as f64);
-                    total_score += word_score;
-
-                    if let Some(original_byte_pos) = if smart_case {
-                        candidate_string.find(word)
-                    } else {
-                        candidate_string.to_lowercase().find(&word_lower)
-                    } {
-                        let word_byte_len = word.len();
-                        for i in 0..word_byte_len {
-                            let pos = original_byte_pos + i;
-                            if pos < candidate_string.len()
-                                && candidate_string.is_char_boundary(pos)
-                            {
-                                all_positions.push(pos);
-                            }
-                        }
-                    }
-                }
-            } else {
-                all_words_match = false;
-                break;
-            }
-        }
+#[derive(Default)]
+struct LastDuplicateWorkspaceMerge(Option<Instant>);
 
-        if all_words_match {
-            all_positions.sort_unstable();
-            all_positions.dedup();
+impl Global for LastDuplicateWorkspaceMerge {}
 
-            results.push(StringMatch {
-                candidate_id: candidate_borrowed.id,
-                score: total_score / words.len() as f64, // Average score across words
-                positions: all_positions,
-                string: candidate_string.clone(),
-            });
-        }
+/// Returns whether duplicate recent projects are due to be merged, recording the attempt.
+fn should_merge_duplicate_workspaces(cx: &mut App) -> bool {
+    let last_merge = cx.default_global::<LastDuplicateWorkspaceMerge>();
+    let now = Instant::now();
+    if last_merge
+        .0
+        .is_some_and(|last_merge| now - last_merge < MERGE_DUPLICATE_WORKSPACES_INTERVAL)
+    {
+        return false;
     }
-
-    results.sort_by(|a, b| {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-    results.truncate(max_results);
-    results
+    last_merge.0 = Some(now);
+    true
 }
 
 #[derive(Clone, Debug)]
@@ -322,10 +290,21 @@ pub fn init(cx: &mut App) {
 
     cx.on_action(|open_recent: &OpenRecent, cx| {
         let create_new_window = open_recent.create_new_window;
+        let root_filter = open_recent
+            .root_filter
+            .as_deref()
+            .map(|root_filter| recent_files::expand_tilde(Path::new(root_filter)));
         with_active_or_new_workspace(cx, move |workspace, window, cx| {
             let Some(recent_projects) = workspace.active_modal::<RecentProjects>(cx) else {
                 let focus_handle = workspace.focus_handle(cx);
-                RecentProjects::open(workspace, create_new_window, window, focus_handle, cx);
+                RecentProjects::open(
+                    workspace,
+                    create_new_window,
+                    root_filter,
+                    window,
+                    focus_handle,
+                    cx,
+                );
                 return;
             };
 
@@ -351,6 +330,21 @@ pub fn init(cx: &mut App) {
             });
         });
     });
+    cx.on_action(|open_recent_any: &OpenRecentAny, cx| {
+        let create_new_window = open_recent_any.create_new_window;
+        with_active_or_new_workspace(cx, move |workspace, window, cx| {
+            let Some(recent_any) = workspace.active_modal::<RecentAny>(cx) else {
+                RecentAny::open(workspace, create_new_window, window, cx);
+                return;
+            };
+
+            recent_any.update(cx, |recent_any, cx| {
+                recent_any
+                    .picker
+                    .update(cx, |picker, cx| picker.cycle_selection(window, cx))
+            });
+        });
+    });
     cx.on_action(|open_remote: &OpenRemote, cx| {
         let from_existing_connection = open_remote.from_existing_connection;
         let create_new_window = open_remote.create_new_window;
@@ -465,6 +459,7 @@ pub fn add_wsl_distro(
 pub struct RecentProjects {
     pub picker: Entity<Picker<RecentProjectsDelegate>>,
     rem_width: f32,
+    _load_task: Task<()>,
     _subscription: Subscription,
 }
 
@@ -486,40 +481,142 @@ impl RecentProjects {
             }
         });
         let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        let mut this = Self {
+            picker,
+            rem_width,
+            _load_task: Task::ready(()),
+            _subscription,
+        };
+        this.load_workspaces(window, cx);
+        this
+    }
+
+    fn load_workspaces(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         // We do not want to block the UI on a potentially lengthy call to DB, so we're gonna swap
         // out workspace locations once the future runs to completion.
-        cx.spawn_in(window, async move |this, cx| {
+        let show_language_badge = RecentProjectsSettings::get_global(cx).show_language_badge;
+        let merge_duplicates = should_merge_duplicate_workspaces(cx)
+            .then(|| cx.background_spawn(WORKSPACE_DB.merge_duplicate_workspaces()));
+        self._load_task = cx.spawn_in(window, async move |this, cx| {
+            if let Some(merge_duplicates) = merge_duplicates {
+                let merged_ids = merge_duplicates.await.log_err().unwrap_or_default();
+                if !merged_ids.is_empty() {
+                    log::info!(
+                        "merged {} recent projects into entries for the same canonical paths",
+                        merged_ids.len()
+                    );
+                    cx.update(|_, cx| {
+                        if let Some(history_manager) = HistoryManager::global(cx) {
+                            history_manager.update(cx, |history_manager, cx| {
+                                for id in merged_ids {
+                                    history_manager.delete_history(id, cx);
+                                }
+                            });
+                        }
+                    })
+                    .ok();
+                }
+            }
             let workspaces = WORKSPACE_DB
                 .recent_workspaces_on_disk()
                 .await
                 .log_err()
                 .unwrap_or_default();
+            let project_languages = if show_language_badge {
+                let workspaces = workspaces.clone();
+                let (project_languages, stale_workspaces) = cx
+                    .background_spawn(async move {
+                        project_language::cached_project_languages(&workspaces)
+                    })
+                    .await;
+                cx.background_spawn(project_language::refresh_project_languages(
+                    stale_workspaces,
+                ))
+                .detach();
+                project_languages
+            } else {
+                HashMap::default()
+            };
+            let workspace_ids = workspaces
+                .iter()
+                .map(|(workspace_id, _, _)| *workspace_id)
+                .collect::<Vec<_>>();
+            let (window_preferences, pinned_projects) = cx
+                .background_spawn(async move {
+                    (
+                        WindowPreferences::load(workspace_ids.iter().copied()),
+                        PinnedProjects::load(workspace_ids),
+                    )
+                })
+                .await;
             this.update_in(cx, move |this, window, cx| {
                 this.picker.update(cx, move |picker, cx| {
                     picker.delegate.set_workspaces(workspaces);
+                    picker.delegate.project_languages = project_languages;
+                    picker.delegate.window_preferences = window_preferences;
+                    picker.delegate.pinned_projects = pinned_projects;
+                    picker.delegate.is_refreshing = false;
                     picker.update_matches(picker.query(cx), window, cx)
                 })
             })
-            .ok()
-        })
-        .detach();
-        Self {
-            picker,
-            rem_width,
-            _subscription,
+            .ok();
+        });
+    }
+
+    fn refresh(&mut self, _: &RefreshPickerResults, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.is_refreshing = true;
+            cx.notify();
+        });
+        self.load_workspaces(window, cx);
+    }
+
+    /// Backspace in an empty query clears the root filter, if there is one, before it
+    /// reaches the query editor.
+    fn clear_root_filter(
+        &mut self,
+        _: &editor::actions::Backspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cleared = self.picker.update(cx, |picker, cx| {
+            if !picker.query(cx).is_empty() || picker.delegate.root_filter.take().is_none() {
+                return false;
+            }
+            picker.refresh_placeholder(window, cx);
+            picker.refresh(window, cx);
+            true
+        });
+        if cleared {
+            cx.stop_propagation();
         }
     }
 
+    fn open_in_terminal(
+        &mut self,
+        _: &OpenInTerminal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            let ix = picker.delegate.selected_index();
+            picker.delegate.open_in_terminal(ix, window, cx);
+        });
+    }
+
     pub fn open(
         workspace: &mut Workspace,
         create_new_window: bool,
+        root_filter: Option<PathBuf>,
         window: &mut Window,
         focus_handle: FocusHandle,
         cx: &mut Context<Workspace>,
     ) {
         let weak = cx.entity().downgrade();
         workspace.toggle_modal(window, cx, |window, cx| {
-            let delegate = RecentProjectsDelegate::new(weak, create_new_window, true, focus_handle);
+            let mut delegate =
+                RecentProjectsDelegate::new(weak, create_new_window, true, focus_handle);
+            delegate.root_filter = root_filter;
 
             Self::new(delegate, 34., window, cx)
         })
@@ -555,6 +652,15 @@ impl Render for RecentProjects {
         v_flex()
             .key_context("RecentProjects")
             .w(rems(self.rem_width))
+            .capture_action(cx.listener(Self::clear_root_filter))
+            .on_action(cx.listener(Self::refresh))
+            .on_action(cx.listener(Self::open_in_terminal))
+            .on_action(cx.listener(|this, _: &DeleteSelected, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    let ix = picker.delegate.selected_index();
+                    picker.delegate.delete_recent_project(ix, window, cx);
+                });
+            }))
             .child(self.picker.clone())
             .on_mouse_down_out(cx.listener(|this, _, window, cx| {
                 this.picker.update(cx, |this, cx| {
@@ -564,15 +670,75 @@ impl Render for RecentProjects {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum RecentProjectsRow {
+    /// A group header, which can't be selected.
+    Header(SharedString),
+    /// The match at this index in `matches`.
+    Match(usize),
+}
+
+/// Groups the matches by the parent directory of their first root, given in match order.
+/// Groups are ordered by their best match, and matches keep their order within a group.
+fn rows_grouped_by_parent_dir(first_roots: &[Option<&Path>]) -> Vec<RecentProjectsRow> {
+    let mut groups: Vec<(Option<&Path>, Vec<usize>)> = Vec::new();
+    for (match_index, root) in first_roots.iter().enumerate() {
+        let parent = root.and_then(Path::parent);
+        match groups
+            .iter_mut()
+            .find(|(group_parent, _)| *group_parent == parent)
+        {
+            Some((_, match_indices)) => match_indices.push(match_index),
+            None => groups.push((parent, vec![match_index])),
+        }
+    }
+    groups
+        .into_iter()
+        .flat_map(|(parent, match_indices)| {
+            let label = parent.map_or_else(
+                || SharedString::new_static("Other"),
+                |parent| parent.compact().to_string_lossy().into_owned().into(),
+            );
+            std::iter::once(RecentProjectsRow::Header(label))
+                .chain(match_indices.into_iter().map(RecentProjectsRow::Match))
+        })
+        .collect()
+}
+
+/// Whether any of the roots is `directory` or inside it.
+fn has_root_under(paths: &PathList, directory: &Path) -> bool {
+    paths
+        .ordered_paths()
+        .any(|path| path.starts_with(directory))
+}
+
 pub struct RecentProjectsDelegate {
     workspace: WeakEntity<Workspace>,
     workspaces: Vec<(WorkspaceId, SerializedWorkspaceLocation, PathList)>,
     selected_match_index: usize,
     matches: Vec<StringMatch>,
+    /// The rows of the picker when `recent_projects.group_by` groups the matches. Empty when
+    /// every match is a row of its own.
+    rows: Vec<RecentProjectsRow>,
     render_paths: bool,
     create_new_window: bool,
+    /// When set, only the projects with a root under this directory are listed.
+    root_filter: Option<PathBuf>,
     // Flag to reset index when there is a new query vs not reset index when user delete an item
     reset_selected_match_index: bool,
+    /// The candidate the user last moved the selection to, kept selected across
+    /// queries when `recent_projects.sticky_selection` is enabled.
+    selected_candidate_id: Option<usize>,
+    /// Cached primary languages of local projects, shown as badges when
+    /// `recent_projects.show_language_badge` is enabled.
+    project_languages: HashMap<WorkspaceId, SharedString>,
+    /// How each project was last opened from the picker, which confirming it repeats.
+    window_preferences: WindowPreferences,
+    /// The projects listed above all others that match the query.
+    pinned_projects: PinnedProjects,
+    /// Cancels the matching of the previous query once a newer one is typed.
+    search_cancelled: Option<Arc<AtomicBool>>,
+    is_refreshing: bool,
     has_any_non_local_projects: bool,
     focus_handle: FocusHandle,
 }
@@ -589,9 +755,17 @@ impl RecentProjectsDelegate {
             workspaces: Vec::new(),
             selected_match_index: 0,
             matches: Default::default(),
+            rows: Vec::new(),
             create_new_window,
+            root_filter: None,
             render_paths,
             reset_selected_match_index: true,
+            selected_candidate_id: None,
+            project_languages: HashMap::default(),
+            window_preferences: WindowPreferences::default(),
+            pinned_projects: PinnedProjects::default(),
+            search_cancelled: None,
+            is_refreshing: false,
             has_any_non_local_projects: false,
             focus_handle,
         }
@@ -607,10 +781,141 @@ impl RecentProjectsDelegate {
             .iter()
             .all(|(_, location, _)| matches!(location, SerializedWorkspaceLocation::Local));
     }
+
+    /// The match shown in the row at `ix`, or `None` for group headers.
+    fn match_at_row(&self, ix: usize) -> Option<&StringMatch> {
+        if self.rows.is_empty() {
+            return self.matches.get(ix);
+        }
+        match self.rows.get(ix)? {
+            RecentProjectsRow::Header(_) => None,
+            RecentProjectsRow::Match(match_index) => self.matches.get(*match_index),
+        }
+    }
+
+    /// The row showing the match at `match_index`.
+    fn row_for_match(&self, match_index: usize) -> Option<usize> {
+        if self.rows.is_empty() {
+            return (match_index < self.matches.len()).then_some(match_index);
+        }
+        self.rows
+            .iter()
+            .position(|row| *row == RecentProjectsRow::Match(match_index))
+    }
+
+    /// The first selectable row at or after `ix`, falling back to the first selectable row.
+    fn selectable_row_from(&self, ix: usize) -> usize {
+        (ix..self.match_count())
+            .chain(0..ix)
+            .find(|&row| self.match_at_row(row).is_some())
+            .unwrap_or(0)
+    }
+
+    /// Shows `matches`, grouping them into rows and restoring the selection.
+    fn set_matches(&mut self, matches: Vec<StringMatch>, cx: &App) {
+        self.matches = matches;
+        self.rows = match RecentProjectsSettings::get_global(cx).group_by {
+            RecentProjectsGroupBy::None => Vec::new(),
+            RecentProjectsGroupBy::ParentDir => {
+                let first_roots = self
+                    .matches
+                    .iter()
+                    .map(|hit| {
+                        let (_, _, paths) = self.workspaces.get(hit.candidate_id)?;
+                        paths.ordered_paths().next().map(PathBuf::as_path)
+                    })
+                    .collect::<Vec<_>>();
+                rows_grouped_by_parent_dir(&first_roots)
+            }
+        };
+
+        if self.reset_selected_match_index {
+            let sticky_index = self
+                .selected_candidate_id
+                .filter(|_| RecentProjectsSettings::get_global(cx).sticky_selection)
+                .and_then(|candidate_id| {
+                    self.matches
+                        .iter()
+                        .position(|m| m.candidate_id == candidate_id)
+                })
+                .and_then(|match_index| self.row_for_match(match_index));
+            match sticky_index {
+                Some(ix) => self.selected_match_index = ix,
+                None => {
+                    self.selected_match_index = self.selectable_row_from(0);
+                    self.selected_candidate_id = None;
+                }
+            }
+        } else {
+            self.selected_match_index = self.selectable_row_from(self.selected_match_index);
+            if self.selected_candidate_id.is_some() {
+                self.selected_candidate_id = self
+                    .match_at_row(self.selected_match_index)
+                    .map(|m| m.candidate_id);
+            }
+        }
+        self.reset_selected_match_index = true;
+    }
+
+    /// Whether confirming the selected project without the modifier opens it in a new window,
+    /// and whether that's what it was last opened in.
+    fn selected_opens_in_new_window(&self) -> Option<(bool, bool)> {
+        let hit = self.match_at_row(self.selected_index())?;
+        let (workspace_id, _, _) = self.workspaces.get(hit.candidate_id)?;
+        let new_window = self.window_preferences.opens_in_new_window(
+            *workspace_id,
+            self.create_new_window,
+            false,
+        );
+        let remembered = self.window_preferences.remembered(*workspace_id).is_some();
+        Some((new_window, remembered))
+    }
+
+    /// Pins the project at `ix` to the top of the picker, or unpins it. The matches are
+    /// re-sorted by updating them, which keeps the selected row.
+    fn toggle_pinned(&mut self, ix: usize, cx: &mut Context<Picker<Self>>) {
+        let Some((workspace_id, _, _)) = self
+            .match_at_row(ix)
+            .and_then(|hit| self.workspaces.get(hit.candidate_id))
+        else {
+            return;
+        };
+        let workspace_id = *workspace_id;
+        let pinned = self.pinned_projects.toggle(workspace_id);
+        cx.background_spawn(save_pinned_project(workspace_id, pinned))
+            .detach();
+        self.reset_selected_match_index = false;
+    }
+
+    /// Opens a terminal at the root of the local project at `ix`, asking which root to use
+    /// when the project has several. Remote projects are ignored.
+    fn open_in_terminal(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some((_, SerializedWorkspaceLocation::Local, paths)) = self
+            .match_at_row(ix)
+            .and_then(|hit| self.workspaces.get(hit.candidate_id))
+        else {
+            return;
+        };
+        let roots = paths.ordered_paths().cloned().collect::<Vec<_>>();
+        match roots.as_slice() {
+            [] => {}
+            [root] => {
+                open_terminal_at(root.clone(), window, cx);
+                cx.emit(DismissEvent);
+            }
+            _ => {
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        TerminalRootPicker::toggle(workspace, roots, window, cx)
+                    })
+                    .log_err();
+            }
+        }
+    }
 }
 impl EventEmitter<DismissEvent> for RecentProjectsDelegate {}
 impl PickerDelegate for RecentProjectsDelegate {
-    type ListItem = ListItem;
+    type ListItem = AnyElement;
 
     fn placeholder_text(&self, window: &mut Window, _: &mut App) -> Arc<str> {
         let (create_window, reuse_window) = if self.create_new_window {
@@ -624,13 +929,22 @@ impl PickerDelegate for RecentProjectsDelegate {
                 window.keystroke_text_for(&menu::Confirm),
             )
         };
-        Arc::from(format!(
-            "{reuse_window} reuses this window, {create_window} opens a new one",
-        ))
+        let hint = format!("{reuse_window} reuses this window, {create_window} opens a new one");
+        match &self.root_filter {
+            Some(root_filter) => Arc::from(format!(
+                "In {}, backspace shows all. {hint}",
+                root_filter.compact().display(),
+            )),
+            None => Arc::from(hint),
+        }
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        if self.rows.is_empty() {
+            self.matches.len()
+        } else {
+            self.rows.len()
+        }
     }
 
     fn selected_index(&self) -> usize {
@@ -644,22 +958,40 @@ impl PickerDelegate for RecentProjectsDelegate {
         _cx: &mut Context<Picker<Self>>,
     ) {
         self.selected_match_index = ix;
+        self.selected_candidate_id = self.match_at_row(ix).map(|m| m.candidate_id);
+    }
+
+    fn can_select(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) -> bool {
+        self.match_at_row(ix).is_some()
     }
 
     fn update_matches(
         &mut self,
         query: String,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> gpui::Task<()> {
-        let query = query.trim_start();
+        let query = query.trim_start().to_string();
         let smart_case = query.chars().any(|c| c.is_uppercase());
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        if let Some(previous_cancel_flag) = self.search_cancelled.replace(cancel_flag.clone()) {
+            previous_cancel_flag.store(true, atomic::Ordering::Relaxed);
+        }
+        let mut pinned_candidates = HashSet::default();
         let candidates = self
             .workspaces
             .iter()
             .enumerate()
             .filter(|(_, (id, _, _))| !self.is_current_workspace(*id, cx))
-            .map(|(id, (_, _, paths))| {
+            .filter(|(_, (_, _, paths))| {
+                self.root_filter
+                    .as_deref()
+                    .is_none_or(|root_filter| has_root_under(paths, root_filter))
+            })
+            .map(|(id, (workspace_id, _, paths))| {
+                if self.pinned_projects.is_pinned(*workspace_id) {
+                    pinned_candidates.insert(id);
+                }
                 let combined_string = paths
                     .ordered_paths()
                     .map(|path| path.compact().to_string_lossy().into_owned())
@@ -668,113 +1000,92 @@ impl PickerDelegate for RecentProjectsDelegate {
                 StringMatchCandidate::new(id, &combined_string)
             })
             .collect::<Vec<_>>();
-        self.matches = smol::block_on(match_strings_order_insensitive(
-            candidates.as_slice(),
-            query,
-            smart_case,
-            100,
-            &Default::default(),
-        ));
-        self.matches.sort_unstable_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score) // Descending score
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.candidate_id.cmp(&b.candidate_id)) // Ascending candidate_id for ties
+        let executor = cx.background_executor().clone();
+        let matches = cx.background_spawn({
+            let cancel_flag = cancel_flag.clone();
+            async move {
+                // Pinned projects come first however poorly they match, so the results are
+                // only limited once they're sorted. Without a query, every project is listed.
+                let options = WordMatchOptions {
+                    smart_case,
+                    ..WordMatchOptions::default()
+                };
+                let mut matches = match_strings_order_insensitive(
+                    candidates.as_slice(),
+                    &query,
+                    &options,
+                    &cancel_flag,
+                    executor,
+                )
+                .await;
+                // Pinned first, then descending score, ascending candidate_id for ties.
+                matches.sort_unstable_by(|a, b| {
+                    pinned_candidates
+                        .contains(&b.candidate_id)
+                        .cmp(&pinned_candidates.contains(&a.candidate_id))
+                        .then_with(|| {
+                            b.score
+                                .partial_cmp(&a.score)
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .then_with(|| a.candidate_id.cmp(&b.candidate_id))
+                });
+                if !query.is_empty() {
+                    matches.truncate(MAX_MATCHES);
+                }
+                matches
+            }
         });
-
-        if self.reset_selected_match_index {
-            self.selected_match_index = 0;
-        }
-        self.reset_selected_match_index = true;
-        Task::ready(())
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = matches.await;
+            // A newer query started while this one was matching.
+            if cancel_flag.load(atomic::Ordering::Relaxed) {
+                return;
+            }
+            picker
+                .update(cx, |picker, cx| {
+                    picker.delegate.set_matches(matches, cx);
+                    cx.notify();
+                })
+                .ok();
+        })
     }
 
     fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
         if let Some((selected_match, workspace)) = self
-            .matches
-            .get(self.selected_index())
+            .match_at_row(self.selected_index())
             .zip(self.workspace.upgrade())
         {
             let (candidate_workspace_id, candidate_workspace_location, candidate_workspace_paths) =
                 &self.workspaces[selected_match.candidate_id];
-            let replace_current_window = if self.create_new_window {
-                secondary
-            } else {
-                !secondary
-            };
-            workspace.update(cx, |workspace, cx| {
-                if workspace.database_id() == Some(*candidate_workspace_id) {
-                    return;
-                }
-                match candidate_workspace_location.clone() {
-                    SerializedWorkspaceLocation::Local => {
-                        let paths = candidate_workspace_paths.paths().to_vec();
-                        if replace_current_window {
-                            cx.spawn_in(window, async move |workspace, cx| {
-                                let continue_replacing = workspace
-                                    .update_in(cx, |workspace, window, cx| {
-                                        workspace.prepare_to_close(
-                                            CloseIntent::ReplaceWindow,
-                                            window,
-                                            cx,
-                                        )
-                                    })?
-                                    .await?;
-                                if continue_replacing {
-                                    workspace
-                                        .update_in(cx, |workspace, window, cx| {
-                                            workspace
-                                                .open_workspace_for_paths(true, paths, window, cx)
-                                        })?
-                                        .await
-                                } else {
-                                    Ok(())
-                                }
-                            })
-                        } else {
-                            workspace.open_workspace_for_paths(false, paths, window, cx)
-                        }
-                    }
-                    SerializedWorkspaceLocation::Remote(mut connection) => {
-                        let app_state = workspace.app_state().clone();
-
-                        let replace_window = if replace_current_window {
-                            window.window_handle().downcast::<Workspace>()
-                        } else {
-                            None
-                        };
-
-                        let open_options = OpenOptions {
-                            replace_window,
-                            ..Default::default()
-                        };
-
-                        if let RemoteConnectionOptions::Ssh(connection) = &mut connection {
-                            RemoteSettings::get_global(cx)
-                                .fill_connection_options_from_settings(connection);
-                        };
-
-                        let paths = candidate_workspace_paths.paths().to_vec();
-
-                        cx.spawn_in(window, async move |_, cx| {
-                            open_remote_project(
-                                connection.clone(),
-                                paths,
-                                app_state,
-                                open_options,
-                                cx,
-                            )
-                            .await
-                        })
-                    }
-                }
-                .detach_and_prompt_err(
-                    "Failed to open project",
-                    window,
-                    cx,
-                    |_, _, _| None,
-                );
-            });
+            let new_window = self.window_preferences.opens_in_new_window(
+                *candidate_workspace_id,
+                self.create_new_window,
+                secondary,
+            );
+            let replace_current_window = !new_window;
+            let is_current_project = self.is_current_project(
+                *candidate_workspace_id,
+                candidate_workspace_location,
+                candidate_workspace_paths,
+                cx,
+            );
+            if !is_current_project {
+                self.window_preferences
+                    .remember(*candidate_workspace_id, new_window);
+                cx.background_spawn(save_window_preference(*candidate_workspace_id, new_window))
+                    .detach();
+                workspace.update(cx, |workspace, cx| {
+                    open_recent_project(
+                        workspace,
+                        candidate_workspace_location.clone(),
+                        candidate_workspace_paths,
+                        replace_current_window,
+                        window,
+                        cx,
+                    );
+                });
+            }
             cx.emit(DismissEvent);
         }
     }
@@ -797,9 +1108,19 @@ impl PickerDelegate for RecentProjectsDelegate {
         window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> Option<Self::ListItem> {
-        let hit = self.matches.get(ix)?;
+        if let Some(RecentProjectsRow::Header(label)) = self.rows.get(ix) {
+            return Some(
+                ListSubHeader::new(label.clone())
+                    .inset(true)
+                    .into_any_element(),
+            );
+        }
+        let hit = self.match_at_row(ix)?;
 
-        let (_, location, paths) = self.workspaces.get(hit.candidate_id)?;
+        let (workspace_id, location, paths) = self.workspaces.get(hit.candidate_id)?;
+        let language = self.project_languages.get(workspace_id).cloned();
+        let is_pinned = self.pinned_projects.is_pinned(*workspace_id);
+        let is_current_project = self.is_current_project(*workspace_id, location, paths, cx);
 
         let mut path_start_offset = 0;
 
@@ -828,9 +1149,31 @@ impl PickerDelegate for RecentProjectsDelegate {
         };
 
         let focus_handle = self.focus_handle.clone();
+        let is_local = matches!(location, SerializedWorkspaceLocation::Local);
 
         let secondary_actions = h_flex()
             .gap_px()
+            .when(is_local, |this| {
+                let focus_handle = focus_handle.clone();
+                this.child(
+                    IconButton::new("open_in_terminal", IconName::Terminal)
+                        .icon_size(IconSize::XSmall)
+                        .tooltip(move |_, cx| {
+                            Tooltip::for_action_in(
+                                "Open in Terminal",
+                                &OpenInTerminal,
+                                &focus_handle,
+                                cx,
+                            )
+                        })
+                        .on_click(cx.listener(move |this, _event, window, cx| {
+                            cx.stop_propagation();
+                            window.prevent_default();
+                            this.delegate.set_selected_index(ix, window, cx);
+                            this.delegate.open_in_terminal(ix, window, cx);
+                        })),
+                )
+            })
             .child(
                 IconButton::new("open_new_window", IconName::ArrowUpRight)
                     .icon_size(IconSize::XSmall)
@@ -848,7 +1191,29 @@ impl PickerDelegate for RecentProjectsDelegate {
                         cx.stop_propagation();
                         window.prevent_default();
                         this.delegate.set_selected_index(ix, window, cx);
-                        this.delegate.confirm(true, window, cx);
+                        // The modifier reuses the window for projects remembered as opening
+                        // in a new one.
+                        let secondary = !this
+                            .delegate
+                            .selected_opens_in_new_window()
+                            .is_some_and(|(new_window, _)| new_window);
+                        this.delegate.confirm(secondary, window, cx);
+                    })),
+            )
+            .child(
+                IconButton::new("toggle_pinned", IconName::Pin)
+                    .icon_size(IconSize::XSmall)
+                    .toggle_state(is_pinned)
+                    .tooltip(Tooltip::text(if is_pinned {
+                        "Unpin Project"
+                    } else {
+                        "Pin Project to Top"
+                    }))
+                    .on_click(cx.listener(move |this, _event, window, cx| {
+                        cx.stop_propagation();
+                        window.prevent_default();
+                        this.delegate.toggle_pinned(ix, cx);
+                        this.update_matches(this.query(cx), window, cx);
                     })),
             )
             .child(
@@ -899,6 +1264,42 @@ impl PickerDelegate for RecentProjectsDelegate {
                             }
                             highlighted.render(window, cx)
                         })
+                        .when(is_pinned, |this| {
+                            this.child(
+                                Icon::new(IconName::Pin)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                        })
+                        .when(is_current_project, |this| {
+                            this.child(
+                                div()
+                                    .flex_none()
+                                    .px_1()
+                                    .rounded_sm()
+                                    .bg(cx.theme().colors().element_selected)
+                                    .child(
+                                        Label::new("current")
+                                            .size(LabelSize::XSmall)
+                                            .color(Color::Accent),
+                                    ),
+                            )
+                        })
+                        .when_some(language, |this, language| {
+                            this.child(
+                                div()
+                                    .flex_none()
+                                    .px_1()
+                                    .rounded_sm()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border_variant)
+                                    .child(
+                                        Label::new(language)
+                                            .size(LabelSize::XSmall)
+                                            .color(Color::Muted),
+                                    ),
+                            )
+                        })
                         .tooltip(move |_, cx| {
                             let tooltip_highlighted_location = highlighted_match.clone();
                             cx.new(|_| MatchTooltip {
@@ -913,11 +1314,31 @@ impl PickerDelegate for RecentProjectsDelegate {
                     } else {
                         el.end_hover_slot(secondary_actions)
                     }
-                }),
+                })
+                .into_any_element(),
         )
     }
 
-    fn render_footer(&self, _: &mut Window, cx: &mut Context<Picker<Self>>) -> Option<AnyElement> {
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        // What confirming the selected project does, which depends on how it was last opened.
+        let window_hint = self
+            .selected_opens_in_new_window()
+            .map(|(new_window, remembered)| {
+                let target = if new_window {
+                    "a new window"
+                } else {
+                    "this window"
+                };
+                let reason = if remembered { ", as last time" } else { "" };
+                format!(
+                    "{} opens in {target}{reason}",
+                    window.keystroke_text_for(&menu::Confirm)
+                )
+            });
         Some(
             h_flex()
                 .w_full()
@@ -926,6 +1347,22 @@ impl PickerDelegate for RecentProjectsDelegate {
                 .justify_end()
                 .border_t_1()
                 .border_color(cx.theme().colors().border_variant)
+                .when_some(window_hint, |this, window_hint| {
+                    this.child(
+                        div().mr_auto().child(
+                            Label::new(window_hint)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                    )
+                })
+                .when(self.is_refreshing, |this| {
+                    this.child(
+                        Label::new("Refreshing…")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                })
                 .child(
                     Button::new("remote", "Open Remote Folder")
                         .key_binding(KeyBinding::for_action(
@@ -958,21 +1395,103 @@ impl PickerDelegate for RecentProjectsDelegate {
     }
 }
 
-// Compute the highlighted text for the name and path
-fn highlights_for_path(
-    path: &Path,
-    match_positions: &Vec<usize>,
-    path_start_offset: usize,
-) -> (Option<HighlightedMatch>, HighlightedMatch) {
-    let path_string = path.to_string_lossy();
-    let path_text = path_string.to_string();
-    let path_byte_len = path_text.len();
-    // Get the subset of match highlight positions that line up with the given path.
-    // Also adjusts them to start at the path start
-    let path_positions = match_positions
-        .iter()
-        .copied()
-        .skip_while(|position| *position < path_start_offset)
+/// Opens a recent project, either in place of the current window or in a new one.
+pub(crate) fn open_recent_project(
+    workspace: &mut Workspace,
+    location: SerializedWorkspaceLocation,
+    paths: &PathList,
+    replace_current_window: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    match location {
+        SerializedWorkspaceLocation::Local => {
+            let paths = paths.paths().to_vec();
+            if replace_current_window {
+                replace_window_with_local_paths(workspace, paths, window, cx)
+            } else {
+                workspace.open_workspace_for_paths(false, paths, window, cx)
+            }
+        }
+        SerializedWorkspaceLocation::Remote(mut connection) => {
+            let app_state = workspace.app_state().clone();
+
+            let replace_window = if replace_current_window {
+                window.window_handle().downcast::<Workspace>()
+            } else {
+                None
+            };
+
+            let open_options = OpenOptions {
+                replace_window,
+                ..Default::default()
+            };
+
+            if let RemoteConnectionOptions::Ssh(connection) = &mut connection {
+                RemoteSettings::get_global(cx).fill_connection_options_from_settings(connection);
+            };
+
+            let paths = paths.paths().to_vec();
+
+            cx.spawn_in(window, async move |_, cx| {
+                open_remote_project(connection.clone(), paths, app_state, open_options, cx).await
+            })
+        }
+    }
+    .detach_and_prompt_err("Failed to open project", window, cx, |_, _, _| None);
+}
+
+/// Opens local paths in place of the current window. When the window has unsaved changes,
+/// `recent_projects.on_dirty` decides whether to prompt for them, open a new window instead,
+/// or discard them.
+fn replace_window_with_local_paths(
+    workspace: &mut Workspace,
+    paths: Vec<PathBuf>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<anyhow::Result<()>> {
+    let has_dirty_items = workspace.items(cx).any(|item| item.is_dirty(cx));
+    match RecentProjectsSettings::get_global(cx).on_dirty {
+        RecentProjectsOnDirty::NewWindow if has_dirty_items => {
+            workspace.open_workspace_for_paths(false, paths, window, cx)
+        }
+        RecentProjectsOnDirty::Discard if has_dirty_items => {
+            workspace.open_workspace_for_paths(true, paths, window, cx)
+        }
+        _ => cx.spawn_in(window, async move |workspace, cx| {
+            let continue_replacing = workspace
+                .update_in(cx, |workspace, window, cx| {
+                    workspace.prepare_to_close(CloseIntent::ReplaceWindow, window, cx)
+                })?
+                .await?;
+            if continue_replacing {
+                workspace
+                    .update_in(cx, |workspace, window, cx| {
+                        workspace.open_workspace_for_paths(true, paths, window, cx)
+                    })?
+                    .await
+            } else {
+                Ok(())
+            }
+        }),
+    }
+}
+
+// Compute the highlighted text for the name and path
+fn highlights_for_path(
+    path: &Path,
+    match_positions: &Vec<usize>,
+    path_start_offset: usize,
+) -> (Option<HighlightedMatch>, HighlightedMatch) {
+    let path_string = path.to_string_lossy();
+    let path_text = path_string.to_string();
+    let path_byte_len = path_text.len();
+    // Get the subset of match highlight positions that line up with the given path.
+    // Also adjusts them to start at the path start
+    let path_positions = match_positions
+        .iter()
+        .copied()
+        .skip_while(|position| *position < path_start_offset)
         .take_while(|position| *position < path_start_offset + path_byte_len)
         .map(|position| position - path_start_offset)
         .collect::<Vec<_>>();
@@ -1012,16 +1531,41 @@ impl RecentProjectsDelegate {
         window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) {
-        if let Some(selected_match) = self.matches.get(ix) {
-            let (workspace_id, _, _) = self.workspaces[selected_match.candidate_id];
+        if let Some(selected_match) = self.match_at_row(ix) {
+            let (workspace_id, _, paths) = &self.workspaces[selected_match.candidate_id];
+            let workspace_id = *workspace_id;
+            let confirmation = RecentProjectsSettings::get_global(cx)
+                .confirm_delete
+                .then(|| {
+                    let project = paths
+                        .paths()
+                        .iter()
+                        .map(|path| path.compact().to_string_lossy().into_owned())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    window.prompt(
+                        gpui::PromptLevel::Warning,
+                        &format!("Delete {project} from Recent Projects?"),
+                        None,
+                        &["Delete", "Cancel"],
+                        cx,
+                    )
+                });
             cx.spawn_in(window, async move |this, cx| {
+                if let Some(confirmation) = confirmation
+                    && !matches!(confirmation.await, Ok(0))
+                {
+                    return Ok(());
+                }
                 let _ = WORKSPACE_DB.delete_workspace_by_id(workspace_id).await;
+                save_pinned_project(workspace_id, false).await;
                 let workspaces = WORKSPACE_DB
                     .recent_workspaces_on_disk()
                     .await
                     .unwrap_or_default();
                 this.update_in(cx, move |picker, window, cx| {
                     picker.delegate.set_workspaces(workspaces);
+                    picker.delegate.pinned_projects.remove(workspace_id);
                     picker
                         .delegate
                         .set_selected_index(ix.saturating_sub(1), window, cx);
@@ -1039,11 +1583,7 @@ impl RecentProjectsDelegate {
         }
     }
 
-    fn is_current_workspace(
-        &self,
-        workspace_id: WorkspaceId,
-        cx: &mut Context<Picker<Self>>,
-    ) -> bool {
+    fn is_current_workspace(&self, workspace_id: WorkspaceId, cx: &App) -> bool {
         if let Some(workspace) = self.workspace.upgrade() {
             let workspace = workspace.read(cx);
             if Some(workspace_id) == workspace.database_id() {
@@ -1053,11 +1593,35 @@ impl RecentProjectsDelegate {
 
         false
     }
+
+    /// Like `is_current_workspace`, but also recognizes a local entry with a stale id
+    /// whose roots are the ones open in this window.
+    fn is_current_project(
+        &self,
+        workspace_id: WorkspaceId,
+        location: &SerializedWorkspaceLocation,
+        paths: &PathList,
+        cx: &App,
+    ) -> bool {
+        if self.is_current_workspace(workspace_id, cx) {
+            return true;
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return false;
+        };
+        let workspace = workspace.read(cx);
+        matches!(location, SerializedWorkspaceLocation::Local)
+            && workspace.project().read(cx).is_local()
+            && !paths.is_empty()
+            && PathList::new(&workspace.root_paths(cx)) == *paths
+    }
 }
 
 pub struct RecentProjectsZoxide {
     pub picker: Entity<Picker<RecentProjectsZoxideDelegate>>,
     rem_width: f32,
+    _load_task: Task<()>,
+    _git_check_task: Task<()>,
     _subscription: Subscription,
 }
 
@@ -1073,37 +1637,96 @@ impl RecentProjectsZoxide {
         let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
         let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
 
-        // Load zoxide directories asynchronously
-        cx.spawn_in(window, async move |this, cx| {
-            #[allow(clippy::disallowed_methods)]
-            let output = std::process::Command::new("zoxide")
-                .args(&["query", "--list"])
-                .output();
-
-            let directories = match output {
-                Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .map(|line| line.trim().to_string())
-                    .filter(|line| !line.is_empty())
-                    .collect::<Vec<_>>(),
-                _ => Vec::new(),
-            };
+        let mut this = Self {
+            picker,
+            rem_width,
+            _load_task: Task::ready(()),
+            _git_check_task: Task::ready(()),
+            _subscription,
+        };
+        this.load_directories(window, cx);
+        this
+    }
 
+    fn load_directories(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let zoxide = Zoxide::new(
+            RecentProjectsSettings::get_global(cx)
+                .zoxide_binary_path
+                .clone(),
+        );
+        self._load_task = cx.spawn_in(window, async move |this, cx| {
+            let directories = cx
+                .background_spawn(async move { zoxide.query_directories() })
+                .await;
             this.update_in(cx, move |this, window, cx| {
-                this.picker.update(cx, move |picker, cx| {
+                this.picker.update(cx, |picker, cx| {
                     picker.delegate.set_directories(directories);
+                    picker.delegate.is_refreshing = false;
                     picker.update_matches(picker.query(cx), window, cx)
-                })
+                });
+                this.check_git_repositories(window, cx);
             })
-            .ok()
-        })
-        .detach();
+            .ok();
+        });
+    }
 
-        Self {
-            picker,
-            rem_width,
-            _subscription,
-        }
+    /// Looks for a `.git` entry in each directory that wasn't checked yet, a few at a time,
+    /// showing the results as they arrive. Closing the picker drops the task, which stops
+    /// the checks.
+    fn check_git_repositories(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let unchecked = self.picker.read(cx).delegate.unchecked_directories();
+        self._git_check_task = cx.spawn_in(window, async move |this, cx| {
+            for batch in unchecked.chunks(GIT_CHECK_CONCURRENCY) {
+                let checks = batch
+                    .iter()
+                    .cloned()
+                    .map(|directory| async move {
+                        let git_entry = Path::new(&directory).join(".git");
+                        let is_repository = smol::fs::symlink_metadata(git_entry).await.is_ok();
+                        (directory, is_repository)
+                    })
+                    .collect::<Vec<_>>();
+                let results = cx.background_spawn(futures::future::join_all(checks)).await;
+                let updated = this.update_in(cx, |this, window, cx| {
+                    this.picker.update(cx, |picker, cx| {
+                        let hides_directories = picker.delegate.git_repositories_only
+                            && results.iter().any(|(_, is_repository)| !is_repository);
+                        picker.delegate.git_repositories.extend(results);
+                        // Only directories found not to be repositories change the filtered
+                        // list. The query and the selected directory are kept.
+                        if hides_directories {
+                            picker.delegate.reset_selected_match_index = false;
+                            picker.update_matches(picker.query(cx), window, cx);
+                        } else {
+                            cx.notify();
+                        }
+                    })
+                });
+                if updated.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn refresh(&mut self, _: &RefreshPickerResults, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.is_refreshing = true;
+            cx.notify();
+        });
+        self.load_directories(window, cx);
+    }
+
+    fn toggle_git_repositories_only(
+        &mut self,
+        _: &ToggleGitRepositoriesOnly,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.git_repositories_only = !picker.delegate.git_repositories_only;
+            picker.refresh(window, cx);
+        });
     }
 
     pub fn open(
@@ -1133,6 +1756,8 @@ impl Render for RecentProjectsZoxide {
         v_flex()
             .key_context("RecentProjectsZoxide")
             .w(rems(self.rem_width))
+            .on_action(cx.listener(Self::refresh))
+            .on_action(cx.listener(Self::toggle_git_repositories_only))
             .child(self.picker.clone())
             .on_mouse_down_out(cx.listener(|this, _, window, cx| {
                 this.picker.update(cx, |this, cx| {
@@ -1145,10 +1770,22 @@ impl Render for RecentProjectsZoxide {
 pub struct RecentProjectsZoxideDelegate {
     workspace: WeakEntity<Workspace>,
     directories: Vec<String>,
+    /// Why the directories couldn't be listed, as opposed to zoxide having none.
+    load_error: Option<ZoxideError>,
     selected_match_index: usize,
     matches: Vec<StringMatch>,
     create_new_window: bool,
     reset_selected_match_index: bool,
+    /// Number of directories matching the query, before the list is capped.
+    matched_directory_count: usize,
+    /// Whether each directory is a git repository, for the directories checked so far.
+    /// Kept across refreshes, so only new directories are checked again.
+    git_repositories: HashMap<String, bool>,
+    /// When set, the directories known not to be git repositories are hidden.
+    git_repositories_only: bool,
+    /// Cancels the matching of the previous query once a newer one is typed.
+    search_cancelled: Option<Arc<AtomicBool>>,
+    is_refreshing: bool,
 }
 
 impl RecentProjectsZoxideDelegate {
@@ -1156,27 +1793,90 @@ impl RecentProjectsZoxideDelegate {
         Self {
             workspace,
             directories: Vec::new(),
+            load_error: None,
             selected_match_index: 0,
             matches: Default::default(),
             create_new_window,
             reset_selected_match_index: true,
+            matched_directory_count: 0,
+            git_repositories: HashMap::default(),
+            git_repositories_only: false,
+            search_cancelled: None,
+            is_refreshing: false,
         }
     }
 
-    pub fn set_directories(&mut self, directories: Vec<String>) {
-        self.directories = directories;
-    }
-
-    fn format_path_for_display(&self, path: &str) -> String {
-        if let Some(home_dir) = std::env::var("HOME").ok() {
-            if path.starts_with(&home_dir) {
-                return path.replacen(&home_dir, "~", 1);
+    pub(crate) fn set_directories(&mut self, directories: Result<Vec<String>, ZoxideError>) {
+        match directories {
+            Ok(directories) => {
+                self.directories = directories;
+                self.load_error = None;
+            }
+            Err(error) => {
+                log::error!("failed to list zoxide directories: {error:?}");
+                self.directories = Vec::new();
+                self.load_error = Some(error);
             }
         }
-        path.to_string()
+    }
+
+    /// Whether `directory` is a git repository, or `None` when it wasn't checked yet.
+    fn is_git_repository(&self, directory: &str) -> Option<bool> {
+        self.git_repositories.get(directory).copied()
+    }
+
+    fn unchecked_directories(&self) -> Vec<String> {
+        self.directories
+            .iter()
+            .filter(|directory| self.is_git_repository(directory).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// The directories to match the query against. Filtering to git repositories keeps the
+    /// directories that weren't checked yet, until they turn out not to be ones.
+    fn candidates(&self) -> Vec<StringMatchCandidate> {
+        self.directories
+            .iter()
+            .enumerate()
+            .filter(|(_, directory)| {
+                !self.git_repositories_only || self.is_git_repository(directory) != Some(false)
+            })
+            .map(|(id, directory)| StringMatchCandidate::new(id, directory))
+            .collect()
     }
 }
 
+/// Abbreviates a leading `home_dir` in `path` to `~`, moving the match `positions` (byte
+/// offsets into `path`) along with it. A match inside the home directory highlights the `~`.
+fn abbreviate_home_dir(
+    path: &str,
+    home_dir: Option<&str>,
+    positions: &[usize],
+) -> (String, Vec<usize>) {
+    let home_dir = home_dir
+        .map(|home_dir| home_dir.trim_end_matches('/'))
+        .filter(|home_dir| !home_dir.is_empty());
+    let Some((home_dir, rest)) = home_dir.and_then(|home_dir| {
+        let rest = path.strip_prefix(home_dir)?;
+        (rest.is_empty() || rest.starts_with('/')).then_some((home_dir, rest))
+    }) else {
+        return (path.to_string(), positions.to_vec());
+    };
+
+    let mut display_positions = Vec::with_capacity(positions.len());
+    if positions.iter().any(|&position| position < home_dir.len()) {
+        display_positions.push(0);
+    }
+    display_positions.extend(
+        positions
+            .iter()
+            .filter(|&&position| position >= home_dir.len())
+            .map(|&position| position - home_dir.len() + '~'.len_utf8()),
+    );
+    (format!("~{rest}"), display_positions)
+}
+
 impl EventEmitter<DismissEvent> for RecentProjectsZoxideDelegate {}
 
 impl PickerDelegate for RecentProjectsZoxideDelegate {
@@ -1219,34 +1919,79 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
     fn update_matches(
         &mut self,
         query: String,
-        _: &mut Window,
-        _cx: &mut Context<Picker<Self>>,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
     ) -> gpui::Task<()> {
-        let query = query.trim_start();
+        let query = query.trim_start().to_string();
         let smart_case = query.chars().any(|c| c.is_uppercase());
-        let candidates = self
-            .directories
-            .iter()
-            .enumerate()
-            .map(|(id, path)| StringMatchCandidate::new(id, path))
-            .collect::<Vec<_>>();
-
-        self.matches = smol::block_on(match_strings_order_insensitive(
-            candidates.as_slice(),
-            query,
-            smart_case,
-            100,
-            &Default::default(),
-        ));
-
-        // Don't sort - preserve zoxide's order
-        self.matches.sort_unstable_by_key(|m| m.candidate_id);
-
-        if self.reset_selected_match_index {
-            self.selected_match_index = 0;
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        if let Some(previous_cancel_flag) = self.search_cancelled.replace(cancel_flag.clone()) {
+            previous_cancel_flag.store(true, atomic::Ordering::Relaxed);
         }
-        self.reset_selected_match_index = true;
-        Task::ready(())
+        let candidates = self.candidates();
+
+        let executor = cx.background_executor().clone();
+        let matches = cx.background_spawn({
+            let cancel_flag = cancel_flag.clone();
+            async move {
+                let options = WordMatchOptions {
+                    smart_case,
+                    ..WordMatchOptions::default()
+                };
+                let mut matches = match_strings_order_insensitive(
+                    candidates.as_slice(),
+                    &query,
+                    &options,
+                    &cancel_flag,
+                    executor,
+                )
+                .await;
+                let matched_directory_count = matches.len();
+                if !query.is_empty() {
+                    matches.truncate(100);
+                }
+                // Don't sort - preserve zoxide's order
+                matches.sort_unstable_by_key(|m| m.candidate_id);
+                (matches, matched_directory_count)
+            }
+        });
+        cx.spawn_in(window, async move |picker, cx| {
+            let (matches, matched_directory_count) = matches.await;
+            // A newer query started while this one was matching.
+            if cancel_flag.load(atomic::Ordering::Relaxed) {
+                return;
+            }
+            picker
+                .update(cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    let selected_candidate_id = delegate
+                        .matches
+                        .get(delegate.selected_match_index)
+                        .map(|hit| hit.candidate_id);
+                    delegate.matches = matches;
+                    delegate.matched_directory_count = matched_directory_count;
+                    if delegate.reset_selected_match_index {
+                        delegate.selected_match_index = 0;
+                    } else {
+                        // The selected directory stays selected while others are hidden.
+                        delegate.selected_match_index = selected_candidate_id
+                            .and_then(|candidate_id| {
+                                delegate
+                                    .matches
+                                    .iter()
+                                    .position(|hit| hit.candidate_id == candidate_id)
+                            })
+                            .unwrap_or_else(|| {
+                                delegate
+                                    .selected_match_index
+                                    .min(delegate.matches.len().saturating_sub(1))
+                            });
+                    }
+                    delegate.reset_selected_match_index = true;
+                    cx.notify();
+                })
+                .ok();
+        })
     }
 
     fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
@@ -1258,11 +2003,14 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
             let directory_path = &self.directories[selected_match.candidate_id];
             let path = std::path::PathBuf::from(directory_path);
 
-            // Add to zoxide
-            #[allow(clippy::disallowed_methods)]
-            let _ = std::process::Command::new("zoxide")
-                .args(&["add", directory_path])
-                .output();
+            let zoxide = Zoxide::new(
+                RecentProjectsSettings::get_global(cx)
+                    .zoxide_binary_path
+                    .clone(),
+            );
+            let directory = path.clone();
+            cx.background_spawn(async move { zoxide.add(&directory).log_err() })
+                .detach();
 
             let replace_current_window = if self.create_new_window {
                 !secondary
@@ -1274,26 +2022,7 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
                 .update(cx, |workspace, cx| {
                     let paths = vec![path];
                     if replace_current_window {
-                        cx.spawn_in(window, async move |workspace, cx| {
-                            let continue_replacing = workspace
-                                .update_in(cx, |workspace, window, cx| {
-                                    workspace.prepare_to_close(
-                                        CloseIntent::ReplaceWindow,
-                                        window,
-                                        cx,
-                                    )
-                                })?
-                                .await?;
-                            if continue_replacing {
-                                workspace
-                                    .update_in(cx, |workspace, window, cx| {
-                                        workspace.open_workspace_for_paths(true, paths, window, cx)
-                                    })?
-                                    .await
-                            } else {
-                                Ok(())
-                            }
-                        })
+                        replace_window_with_local_paths(workspace, paths, window, cx)
                     } else {
                         workspace.open_workspace_for_paths(false, paths, window, cx)
                     }
@@ -1306,14 +2035,66 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
     fn dismissed(&mut self, _window: &mut Window, _: &mut Context<Picker<Self>>) {}
 
     fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
-        let text = if self.directories.is_empty() {
-            "No zoxide directories found. Make sure zoxide is installed and has been used.".into()
+        let text = if let Some(error) = &self.load_error {
+            error.message().into()
+        } else if self.directories.is_empty() {
+            "No zoxide directories found. Open a few directories with zoxide first.".into()
         } else {
             "No matches".into()
         };
         Some(text)
     }
 
+    fn render_footer(&self, _: &mut Window, cx: &mut Context<Picker<Self>>) -> Option<AnyElement> {
+        let status = if self.is_refreshing {
+            SharedString::new_static("Refreshing…")
+        } else if self.load_error.is_some() {
+            SharedString::new_static("zoxide unavailable")
+        } else {
+            format!(
+                "{} of {} zoxide directories",
+                self.matched_directory_count,
+                self.directories.len()
+            )
+            .into()
+        };
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_2()
+                .justify_between()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Label::new(status)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Label::new("Opening a directory adds it to zoxide")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            Button::new("git_repositories_only", "Git Repositories Only")
+                                .toggle_state(self.git_repositories_only)
+                                .key_binding(KeyBinding::for_action(&ToggleGitRepositoriesOnly, cx))
+                                .on_click(|_, window, cx| {
+                                    window.dispatch_action(
+                                        ToggleGitRepositoriesOnly.boxed_clone(),
+                                        cx,
+                                    )
+                                }),
+                        ),
+                )
+                .into_any(),
+        )
+    }
+
     fn render_match(
         &self,
         ix: usize,
@@ -1323,47 +2104,17 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
     ) -> Option<Self::ListItem> {
         let hit = self.matches.get(ix)?;
         let directory_path = self.directories.get(hit.candidate_id)?;
-        let display_path = self.format_path_for_display(directory_path);
-
-        // Adjust highlight positions if the path was shortened
-        let adjusted_positions = if display_path != *directory_path {
-            let display_chars: Vec<char> = display_path.chars().collect();
-
-            // Find the offset where the paths start to match
-            let mut offset = 0;
-            if let Some(home_dir) = std::env::var("HOME").ok() {
-                if directory_path.starts_with(&home_dir) && display_path.starts_with('~') {
-                    // The home directory was replaced with ~, so offset is home_dir.len() - 1
-                    offset = home_dir.chars().count().saturating_sub(1);
-                }
-            }
-
-            // Adjust positions and filter out any that are now out of bounds
-            hit.positions
-                .iter()
-                .filter_map(|&pos| {
-                    if pos >= offset {
-                        let adjusted_pos = pos - offset;
-                        if adjusted_pos < display_chars.len() {
-                            Some(adjusted_pos)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        } else {
-            hit.positions.clone()
-        };
+        let home_dir = std::env::var("HOME").ok();
+        let (display_path, highlight_positions) =
+            abbreviate_home_dir(directory_path, home_dir.as_deref(), &hit.positions);
 
         let highlighted_text = HighlightedMatch {
             text: display_path.clone(),
-            highlight_positions: adjusted_positions,
+            highlight_positions,
             color: Color::Default,
         };
 
+        let is_git_repository = self.is_git_repository(directory_path) == Some(true);
         let tooltip_text = display_path;
         Some(
             ListItem::new(ix)
@@ -1375,7 +2126,14 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
                         .flex_grow()
                         .gap_3()
                         .child(Icon::new(IconName::Folder).color(Color::Muted))
-                        .child(highlighted_text.render(window, cx)),
+                        .child(highlighted_text.render(window, cx))
+                        .when(is_git_repository, |this| {
+                            this.child(
+                                Icon::new(IconName::GitBranch)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                        }),
                 )
                 .tooltip(move |_, cx| {
                     cx.new(|_| SimpleTooltip {
@@ -1385,14 +2143,6 @@ impl PickerDelegate for RecentProjectsZoxideDelegate {
                 }),
         )
     }
-
-    fn render_footer(
-        &self,
-        _window: &mut Window,
-        _cx: &mut Context<Picker<Self>>,
-    ) -> Option<AnyElement> {
-        None
-    }
 }
 
 struct SimpleTooltip {
@@ -1419,7 +2169,7 @@ impl Render for MatchTooltip {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
     use editor::Editor;
     use gpui::{TestAppContext, UpdateGlobal, WindowHandle};
@@ -1431,6 +2181,55 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_abbreviate_home_dir_keeps_highlights_aligned() {
+        let home_dir = "/home/jürgen";
+        let path = "/home/jürgen/dev/zed";
+        let zed_start = path.find("zed").unwrap();
+        let (display_path, positions) = abbreviate_home_dir(
+            path,
+            Some(home_dir),
+            &[zed_start, zed_start + 1, zed_start + 2],
+        );
+        assert_eq!(display_path, "~/dev/zed");
+        assert_eq!(positions, [6, 7, 8]);
+        assert!(
+            positions
+                .iter()
+                .all(|&position| display_path.is_char_boundary(position))
+        );
+
+        // A match spanning the abbreviation boundary highlights the `~` and the rest of
+        // the match after it.
+        let ergen_start = path.find("rgen").unwrap();
+        let (display_path, positions) = abbreviate_home_dir(
+            path,
+            Some(home_dir),
+            &[
+                ergen_start,
+                ergen_start + 3,
+                ergen_start + 4,
+                ergen_start + 5,
+            ],
+        );
+        assert_eq!(display_path, "~/dev/zed");
+        assert_eq!(positions, [0, 1, 2]);
+
+        let (display_path, positions) = abbreviate_home_dir(path, Some("/home/jürgen/"), &[]);
+        assert_eq!(display_path, "~/dev/zed");
+        assert!(positions.is_empty());
+
+        // Only whole path components are abbreviated.
+        let (display_path, positions) =
+            abbreviate_home_dir("/home/jürgenx/zed", Some(home_dir), &[14]);
+        assert_eq!(display_path, "/home/jürgenx/zed");
+        assert_eq!(positions, [14]);
+
+        let (display_path, positions) = abbreviate_home_dir(path, None, &[1]);
+        assert_eq!(display_path, path);
+        assert_eq!(positions, [1]);
+    }
+
     #[gpui::test]
     async fn test_prompts_on_dirty_before_submit(cx: &mut TestAppContext) {
         let app_state = init_test(cx);
@@ -1544,6 +2343,537 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_opens_new_window_on_dirty_when_configured(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings
+                        .session
+                        .get_or_insert_default()
+                        .restore_unsaved_buffers = Some(false);
+                    settings.recent_projects.get_or_insert_default().on_dirty =
+                        Some(RecentProjectsOnDirty::NewWindow);
+                });
+            });
+        });
+
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "main.ts": "a" }))
+            .await;
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/other"), json!({ "lib.ts": "b" }))
+            .await;
+        cx.update(|cx| {
+            open_paths(
+                &[PathBuf::from(path!("/dir/main.ts"))],
+                app_state,
+                workspace::OpenOptions::default(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+        let workspace = cx.update(|cx| cx.windows()[0].downcast::<Workspace>().unwrap());
+        let editor = workspace
+            .read_with(cx, |workspace, cx| {
+                workspace
+                    .active_item(cx)
+                    .unwrap()
+                    .downcast::<Editor>()
+                    .unwrap()
+            })
+            .unwrap();
+        workspace
+            .update(cx, |_, window, cx| {
+                editor.update(cx, |editor, cx| editor.insert("EDIT", window, cx));
+            })
+            .unwrap();
+
+        let recent_projects_picker = open_recent_projects(&workspace, cx);
+        workspace
+            .update(cx, |_, _, cx| {
+                recent_projects_picker.update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = vec![StringMatch {
+                        candidate_id: 0,
+                        score: 1.0,
+                        positions: Vec::new(),
+                        string: "fake candidate".to_string(),
+                    }];
+                    delegate.set_workspaces(vec![(
+                        WorkspaceId::default(),
+                        SerializedWorkspaceLocation::Local,
+                        PathList::new(&[path!("/other")]),
+                    )]);
+                });
+            })
+            .unwrap();
+
+        cx.dispatch_action(*workspace, menu::Confirm);
+        cx.run_until_parked();
+        assert!(
+            !cx.has_pending_prompt(),
+            "Should open a new window instead of prompting for the dirty project"
+        );
+        assert_eq!(cx.update(|cx| cx.windows().len()), 2);
+        workspace
+            .update(cx, |workspace, _, _| {
+                assert!(
+                    workspace.is_edited(),
+                    "The dirty project should stay open in its own window"
+                )
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_open_in_terminal_uses_project_root_as_cwd(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "main.ts": "a" }))
+            .await;
+        cx.update(|cx| {
+            open_paths(
+                &[PathBuf::from(path!("/dir/main.ts"))],
+                app_state,
+                workspace::OpenOptions::default(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        let workspace = cx.update(|cx| cx.windows()[0].downcast::<Workspace>().unwrap());
+
+        let terminal_directories = Rc::new(RefCell::new(Vec::new()));
+        workspace
+            .update(cx, |workspace, _, _| {
+                let terminal_directories = terminal_directories.clone();
+                workspace.register_action(move |_, action: &workspace::OpenTerminal, _, _| {
+                    assert!(action.local);
+                    terminal_directories
+                        .borrow_mut()
+                        .push(action.working_directory.clone());
+                });
+            })
+            .unwrap();
+
+        let set_project = |paths: &[&str], cx: &mut TestAppContext| {
+            let recent_projects_picker = open_recent_projects(&workspace, cx);
+            workspace
+                .update(cx, |_, _, cx| {
+                    recent_projects_picker.update(cx, |picker, _| {
+                        let delegate = &mut picker.delegate;
+                        delegate.matches = vec![StringMatch {
+                            candidate_id: 0,
+                            score: 1.0,
+                            positions: Vec::new(),
+                            string: "fake candidate".to_string(),
+                        }];
+                        delegate.set_workspaces(vec![(
+                            WorkspaceId::default(),
+                            SerializedWorkspaceLocation::Local,
+                            PathList::new(paths),
+                        )]);
+                    });
+                })
+                .unwrap();
+        };
+
+        set_project(&[path!("/test/project")], cx);
+        cx.dispatch_action(*workspace, OpenInTerminal);
+        cx.run_until_parked();
+        assert_eq!(
+            *terminal_directories.borrow(),
+            [PathBuf::from(path!("/test/project"))]
+        );
+        workspace
+            .update(cx, |workspace, _, cx| {
+                assert!(workspace.active_modal::<RecentProjects>(cx).is_none());
+            })
+            .unwrap();
+
+        set_project(&[path!("/test/first"), path!("/test/second")], cx);
+        cx.dispatch_action(*workspace, OpenInTerminal);
+        cx.run_until_parked();
+        assert_eq!(
+            terminal_directories.borrow().len(),
+            1,
+            "A multi-root project should ask for the root before opening a terminal"
+        );
+        workspace
+            .update(cx, |workspace, _, cx| {
+                assert!(workspace.active_modal::<RecentProjects>(cx).is_none());
+                assert!(workspace.active_modal::<TerminalRootPicker>(cx).is_some());
+            })
+            .unwrap();
+        cx.dispatch_action(*workspace, menu::Confirm);
+        cx.run_until_parked();
+        assert_eq!(
+            *terminal_directories.borrow(),
+            [
+                PathBuf::from(path!("/test/project")),
+                PathBuf::from(path!("/test/first")),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_confirming_current_project_dismisses_picker(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "main.ts": "a" }))
+            .await;
+        cx.update(|cx| {
+            open_paths(
+                &[PathBuf::from(path!("/dir"))],
+                app_state,
+                workspace::OpenOptions::default(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        let workspace = cx.update(|cx| cx.windows()[0].downcast::<Workspace>().unwrap());
+
+        let recent_projects_picker = open_recent_projects(&workspace, cx);
+        workspace
+            .update(cx, |_, _, cx| {
+                recent_projects_picker.update(cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = vec![StringMatch {
+                        candidate_id: 0,
+                        score: 1.0,
+                        positions: Vec::new(),
+                        string: "fake candidate".to_string(),
+                    }];
+                    delegate.set_workspaces(vec![(
+                        WorkspaceId::default(),
+                        SerializedWorkspaceLocation::Local,
+                        PathList::new(&[path!("/dir")]),
+                    )]);
+                    let (workspace_id, location, paths) = &delegate.workspaces[0];
+                    assert!(
+                        delegate.is_current_project(*workspace_id, location, paths, cx),
+                        "An entry with a stale id but the open roots should be the current project"
+                    );
+                });
+            })
+            .unwrap();
+
+        cx.dispatch_action(*workspace, menu::Confirm);
+        cx.run_until_parked();
+        workspace
+            .update(cx, |workspace, _, cx| {
+                assert!(workspace.active_modal::<RecentProjects>(cx).is_none());
+            })
+            .unwrap();
+        assert!(!cx.has_pending_prompt());
+        assert_eq!(cx.update(|cx| cx.windows().len()), 1);
+    }
+
+    #[test]
+    fn test_rows_grouped_by_parent_dir() {
+        use RecentProjectsRow::{Header, Match};
+
+        let first_roots = [
+            Some(Path::new("/work/org-a/api")),
+            Some(Path::new("/work/org-b/site")),
+            Some(Path::new("/work/org-a/web")),
+            None,
+            Some(Path::new("/work/org-b/docs")),
+        ];
+        assert_eq!(
+            rows_grouped_by_parent_dir(&first_roots),
+            [
+                Header("/work/org-a".into()),
+                Match(0),
+                Match(2),
+                Header("/work/org-b".into()),
+                Match(1),
+                Match(4),
+                Header("Other".into()),
+                Match(3),
+            ]
+        );
+        assert!(rows_grouped_by_parent_dir(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_root_filter_matches_whole_path_components() {
+        let paths = PathList::new(&[path!("/oss/zed"), path!("/work/api")]);
+        assert!(has_root_under(&paths, Path::new(path!("/work"))));
+        assert!(has_root_under(&paths, Path::new(path!("/work/api"))));
+        assert!(!has_root_under(&paths, Path::new(path!("/wor"))));
+        assert!(!has_root_under(&paths, Path::new(path!("/work/api/src"))));
+        assert!(!has_root_under(
+            &PathList::new(&[path!("/workshop/site")]),
+            Path::new(path!("/work"))
+        ));
+    }
+
+    #[gpui::test]
+    async fn test_root_filter_expands_tilde_and_clears_on_backspace(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "main.ts": "a" }))
+            .await;
+        cx.update(|cx| {
+            open_paths(
+                &[PathBuf::from(path!("/dir/main.ts"))],
+                app_state,
+                workspace::OpenOptions::default(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+        let workspace = cx.update(|cx| cx.windows()[0].downcast::<Workspace>().unwrap());
+        cx.dispatch_action(
+            workspace.into(),
+            OpenRecent {
+                create_new_window: false,
+                root_filter: Some("~/work".to_string()),
+            },
+        );
+        cx.run_until_parked();
+
+        let home = util::paths::home_dir();
+        let recent_projects_picker = workspace
+            .update(cx, |workspace, _, cx| {
+                workspace
+                    .active_modal::<RecentProjects>(cx)
+                    .unwrap()
+                    .read(cx)
+                    .picker
+                    .clone()
+            })
+            .unwrap();
+        workspace
+            .update(cx, |_, window, cx| {
+                recent_projects_picker.update(cx, |picker, cx| {
+                    assert_eq!(picker.delegate.root_filter, Some(home.join("work")));
+                    picker.delegate.set_workspaces(vec![
+                        (
+                            WorkspaceId::default(),
+                            SerializedWorkspaceLocation::Local,
+                            PathList::new(&[home.join("oss").join("zed")]),
+                        ),
+                        (
+                            WorkspaceId::default(),
+                            SerializedWorkspaceLocation::Local,
+                            PathList::new(&[home.join("work").join("api")]),
+                        ),
+                    ]);
+                    picker.update_matches(String::new(), window, cx);
+                });
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        let candidate_ids = |cx: &mut TestAppContext| {
+            recent_projects_picker.read_with(cx, |picker, _| {
+                picker
+                    .delegate
+                    .matches
+                    .iter()
+                    .map(|hit| hit.candidate_id)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(candidate_ids(cx), [1]);
+
+        cx.dispatch_action(workspace.into(), editor::actions::Backspace);
+        cx.run_until_parked();
+        recent_projects_picker.read_with(cx, |picker, _| {
+            assert_eq!(picker.delegate.root_filter, None);
+        });
+        let mut all_candidate_ids = candidate_ids(cx);
+        all_candidate_ids.sort_unstable();
+        assert_eq!(all_candidate_ids, [0, 1]);
+        workspace
+            .update(cx, |workspace, _, cx| {
+                assert!(
+                    workspace.active_modal::<RecentProjects>(cx).is_some(),
+                    "Clearing the filter should keep the picker open"
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_git_repositories_only_filter() {
+        let mut delegate = RecentProjectsZoxideDelegate::new(WeakEntity::new_invalid(), false);
+        delegate.set_directories(Ok(vec![
+            "/code/zed".to_string(),
+            "/downloads".to_string(),
+            "/code/unchecked".to_string(),
+        ]));
+        delegate.git_repositories = HashMap::from_iter([
+            ("/code/zed".to_string(), true),
+            ("/downloads".to_string(), false),
+        ]);
+        let candidate_ids = |delegate: &RecentProjectsZoxideDelegate| {
+            delegate
+                .candidates()
+                .iter()
+                .map(|candidate| candidate.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(candidate_ids(&delegate), [0, 1, 2]);
+        assert_eq!(delegate.unchecked_directories(), ["/code/unchecked"]);
+
+        // Directories that weren't checked yet stay until they turn out not to be repositories.
+        delegate.git_repositories_only = true;
+        assert_eq!(candidate_ids(&delegate), [0, 2]);
+        delegate
+            .git_repositories
+            .insert("/code/unchecked".to_string(), false);
+        assert_eq!(candidate_ids(&delegate), [0]);
+        assert!(delegate.unchecked_directories().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_pinned_projects_sort_first(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "main.ts": "a" }))
+            .await;
+        cx.update(|cx| {
+            open_paths(
+                &[PathBuf::from(path!("/dir"))],
+                app_state,
+                workspace::OpenOptions::default(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        let workspace = cx.update(|cx| cx.windows()[0].downcast::<Workspace>().unwrap());
+        let recent_projects_picker = open_recent_projects(&workspace, cx);
+        cx.run_until_parked();
+
+        let pinned_id = WORKSPACE_DB.next_id().await.unwrap();
+        let search = |query: &str, toggle_pinned: bool, cx: &mut TestAppContext| {
+            let query = query.to_string();
+            workspace
+                .update(cx, |_, window, cx| {
+                    recent_projects_picker.update(cx, |picker, cx| {
+                        if toggle_pinned {
+                            picker.delegate.pinned_projects.toggle(pinned_id);
+                        }
+                        picker.update_matches(query, window, cx);
+                    });
+                })
+                .unwrap();
+            cx.run_until_parked();
+            recent_projects_picker.read_with(cx, |picker, _| {
+                picker
+                    .delegate
+                    .matches
+                    .iter()
+                    .map(|hit| hit.candidate_id)
+                    .collect::<Vec<_>>()
+            })
+        };
+        workspace
+            .update(cx, |_, _, cx| {
+                recent_projects_picker.update(cx, |picker, _| {
+                    picker.delegate.set_workspaces(vec![
+                        (
+                            WorkspaceId::default(),
+                            SerializedWorkspaceLocation::Local,
+                            PathList::new(&[path!("/code/zed-app")]),
+                        ),
+                        (
+                            pinned_id,
+                            SerializedWorkspaceLocation::Local,
+                            PathList::new(&[path!("/code/notes/zed-talk")]),
+                        ),
+                    ]);
+                });
+            })
+            .unwrap();
+
+        assert_eq!(search("zed", false, cx), [0, 1]);
+        // The pinned project comes first despite matching worse, but only when it matches.
+        assert_eq!(search("zed", true, cx), [1, 0]);
+        assert_eq!(search("app", false, cx), [0]);
+        // Unpinning takes effect right away.
+        assert_eq!(search("zed", true, cx), [0, 1]);
+    }
+
+    #[gpui::test]
+    async fn test_only_query_results_are_limited(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/dir"), json!({ "main.ts": "a" }))
+            .await;
+        cx.update(|cx| {
+            open_paths(
+                &[PathBuf::from(path!("/dir"))],
+                app_state,
+                workspace::OpenOptions::default(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+        let workspace = cx.update(|cx| cx.windows()[0].downcast::<Workspace>().unwrap());
+        let recent_projects_picker = open_recent_projects(&workspace, cx);
+        cx.run_until_parked();
+
+        let project_count = MAX_MATCHES + 20;
+        let code_dir = PathBuf::from(path!("/code"));
+        let workspaces = (0..project_count)
+            .map(|ix| {
+                (
+                    WorkspaceId::default(),
+                    SerializedWorkspaceLocation::Local,
+                    PathList::new(&[code_dir.join(format!("project-{ix}"))]),
+                )
+            })
+            .collect();
+        workspace
+            .update(cx, |_, _, cx| {
+                recent_projects_picker
+                    .update(cx, |picker, _| picker.delegate.set_workspaces(workspaces));
+            })
+            .unwrap();
+        let match_count = |query: &str, cx: &mut TestAppContext| {
+            let query = query.to_string();
+            workspace
+                .update(cx, |_, window, cx| {
+                    recent_projects_picker
+                        .update(cx, |picker, cx| picker.update_matches(query, window, cx));
+                })
+                .unwrap();
+            cx.run_until_parked();
+            recent_projects_picker.read_with(cx, |picker, _| picker.delegate.matches.len())
+        };
+
+        assert_eq!(match_count("", cx), project_count);
+        assert_eq!(match_count("project", cx), MAX_MATCHES);
+    }
+
     fn open_recent_projects(
         workspace: &WindowHandle<Workspace>,
         cx: &mut TestAppContext,
@@ -1552,6 +2882,7 @@ mod tests {
             (*workspace).into(),
             OpenRecent {
                 create_new_window: false,
+                root_filter: None,
             },
         );
         workspace
@@ -0,0 +1,110 @@
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use util::ResultExt as _;
+use workspace::WorkspaceId;
+
+const NEW_WINDOW_KEY: &str = "recent_project_opens_in_new_window";
+
+fn preference_key(workspace_id: WorkspaceId) -> String {
+    format!("{NEW_WINDOW_KEY}_{}", i64::from(workspace_id))
+}
+
+/// The remembered choice takes the place of `create_new_window`, and `secondary` does the
+/// opposite of either.
+fn opens_in_new_window(remembered: Option<bool>, create_new_window: bool, secondary: bool) -> bool {
+    remembered.unwrap_or(create_new_window) != secondary
+}
+
+/// Whether each recent project was last opened in a new window rather than in place of
+/// the current one, so confirming it again does the same.
+#[derive(Default)]
+pub(crate) struct WindowPreferences {
+    new_window: HashMap<WorkspaceId, bool>,
+}
+
+impl WindowPreferences {
+    /// Reads the remembered choices of the given workspaces.
+    pub(crate) fn load(workspace_ids: impl IntoIterator<Item = WorkspaceId>) -> Self {
+        let new_window = workspace_ids
+            .into_iter()
+            .filter_map(|workspace_id| {
+                let value = KEY_VALUE_STORE
+                    .read_kvp(&preference_key(workspace_id))
+                    .log_err()
+                    .flatten()?;
+                Some((workspace_id, value.parse::<bool>().log_err()?))
+            })
+            .collect();
+        Self { new_window }
+    }
+
+    /// Whether the workspace was last opened in a new window, if it was opened from the
+    /// picker before.
+    pub(crate) fn remembered(&self, workspace_id: WorkspaceId) -> Option<bool> {
+        self.new_window.get(&workspace_id).copied()
+    }
+
+    /// Whether confirming the workspace opens it in a new window.
+    pub(crate) fn opens_in_new_window(
+        &self,
+        workspace_id: WorkspaceId,
+        create_new_window: bool,
+        secondary: bool,
+    ) -> bool {
+        opens_in_new_window(self.remembered(workspace_id), create_new_window, secondary)
+    }
+
+    /// Remembers how the workspace was opened, for as long as the picker is open. Use
+    /// [`save_window_preference`] to keep it for the next time.
+    pub(crate) fn remember(&mut self, workspace_id: WorkspaceId, new_window: bool) {
+        self.new_window.insert(workspace_id, new_window);
+    }
+}
+
+pub(crate) async fn save_window_preference(workspace_id: WorkspaceId, new_window: bool) {
+    KEY_VALUE_STORE
+        .write_kvp(preference_key(workspace_id), new_window.to_string())
+        .await
+        .log_err();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_in_new_window() {
+        // Without a remembered choice, the default applies and the modifier inverts it.
+        assert!(!opens_in_new_window(None, false, false));
+        assert!(opens_in_new_window(None, false, true));
+        assert!(opens_in_new_window(None, true, false));
+        assert!(!opens_in_new_window(None, true, true));
+
+        // A remembered choice replaces the default, and the modifier still inverts it.
+        assert!(opens_in_new_window(Some(true), false, false));
+        assert!(!opens_in_new_window(Some(true), false, true));
+        assert!(!opens_in_new_window(Some(false), true, false));
+        assert!(opens_in_new_window(Some(false), true, true));
+    }
+
+    #[test]
+    fn test_remembered_window_preference() {
+        let workspace_id = WorkspaceId::default();
+        let mut preferences = WindowPreferences::default();
+        assert_eq!(preferences.remembered(workspace_id), None);
+        assert!(!preferences.opens_in_new_window(workspace_id, false, false));
+
+        // Opening it in a new window with the modifier is remembered, so the plain key does
+        // the same next time, while the modifier now reuses the window.
+        let new_window = preferences.opens_in_new_window(workspace_id, false, true);
+        preferences.remember(workspace_id, new_window);
+        assert!(preferences.opens_in_new_window(workspace_id, false, false));
+        assert!(!preferences.opens_in_new_window(workspace_id, false, true));
+
+        // Overriding it with the modifier again updates what's remembered.
+        let new_window = preferences.opens_in_new_window(workspace_id, false, true);
+        preferences.remember(workspace_id, new_window);
+        assert_eq!(preferences.remembered(workspace_id), Some(false));
+        assert!(!preferences.opens_in_new_window(workspace_id, true, false));
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Output,
+    sync::LazyLock,
+};
+
+use collections::HashMap;
+use util::{ResultExt as _, shell::get_system_shell, shell_env};
+
+/// The binary used when `recent_projects.zoxide_binary_path` is not set, looked up on `PATH`.
+const DEFAULT_ZOXIDE_BINARY: &str = "zoxide";
+
+/// The environment of the user's login shell, captured once and reused for every zoxide
+/// invocation. Zed launched from a GUI doesn't inherit the shell's `PATH` or variables such
+/// as `_ZO_DATA_DIR`, so zoxide would otherwise read the wrong database or not be found at all.
+static LOGIN_SHELL_ENVIRONMENT: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    smol::block_on(shell_env::capture(
+        get_system_shell(),
+        &[],
+        util::paths::home_dir(),
+    ))
+    .log_err()
+    .unwrap_or_default()
+});
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ZoxideError {
+    /// The zoxide binary could not be found.
+    NotFound(PathBuf),
+    /// zoxide ran but exited unsuccessfully, or could not be spawned.
+    Failed(String),
+}
+
+impl ZoxideError {
+    pub(crate) fn message(&self) -> String {
+        match self {
+            ZoxideError::NotFound(binary) => format!(
+                "zoxide not found at {}. Install it or set recent_projects.zoxide_binary_path.",
+                binary.display()
+            ),
+            ZoxideError::Failed(error) => format!("zoxide failed: {error}"),
+        }
+    }
+}
+
+/// Runs zoxide using the configured binary and the login shell's environment. Must not be
+/// used on the main thread, as the first invocation captures the environment by spawning a
+/// shell.
+pub(crate) struct Zoxide {
+    binary: PathBuf,
+}
+
+impl Zoxide {
+    pub(crate) fn new(binary_path: Option<PathBuf>) -> Self {
+        Self {
+            binary: binary_path.unwrap_or_else(|| PathBuf::from(DEFAULT_ZOXIDE_BINARY)),
+        }
+    }
+
+    /// Lists the directories in zoxide's database, ordered by zoxide's ranking.
+    pub(crate) fn query_directories(&self) -> Result<Vec<String>, ZoxideError> {
+        self.query_directories_with_env(&LOGIN_SHELL_ENVIRONMENT)
+    }
+
+    fn query_directories_with_env(
+        &self,
+        env: &HashMap<String, String>,
+    ) -> Result<Vec<String>, ZoxideError> {
+        let output = self.run(&["query", "--list"], env)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    /// Adds the directory to zoxide's database, or bumps its rank if it's already there.
+    pub(crate) fn add(&self, directory: &Path) -> Result<(), ZoxideError> {
+        let directory = directory.to_string_lossy();
+        self.run(&["add", &directory], &LOGIN_SHELL_ENVIRONMENT)?;
+        Ok(())
+    }
+
+    fn run(&self, args: &[&str], env: &HashMap<String, String>) -> Result<Output, ZoxideError> {
+        #[allow(clippy::disallowed_methods)]
+        let output = std::process::Command::new(&self.binary)
+            .args(args)
+            .envs(env)
+            .output()
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::NotFound => ZoxideError::NotFound(self.binary.clone()),
+                _ => ZoxideError::Failed(error.to_string()),
+            })?;
+        if output.status.success() {
+            Ok(output)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            Err(ZoxideError::Failed(if stderr.is_empty() {
+                output.status.to_string()
+            } else {
+                stderr
+            }))
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt as _;
+
+    fn fake_zoxide(dir: &Path, script: &str) -> PathBuf {
+        let path = dir.join("zoxide");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn query(binary: PathBuf) -> Result<Vec<String>, ZoxideError> {
+        Zoxide::new(Some(binary)).query_directories_with_env(&HashMap::default())
+    }
+
+    #[test]
+    fn test_zoxide_binary_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_zoxide(
+            dir.path(),
+            "echo /home/user/zed\necho\necho /home/user/notes",
+        );
+        assert_eq!(
+            query(binary).unwrap(),
+            ["/home/user/zed", "/home/user/notes"]
+        );
+    }
+
+    #[test]
+    fn test_zoxide_binary_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("missing-zoxide");
+        assert_eq!(query(binary.clone()), Err(ZoxideError::NotFound(binary)));
+    }
+
+    #[test]
+    fn test_zoxide_non_zero_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_zoxide(dir.path(), "echo 'database is corrupt' >&2\nexit 3");
+        assert_eq!(
+            query(binary),
+            Err(ZoxideError::Failed("database is corrupt".to_string()))
+        );
+
+        let binary = fake_zoxide(dir.path(), "exit 1");
+        assert!(matches!(query(binary), Err(ZoxideError::Failed(_))));
+    }
+
+    #[test]
+    fn test_zoxide_receives_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = fake_zoxide(dir.path(), "echo \"$_ZO_DATA_DIR\"");
+        let env = HashMap::from_iter([("_ZO_DATA_DIR".to_string(), "/tmp/zo".to_string())]);
+        assert_eq!(
+            Zoxide::new(Some(binary)).query_directories_with_env(&env),
+            Ok(vec!["/tmp/zo".to_string()])
+        );
+    }
+}
@@ -1,18 +1,19 @@
 use collections::HashMap;
 use editor::{
-    Anchor as MultiBufferAnchor, Editor, EditorEvent, EditorSettings, HighlightKey, MultiBuffer,
-    MultiBufferOffset, MultiBufferSnapshot, SelectionEffects, ToOffset, scroll::Autoscroll,
+    Anchor as MultiBufferAnchor, DisplayPoint, Editor, EditorEvent, EditorSettings, HighlightKey,
+    MultiBuffer, MultiBufferOffset, MultiBufferSnapshot, SelectionEffects, ToOffset,
+    display_map::DisplayRow, scroll::Autoscroll,
 };
 use gpui::{
-    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Global,
-    HighlightStyle, KeyBinding, KeyContext, Render, SharedString, StyledText, Subscription, Task,
-    UpdateGlobal, WeakEntity, Window, actions,
+    Action, App, ClipboardItem, Context, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, Global, HighlightStyle, KeyBinding, KeyContext, KeyDownEvent, Keystroke, Render,
+    SharedString, StyledText, Subscription, Task, UpdateGlobal, WeakEntity, Window, actions,
 };
 use language::language_settings::SoftWrap;
 use language::{HighlightId, Point, ToOffset as _};
-use picker::{Picker, PickerDelegate};
+use picker::{Direction, Picker, PickerDelegate};
 use project::search::SearchQuery;
-use settings::Settings;
+use settings::{BufferSearchWrapResults, Settings};
 use std::{
     ops::Range,
     sync::{
@@ -21,29 +22,69 @@ use std::{
     },
     time::Duration,
 };
-use text::Bias;
+use text::{Bias, BufferId};
 use ui::{
-    Button, ButtonStyle, Color, CommonAnimationExt, Divider, Icon, IconName,
+    Button, ButtonStyle, Color, CommonAnimationExt, Divider, Icon, IconName, Indicator,
     KeyBinding as UiKeyBinding, Label, ListItem, Tooltip, prelude::*,
 };
 use ui_input::ErasedEditor;
 use util::{ResultExt, paths::PathMatcher};
 use vim_mode_setting::VimModeSetting;
 use workspace::searchable::SearchableItem;
-use workspace::{ModalView, Workspace};
+use workspace::{ModalView, Toast, Workspace, notifications::NotificationId};
 
-use crate::{NextHistoryQuery, PreviousHistoryQuery};
+use crate::{
+    NextHistoryQuery, PreviousHistoryQuery, SearchOption, SearchOptions, SearchSource,
+    ToggleCaseSensitive, ToggleRegex, ToggleWholeWord,
+    buffer_search_pill::{LastBufferSearch, last_search_in, remember_last_search},
+    buffer_search_settings::BufferSearchSettings,
+};
 use project::search_history::{SearchHistory, SearchHistoryCursor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Opens the buffer search modal for the active editor.
+#[derive(PartialEq, Clone, Default, Deserialize, JsonSchema, Action)]
+#[action(namespace = buffer_search_modal)]
+#[serde(deny_unknown_fields)]
+pub struct ToggleBufferSearch {
+    /// Whether to resume the last search confirmed in the editor's buffer, with its
+    /// query and options.
+    #[serde(default)]
+    pub restore: bool,
+}
 
-actions!(buffer_search_modal, [ToggleBufferSearch, ToggleLineMode]);
+actions!(
+    buffer_search_modal,
+    [
+        ToggleLineMode,
+        ExportResultsToBuffer,
+        /// Toggles whether confirming a result copies its line to the clipboard instead of
+        /// moving the cursor there.
+        ToggleCopyOnConfirm,
+        /// Toggles whether runs of whitespace in a literal query match any run of
+        /// whitespace, including line breaks, so pasted code is found regardless of
+        /// its indentation.
+        ToggleIgnoreWhitespace,
+        /// Toggles whether only matches inside comments are shown.
+        ToggleInCommentsOnly,
+        /// Toggles whether only matches inside string literals are shown.
+        ToggleInStringsOnly
+    ]
+);
 
 struct BufferSearchHistory(SearchHistory);
 impl Global for BufferSearchHistory {}
 
 const MAX_PREVIEW_BYTES: usize = 200;
 const PREVIEW_DEBOUNCE_MS: u64 = 50;
+const MAX_EXPORTED_RESULTS: usize = 10_000;
+/// How many matches are turned into anchors between checks for cancellation.
+const ANCHOR_BATCH_SIZE: usize = 4096;
 
 type AnchorRange = Range<MultiBufferAnchor>;
+/// The offset of the selected match, the indices of the matches on its line, and all matches.
+type PreviewData = (usize, Range<usize>, Arc<Vec<AnchorRange>>);
 
 pub fn init(cx: &mut App) {
     cx.set_global(BufferSearchHistory(SearchHistory::new(
@@ -54,6 +95,31 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("ctrl-c", NextHistoryQuery, Some("BufferSearchModal")),
         KeyBinding::new("ctrl-t", PreviousHistoryQuery, Some("BufferSearchModal")),
         KeyBinding::new("ctrl-r", ToggleLineMode, Some("BufferSearchModal")),
+        KeyBinding::new(
+            "ctrl-shift-e",
+            ExportResultsToBuffer,
+            Some("BufferSearchModal"),
+        ),
+        KeyBinding::new(
+            "ctrl-shift-y",
+            ToggleCopyOnConfirm,
+            Some("BufferSearchModal"),
+        ),
+        KeyBinding::new(
+            "alt-shift-w",
+            ToggleIgnoreWhitespace,
+            Some("BufferSearchModal"),
+        ),
+        KeyBinding::new(
+            "alt-shift-c",
+            ToggleInCommentsOnly,
+            Some("BufferSearchModal"),
+        ),
+        KeyBinding::new(
+            "alt-shift-s",
+            ToggleInStringsOnly,
+            Some("BufferSearchModal"),
+        ),
     ]);
     cx.observe_new(BufferSearchModal::register).detach();
 }
@@ -70,6 +136,60 @@ struct LineMatchData {
     primary_match_offset: usize,
     // The range of match indices in all_matches that this line contains
     match_indices: Range<usize>,
+    // Where the match sits relative to what the user was looking at when the modal opened
+    proximity: MatchProximity,
+}
+
+/// Where a result lies relative to the target editor's state when the modal was opened.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum MatchProximity {
+    #[default]
+    Elsewhere,
+    Visible,
+    InSelection,
+}
+
+/// The parts of the target editor the user was looking at when the modal was opened,
+/// as offsets into the multibuffer.
+#[derive(Clone, Debug, Default)]
+struct InitialView {
+    visible_range: Option<Range<usize>>,
+    /// The newest selection, if it wasn't empty.
+    selection: Option<Range<usize>>,
+}
+
+impl InitialView {
+    fn proximity(&self, offset: usize) -> MatchProximity {
+        if self
+            .selection
+            .as_ref()
+            .is_some_and(|selection| selection.contains(&offset))
+        {
+            MatchProximity::InSelection
+        } else if self
+            .visible_range
+            .as_ref()
+            .is_some_and(|visible_range| visible_range.contains(&offset))
+        {
+            MatchProximity::Visible
+        } else {
+            MatchProximity::Elsewhere
+        }
+    }
+}
+
+/// The range of offsets covered by the rows currently on screen in the editor.
+fn visible_offset_range(editor: &mut Editor, cx: &mut Context<Editor>) -> Option<Range<usize>> {
+    let line_count = editor.visible_line_count()?;
+    let top_row = editor.scroll_position(cx).y.max(0.) as u32;
+    let snapshot = editor.display_snapshot(cx);
+    let max_row = snapshot.max_point().row();
+    let start_row = DisplayRow(top_row).min(max_row);
+    let end_row = DisplayRow(top_row + line_count.ceil() as u32).min(max_row);
+    let start = DisplayPoint::new(start_row, 0).to_offset(&snapshot, Bias::Left);
+    let end =
+        DisplayPoint::new(end_row, snapshot.line_len(end_row)).to_offset(&snapshot, Bias::Right);
+    Some(start.0..end.0)
 }
 
 // Helper to find safe char boundaries for highlighting
@@ -91,6 +211,95 @@ fn find_safe_char_boundaries(text: &str, start: usize, end: usize) -> Option<(us
     }
 }
 
+/// The offsets and text of `row`, without its line ending. Buffers normalize `\r\n` when
+/// loaded, but a `\r` left before the newline is dropped as well, so it never shows up in a
+/// preview or shifts the ranges computed from the length of the line.
+fn line_content(snapshot: &MultiBufferSnapshot, row: u32) -> (Range<MultiBufferOffset>, String) {
+    let start = snapshot.point_to_offset(Point::new(row, 0));
+    let end = snapshot.point_to_offset(snapshot.clip_point(Point::new(row, u32::MAX), Bias::Left));
+    let mut text = snapshot.text_for_range(start..end).collect::<String>();
+    if text.ends_with('\r') {
+        text.pop();
+    }
+    (start..start + text.len(), text)
+}
+
+struct MatchPreview {
+    text: SharedString,
+    /// The part of the line shown in the preview.
+    line_range: Range<usize>,
+    /// The length of the `…` the preview starts with, if it doesn't start at the line's text.
+    prefix_len: usize,
+    match_ranges: Vec<Range<usize>>,
+    active_match_index: Option<usize>,
+}
+
+/// The preview of the `active` one of the matches on a line, centered around it. The match
+/// ranges are relative to the start of `line_text`, which excludes the line ending, and the
+/// ones reaching past it are highlighted up to the end of the line.
+fn match_preview(line_text: &str, match_ranges: &[Range<usize>], active: usize) -> MatchPreview {
+    let trim_start = line_text.len() - line_text.trim_start().len();
+    let range = &match_ranges[active];
+    // A match starting at the line ending, like `$`, is previewed at the end of the line.
+    let rel_match_start = range.start.min(line_text.len());
+
+    let (p_start, p_end) = {
+        let match_len = range.end - range.start;
+        let context = (MAX_PREVIEW_BYTES.saturating_sub(match_len)) / 2;
+        let mut start = rel_match_start.saturating_sub(context);
+
+        if start < trim_start {
+            start = trim_start;
+        }
+
+        if rel_match_start < start {
+            start = rel_match_start;
+        }
+
+        let end = (start + MAX_PREVIEW_BYTES).min(line_text.len());
+        (start, end)
+    };
+    let (p_start, p_end) =
+        find_safe_char_boundaries(line_text, p_start, p_end).unwrap_or((p_start, p_end));
+
+    let previewed_text = line_text[p_start..p_end].trim_end();
+    let preview_end = p_start + previewed_text.len();
+    let mut preview_string = String::new();
+    if p_start > trim_start {
+        preview_string.push('…');
+    }
+    preview_string.push_str(previewed_text);
+    if p_end < line_text.trim_end().len() {
+        preview_string.push('…');
+    }
+    let prefix_len = if p_start > trim_start {
+        '…'.len_utf8()
+    } else {
+        0
+    };
+
+    let mut list_match_ranges = Vec::new();
+    let mut active_match_index = None;
+    for (index, other_range) in match_ranges.iter().enumerate() {
+        let start = other_range.start.max(p_start);
+        let end = other_range.end.min(preview_end);
+        if start < end {
+            list_match_ranges.push((start - p_start) + prefix_len..(end - p_start) + prefix_len);
+            if index == active {
+                active_match_index = Some(list_match_ranges.len() - 1);
+            }
+        }
+    }
+
+    MatchPreview {
+        text: preview_string.into(),
+        line_range: p_start..preview_end,
+        prefix_len,
+        match_ranges: list_match_ranges,
+        active_match_index,
+    }
+}
+
 // Truncate preview text with ellipsis
 fn truncate_preview(text: &str, max_bytes: usize) -> SharedString {
     let trimmed = text.trim();
@@ -168,6 +377,61 @@ fn merge_highlights(
     result
 }
 
+struct ExportedResult {
+    path: Option<String>,
+    line_number: u32,
+    line_text: String,
+}
+
+/// Collects the full text of the line containing each match offset, up to `max_results`.
+fn exported_results(
+    snapshot: &MultiBufferSnapshot,
+    match_offsets: &[usize],
+    buffer_paths: &HashMap<BufferId, String>,
+    max_results: usize,
+) -> Vec<ExportedResult> {
+    match_offsets
+        .iter()
+        .take(max_results)
+        .map(|offset| {
+            let row = snapshot.offset_to_point(MultiBufferOffset(*offset)).row;
+            let line_start = Point::new(row, 0);
+            let (_, line_text) = line_content(snapshot, row);
+            match snapshot.point_to_buffer_point(line_start) {
+                Some((buffer, buffer_point, _)) => ExportedResult {
+                    path: buffer_paths.get(&buffer.remote_id()).cloned(),
+                    line_number: buffer_point.row + 1,
+                    line_text,
+                },
+                None => ExportedResult {
+                    path: None,
+                    line_number: row + 1,
+                    line_text,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Formats results as grep-style `path:line: text` lines, noting how many were left out.
+fn format_exported_results(results: &[ExportedResult], total_count: usize) -> String {
+    let mut text = String::new();
+    for result in results {
+        let path = result.path.as_deref().unwrap_or("untitled");
+        text.push_str(&format!(
+            "{path}:{}: {}\n",
+            result.line_number, result.line_text
+        ));
+    }
+    let omitted_count = total_count.saturating_sub(results.len());
+    if omitted_count > 0 {
+        text.push_str(&format!(
+            "… {omitted_count} more results were not exported\n"
+        ));
+    }
+    text
+}
+
 #[inline]
 fn preview_content_len(preview_text: &str) -> usize {
     preview_text
@@ -183,9 +447,20 @@ pub struct BufferSearchDelegate {
     target_editor: Entity<Editor>,
     target_buffer: Entity<MultiBuffer>,
     line_mode: bool,
+    /// Whether confirming copies the line of the selected result rather than moving the
+    /// cursor of the target editor. Secondary confirm always copies.
+    copy_on_confirm: bool,
+    /// Whether whitespace differences are ignored by literal queries.
+    ignore_whitespace: bool,
+    /// Whether matches outside of comments are dropped. Combined with `in_strings_only`,
+    /// matches inside either are kept.
+    in_comments_only: bool,
+    /// Whether matches outside of string literals are dropped.
+    in_strings_only: bool,
     items: Vec<LineMatchData>,
     selected_index: usize,
     initial_cursor_offset: usize,
+    initial_view: InitialView,
     search_cancelled: Option<Arc<AtomicBool>>,
     buffer_search_modal: WeakEntity<BufferSearchModal>,
     match_count: usize,
@@ -195,15 +470,29 @@ pub struct BufferSearchDelegate {
     regex_error: Option<String>,
     all_matches: Arc<Vec<AnchorRange>>,
     search_history_cursor: SearchHistoryCursor,
+    search_options: SearchOptions,
+    /// Options the user toggled during this session, as opposed to ones
+    /// inherited from settings or coupled to another option.
+    explicit_options: SearchOptions,
+    /// Shown while the query is the history entry it was recorded for.
+    history_notice: Option<(String, SharedString)>,
+    /// A query that found nothing literally but has matches as a regex, shown as a
+    /// suggestion to enable regex mode while it's still the current query.
+    regex_suggestion: Option<String>,
+    /// Set when moving the selection wrapped around, until the selection changes again.
+    wrap_notice: Option<SharedString>,
 }
 
 pub struct BufferSearchModal {
+    workspace: WeakEntity<Workspace>,
     picker: Entity<Picker<BufferSearchDelegate>>,
     preview_editor: Option<Entity<Editor>>,
     target_buffer: Entity<MultiBuffer>,
     _picker_subscription: Subscription,
     _preview_editor_subscription: Option<Subscription>,
     _preview_debounce_task: Option<Task<()>>,
+    /// What the preview editor currently shows, so reselecting it doesn't move the preview.
+    last_preview: Option<PreviewData>,
 }
 
 impl ModalView for BufferSearchModal {}
@@ -250,6 +539,7 @@ impl Render for BufferSearchModal {
             .on_click(move |_, window, cx| {
                 window.focus(&picker.focus_handle(cx), cx);
             })
+            .capture_key_down(cx.listener(Self::redirect_typing_to_query))
             .when_some(preview_editor, |this, editor| this.child(editor))
             .when(self.preview_editor.is_none(), |this| {
                 this.child(
@@ -278,8 +568,49 @@ impl Render for BufferSearchModal {
                     .child(results_panel)
                     .child(preview_panel),
             )
+            .capture_action(cx.listener(|this, _: &menu::SelectNext, window, cx| {
+                this.select_adjacent_result(Direction::Down, window, cx);
+                cx.stop_propagation();
+            }))
+            .capture_action(cx.listener(|this, _: &menu::SelectPrevious, window, cx| {
+                this.select_adjacent_result(Direction::Up, window, cx);
+                cx.stop_propagation();
+            }))
             .on_action(cx.listener(Self::next_history_query))
             .on_action(cx.listener(Self::previous_history_query))
+            .on_action(cx.listener(Self::export_results_to_buffer))
+            .on_action(cx.listener(|this, _: &ToggleCopyOnConfirm, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.toggle_copy_on_confirm(window, cx)
+                });
+            }))
+            .on_action(cx.listener(|this, _: &ToggleIgnoreWhitespace, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.ignore_whitespace = !picker.delegate.ignore_whitespace;
+                    picker.refresh(window, cx);
+                });
+            }))
+            .on_action(cx.listener(|this, _: &ToggleInCommentsOnly, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.in_comments_only = !picker.delegate.in_comments_only;
+                    picker.refresh(window, cx);
+                });
+            }))
+            .on_action(cx.listener(|this, _: &ToggleInStringsOnly, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.in_strings_only = !picker.delegate.in_strings_only;
+                    picker.refresh(window, cx);
+                });
+            }))
+            .on_action(cx.listener(|this, _: &ToggleWholeWord, window, cx| {
+                this.toggle_search_option(SearchOptions::WHOLE_WORD, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleCaseSensitive, window, cx| {
+                this.toggle_search_option(SearchOptions::CASE_SENSITIVE, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleRegex, window, cx| {
+                this.toggle_search_option(SearchOptions::REGEX, window, cx);
+            }))
     }
 }
 
@@ -287,38 +618,49 @@ impl BufferSearchModal {
     pub fn toggle_for_editor(
         workspace: &mut Workspace,
         editor: Entity<Editor>,
+        restore: bool,
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) {
-        let (selected_text, buffer, cursor_offset) = editor.update(cx, |editor, cx| {
-            let snapshot = editor.buffer().read(cx).snapshot(cx);
-            let selection = editor.selections.newest_anchor();
-            let range = selection.range();
-            let head = selection.head();
-
-            let selected_text = if range.start.cmp(&range.end, &snapshot).is_ne() {
-                editor.buffer().read(cx).as_singleton().map(|buffer| {
-                    let buffer = buffer.read(cx);
-                    let start = range.start.text_anchor.to_offset(&buffer);
-                    let end = range.end.text_anchor.to_offset(&buffer);
-                    let mut text = buffer.text_for_range(start..end).collect::<String>();
-                    if text.ends_with('\n') {
-                        text.pop();
-                    }
-                    text
-                })
-            } else if !VimModeSetting::get_global(cx).0 {
-                let query = editor.query_suggestion(window, cx);
-                if query.is_empty() { None } else { Some(query) }
-            } else {
-                None
-            };
+        let restored_search = restore
+            .then(|| last_search_in(editor.read(cx).buffer(), cx))
+            .flatten();
+        let (selected_text, buffer, cursor_offset, initial_view) =
+            editor.update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let selection = editor.selections.newest_anchor();
+                let range = selection.range();
+                let head = selection.head();
+
+                let selected_text = if range.start.cmp(&range.end, &snapshot).is_ne() {
+                    editor.buffer().read(cx).as_singleton().map(|buffer| {
+                        let buffer = buffer.read(cx);
+                        let start = range.start.text_anchor.to_offset(&buffer);
+                        let end = range.end.text_anchor.to_offset(&buffer);
+                        let mut text = buffer.text_for_range(start..end).collect::<String>();
+                        if text.ends_with('\n') {
+                            text.pop();
+                        }
+                        text
+                    })
+                } else if !VimModeSetting::get_global(cx).0 {
+                    let query = editor.query_suggestion(window, cx);
+                    if query.is_empty() { None } else { Some(query) }
+                } else {
+                    None
+                };
 
-            let buffer = editor.buffer().clone();
-            let cursor_offset = head.to_offset(&snapshot).0;
+                let buffer = editor.buffer().clone();
+                let cursor_offset = head.to_offset(&snapshot).0;
+                let selected_range =
+                    range.start.to_offset(&snapshot).0..range.end.to_offset(&snapshot).0;
+                let initial_view = InitialView {
+                    visible_range: visible_offset_range(editor, cx),
+                    selection: (!selected_range.is_empty()).then_some(selected_range),
+                };
 
-            (selected_text, buffer, cursor_offset)
-        });
+                (selected_text, buffer, cursor_offset, initial_view)
+            });
 
         let weak_workspace = cx.entity().downgrade();
         workspace.toggle_modal(window, cx, |window, cx| {
@@ -327,7 +669,9 @@ impl BufferSearchModal {
                 editor,
                 buffer,
                 cursor_offset,
+                initial_view,
                 selected_text,
+                restored_search,
                 window,
                 cx,
             )
@@ -340,20 +684,28 @@ impl BufferSearchModal {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let mut cursor = self.picker.read(cx).delegate.search_history_cursor.clone();
+        let (mut cursor, is_valid) = {
+            let delegate = &self.picker.read(cx).delegate;
+            (
+                delegate.search_history_cursor.clone(),
+                delegate.history_query_validator(),
+            )
+        };
 
-        let next_query = BufferSearchHistory::update_global(cx, |history, _| {
-            history.0.next(&mut cursor).map(|s| s.to_string())
+        let (next_query, skipped_count) = BufferSearchHistory::update_global(cx, |history, _| {
+            step_to_valid_history_query(&mut history.0, &mut cursor, is_valid, |history, cursor| {
+                history.next(cursor).map(str::to_string)
+            })
         });
 
         self.picker.update(cx, |picker, cx| {
             picker.delegate.search_history_cursor = cursor;
-            if let Some(query) = next_query {
-                picker.set_query(&query, window, cx);
-            } else {
+            let query = next_query.unwrap_or_else(|| {
                 picker.delegate.search_history_cursor.reset();
-                picker.set_query("", window, cx);
-            }
+                String::new()
+            });
+            picker.delegate.set_history_notice(&query, skipped_count);
+            picker.set_query(&query, window, cx);
         });
     }
 
@@ -363,11 +715,12 @@ impl BufferSearchModal {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let (current_query_empty, cursor_snapshot) = {
+        let (current_query_empty, cursor_snapshot, is_valid) = {
             let picker = self.picker.read(cx);
             (
                 picker.query(cx).is_empty(),
                 picker.delegate.search_history_cursor.clone(),
+                picker.delegate.history_query_validator(),
             )
         };
 
@@ -376,6 +729,7 @@ impl BufferSearchModal {
                 .global::<BufferSearchHistory>()
                 .0
                 .current(&cursor_snapshot)
+                .filter(|query| is_valid(*query))
                 .map(|s| s.to_string())
             {
                 self.picker
@@ -385,24 +739,138 @@ impl BufferSearchModal {
         }
 
         let mut cursor_mut = cursor_snapshot;
-        let prev_query = BufferSearchHistory::update_global(cx, |history, _| {
-            history.0.previous(&mut cursor_mut).map(|s| s.to_string())
+        let (prev_query, skipped_count) = BufferSearchHistory::update_global(cx, |history, _| {
+            step_to_valid_history_query(
+                &mut history.0,
+                &mut cursor_mut,
+                is_valid,
+                |history, cursor| history.previous(cursor).map(str::to_string),
+            )
         });
 
         if let Some(query) = prev_query {
             self.picker.update(cx, |picker, cx| {
                 picker.delegate.search_history_cursor = cursor_mut;
+                picker.delegate.set_history_notice(&query, skipped_count);
                 picker.set_query(&query, window, cx);
             });
         }
     }
 
+    fn select_adjacent_result(
+        &mut self,
+        direction: Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let wrap_results = BufferSearchSettings::get_global(cx).wrap_results;
+        self.picker.update(cx, |picker, cx| {
+            let Some((ix, wrapped)) = step_result_index(
+                picker.delegate.selected_index,
+                picker.delegate.items.len(),
+                &direction,
+                wrap_results,
+            ) else {
+                return;
+            };
+            let wrap_notice = match direction {
+                Direction::Down => "Wrapped to top",
+                Direction::Up => "Wrapped to bottom",
+            };
+            picker.set_selected_index(ix, Some(direction), true, window, cx);
+            if wrapped {
+                picker.delegate.wrap_notice = Some(wrap_notice.into());
+            }
+            cx.notify();
+        });
+    }
+
+    fn export_results_to_buffer(
+        &mut self,
+        _: &ExportResultsToBuffer,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let match_offsets = self
+            .picker
+            .read(cx)
+            .delegate
+            .items
+            .iter()
+            .map(|item| item.primary_match_offset)
+            .collect::<Vec<_>>();
+        let multi_buffer = self.target_buffer.read(cx);
+        let snapshot = multi_buffer.snapshot(cx);
+        let buffer_paths = multi_buffer
+            .all_buffers_iter()
+            .filter_map(|buffer| {
+                let buffer = buffer.read(cx);
+                let path = buffer.file()?.full_path(cx);
+                Some((buffer.remote_id(), path.to_string_lossy().into_owned()))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let format_task = cx.background_spawn(async move {
+            let results = exported_results(
+                &snapshot,
+                &match_offsets,
+                &buffer_paths,
+                MAX_EXPORTED_RESULTS,
+            );
+            format_exported_results(&results, match_offsets.len())
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let text = format_task.await;
+            this.update(cx, |_, cx| cx.emit(DismissEvent)).ok();
+            workspace.update_in(cx, |workspace, window, cx| {
+                let project = workspace.project().clone();
+                let buffer = project.update(cx, |project, cx| {
+                    project.create_local_buffer(&text, None, false, cx)
+                });
+                let editor = cx.new(|cx| Editor::for_buffer(buffer, Some(project), window, cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_search_option(
+        &mut self,
+        option: SearchOptions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let regex_default_case_sensitive =
+            BufferSearchSettings::get_global(cx).regex_default_case_sensitive;
+        self.picker.update(cx, |picker, cx| {
+            let delegate = &mut picker.delegate;
+            if option == SearchOptions::CASE_SENSITIVE {
+                // Toggle what the button shows, which includes the smartcase inference.
+                delegate.search_options = smartcase_search_options(
+                    delegate.search_options,
+                    delegate.explicit_options,
+                    &delegate.current_query,
+                );
+            }
+            toggle_search_option(
+                &mut delegate.search_options,
+                &mut delegate.explicit_options,
+                option,
+                regex_default_case_sensitive,
+            );
+            picker.refresh(window, cx);
+        });
+    }
+
     fn register(
         workspace: &mut Workspace,
         _window: Option<&mut Window>,
         _cx: &mut Context<Workspace>,
     ) {
-        workspace.register_action(|workspace, _: &ToggleBufferSearch, window, cx| {
+        workspace.register_action(|workspace, action: &ToggleBufferSearch, window, cx| {
             let Some(editor) = workspace
                 .active_item(cx)
                 .and_then(|item| item.act_as::<Editor>(cx))
@@ -410,7 +878,7 @@ impl BufferSearchModal {
                 return;
             };
 
-            Self::toggle_for_editor(workspace, editor, window, cx);
+            Self::toggle_for_editor(workspace, editor, action.restore, window, cx);
         });
 
         workspace.register_action(Self::toggle_line_mode);
@@ -434,23 +902,50 @@ impl BufferSearchModal {
     }
 
     fn new(
-        _workspace: WeakEntity<Workspace>,
+        workspace: WeakEntity<Workspace>,
         target_editor: Entity<Editor>,
         target_buffer: Entity<MultiBuffer>,
         initial_cursor_offset: usize,
+        initial_view: InitialView,
         initial_query: Option<String>,
+        restored_search: Option<LastBufferSearch>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         let weak_self = cx.entity().downgrade();
+        let settings = BufferSearchSettings::get_global(cx);
+        let in_comments_only = settings.in_comments_only;
+        let in_strings_only = settings.in_strings_only;
+        // A resumed search keeps its options, as if they were toggled in this session.
+        let (initial_query, line_mode, search_options, explicit_options) = match restored_search {
+            Some(search) => (
+                Some(search.query),
+                search.line_mode,
+                search.options,
+                search.options,
+            ),
+            // A new search starts with the options from the `search` settings, which match
+            // case-insensitively by default.
+            None => (
+                initial_query,
+                true,
+                SearchOptions::from_settings(&EditorSettings::get_global(cx).search),
+                SearchOptions::NONE,
+            ),
+        };
 
         let delegate = BufferSearchDelegate {
             target_editor,
             target_buffer: target_buffer.clone(),
-            line_mode: true,
+            line_mode,
+            copy_on_confirm: false,
+            ignore_whitespace: false,
+            in_comments_only,
+            in_strings_only,
             items: Vec::new(),
             selected_index: 0,
             initial_cursor_offset,
+            initial_view,
             search_cancelled: None,
             buffer_search_modal: weak_self,
             match_count: 0,
@@ -460,6 +955,11 @@ impl BufferSearchModal {
             regex_error: None,
             all_matches: Arc::new(Vec::new()),
             search_history_cursor: SearchHistoryCursor::default(),
+            search_options,
+            explicit_options,
+            history_notice: None,
+            regex_suggestion: None,
+            wrap_notice: None,
         };
 
         let picker = cx.new(|cx| {
@@ -477,12 +977,14 @@ impl BufferSearchModal {
         let picker_subscription = cx.subscribe_in(&picker, window, Self::on_picker_event);
 
         Self {
+            workspace,
             picker,
             preview_editor: None,
             target_buffer,
             _picker_subscription: picker_subscription,
             _preview_editor_subscription: None,
             _preview_debounce_task: None,
+            last_preview: None,
         }
     }
 
@@ -498,16 +1000,58 @@ impl BufferSearchModal {
 
     fn on_preview_editor_event(
         &mut self,
-        _editor: &Entity<Editor>,
+        editor: &Entity<Editor>,
         event: &EditorEvent,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) {
-        if !matches!(event, EditorEvent::Edited { .. }) {
-            return;
+        if let EditorEvent::SelectionsChanged { local: true } = event
+            && BufferSearchSettings::get_global(cx).sync_preview_selection
+            && editor.focus_handle(cx).is_focused(window)
+        {
+            self.select_result_near_preview_cursor(editor, window, cx);
         }
     }
 
+    /// Selects the result nearest to the preview editor's cursor, without moving the cursor.
+    fn select_result_near_preview_cursor(
+        &mut self,
+        editor: &Entity<Editor>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let all_matches = self.picker.read(cx).delegate.all_matches.clone();
+        let editor = editor.read(cx);
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let cursor_offset = editor
+            .selections
+            .newest_anchor()
+            .head()
+            .to_offset(&snapshot)
+            .0;
+        let match_offsets = match_offsets(&all_matches, &snapshot);
+        let Some(match_index) = nearest_match_index(&match_offsets, cursor_offset) else {
+            return;
+        };
+        let picker = self.picker.clone();
+        // Selecting an item updates the preview through this modal, so it can't happen while
+        // this modal is being updated.
+        window.defer(cx, move |window, cx| {
+            picker.update(cx, |picker, cx| {
+                let Some(ix) = picker
+                    .delegate
+                    .items
+                    .iter()
+                    .position(|item| item.match_indices.contains(&match_index))
+                else {
+                    return;
+                };
+                picker.set_selected_index(ix, None, true, window, cx);
+                cx.notify();
+            });
+        });
+    }
+
     fn navigate_and_highlight_matches(
         editor: &mut Editor,
         match_offset: usize,
@@ -517,12 +1061,27 @@ impl BufferSearchModal {
         cx: &mut Context<Editor>,
     ) {
         let buffer_snapshot = editor.buffer().read(cx).snapshot(cx);
-        let offset = MultiBufferOffset(match_offset);
-        let anchor = buffer_snapshot.anchor_before(offset);
-        editor.change_selections(Default::default(), window, cx, |s| {
-            s.select_anchor_ranges([anchor..anchor])
-        });
-        editor.request_autoscroll(Autoscroll::center(), cx);
+        // When the cursor already belongs to the selected result, as after clicking near it in
+        // the preview with `buffer_search.sync_preview_selection`, leave it where it is.
+        let cursor_offset = editor
+            .selections
+            .newest_anchor()
+            .head()
+            .to_offset(&buffer_snapshot)
+            .0;
+        let cursor_on_active_match = nearest_match_index(
+            &match_offsets(match_ranges, &buffer_snapshot),
+            cursor_offset,
+        )
+        .is_some_and(|index| active_match_indices.contains(&index));
+        if !cursor_on_active_match {
+            let offset = MultiBufferOffset(match_offset);
+            let anchor = buffer_snapshot.anchor_before(offset);
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.select_anchor_ranges([anchor..anchor])
+            });
+            editor.request_autoscroll(Autoscroll::center(), cx);
+        }
 
         editor.highlight_background(
             HighlightKey::BufferSearchHighlights,
@@ -540,10 +1099,25 @@ impl BufferSearchModal {
 
     fn schedule_preview_update(
         &mut self,
-        data: Option<(usize, Range<usize>, Arc<Vec<AnchorRange>>)>,
+        data: Option<PreviewData>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if let Some(next) = &data {
+            if is_same_preview(self.last_preview.as_ref(), next) {
+                self._preview_debounce_task = None;
+                return;
+            }
+            // Stepping between the matches of the previewed line is applied right away. The
+            // debounce is for sweeps across many lines, where most previews would be wasted.
+            if self.preview_editor.is_some() && is_same_line_group(self.last_preview.as_ref(), next)
+            {
+                self._preview_debounce_task = None;
+                self.update_preview(data, window, cx);
+                return;
+            }
+        }
+
         self._preview_debounce_task = Some(cx.spawn_in(window, async move |this, cx| {
             cx.background_executor()
                 .timer(Duration::from_millis(PREVIEW_DEBOUNCE_MS))
@@ -556,12 +1130,52 @@ impl BufferSearchModal {
         }));
     }
 
+    /// Sends what's typed while the preview has focus to the query instead, so a new search
+    /// can be typed right after selecting text in the preview. Other keys, like the arrows
+    /// and page keys, keep going to the preview.
+    fn redirect_typing_to_query(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(preview_editor) = &self.preview_editor else {
+            return;
+        };
+        // Vim mode moves around the preview with printable keys.
+        if !preview_editor.focus_handle(cx).is_focused(window) || VimModeSetting::get_global(cx).0 {
+            return;
+        }
+        // A composition can't move between editors, so it restarts in the query editor.
+        if event.keystroke.is_ime_in_progress() {
+            self.picker.focus_handle(cx).focus(window, cx);
+            return;
+        }
+        let Some(text) = typed_text(&event.keystroke) else {
+            return;
+        };
+        let text = text.to_string();
+        cx.stop_propagation();
+        self.picker.update(cx, |picker, cx| {
+            let query = picker.query(cx) + &text;
+            picker.set_query(&query, window, cx);
+            picker.focus(window, cx);
+        });
+    }
+
     fn update_preview(
         &mut self,
-        data: Option<(usize, Range<usize>, Arc<Vec<AnchorRange>>)>,
+        data: Option<PreviewData>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.preview_editor.is_some()
+            && let Some(next) = &data
+            && is_same_preview(self.last_preview.as_ref(), next)
+        {
+            return;
+        }
+        self.last_preview = data.clone();
         let Some((match_offset, active_indices, match_ranges)) = data else {
             self.preview_editor = None;
             self._preview_editor_subscription = None;
@@ -634,25 +1248,422 @@ impl BufferSearchModal {
     }
 }
 
-fn build_search_query(query: &str) -> Result<SearchQuery, String> {
-    SearchQuery::text(
-        query,
-        false, // whole_word: always false
-        true,  // case_sensitive: always true
-        false, // include_ignored: always false
-        PathMatcher::default(),
-        PathMatcher::default(),
-        false,
-        None,
-    )
+/// The text `keystroke` types, unless it's a shortcut or a key that doesn't type anything,
+/// like the arrows, enter or tab. Alt only counts as typing when it turns the key into
+/// another character, like option-e typing an accent on macOS.
+fn typed_text(keystroke: &Keystroke) -> Option<&str> {
+    let modifiers = &keystroke.modifiers;
+    if modifiers.control || modifiers.platform || modifiers.function {
+        return None;
+    }
+    let text = keystroke
+        .key_char
+        .as_deref()
+        .filter(|text| !text.is_empty() && !text.chars().any(char::is_control))?;
+    if modifiers.alt && text.to_lowercase() == keystroke.key.to_lowercase() {
+        return None;
+    }
+    Some(text)
+}
+
+/// Whether `next` selects the match that `last` already previews, among the same matches.
+fn is_same_preview(last: Option<&PreviewData>, next: &PreviewData) -> bool {
+    last.is_some_and(|last| last.0 == next.0 && is_same_line_group(Some(last), next))
+}
+
+/// Whether `next` selects a match on the line that `last` previews, among the same matches.
+fn is_same_line_group(last: Option<&PreviewData>, next: &PreviewData) -> bool {
+    last.is_some_and(|last| last.1 == next.1 && Arc::ptr_eq(&last.2, &next.2))
+}
+
+/// Steps through the history with `step` until reaching a query accepted by `is_valid`,
+/// returning it along with the number of entries skipped on the way. Each entry is
+/// visited at most once, so a history full of invalid entries can't loop forever.
+fn step_to_valid_history_query(
+    history: &mut SearchHistory,
+    cursor: &mut SearchHistoryCursor,
+    is_valid: impl Fn(&str) -> bool,
+    step: impl Fn(&mut SearchHistory, &mut SearchHistoryCursor) -> Option<String>,
+) -> (Option<String>, usize) {
+    let mut skipped_count = 0;
+    for _ in 0..history.len() {
+        let Some(query) = step(history, cursor) else {
+            break;
+        };
+        if is_valid(&query) {
+            return (Some(query), skipped_count);
+        }
+        skipped_count += 1;
+    }
+    (None, skipped_count)
+}
+
+/// Toggles `option` and records it as explicitly set. When
+/// `regex_default_case_sensitive` is enabled, turning regex on also turns on
+/// case sensitivity, unless the user already toggled case sensitivity themselves.
+/// This only happens at the moment regex is enabled, so case sensitivity can
+/// still be switched off afterwards.
+fn toggle_search_option(
+    options: &mut SearchOptions,
+    explicit_options: &mut SearchOptions,
+    option: SearchOptions,
+    regex_default_case_sensitive: bool,
+) {
+    options.toggle(option);
+    explicit_options.insert(option);
+
+    if option == SearchOptions::REGEX
+        && options.contains(SearchOptions::REGEX)
+        && regex_default_case_sensitive
+        && !explicit_options.contains(SearchOptions::CASE_SENSITIVE)
+    {
+        // Counts as choosing case sensitivity, so that smartcase keeps it for lowercase
+        // queries.
+        options.insert(SearchOptions::CASE_SENSITIVE);
+        explicit_options.insert(SearchOptions::CASE_SENSITIVE);
+    }
+}
+
+/// Returns whether `query` contains an uppercase letter. In regex queries, escaped
+/// characters such as `\S` or `\W` are classes rather than text and are ignored.
+fn query_has_uppercase(query: &str, is_regex: bool) -> bool {
+    let mut escaped = false;
+    for character in query.chars() {
+        if escaped {
+            escaped = false;
+        } else if is_regex && character == '\\' {
+            escaped = true;
+        } else if character.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Applies smartcase: the search is case sensitive exactly when the query has an
+/// uppercase letter, unless case sensitivity was explicitly chosen during this session.
+fn smartcase_search_options(
+    options: SearchOptions,
+    explicit_options: SearchOptions,
+    query: &str,
+) -> SearchOptions {
+    let mut options = options;
+    if !explicit_options.contains(SearchOptions::CASE_SENSITIVE) {
+        options.set(
+            SearchOptions::CASE_SENSITIVE,
+            query_has_uppercase(query, options.contains(SearchOptions::REGEX)),
+        );
+    }
+    options
+}
+
+/// Whether `query` contains unescaped regex metacharacters or escaped character
+/// classes and compiles as a regex, so that it's worth suggesting regex mode.
+fn looks_like_regex(query: &str) -> bool {
+    let mut escaped = false;
+    let mut has_regex_syntax = false;
+    for character in query.chars() {
+        if escaped {
+            escaped = false;
+            if matches!(character, 'd' | 'D' | 'w' | 'W' | 's' | 'S' | 'b' | 'B') {
+                has_regex_syntax = true;
+            }
+        } else if character == '\\' {
+            escaped = true;
+        } else if matches!(
+            character,
+            '.' | '*' | '+' | '?' | '[' | ']' | '(' | ')' | '{' | '}' | '|' | '^' | '$'
+        ) {
+            has_regex_syntax = true;
+        }
+    }
+    has_regex_syntax && build_search_query(query, SearchOptions::REGEX).is_ok()
+}
+
+/// Whether `query` matches anywhere in `buffer_snapshot`.
+async fn has_any_match(buffer_snapshot: &MultiBufferSnapshot, query: &SearchQuery) -> bool {
+    let full_range = buffer_snapshot.anchor_before(MultiBufferOffset(0))
+        ..buffer_snapshot.anchor_after(buffer_snapshot.len());
+    for (search_buffer, search_range, _, _) in
+        buffer_snapshot.range_to_buffer_ranges_with_deleted_hunks(full_range)
+    {
+        let matches = query
+            .search(
+                search_buffer,
+                Some(search_range.start.0..search_range.end.0),
+            )
+            .await;
+        if !matches.is_empty() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Searches the whole multibuffer for `query`, returning the anchor ranges of the matches in
+/// buffer order, or `None` when cancelled along the way.
+async fn search_match_ranges(
+    buffer_snapshot: &MultiBufferSnapshot,
+    query: &SearchQuery,
+    cancelled: &AtomicBool,
+) -> Option<Vec<AnchorRange>> {
+    let mut ranges = Vec::new();
+
+    // Search the entire multi-buffer range
+    let full_range = buffer_snapshot.anchor_before(MultiBufferOffset(0))
+        ..buffer_snapshot.anchor_after(buffer_snapshot.len());
+
+    // Break down multi-buffer into individual buffer ranges, following Editor::find_matches pattern
+    for (search_buffer, search_range, excerpt_id, deleted_hunk_anchor) in
+        buffer_snapshot.range_to_buffer_ranges_with_deleted_hunks(full_range)
+    {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let buffer_matches = query
+            .search(
+                search_buffer,
+                Some(search_range.start.0..search_range.end.0),
+            )
+            .await;
+
+        // Convert buffer-relative matches to multi-buffer anchor ranges
+        extend_in_batches(&mut ranges, buffer_matches, cancelled, |match_range| {
+            let start = search_buffer.anchor_after(search_range.start + match_range.start);
+            let end = search_buffer.anchor_before(search_range.start + match_range.end);
+            if let Some(deleted_hunk_anchor) = deleted_hunk_anchor {
+                deleted_hunk_anchor.with_diff_base_anchor(start)
+                    ..deleted_hunk_anchor.with_diff_base_anchor(end)
+            } else {
+                MultiBufferAnchor::range_in_buffer(excerpt_id, start..end)
+            }
+        })?;
+    }
+    Some(ranges)
+}
+
+/// Extends `target` with `items` mapped by `f`, checking `cancelled` before every batch of
+/// [`ANCHOR_BATCH_SIZE`] items so that huge match sets can be abandoned promptly. Returns
+/// `None` when cancelled.
+fn extend_in_batches<T, U>(
+    target: &mut Vec<U>,
+    items: impl IntoIterator<Item = T>,
+    cancelled: &AtomicBool,
+    mut f: impl FnMut(T) -> U,
+) -> Option<()> {
+    for (index, item) in items.into_iter().enumerate() {
+        if index % ANCHOR_BATCH_SIZE == 0 && cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        target.push(f(item));
+    }
+    Some(())
+}
+
+/// Keeps the matches that start inside a comment (with `in_comments`) or a string literal
+/// (with `in_strings`), as marked by the language's overrides query.
+fn filter_matches_by_scope(
+    match_ranges: Vec<AnchorRange>,
+    snapshot: &MultiBufferSnapshot,
+    in_comments: bool,
+    in_strings: bool,
+) -> Vec<AnchorRange> {
+    match_ranges
+        .into_iter()
+        .filter(|range| {
+            let scope = snapshot.language_scope_at(range.start);
+            match scope.as_ref().and_then(|scope| scope.override_name()) {
+                Some("comment") => in_comments,
+                Some("string") => in_strings,
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+fn match_offsets(
+    match_ranges: &[AnchorRange],
+    snapshot: &MultiBufferSnapshot,
+) -> Vec<Range<usize>> {
+    match_ranges
+        .iter()
+        .map(|range| range.start.to_offset(snapshot).0..range.end.to_offset(snapshot).0)
+        .collect()
+}
+
+/// The index of the match containing `offset`, or otherwise the one closest to it.
+/// `match_ranges` must be sorted, as search results are.
+fn nearest_match_index(match_ranges: &[Range<usize>], offset: usize) -> Option<usize> {
+    let distance = |index: usize| {
+        let range = &match_ranges[index];
+        range.start.saturating_sub(offset) + offset.saturating_sub(range.end)
+    };
+    let next = match_ranges.partition_point(|range| range.end < offset);
+    let previous = next.checked_sub(1);
+    let next = (next < match_ranges.len()).then_some(next);
+    match (previous, next) {
+        (Some(previous), Some(next)) if distance(previous) < distance(next) => Some(previous),
+        (_, Some(next)) => Some(next),
+        (previous, None) => previous,
+    }
+}
+
+/// Where moving the selection from `index` lands and whether that wrapped around to
+/// the other end, or `None` when the selection stays where it is.
+fn step_result_index(
+    index: usize,
+    count: usize,
+    direction: &Direction,
+    wrap_results: BufferSearchWrapResults,
+) -> Option<(usize, bool)> {
+    let last = count.checked_sub(1)?;
+    match direction {
+        Direction::Down if index < last => return Some((index + 1, false)),
+        Direction::Up if index > 0 => return Some(((index - 1).min(last), false)),
+        _ => {}
+    }
+    if last == 0 {
+        return None;
+    }
+    match wrap_results {
+        BufferSearchWrapResults::WrapWithIndicator => match direction {
+            Direction::Down => Some((0, true)),
+            Direction::Up => Some((last, true)),
+        },
+        BufferSearchWrapResults::StopAtEnds => None,
+    }
+}
+
+fn build_search_query(query: &str, options: SearchOptions) -> Result<SearchQuery, String> {
+    let whole_word = options.contains(SearchOptions::WHOLE_WORD);
+    let case_sensitive = options.contains(SearchOptions::CASE_SENSITIVE);
+    if options.contains(SearchOptions::REGEX) {
+        SearchQuery::regex(
+            query,
+            whole_word,
+            case_sensitive,
+            false,
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            false,
+            None,
+        )
+    } else {
+        SearchQuery::text(
+            query,
+            whole_word,
+            case_sensitive,
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            false,
+            None,
+        )
+    }
     .map_err(|e| e.to_string())
 }
 
+/// Builds the query the modal searches for. With `ignore_whitespace`, a literal query is
+/// searched for as a regex in which each run of whitespace matches any run of whitespace.
+fn build_modal_search_query(
+    query: &str,
+    options: SearchOptions,
+    ignore_whitespace: bool,
+) -> Result<SearchQuery, String> {
+    if ignore_whitespace && !options.contains(SearchOptions::REGEX) {
+        build_search_query(
+            &whitespace_insensitive_pattern(query),
+            options | SearchOptions::REGEX,
+        )
+    } else {
+        build_search_query(query, options)
+    }
+}
+
+/// Translates a literal query into a regex that matches it with any whitespace between its
+/// tokens. Leading and trailing whitespace is dropped, so that indentation doesn't matter.
+/// The separator spells out `\n` so that [`SearchQuery`] searches across lines.
+fn whitespace_insensitive_pattern(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(r"[\s\n]+")
+}
+
 impl BufferSearchDelegate {
+    /// Whether a history entry can be searched for with the current options.
+    fn history_query_validator(&self) -> impl Fn(&str) -> bool + use<> {
+        let line_mode = self.line_mode;
+        let search_options = self.search_options;
+        let ignore_whitespace = self.ignore_whitespace;
+        move |query| {
+            line_mode || build_modal_search_query(query, search_options, ignore_whitespace).is_ok()
+        }
+    }
+
+    fn set_history_notice(&mut self, query: &str, skipped_count: usize) {
+        self.history_notice = (skipped_count > 0).then(|| {
+            let entries = if skipped_count == 1 {
+                "entry"
+            } else {
+                "entries"
+            };
+            let notice = if query.is_empty() {
+                format!(
+                    "Reached the end of the history, skipping {skipped_count} {entries} invalid with the current options"
+                )
+            } else {
+                format!("Skipped {skipped_count} history {entries} invalid with the current options")
+            };
+            (query.to_string(), notice.into())
+        });
+    }
+
+    fn toggle_copy_on_confirm(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.copy_on_confirm = !self.copy_on_confirm;
+        cx.defer_in(window, |picker, window, cx| {
+            picker.refresh_placeholder(window, cx)
+        });
+        cx.notify();
+    }
+
+    /// Copies the full line of the selected result to the clipboard, leaving the cursor of
+    /// the target editor where it is.
+    fn copy_selected_line(&self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(item) = self.items.get(self.selected_index) else {
+            return;
+        };
+        let snapshot = self.target_buffer.read(cx).snapshot(cx);
+        let offsets = [item.primary_match_offset];
+        let Some(line) = exported_results(&snapshot, &offsets, &HashMap::default(), 1).pop() else {
+            return;
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(line.line_text));
+
+        let Some(workspace) = window.root::<Workspace>().flatten() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            struct MatchedLineCopied;
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<MatchedLineCopied>(),
+                    format!("Copied line {}", line.line_number),
+                )
+                .autohide(),
+                cx,
+            );
+        });
+    }
+
     fn spawn_line_search(
         &self,
         query: String,
         initial_cursor: usize,
+        initial_view: InitialView,
         buffer_snapshot: MultiBufferSnapshot,
         cancelled: Arc<AtomicBool>,
         window: &mut Window,
@@ -677,20 +1688,12 @@ impl BufferSearchDelegate {
 
                     for line in 0..line_count {
                         if cancelled_clone.load(Ordering::Relaxed) {
-                            return (new_items, all_match_ranges);
+                            return (new_items, Arc::default());
                         }
 
-                        let line_start_offset =
-                            buffer_snapshot.point_to_offset(Point::new(line, 0));
-                        let line_end_offset = if line < buffer_snapshot.max_point().row {
-                            buffer_snapshot.point_to_offset(Point::new(line + 1, 0))
-                        } else {
-                            buffer_snapshot.len()
-                        };
-
-                        let line_text: String = buffer_snapshot
-                            .text_for_range(line_start_offset..line_end_offset)
-                            .collect();
+                        let (line_range, line_text) = line_content(&buffer_snapshot, line);
+                        let line_start_offset = line_range.start;
+                        let line_end_offset = line_range.end;
 
                         let mut line_match_ranges = Vec::new();
                         let mut matches = true;
@@ -797,20 +1800,22 @@ impl BufferSearchDelegate {
                                 (line + 1).to_string().into()
                             };
 
+                            let primary_match_offset = (line_start_offset
+                                + line_match_ranges.first().map(|r| r.start).unwrap_or(0))
+                            .0;
                             new_items.push(LineMatchData {
                                 line_label,
                                 preview_text,
                                 list_match_ranges: Arc::new(list_match_ranges),
                                 active_match_index_in_list: None,
                                 syntax_highlights,
-                                primary_match_offset: (line_start_offset
-                                    + line_match_ranges.first().map(|r| r.start).unwrap_or(0))
-                                .0,
+                                primary_match_offset,
                                 match_indices: start_match_index..end_match_index,
+                                proximity: initial_view.proximity(primary_match_offset),
                             });
                         }
                     }
-                    (new_items, all_match_ranges)
+                    (new_items, Arc::new(all_match_ranges))
                 })
                 .await;
 
@@ -818,8 +1823,6 @@ impl BufferSearchDelegate {
                 return;
             }
 
-            let all_match_ranges = Arc::new(all_match_ranges);
-
             picker
                 .update(cx, |picker, cx| {
                     picker.delegate.match_count = new_items.len();
@@ -931,6 +1934,11 @@ impl BufferSearchDelegate {
         } else {
             Color::Muted
         };
+        let proximity_color = match item.proximity {
+            MatchProximity::InSelection => Some(Color::Accent),
+            MatchProximity::Visible => Some(Color::Muted),
+            MatchProximity::Elsewhere => None,
+        };
 
         ListItem::new(ix).inset(true).toggle_state(selected).child(
             h_flex()
@@ -949,9 +1957,16 @@ impl BufferSearchDelegate {
                         .child(StyledText::new(preview_text).with_highlights(highlights)),
                 )
                 .child(
-                    Label::new(line_label.clone())
-                        .size(ui::LabelSize::Small)
-                        .color(label_color),
+                    h_flex()
+                        .gap_1()
+                        .when_some(proximity_color, |this, color| {
+                            this.child(Indicator::dot().color(color))
+                        })
+                        .child(
+                            Label::new(line_label.clone())
+                                .size(ui::LabelSize::Small)
+                                .color(label_color),
+                        ),
                 ),
         )
     }
@@ -975,6 +1990,7 @@ impl PickerDelegate for BufferSearchDelegate {
         _cx: &mut Context<Picker<Self>>,
     ) {
         self.selected_index = ix;
+        self.wrap_notice = None;
     }
 
     fn selected_index_changed(
@@ -1006,7 +2022,11 @@ impl PickerDelegate for BufferSearchDelegate {
     }
 
     fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
-        "Search in buffer...".into()
+        if self.copy_on_confirm {
+            "Search in buffer, confirm copies the line...".into()
+        } else {
+            "Search in buffer...".into()
+        }
     }
 
     fn render_editor(
@@ -1054,6 +2074,83 @@ impl PickerDelegate for BufferSearchDelegate {
                                             .color(Color::Muted),
                                         )
                                     })
+                                    .when_some(self.wrap_notice.clone(), |this, notice| {
+                                        this.child(
+                                            Label::new(notice)
+                                                .size(LabelSize::Small)
+                                                .color(Color::Warning),
+                                        )
+                                    })
+                                    .when_some(
+                                        self.focus_handle.clone().filter(|_| !self.line_mode),
+                                        |this, focus_handle| {
+                                            this.children(
+                                                [
+                                                    SearchOption::WholeWord,
+                                                    SearchOption::CaseSensitive,
+                                                    SearchOption::Regex,
+                                                ]
+                                                .map(|option| {
+                                                    option.as_button(
+                                                        smartcase_search_options(
+                                                            self.search_options,
+                                                            self.explicit_options,
+                                                            &self.current_query,
+                                                        ),
+                                                        SearchSource::Buffer,
+                                                        focus_handle.clone(),
+                                                    )
+                                                }),
+                                            )
+                                        },
+                                    )
+                                    .when(!self.line_mode, |this| {
+                                        this.child(
+                                            IconButton::new("ignore-whitespace", IconName::Space)
+                                                .icon_size(IconSize::Small)
+                                                .style(ButtonStyle::Subtle)
+                                                .toggle_state(self.ignore_whitespace)
+                                                .on_click(cx.listener(|picker, _, window, cx| {
+                                                    picker.delegate.ignore_whitespace =
+                                                        !picker.delegate.ignore_whitespace;
+                                                    picker.refresh(window, cx);
+                                                }))
+                                                .tooltip(Tooltip::for_action_title(
+                                                    "Ignore Whitespace Differences",
+                                                    &ToggleIgnoreWhitespace,
+                                                )),
+                                        )
+                                        .child(
+                                            IconButton::new("in-comments-only", IconName::Hash)
+                                                .icon_size(IconSize::Small)
+                                                .style(ButtonStyle::Subtle)
+                                                .toggle_state(self.in_comments_only)
+                                                .on_click(cx.listener(|picker, _, window, cx| {
+                                                    picker.delegate.in_comments_only =
+                                                        !picker.delegate.in_comments_only;
+                                                    picker.refresh(window, cx);
+                                                }))
+                                                .tooltip(Tooltip::for_action_title(
+                                                    "Only Matches in Comments",
+                                                    &ToggleInCommentsOnly,
+                                                )),
+                                        )
+                                        .child(
+                                            IconButton::new("in-strings-only", IconName::Quote)
+                                                .icon_size(IconSize::Small)
+                                                .style(ButtonStyle::Subtle)
+                                                .toggle_state(self.in_strings_only)
+                                                .on_click(cx.listener(|picker, _, window, cx| {
+                                                    picker.delegate.in_strings_only =
+                                                        !picker.delegate.in_strings_only;
+                                                    picker.refresh(window, cx);
+                                                }))
+                                                .tooltip(Tooltip::for_action_title(
+                                                    "Only Matches in Strings",
+                                                    &ToggleInStringsOnly,
+                                                )),
+                                        )
+                                    })
                                     .child(
                                         Button::new("line-mode", "")
                                             .icon(IconName::ListFilter)
@@ -1077,6 +2174,30 @@ impl PickerDelegate for BufferSearchDelegate {
                                             .tooltip(|window, cx| {
                                                 Tooltip::text("Toggle Line Mode")(window, cx)
                                             }),
+                                    )
+                                    .child(
+                                        Button::new("copy-on-confirm", "")
+                                            .icon(IconName::Copy)
+                                            .icon_size(IconSize::Small)
+                                            .style(ButtonStyle::Subtle)
+                                            .toggle_state(self.copy_on_confirm)
+                                            .when_some(
+                                                self.focus_handle.as_ref(),
+                                                |button, focus_handle| {
+                                                    button.key_binding(
+                                                        UiKeyBinding::for_action_in(
+                                                            &ToggleCopyOnConfirm,
+                                                            focus_handle,
+                                                            cx,
+                                                        )
+                                                        .map(|kb| kb.size(rems_from_px(12.))),
+                                                    )
+                                                },
+                                            )
+                                            .on_click(cx.listener(|picker, _, window, cx| {
+                                                picker.delegate.toggle_copy_on_confirm(window, cx);
+                                            }))
+                                            .tooltip(Tooltip::text("Copy Matched Line on Confirm")),
                                     ),
                             ),
                     ),
@@ -1091,6 +2212,8 @@ impl PickerDelegate for BufferSearchDelegate {
         cx: &mut Context<Picker<Self>>,
     ) -> Task<()> {
         self.current_query = query.clone();
+        self.regex_suggestion = None;
+        self.wrap_notice = None;
         let window_handle = window.window_handle();
 
         if let Some(prev_cancelled) = self.search_cancelled.take() {
@@ -1100,7 +2223,13 @@ impl PickerDelegate for BufferSearchDelegate {
         self.search_cancelled = Some(cancelled.clone());
 
         let initial_cursor = self.initial_cursor_offset;
+        let initial_view = self.initial_view.clone();
         let buffer_snapshot = self.target_buffer.read(cx).snapshot(cx);
+        let search_options =
+            smartcase_search_options(self.search_options, self.explicit_options, &query);
+        let ignore_whitespace = self.ignore_whitespace;
+        let in_comments_only = self.in_comments_only;
+        let in_strings_only = self.in_strings_only;
 
         self.is_searching = true;
 
@@ -1108,6 +2237,7 @@ impl PickerDelegate for BufferSearchDelegate {
             return self.spawn_line_search(
                 query,
                 initial_cursor,
+                initial_view,
                 buffer_snapshot,
                 cancelled,
                 window,
@@ -1136,17 +2266,10 @@ impl PickerDelegate for BufferSearchDelegate {
                                 return new_items;
                             }
 
-                            let line_start =
-                                buffer_snapshot_clone.point_to_offset(Point::new(line, 0));
-                            let line_end = if line < buffer_snapshot_clone.max_point().row {
-                                buffer_snapshot_clone.point_to_offset(Point::new(line + 1, 0))
-                            } else {
-                                buffer_snapshot_clone.len()
-                            };
-
-                            let line_text: String = buffer_snapshot_clone
-                                .text_for_range(line_start..line_end)
-                                .collect();
+                            let (line_range, line_text) =
+                                line_content(&buffer_snapshot_clone, line);
+                            let line_start = line_range.start;
+                            let line_end = line_range.end;
 
                             let preview_text = truncate_preview(&line_text, MAX_PREVIEW_BYTES);
                             let preview_len = preview_content_len(&preview_text);
@@ -1229,6 +2352,7 @@ impl PickerDelegate for BufferSearchDelegate {
                                 syntax_highlights,
                                 primary_match_offset: line_start.0,
                                 match_indices: 0..0,
+                                proximity: initial_view.proximity(line_start.0),
                             });
                         }
                         new_items
@@ -1288,225 +2412,134 @@ impl PickerDelegate for BufferSearchDelegate {
                 return;
             }
 
-            let search_query = match build_search_query(&query) {
-                Ok(q) => {
-                    picker
-                        .update(cx, |picker, cx| {
-                            picker.delegate.regex_error = None;
-                            cx.notify();
-                        })
-                        .log_err();
-                    q
-                }
-                Err(error_message) => {
-                    picker
-                        .update(cx, |picker, cx| {
-                            picker.delegate.regex_error = Some(error_message);
-                            picker.delegate.items.clear();
-                            picker.delegate.match_count = 0;
-                            picker.delegate.is_searching = false;
-                            cx.notify();
-                        })
-                        .log_err();
-                    return;
-                }
-            };
+            let search_query =
+                match build_modal_search_query(&query, search_options, ignore_whitespace) {
+                    Ok(q) => {
+                        picker
+                            .update(cx, |picker, cx| {
+                                picker.delegate.regex_error = None;
+                                cx.notify();
+                            })
+                            .log_err();
+                        q
+                    }
+                    Err(error_message) => {
+                        picker
+                            .update(cx, |picker, cx| {
+                                picker.delegate.regex_error = Some(error_message);
+                                picker.delegate.items.clear();
+                                picker.delegate.match_count = 0;
+                                picker.delegate.is_searching = false;
+                                cx.notify();
+                            })
+                            .log_err();
+                        return;
+                    }
+                };
 
             let cancelled_clone = cancelled.clone();
+            let regex_suggestion_snapshot = (!search_options.contains(SearchOptions::REGEX)
+                && !ignore_whitespace
+                && looks_like_regex(&query))
+            .then(|| buffer_snapshot.clone());
 
             let (all_match_ranges, new_items) = cx
                 .background_executor()
                 .spawn(async move {
-                    let mut ranges = Vec::new();
-
-                    // Search the entire multi-buffer range
-                    let full_range = buffer_snapshot.anchor_before(MultiBufferOffset(0))
-                        ..buffer_snapshot.anchor_after(buffer_snapshot.len());
-
-                    // Break down multi-buffer into individual buffer ranges, following Editor::find_matches pattern
-                    for (search_buffer, search_range, excerpt_id, deleted_hunk_anchor) in
-                        buffer_snapshot.range_to_buffer_ranges_with_deleted_hunks(full_range)
-                    {
-                        // Perform search on this buffer segment
-                        let buffer_matches = search_query
-                            .search(
-                                search_buffer,
-                                Some(search_range.start.0..search_range.end.0),
-                            )
-                            .await;
-
-                        // Convert buffer-relative matches to multi-buffer anchor ranges
-                        ranges.extend(buffer_matches.into_iter().map(|match_range| {
-                            if let Some(deleted_hunk_anchor) = deleted_hunk_anchor {
-                                let start = search_buffer
-                                    .anchor_after(search_range.start + match_range.start);
-                                let end = search_buffer
-                                    .anchor_before(search_range.start + match_range.end);
-                                deleted_hunk_anchor.with_diff_base_anchor(start)
-                                    ..deleted_hunk_anchor.with_diff_base_anchor(end)
-                            } else {
-                                let start = search_buffer
-                                    .anchor_after(search_range.start + match_range.start);
-                                let end = search_buffer
-                                    .anchor_before(search_range.start + match_range.end);
-                                MultiBufferAnchor::range_in_buffer(excerpt_id, start..end)
-                            }
-                        }));
-                    }
-
-                    if cancelled_clone.load(Ordering::Relaxed) {
-                        return (Vec::new(), Vec::new());
-                    }
+                    let Some(ranges) =
+                        search_match_ranges(&buffer_snapshot, &search_query, &cancelled_clone)
+                            .await
+                    else {
+                        return (Arc::default(), Vec::new());
+                    };
+                    let ranges = if in_comments_only || in_strings_only {
+                        filter_matches_by_scope(
+                            ranges,
+                            &buffer_snapshot,
+                            in_comments_only,
+                            in_strings_only,
+                        )
+                    } else {
+                        ranges
+                    };
 
-                    // Group matches by line to compute preview text once per line
-                    let mut lines_data: HashMap<u32, Vec<Range<MultiBufferOffset>>> =
-                        HashMap::default();
-                    for range in ranges.iter() {
-                        let start_offset = range.start.to_offset(&buffer_snapshot);
-                        let start_point = buffer_snapshot.offset_to_point(start_offset);
-                        let end_offset = range.end.to_offset(&buffer_snapshot);
-                        lines_data
-                            .entry(start_point.row)
-                            .or_default()
-                            .push(start_offset..end_offset);
+                    // Matches are in buffer order, so the ones on a line are adjacent. Group them
+                    // by their indices into the offsets, rather than copying them per line.
+                    let offsets = match_offsets(&ranges, &buffer_snapshot);
+                    let mut line_groups: Vec<(u32, Range<usize>)> = Vec::new();
+                    for (index, offset_range) in offsets.iter().enumerate() {
+                        let row = buffer_snapshot
+                            .offset_to_point(MultiBufferOffset(offset_range.start))
+                            .row;
+                        match line_groups.last_mut() {
+                            Some((last_row, group)) if *last_row == row => group.end = index + 1,
+                            _ => line_groups.push((row, index..index + 1)),
+                        }
                     }
 
                     let mut new_items: Vec<LineMatchData> = Vec::with_capacity(ranges.len());
-                    let mut sorted_lines: Vec<u32> = lines_data.keys().cloned().collect();
-                    sorted_lines.sort();
-
-                    let mut match_indices_counter = 0;
 
-                    for line in sorted_lines {
+                    for (line, group) in line_groups {
                         if cancelled_clone.load(Ordering::Relaxed) {
-                            return (Vec::new(), Vec::new());
+                            return (Arc::default(), Vec::new());
                         }
 
-                        if let Some(ranges) = lines_data.remove(&line) {
-                            let line_start = buffer_snapshot.point_to_offset(Point::new(line, 0));
-                            let line_end = if line < buffer_snapshot.max_point().row {
-                                buffer_snapshot.point_to_offset(Point::new(line + 1, 0))
+                        let ranges = &offsets[group.clone()];
+                        let (line_range, line_text) = line_content(&buffer_snapshot, line);
+                        let line_start = line_range.start;
+                        let line_match_ranges = ranges
+                            .iter()
+                            .map(|range| {
+                                range.start.saturating_sub(line_start.0)
+                                    ..range.end.saturating_sub(line_start.0)
+                            })
+                            .collect::<Vec<_>>();
+
+                        // Create an item for each match with its own preview text centered around the match
+                        for (i, range) in ranges.iter().enumerate() {
+                            let preview = match_preview(&line_text, &line_match_ranges, i);
+
+                            let mut item_syntax = Vec::new();
+                            let mut current_rel_offset = preview.prefix_len;
+                            for chunk in buffer_snapshot.chunks(
+                                line_start + preview.line_range.start
+                                    ..line_start + preview.line_range.end,
+                                true,
+                            ) {
+                                let len = chunk.text.len();
+                                if let Some(id) = chunk.syntax_highlight_id {
+                                    item_syntax
+                                        .push((current_rel_offset..current_rel_offset + len, id));
+                                }
+                                current_rel_offset += len;
+                            }
+                            let syntax_highlights = if item_syntax.is_empty() {
+                                None
                             } else {
-                                buffer_snapshot.len()
+                                Some(Arc::new(item_syntax))
                             };
-                            let line_text: String = buffer_snapshot
-                                .text_for_range(line_start..line_end)
-                                .collect();
-
-                            let trim_start = line_text.len() - line_text.trim_start().len();
 
-                            // Create an item for each match with its own preview text centered around the match
-                            for (i, range) in ranges.iter().enumerate() {
-                                let rel_match_start = range.start.0.saturating_sub(line_start.0);
-
-                                let (p_start, p_end) = {
-                                    let match_len = range.end.0 - range.start.0;
-                                    let context = (MAX_PREVIEW_BYTES.saturating_sub(match_len)) / 2;
-                                    let mut start = rel_match_start.saturating_sub(context);
-
-                                    if start < trim_start {
-                                        start = trim_start;
-                                    }
-
-                                    if rel_match_start < start {
-                                        start = rel_match_start;
-                                    }
-
-                                    let end = (start + MAX_PREVIEW_BYTES).min(line_text.len());
-                                    (start, end)
-                                };
-                                let (p_start, p_end) =
-                                    find_safe_char_boundaries(&line_text, p_start, p_end)
-                                        .unwrap_or((p_start, p_end));
-
-                                let mut preview_string = String::new();
-                                if p_start > trim_start {
-                                    preview_string.push('…');
-                                }
-                                preview_string.push_str(line_text[p_start..p_end].trim_end());
-                                if p_end < line_text.trim_end().len() {
-                                    preview_string.push('…');
-                                }
-                                let preview_text: SharedString = preview_string.into();
-                                let prefix_len = if p_start > trim_start {
-                                    '…'.len_utf8()
-                                } else {
-                                    0
-                                };
-
-                                let mut list_match_ranges = Vec::new();
-                                let mut active_match_index_in_list = None;
-
-                                for (j, other_range) in ranges.iter().enumerate() {
-                                    let other_rel_start =
-                                        other_range.start.0.saturating_sub(line_start.0);
-                                    let other_rel_end = other_range
-                                        .end
-                                        .0
-                                        .saturating_sub(line_start.0)
-                                        .min(line_text.len());
-
-                                    let start = other_rel_start.max(p_start);
-                                    let end = other_rel_end.min(p_end);
-                                    if start < end {
-                                        let rel_start = (start - p_start) + prefix_len;
-                                        let rel_end = (end - p_start) + prefix_len;
-                                        list_match_ranges.push(rel_start..rel_end);
-                                        if i == j {
-                                            active_match_index_in_list =
-                                                Some(list_match_ranges.len() - 1);
-                                        }
-                                    }
-                                }
-
-                                let mut item_syntax = Vec::new();
-                                let chunk_offset_start = line_start.0 + p_start;
-                                let chunk_offset_end = line_start.0 + p_end;
-                                let mut current_rel_offset = prefix_len;
-
-                                for chunk in buffer_snapshot.chunks(
-                                    MultiBufferOffset(chunk_offset_start)
-                                        ..MultiBufferOffset(chunk_offset_end),
-                                    true,
-                                ) {
-                                    let len = chunk.text.len();
-                                    if let Some(id) = chunk.syntax_highlight_id {
-                                        item_syntax.push((
-                                            current_rel_offset..current_rel_offset + len,
-                                            id,
-                                        ));
-                                    }
-                                    current_rel_offset += len;
-                                }
-                                let syntax_highlights = if item_syntax.is_empty() {
-                                    None
-                                } else {
-                                    Some(Arc::new(item_syntax))
-                                };
+                            let line_label = if let Some((_, buffer_point, _)) =
+                                buffer_snapshot.point_to_buffer_point(Point::new(line, 0))
+                            {
+                                (buffer_point.row + 1).to_string().into()
+                            } else {
+                                (line + 1).to_string().into()
+                            };
 
-                                let line_label = if let Some((_, buffer_point, _)) =
-                                    buffer_snapshot.point_to_buffer_point(Point::new(line, 0))
-                                {
-                                    (buffer_point.row + 1).to_string().into()
-                                } else {
-                                    (line + 1).to_string().into()
-                                };
-
-                                new_items.push(LineMatchData {
-                                    line_label,
-                                    preview_text,
-                                    list_match_ranges: Arc::new(list_match_ranges),
-                                    active_match_index_in_list,
-                                    syntax_highlights,
-                                    primary_match_offset: range.start.0,
-                                    match_indices: match_indices_counter
-                                        ..(match_indices_counter + 1),
-                                });
-                                match_indices_counter += 1;
-                            }
+                            new_items.push(LineMatchData {
+                                line_label,
+                                preview_text: preview.text,
+                                list_match_ranges: Arc::new(preview.match_ranges),
+                                active_match_index_in_list: preview.active_match_index,
+                                syntax_highlights,
+                                primary_match_offset: range.start,
+                                match_indices: group.start + i..group.start + i + 1,
+                                proximity: initial_view.proximity(range.start),
+                            });
                         }
                     }
-                    (ranges, new_items)
+                    (Arc::new(ranges), new_items)
                 })
                 .await;
 
@@ -1514,7 +2547,18 @@ impl PickerDelegate for BufferSearchDelegate {
                 return;
             }
 
-            let all_match_ranges = Arc::new(all_match_ranges);
+            let mut regex_suggestion = None;
+            if all_match_ranges.is_empty()
+                && let Some(buffer_snapshot) = regex_suggestion_snapshot
+                && let Ok(regex_query) =
+                    build_search_query(&query, search_options | SearchOptions::REGEX)
+            {
+                let has_regex_matches = cx
+                    .background_executor()
+                    .spawn(async move { has_any_match(&buffer_snapshot, &regex_query).await })
+                    .await;
+                regex_suggestion = has_regex_matches.then(|| query.clone());
+            }
 
             picker
                 .update(cx, |picker, cx| {
@@ -1526,6 +2570,7 @@ impl PickerDelegate for BufferSearchDelegate {
                     picker.delegate.items = new_items;
                     picker.delegate.is_searching = false;
                     picker.delegate.all_matches = all_match_ranges.clone();
+                    picker.delegate.regex_suggestion = regex_suggestion;
 
                     // Find closest match to initial cursor
                     // For search results, we look at primary_match_offset (absolute offset in buffer)
@@ -1568,13 +2613,29 @@ impl PickerDelegate for BufferSearchDelegate {
         })
     }
 
-    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
         let query = self.current_query.clone();
         if !query.is_empty() {
+            if BufferSearchSettings::get_global(cx).search_pill
+                && let Some(buffer) = self.target_buffer.read(cx).as_singleton()
+            {
+                let search = LastBufferSearch {
+                    query: query.clone(),
+                    options: self.search_options,
+                    line_mode: self.line_mode,
+                    match_count: self.match_count,
+                };
+                remember_last_search(&buffer, search, cx);
+            }
             BufferSearchHistory::update_global(cx, |history, _| {
                 history.0.add(&mut self.search_history_cursor, query);
             });
         }
+        if self.copy_on_confirm || secondary {
+            self.copy_selected_line(window, cx);
+            cx.emit(DismissEvent);
+            return;
+        }
         if let Some(item) = self.items.get(self.selected_index) {
             let target_editor = self.target_editor.clone();
             let match_offset = item.primary_match_offset;
@@ -1662,7 +2723,7 @@ impl PickerDelegate for BufferSearchDelegate {
     fn render_header(
         &self,
         _window: &mut Window,
-        _cx: &mut Context<Picker<Self>>,
+        cx: &mut Context<Picker<Self>>,
     ) -> Option<AnyElement> {
         if let Some(error) = &self.regex_error {
             return Some(
@@ -1678,6 +2739,785 @@ impl PickerDelegate for BufferSearchDelegate {
                     .into_any(),
             );
         }
+        if let Some((query, notice)) = &self.history_notice
+            && *query == self.current_query
+        {
+            return Some(
+                h_flex()
+                    .w_full()
+                    .px_3()
+                    .py_1()
+                    .child(
+                        Label::new(notice.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any(),
+            );
+        }
+        if let Some(query) = &self.regex_suggestion
+            && *query == self.current_query
+            && !self.search_options.contains(SearchOptions::REGEX)
+        {
+            return Some(
+                h_flex()
+                    .w_full()
+                    .px_3()
+                    .py_1()
+                    .gap_2()
+                    .child(
+                        Label::new("Looks like a regex — enable?")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Button::new("enable-regex", "Enable Regex")
+                            .label_size(LabelSize::Small)
+                            .style(ButtonStyle::Subtle)
+                            .when_some(self.focus_handle.as_ref(), |button, focus_handle| {
+                                button.key_binding(
+                                    UiKeyBinding::for_action_in(&ToggleRegex, focus_handle, cx)
+                                        .map(|kb| kb.size(rems_from_px(12.))),
+                                )
+                            })
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(ToggleRegex.boxed_clone(), cx)
+                            }),
+                    )
+                    .into_any(),
+            );
+        }
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabling_regex_couples_case_sensitivity() {
+        let mut options = SearchOptions::NONE;
+        let mut explicit_options = SearchOptions::NONE;
+
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::REGEX,
+            true,
+        );
+        assert_eq!(
+            options,
+            SearchOptions::REGEX | SearchOptions::CASE_SENSITIVE
+        );
+
+        // The coupling is not a lock: case sensitivity can be turned off again.
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::CASE_SENSITIVE,
+            true,
+        );
+        assert_eq!(options, SearchOptions::REGEX);
+
+        // Re-enabling regex respects the explicit choice.
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::REGEX,
+            true,
+        );
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::REGEX,
+            true,
+        );
+        assert_eq!(options, SearchOptions::REGEX);
+    }
+
+    fn history_of(queries: &[&str]) -> SearchHistory {
+        let mut history = SearchHistory::new(
+            None,
+            project::search_history::QueryInsertionBehavior::AlwaysInsert,
+        );
+        let mut cursor = SearchHistoryCursor::default();
+        for query in queries {
+            history.add(&mut cursor, query.to_string());
+        }
+        history
+    }
+
+    fn previous_valid(
+        history: &mut SearchHistory,
+        cursor: &mut SearchHistoryCursor,
+        options: SearchOptions,
+    ) -> (Option<String>, usize) {
+        step_to_valid_history_query(
+            history,
+            cursor,
+            |query| build_search_query(query, options).is_ok(),
+            |history, cursor| history.previous(cursor).map(str::to_string),
+        )
+    }
+
+    fn next_valid(
+        history: &mut SearchHistory,
+        cursor: &mut SearchHistoryCursor,
+        options: SearchOptions,
+    ) -> (Option<String>, usize) {
+        step_to_valid_history_query(
+            history,
+            cursor,
+            |query| build_search_query(query, options).is_ok(),
+            |history, cursor| history.next(cursor).map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn test_history_navigation_skips_invalid_regex_entries() {
+        let mut history = history_of(&["first", "foo(", "ba.r", "bar("]);
+        let mut cursor = SearchHistoryCursor::default();
+
+        assert_eq!(
+            previous_valid(&mut history, &mut cursor, SearchOptions::REGEX),
+            (Some("ba.r".to_string()), 1)
+        );
+        assert_eq!(
+            previous_valid(&mut history, &mut cursor, SearchOptions::REGEX),
+            (Some("first".to_string()), 1)
+        );
+        assert_eq!(
+            previous_valid(&mut history, &mut cursor, SearchOptions::REGEX),
+            (None, 0)
+        );
+
+        assert_eq!(
+            next_valid(&mut history, &mut cursor, SearchOptions::REGEX),
+            (Some("ba.r".to_string()), 1)
+        );
+        assert_eq!(
+            next_valid(&mut history, &mut cursor, SearchOptions::REGEX),
+            (None, 1)
+        );
+    }
+
+    #[test]
+    fn test_history_navigation_keeps_literal_entries_without_regex() {
+        let mut history = history_of(&["first", "foo(", "ba.r", "bar("]);
+        let mut cursor = SearchHistoryCursor::default();
+
+        for expected in ["bar(", "ba.r", "foo(", "first"] {
+            assert_eq!(
+                previous_valid(&mut history, &mut cursor, SearchOptions::NONE),
+                (Some(expected.to_string()), 0)
+            );
+        }
+        assert_eq!(
+            next_valid(&mut history, &mut cursor, SearchOptions::NONE),
+            (Some("foo(".to_string()), 0)
+        );
+    }
+
+    #[test]
+    fn test_history_navigation_with_only_invalid_entries_terminates() {
+        let mut history = history_of(&["foo(", "bar["]);
+        let mut cursor = SearchHistoryCursor::default();
+
+        assert_eq!(
+            previous_valid(&mut history, &mut cursor, SearchOptions::REGEX),
+            (None, 2)
+        );
+    }
+
+    #[test]
+    fn test_format_exported_results() {
+        let results = vec![
+            ExportedResult {
+                path: Some("src/main.rs".to_string()),
+                line_number: 3,
+                line_text: "    let café = \"naïve 日本語\";".to_string(),
+            },
+            ExportedResult {
+                path: None,
+                line_number: 1,
+                line_text: "🦀 crab".to_string(),
+            },
+        ];
+        assert_eq!(
+            format_exported_results(&results, results.len()),
+            "src/main.rs:3:     let café = \"naïve 日本語\";\nuntitled:1: 🦀 crab\n"
+        );
+    }
+
+    #[test]
+    fn test_format_exported_results_notes_truncation() {
+        let results = vec![ExportedResult {
+            path: Some("a.txt".to_string()),
+            line_number: 10,
+            line_text: "match".to_string(),
+        }];
+        assert_eq!(
+            format_exported_results(&results, 3),
+            "a.txt:10: match\n… 2 more results were not exported\n"
+        );
+        assert_eq!(format_exported_results(&[], 0), "");
+    }
+
+    #[gpui::test]
+    async fn test_search_match_ranges_for_large_match_sets(cx: &mut gpui::TestAppContext) {
+        let text = "foo\n".repeat(25_000);
+        let multi_buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+        let snapshot = cx.update(|cx| multi_buffer.read(cx).snapshot(cx));
+        let query = build_search_query("o", SearchOptions::NONE).unwrap();
+
+        let ranges = search_match_ranges(&snapshot, &query, &AtomicBool::new(false))
+            .await
+            .unwrap();
+        assert_eq!(ranges.len(), 50_000);
+        let offsets = match_offsets(&ranges, &snapshot);
+        for (index, range) in offsets.iter().enumerate() {
+            let start = 4 * (index / 2) + 1 + index % 2;
+            assert_eq!(*range, start..start + 1);
+        }
+
+        assert_eq!(
+            search_match_ranges(&snapshot, &query, &AtomicBool::new(true)).await,
+            None
+        );
+    }
+
+    #[gpui::test]
+    async fn test_ignore_whitespace_matches_across_indentation_and_line_breaks(
+        cx: &mut gpui::TestAppContext,
+    ) {
+        let text = "fn main() {\n\tif foo {\n\t\tbar(1,\n\t\t    2)\n\t}\n}\n";
+        let multi_buffer = cx.update(|cx| MultiBuffer::build_simple(text, cx));
+        let snapshot = cx.update(|cx| multi_buffer.read(cx).snapshot(cx));
+        let matched_text = async |query: &str, ignore_whitespace: bool| {
+            let query =
+                build_modal_search_query(query, SearchOptions::NONE, ignore_whitespace).unwrap();
+            let ranges = search_match_ranges(&snapshot, &query, &AtomicBool::new(false))
+                .await
+                .unwrap();
+            match_offsets(&ranges, &snapshot)
+                .into_iter()
+                .map(|range| text[range].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Tabs in the buffer, spaces in the pasted query.
+        assert!(matched_text("  if foo {\n    bar(", false).await.is_empty());
+        assert_eq!(
+            matched_text("  if foo {\n    bar(", true).await,
+            ["if foo {\n\t\tbar("]
+        );
+        // A line break in the buffer where the query has a single space.
+        assert_eq!(
+            matched_text("bar(1, 2)", true).await,
+            ["bar(1,\n\t\t    2)"]
+        );
+        // Everything but whitespace is still matched literally.
+        assert!(matched_text("bar(1 2)", true).await.is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_filter_matches_by_scope(cx: &mut gpui::TestAppContext) {
+        let text = "// foo comment\nfn foo() {\n    let s = \"foo\";\n}\n";
+        let buffer = cx.update(|cx| {
+            cx.new(|cx| language::Buffer::local(text, cx).with_language(language::rust_lang(), cx))
+        });
+        cx.run_until_parked();
+        let multi_buffer = cx.update(|cx| MultiBuffer::singleton(buffer, cx));
+        let snapshot = cx.update(|cx| multi_buffer.read(cx).snapshot(cx));
+        let query = build_search_query("foo", SearchOptions::NONE).unwrap();
+        let ranges = search_match_ranges(&snapshot, &query, &AtomicBool::new(false))
+            .await
+            .unwrap();
+        assert_eq!(ranges.len(), 3);
+
+        let matched_lines = |in_comments: bool, in_strings: bool| {
+            let ranges =
+                filter_matches_by_scope(ranges.clone(), &snapshot, in_comments, in_strings);
+            match_offsets(&ranges, &snapshot)
+                .into_iter()
+                .map(|range| snapshot.offset_to_point(MultiBufferOffset(range.start)).row)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matched_lines(true, false), [0]);
+        assert_eq!(matched_lines(false, true), [2]);
+        assert_eq!(matched_lines(true, true), [0, 2]);
+    }
+
+    #[gpui::test]
+    fn test_reselecting_the_previewed_match(cx: &mut gpui::TestAppContext) {
+        let multi_buffer = cx.update(|cx| MultiBuffer::build_simple("foo foo\nfoo\n", cx));
+        let snapshot = cx.update(|cx| multi_buffer.read(cx).snapshot(cx));
+        let anchor_range = |range: Range<usize>| {
+            snapshot.anchor_before(MultiBufferOffset(range.start))
+                ..snapshot.anchor_after(MultiBufferOffset(range.end))
+        };
+        let matches = Arc::new(vec![
+            anchor_range(0..3),
+            anchor_range(4..7),
+            anchor_range(8..11),
+        ]);
+        let previewed: PreviewData = (0, 0..2, matches.clone());
+
+        assert!(!is_same_preview(None, &previewed));
+        assert!(is_same_preview(
+            Some(&previewed),
+            &(0, 0..2, matches.clone())
+        ));
+        // The second match on the same line moves the preview without the debounce.
+        let same_line = (4, 0..2, matches.clone());
+        assert!(!is_same_preview(Some(&previewed), &same_line));
+        assert!(is_same_line_group(Some(&previewed), &same_line));
+        // The next line waits for the debounce.
+        let next_line = (8, 2..3, matches.clone());
+        assert!(!is_same_preview(Some(&previewed), &next_line));
+        assert!(!is_same_line_group(Some(&previewed), &next_line));
+        // A new search always updates the preview, even where the matches didn't change.
+        let researched = (0, 0..2, Arc::new(matches.as_ref().clone()));
+        assert!(!is_same_preview(Some(&previewed), &researched));
+        assert!(!is_same_line_group(Some(&previewed), &researched));
+    }
+
+    #[test]
+    fn test_whitespace_insensitive_pattern() {
+        assert_eq!(
+            whitespace_insensitive_pattern("  a.b(c)\t{\n"),
+            r"a\.b\(c\)[\s\n]+\{"
+        );
+        assert_eq!(whitespace_insensitive_pattern("   "), "");
+    }
+
+    #[test]
+    fn test_extend_in_batches_stops_when_cancelled() {
+        let cancelled = AtomicBool::new(false);
+        let mut mapped = Vec::new();
+        assert_eq!(
+            extend_in_batches(&mut mapped, 0..50_000, &cancelled, |item| item * 2),
+            Some(())
+        );
+        assert_eq!(mapped.len(), 50_000);
+        assert_eq!(mapped[49_999], 99_998);
+
+        let mut mapped = Vec::new();
+        let result = extend_in_batches(&mut mapped, 0..50_000, &cancelled, |item| {
+            if item == 10_000 {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            item
+        });
+        assert_eq!(result, None);
+        assert!(mapped.len() > 10_000);
+        assert!(mapped.len() <= 10_000 + ANCHOR_BATCH_SIZE);
+    }
+
+    #[gpui::test]
+    fn test_exported_results_use_full_lines_and_cap(cx: &mut gpui::TestAppContext) {
+        let long_line = format!("ünïcödé {}", "x".repeat(MAX_PREVIEW_BYTES * 2));
+        let text = format!("first\n{long_line}\nthird 日本\n");
+        let multi_buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+        let snapshot = cx.update(|cx| multi_buffer.read(cx).snapshot(cx));
+        let second_line_offset = "first\n".len() + 3;
+        let third_line_offset = text.find("日本").unwrap();
+
+        let results = exported_results(
+            &snapshot,
+            &[second_line_offset, third_line_offset],
+            &HashMap::default(),
+            10,
+        );
+        assert_eq!(
+            format_exported_results(&results, 2),
+            format!("untitled:2: {long_line}\nuntitled:3: third 日本\n")
+        );
+
+        let results = exported_results(
+            &snapshot,
+            &[0, second_line_offset, third_line_offset],
+            &HashMap::default(),
+            1,
+        );
+        assert_eq!(
+            format_exported_results(&results, 3),
+            "untitled:1: first\n… 2 more results were not exported\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_match_previews_with_crlf_line_endings(cx: &mut gpui::TestAppContext) {
+        // Matches at a line start, at a line end, and on a final line without a newline.
+        let text = "foo = 1;\r\n    bar(foo)\r\n\r\nbaz foo";
+        let multi_buffer = cx.update(|cx| MultiBuffer::build_simple(text, cx));
+        let snapshot = cx.update(|cx| multi_buffer.read(cx).snapshot(cx));
+        let query = build_search_query("foo", SearchOptions::NONE).unwrap();
+        let ranges = search_match_ranges(&snapshot, &query, &AtomicBool::new(false))
+            .await
+            .unwrap();
+
+        let previews = match_offsets(&ranges, &snapshot)
+            .into_iter()
+            .map(|range| {
+                let row = snapshot.offset_to_point(MultiBufferOffset(range.start)).row;
+                let (line_range, line_text) = line_content(&snapshot, row);
+                assert!(!line_text.contains('\r'));
+                assert_eq!(
+                    snapshot
+                        .text_for_range(line_range.clone())
+                        .collect::<String>(),
+                    line_text
+                );
+                let match_range = range.start - line_range.start.0..range.end - line_range.start.0;
+                assert_eq!(&line_text[match_range.clone()], "foo");
+                let preview = match_preview(&line_text, &[match_range], 0);
+                (row, preview.text.to_string(), preview.match_ranges)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            previews,
+            [
+                (0, "foo = 1;".to_string(), vec![0..3]),
+                (1, "bar(foo)".to_string(), vec![4..7]),
+                (3, "baz foo".to_string(), vec![4..7]),
+            ]
+        );
+        assert_eq!(line_content(&snapshot, 2).1, "");
+    }
+
+    #[test]
+    fn test_match_preview_clamps_matches_to_the_line() {
+        // A match of the line ending itself starts past the line's text.
+        let preview = match_preview("  let x = 1;", &[12..13], 0);
+        assert_eq!(preview.text.as_ref(), "let x = 1;");
+        assert!(preview.match_ranges.is_empty());
+        assert_eq!(preview.active_match_index, None);
+
+        // A match continuing on the next line is highlighted up to the end of its first line.
+        let preview = match_preview("  let x = 1;", &[2..5, 10..20], 1);
+        assert_eq!(preview.text.as_ref(), "let x = 1;");
+        assert_eq!(preview.match_ranges, vec![0..3, 8..10]);
+        assert_eq!(preview.active_match_index, Some(1));
+        assert_eq!(preview.line_range, 2..12);
+    }
+
+    #[test]
+    fn test_enabling_regex_without_coupling_setting() {
+        let mut options = SearchOptions::NONE;
+        let mut explicit_options = SearchOptions::NONE;
+
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::REGEX,
+            false,
+        );
+        assert_eq!(options, SearchOptions::REGEX);
+    }
+
+    #[test]
+    fn test_smartcase_until_case_sensitivity_is_toggled() {
+        let mut options = SearchOptions::NONE;
+        let mut explicit_options = SearchOptions::NONE;
+
+        assert_eq!(
+            smartcase_search_options(options, explicit_options, "foo"),
+            SearchOptions::NONE
+        );
+        assert_eq!(
+            smartcase_search_options(options, explicit_options, "Foo"),
+            SearchOptions::CASE_SENSITIVE
+        );
+        // A case-sensitive default from the settings still gives way to a lowercase query.
+        assert_eq!(
+            smartcase_search_options(SearchOptions::CASE_SENSITIVE, explicit_options, "foo"),
+            SearchOptions::NONE
+        );
+        // Escaped regex classes are not literal uppercase text.
+        assert_eq!(
+            smartcase_search_options(SearchOptions::REGEX, explicit_options, r"foo\S+"),
+            SearchOptions::REGEX
+        );
+
+        // Toggling case sensitivity off and on again leaves it explicitly off, which
+        // smartcase must not override.
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::CASE_SENSITIVE,
+            false,
+        );
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::CASE_SENSITIVE,
+            false,
+        );
+        assert_eq!(options, SearchOptions::NONE);
+        assert_eq!(
+            smartcase_search_options(options, explicit_options, "Foo"),
+            SearchOptions::NONE
+        );
+
+        // Toggling another option doesn't count as choosing case sensitivity.
+        let mut options = SearchOptions::NONE;
+        let mut explicit_options = SearchOptions::NONE;
+        toggle_search_option(
+            &mut options,
+            &mut explicit_options,
+            SearchOptions::WHOLE_WORD,
+            false,
+        );
+        assert_eq!(
+            smartcase_search_options(options, explicit_options, "Foo"),
+            SearchOptions::WHOLE_WORD | SearchOptions::CASE_SENSITIVE
+        );
+    }
+
+    #[test]
+    fn test_nearest_match_index() {
+        let matches = [2..5, 10..12, 20..25];
+        assert_eq!(nearest_match_index(&matches, 0), Some(0));
+        assert_eq!(nearest_match_index(&matches, 3), Some(0));
+        assert_eq!(nearest_match_index(&matches, 5), Some(0));
+        assert_eq!(nearest_match_index(&matches, 6), Some(0));
+        assert_eq!(nearest_match_index(&matches, 8), Some(1));
+        assert_eq!(nearest_match_index(&matches, 11), Some(1));
+        assert_eq!(nearest_match_index(&matches, 15), Some(1));
+        assert_eq!(nearest_match_index(&matches, 17), Some(2));
+        assert_eq!(nearest_match_index(&matches, 100), Some(2));
+        assert_eq!(nearest_match_index(&[], 3), None);
+    }
+
+    #[test]
+    fn test_initial_view_proximity() {
+        let view = InitialView {
+            visible_range: Some(100..200),
+            selection: Some(150..160),
+        };
+        assert_eq!(view.proximity(50), MatchProximity::Elsewhere);
+        assert_eq!(view.proximity(100), MatchProximity::Visible);
+        assert_eq!(view.proximity(149), MatchProximity::Visible);
+        assert_eq!(view.proximity(150), MatchProximity::InSelection);
+        assert_eq!(view.proximity(159), MatchProximity::InSelection);
+        assert_eq!(view.proximity(160), MatchProximity::Visible);
+        assert_eq!(view.proximity(200), MatchProximity::Elsewhere);
+
+        let view = InitialView {
+            visible_range: None,
+            selection: Some(10..20),
+        };
+        assert_eq!(view.proximity(15), MatchProximity::InSelection);
+        assert_eq!(view.proximity(25), MatchProximity::Elsewhere);
+        assert_eq!(
+            InitialView::default().proximity(0),
+            MatchProximity::Elsewhere
+        );
+    }
+
+    #[test]
+    fn test_step_result_index() {
+        use BufferSearchWrapResults::*;
+
+        assert_eq!(
+            step_result_index(1, 3, &Direction::Down, StopAtEnds),
+            Some((2, false))
+        );
+        assert_eq!(
+            step_result_index(1, 3, &Direction::Up, StopAtEnds),
+            Some((0, false))
+        );
+        assert_eq!(step_result_index(2, 3, &Direction::Down, StopAtEnds), None);
+        assert_eq!(step_result_index(0, 3, &Direction::Up, StopAtEnds), None);
+        assert_eq!(
+            step_result_index(2, 3, &Direction::Down, WrapWithIndicator),
+            Some((0, true))
+        );
+        assert_eq!(
+            step_result_index(0, 3, &Direction::Up, WrapWithIndicator),
+            Some((2, true))
+        );
+        assert_eq!(
+            step_result_index(0, 1, &Direction::Down, WrapWithIndicator),
+            None
+        );
+        assert_eq!(
+            step_result_index(0, 0, &Direction::Down, WrapWithIndicator),
+            None
+        );
+    }
+
+    #[test]
+    fn test_typed_text() {
+        let typed = |keystroke: &str| {
+            let keystroke = Keystroke::parse(keystroke).unwrap().with_simulated_ime();
+            typed_text(&keystroke).map(str::to_string)
+        };
+        assert_eq!(typed("a").as_deref(), Some("a"));
+        assert_eq!(typed("shift-a").as_deref(), Some("A"));
+        assert_eq!(typed("space").as_deref(), Some(" "));
+        assert_eq!(typed("alt-a->å").as_deref(), Some("å"));
+        assert_eq!(typed("alt-shift-a->Å").as_deref(), Some("Å"));
+        // Navigation keys keep scrolling the preview, and shortcuts keep working.
+        for keystroke in [
+            "up", "down", "pageup", "pagedown", "enter", "tab", "ctrl-a", "cmd-c",
+        ] {
+            assert_eq!(typed(keystroke), None, "{keystroke}");
+        }
+        // Alt is a shortcut when it doesn't change the typed character, like on Linux.
+        for keystroke in ["alt-a", "alt-a->a", "alt-shift-a->A"] {
+            assert_eq!(typed(keystroke), None, "{keystroke}");
+        }
+    }
+
+    #[test]
+    fn test_looks_like_regex() {
+        assert!(looks_like_regex(r"fn \w+\("));
+        assert!(looks_like_regex("foo.*bar"));
+        assert!(looks_like_regex("^use "));
+        assert!(looks_like_regex("(a|b)"));
+
+        assert!(!looks_like_regex("foo bar"));
+        assert!(!looks_like_regex(r"foo\.bar"));
+        // Contains metacharacters but doesn't compile.
+        assert!(!looks_like_regex("foo(bar"));
+        assert!(!looks_like_regex("[abc"));
+    }
+
+    fn init_modal_test(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            let store = settings::SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    /// Opens the modal on an editor showing `text`, with the cursor at its start.
+    async fn open_modal<'a>(
+        text: &str,
+        cx: &'a mut gpui::TestAppContext,
+    ) -> (Entity<BufferSearchModal>, &'a mut gpui::VisualTestContext) {
+        let fs = project::FakeFs::new(cx.background_executor.clone());
+        let project = project::Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let editor = workspace.update_in(cx, |workspace, window, cx| {
+            let buffer = cx.new(|cx| {
+                MultiBuffer::singleton(cx.new(|cx| language::Buffer::local(text, cx)), cx)
+            });
+            let editor =
+                cx.new(|cx| Editor::for_multibuffer(buffer, Some(project.clone()), window, cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, window, cx);
+            editor
+        });
+        workspace.update_in(cx, |workspace, window, cx| {
+            BufferSearchModal::toggle_for_editor(workspace, editor, false, window, cx)
+        });
+        cx.run_until_parked();
+        let modal = workspace.read_with(cx, |workspace, cx| {
+            workspace.active_modal::<BufferSearchModal>(cx).unwrap()
+        });
+        (modal, cx)
+    }
+
+    fn modal_search_options(
+        modal: &Entity<BufferSearchModal>,
+        cx: &gpui::VisualTestContext,
+    ) -> SearchOptions {
+        cx.read(|cx| modal.read(cx).picker.read(cx).delegate.search_options)
+    }
+
+    #[gpui::test]
+    async fn test_enabling_regex_in_modal_couples_case_sensitivity(cx: &mut gpui::TestAppContext) {
+        init_modal_test(cx);
+        let (modal, cx) = open_modal("foo\nFoo\n", cx).await;
+        // New searches are case-insensitive by default.
+        assert_eq!(modal_search_options(&modal, cx), SearchOptions::NONE);
+        modal.update_in(cx, |modal, window, cx| {
+            modal.toggle_search_option(SearchOptions::REGEX, window, cx)
+        });
+        assert_eq!(modal_search_options(&modal, cx), SearchOptions::REGEX);
+
+        cx.update(|_, cx| {
+            settings::SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings
+                        .buffer_search
+                        .get_or_insert_default()
+                        .regex_default_case_sensitive = Some(true);
+                });
+            });
+        });
+        for _ in 0..2 {
+            modal.update_in(cx, |modal, window, cx| {
+                modal.toggle_search_option(SearchOptions::REGEX, window, cx)
+            });
+        }
+        assert_eq!(
+            modal_search_options(&modal, cx),
+            SearchOptions::REGEX | SearchOptions::CASE_SENSITIVE
+        );
+    }
+
+    #[gpui::test]
+    async fn test_modal_applies_smartcase(cx: &mut gpui::TestAppContext) {
+        init_modal_test(cx);
+        cx.update(|cx| {
+            settings::SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings
+                        .editor
+                        .search
+                        .get_or_insert_default()
+                        .case_sensitive = Some(true);
+                });
+            });
+        });
+        let (modal, cx) = open_modal("foo\nFoo\nFOO\n", cx).await;
+        let match_count = |query: &str, cx: &mut gpui::VisualTestContext| {
+            modal.update_in(cx, |modal, window, cx| {
+                modal
+                    .picker
+                    .update(cx, |picker, cx| picker.set_query(query, window, cx))
+            });
+            cx.run_until_parked();
+            cx.read(|cx| modal.read(cx).picker.read(cx).delegate.match_count)
+        };
+
+        // A lowercase query matches any case, even with case-sensitive search as the default.
+        assert_eq!(match_count("foo", cx), 3);
+        // An uppercase letter makes the search case-sensitive.
+        assert_eq!(match_count("Foo", cx), 1);
+    }
+
+    #[gpui::test]
+    async fn test_typing_in_preview_goes_to_query(cx: &mut gpui::TestAppContext) {
+        init_modal_test(cx);
+        let (modal, cx) = open_modal("foo\nbar\n", cx).await;
+        modal.update_in(cx, |modal, window, cx| {
+            modal
+                .picker
+                .update(cx, |picker, cx| picker.set_query("foo", window, cx))
+        });
+        cx.run_until_parked();
+        cx.executor()
+            .advance_clock(Duration::from_millis(PREVIEW_DEBOUNCE_MS));
+        cx.run_until_parked();
+
+        modal.update_in(cx, |modal, window, cx| {
+            let preview_editor = modal.preview_editor.clone().unwrap();
+            window.focus(&preview_editor.focus_handle(cx), cx);
+        });
+        cx.simulate_keystrokes("b");
+        cx.run_until_parked();
+        modal.update_in(cx, |modal, window, cx| {
+            let picker = modal.picker.read(cx);
+            assert_eq!(picker.query(cx), "foob");
+            assert!(picker.focus_handle(cx).is_focused(window));
+        });
+    }
+}
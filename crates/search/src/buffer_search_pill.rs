@@ -0,0 +1,221 @@
+use collections::HashMap;
+use editor::{Editor, MultiBuffer};
+use gpui::{
+    Action, App, Context, Entity, EntityId, EventEmitter, Focusable, Global, MouseButton,
+    Subscription, WeakEntity, Window,
+};
+use language::Buffer;
+use settings::Settings as _;
+use ui::{Tooltip, prelude::*};
+use workspace::{ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
+
+use crate::{
+    SearchOptions, buffer_search_modal::ToggleBufferSearch,
+    buffer_search_settings::BufferSearchSettings,
+};
+
+const MAX_PILL_QUERY_CHARS: usize = 30;
+
+/// The last search confirmed in the buffer search modal for a buffer, which the search
+/// pill shows and reopening the modal from it resumes.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LastBufferSearch {
+    pub query: String,
+    pub options: SearchOptions,
+    pub line_mode: bool,
+    pub match_count: usize,
+}
+
+/// The last confirmed search of each buffer, keyed by the buffer's entity, so that every
+/// editor of a buffer shows the same search.
+#[derive(Default)]
+struct LastBufferSearches(HashMap<EntityId, LastBufferSearch>);
+
+impl Global for LastBufferSearches {}
+
+pub(crate) fn remember_last_search(
+    buffer: &Entity<Buffer>,
+    search: LastBufferSearch,
+    cx: &mut App,
+) {
+    let buffer_id = buffer.entity_id();
+    let searches = &mut cx.default_global::<LastBufferSearches>().0;
+    if searches.insert(buffer_id, search).is_none() {
+        cx.observe_release(buffer, move |_, cx| forget_last_search(buffer_id, cx))
+            .detach();
+    }
+}
+
+fn forget_last_search(buffer_id: EntityId, cx: &mut App) {
+    if cx.has_global::<LastBufferSearches>() {
+        cx.global_mut::<LastBufferSearches>().0.remove(&buffer_id);
+    }
+}
+
+fn last_search(buffer_id: EntityId, cx: &App) -> Option<LastBufferSearch> {
+    cx.try_global::<LastBufferSearches>()?
+        .0
+        .get(&buffer_id)
+        .cloned()
+}
+
+/// The last confirmed search of the buffer shown by `multi_buffer`, when it shows a single one.
+pub(crate) fn last_search_in(
+    multi_buffer: &Entity<MultiBuffer>,
+    cx: &App,
+) -> Option<LastBufferSearch> {
+    let buffer = multi_buffer.read(cx).as_singleton()?;
+    last_search(buffer.entity_id(), cx)
+}
+
+/// A toolbar item showing the last search confirmed in the active editor's buffer, which
+/// reopens the buffer search modal with it when clicked.
+pub struct BufferSearchPill {
+    editor: Option<WeakEntity<Editor>>,
+    buffer_id: Option<EntityId>,
+    _observe_searches: Subscription,
+}
+
+impl BufferSearchPill {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            editor: None,
+            buffer_id: None,
+            _observe_searches: cx.observe_global::<LastBufferSearches>(|_, cx| cx.notify()),
+        }
+    }
+
+    fn resume_search(&self, window: &mut Window, cx: &mut App) {
+        if let Some(editor) = self.editor.as_ref().and_then(WeakEntity::upgrade) {
+            window.focus(&editor.focus_handle(cx), cx);
+        }
+        window.dispatch_action(ToggleBufferSearch { restore: true }.boxed_clone(), cx);
+    }
+}
+
+impl Render for BufferSearchPill {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let search = self
+            .buffer_id
+            .filter(|_| BufferSearchSettings::get_global(cx).search_pill)
+            .and_then(|buffer_id| Some((buffer_id, last_search(buffer_id, cx)?)));
+        let Some((buffer_id, search)) = search else {
+            return div().into_any_element();
+        };
+
+        let query = search.query.lines().next().unwrap_or_default();
+        let label = format!(
+            "{} — {}",
+            util::truncate_and_trailoff(query, MAX_PILL_QUERY_CHARS),
+            search.match_count
+        );
+        h_flex()
+            .id("buffer-search-pill")
+            .gap_1()
+            .pl_1p5()
+            .pr_0p5()
+            .rounded_full()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .bg(cx.theme().colors().element_background)
+            .hover(|style| style.bg(cx.theme().colors().element_hover))
+            .cursor_pointer()
+            .child(
+                Icon::new(IconName::MagnifyingGlass)
+                    .size(IconSize::XSmall)
+                    .color(Color::Muted),
+            )
+            .child(Label::new(label).size(LabelSize::Small))
+            .child(
+                IconButton::new("clear-buffer-search-pill", IconName::Close)
+                    .icon_size(IconSize::XSmall)
+                    .shape(ui::IconButtonShape::Square)
+                    .tooltip(Tooltip::text("Clear Search"))
+                    .on_click(move |_, _, cx| {
+                        cx.stop_propagation();
+                        forget_last_search(buffer_id, cx);
+                    }),
+            )
+            .tooltip(Tooltip::text("Resume Search"))
+            .on_click(cx.listener(|this, _, window, cx| this.resume_search(window, cx)))
+            .on_mouse_down(MouseButton::Middle, move |_, _, cx| {
+                forget_last_search(buffer_id, cx);
+            })
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for BufferSearchPill {}
+
+impl ToolbarItemView for BufferSearchPill {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> ToolbarItemLocation {
+        self.editor = None;
+        self.buffer_id = None;
+        cx.notify();
+
+        if !BufferSearchSettings::get_global(cx).search_pill {
+            return ToolbarItemLocation::Hidden;
+        }
+        let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) else {
+            return ToolbarItemLocation::Hidden;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return ToolbarItemLocation::Hidden;
+        };
+        self.buffer_id = Some(buffer.entity_id());
+        self.editor = Some(editor.downgrade());
+        ToolbarItemLocation::PrimaryRight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str, match_count: usize) -> LastBufferSearch {
+        LastBufferSearch {
+            query: query.to_string(),
+            options: SearchOptions::CASE_SENSITIVE,
+            line_mode: false,
+            match_count,
+        }
+    }
+
+    #[gpui::test]
+    fn test_last_search_store_clear_and_restore(cx: &mut gpui::TestAppContext) {
+        let buffer = cx.new(|cx| Buffer::local("fn render() {}\nfn main() {}\n", cx));
+        let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+        let other_buffer = cx.new(|cx| Buffer::local("", cx));
+
+        cx.update(|cx| {
+            assert_eq!(last_search_in(&multi_buffer, cx), None);
+
+            remember_last_search(&buffer, search("fn", 2), cx);
+            assert_eq!(last_search_in(&multi_buffer, cx), Some(search("fn", 2)));
+            assert_eq!(last_search(other_buffer.entity_id(), cx), None);
+
+            // A later confirmed search replaces the earlier one.
+            remember_last_search(&buffer, search("fn render", 1), cx);
+            assert_eq!(
+                last_search_in(&multi_buffer, cx),
+                Some(search("fn render", 1))
+            );
+
+            forget_last_search(buffer.entity_id(), cx);
+            assert_eq!(last_search_in(&multi_buffer, cx), None);
+
+            remember_last_search(&other_buffer, search("main", 1), cx);
+        });
+
+        // Searches are forgotten along with their buffer.
+        let other_buffer_id = other_buffer.entity_id();
+        drop(other_buffer);
+        cx.run_until_parked();
+        cx.update(|cx| assert_eq!(last_search(other_buffer_id, cx), None));
+    }
+}
@@ -0,0 +1,27 @@
+use settings::{BufferSearchWrapResults, RegisterSetting, Settings};
+
+#[derive(Clone, RegisterSetting)]
+pub struct BufferSearchSettings {
+    pub regex_default_case_sensitive: bool,
+    pub wrap_results: BufferSearchWrapResults,
+    pub sync_preview_selection: bool,
+    pub in_comments_only: bool,
+    pub in_strings_only: bool,
+    pub search_pill: bool,
+}
+
+impl Settings for BufferSearchSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let buffer_search = content.buffer_search.clone().unwrap_or_default();
+        Self {
+            regex_default_case_sensitive: buffer_search
+                .regex_default_case_sensitive
+                .unwrap_or(false),
+            wrap_results: buffer_search.wrap_results.unwrap_or_default(),
+            sync_preview_selection: buffer_search.sync_preview_selection.unwrap_or(false),
+            in_comments_only: buffer_search.in_comments_only.unwrap_or(false),
+            in_strings_only: buffer_search.in_strings_only.unwrap_or(false),
+            search_pill: buffer_search.search_pill.unwrap_or(false),
+        }
+    }
+}
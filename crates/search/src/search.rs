@@ -1,5 +1,6 @@
 use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
+pub use buffer_search_pill::BufferSearchPill;
 pub use editor::HighlightKey;
 use editor::SearchSettings;
 use gpui::{Action, App, ClickEvent, FocusHandle, IntoElement, actions};
@@ -17,6 +18,8 @@ use crate::project_search::ProjectSearchBar;
 
 pub mod buffer_search;
 pub mod buffer_search_modal;
+pub mod buffer_search_pill;
+pub mod buffer_search_settings;
 pub mod clipboard_history_modal;
 pub mod emoji_picker_modal;
 pub mod emoji_picker_settings;
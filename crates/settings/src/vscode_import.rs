@@ -222,6 +222,9 @@ impl VsCodeSettings {
             workspace: self.workspace_settings_content(),
             which_key: None,
             emoji_picker: None,
+            buffer_search: None,
+            recent_files: None,
+            recent_projects: None,
         }
     }
 
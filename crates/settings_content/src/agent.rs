@@ -130,6 +130,42 @@ pub struct AgentSettingsContent {
     ///
     /// Default: false
     pub show_turn_stats: Option<bool>,
+    /// Maximum number of threads kept running in the background after their tab was
+    /// closed while generating. Threads that are still generating are never dropped.
+    ///
+    /// Default: 5
+    pub max_detached_threads: Option<usize>,
+    /// Whether to hide the agent panel's tab bar while only one tab is open. The panel's
+    /// menus stay available in a slim strip at the top right of the panel.
+    ///
+    /// Default: false
+    pub hide_single_tab_bar: Option<bool>,
+    /// Whether text threads open as tabs of the agent panel. When disabled, they open as
+    /// items in the workspace instead, leaving the panel's tabs to agent threads.
+    ///
+    /// Default: true
+    pub show_text_thread_tabs: Option<bool>,
+    /// Whether to name the agent panel's tabs after the order they were opened in, as in
+    /// "Agent 1" and "Agent 2", until their threads get a title.
+    ///
+    /// Default: false
+    pub auto_index_untitled_tabs: Option<bool>,
+    /// The fraction of the model's context window a thread can use before its context
+    /// usage indicator turns to a warning color.
+    ///
+    /// Default: 0.8
+    pub context_warning_threshold: Option<f32>,
+    /// The fraction of the model's context window a thread can use before the panel
+    /// offers to summarize it and continue in a new thread.
+    ///
+    /// Default: 0.95
+    pub context_summarize_threshold: Option<f32>,
+    /// How much room the message editor area of the agent panel takes up. The compact
+    /// density trims its padding, shrinks its buttons and collapses the attached
+    /// context into a single pill.
+    ///
+    /// Default: comfortable
+    pub ui_density: Option<AgentUiDensity>,
     /// Per-tool permission rules for granular control over which tool actions require confirmation.
     ///
     /// This setting only applies to the native Zed agent. External agent servers (Claude Code, Gemini CLI, etc.)
@@ -295,6 +331,26 @@ pub enum NotifyWhenAgentWaiting {
     Never,
 }
 
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    PartialEq,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentUiDensity {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
 pub struct LanguageModelSelection {
@@ -182,6 +182,15 @@ pub struct SettingsContent {
     ///
     /// Default: ["😄 smile", "😭 sad", "🤔 thinking"]
     pub emoji_picker: Option<Vec<String>>,
+
+    /// Settings for the buffer search modal.
+    pub buffer_search: Option<BufferSearchSettingsContent>,
+
+    /// Settings for the recent files picker.
+    pub recent_files: Option<RecentFilesSettingsContent>,
+
+    /// Settings for the recent projects picker.
+    pub recent_projects: Option<RecentProjectsSettingsContent>,
 }
 
 impl SettingsContent {
@@ -1084,6 +1093,249 @@ pub struct ReplSettingsContent {
     pub output_max_width_columns: Option<usize>,
 }
 
+/// Settings for the buffer search modal.
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct BufferSearchSettingsContent {
+    /// Whether enabling regex mode also enables case-sensitive matching,
+    /// unless case sensitivity was already toggled explicitly.
+    ///
+    /// Default: false
+    pub regex_default_case_sensitive: Option<bool>,
+    /// What happens when moving the selection past the first or last result.
+    ///
+    /// Default: wrap_with_indicator
+    pub wrap_results: Option<BufferSearchWrapResults>,
+    /// Whether moving the cursor in the preview editor selects the result
+    /// nearest to it, in addition to the selected result driving the preview.
+    ///
+    /// Default: false
+    pub sync_preview_selection: Option<bool>,
+    /// Whether the search modal starts out only showing matches inside comments.
+    /// Combined with `in_strings_only`, matches inside either are shown.
+    ///
+    /// Default: false
+    pub in_comments_only: Option<bool>,
+    /// Whether the search modal starts out only showing matches inside string literals.
+    ///
+    /// Default: false
+    pub in_strings_only: Option<bool>,
+    /// Whether the toolbar of an editor shows the last search confirmed in the search
+    /// modal for its buffer, which reopens the modal with that search when clicked.
+    ///
+    /// Default: false
+    pub search_pill: Option<bool>,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferSearchWrapResults {
+    /// Wrap around to the other end and show a hint that the results wrapped.
+    #[default]
+    WrapWithIndicator,
+    /// Keep the selection on the first or last result.
+    StopAtEnds,
+}
+
+/// How each word of a query is matched in the recent files picker.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentFilesMatchMode {
+    /// A word matches files that contain it as a whole.
+    #[default]
+    Substring,
+    /// A word matches files that contain its characters in order, like `rcmd`
+    /// for `recent_command.rs`.
+    Fuzzy,
+}
+
+/// Settings for the recent files picker.
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct RecentFilesSettingsContent {
+    /// The maximum number of recent files to remember. Lowering it drops the
+    /// least recently opened files the next time a file is opened.
+    ///
+    /// Default: 3000
+    pub max_entries: Option<usize>,
+    /// The maximum number of recent files to keep per parent directory.
+    /// Files opened within the last 24 hours are never pruned.
+    /// Pruning is disabled when unset.
+    ///
+    /// Default: null
+    pub max_entries_per_directory: Option<usize>,
+    /// Whether files opened from the picker in the current project replace the
+    /// preview tab instead of opening a permanent tab.
+    ///
+    /// Default: false
+    pub open_as_preview: Option<bool>,
+    /// Whether file names are left-aligned in a shared column with their
+    /// directories right-aligned, so that similar paths line up vertically.
+    ///
+    /// Default: false
+    pub align_paths: Option<bool>,
+    /// Debug aid: whether to show each entry's match score, recency rank and
+    /// time since it was last opened, to make the ordering of the picker transparent.
+    ///
+    /// Default: false
+    pub debug_scores: Option<bool>,
+    /// Whether to fall back to fuzzy matching, which also finds abbreviations
+    /// like `rcfls` for `recent_files/src/lib.rs`, when no file contains all
+    /// the words of the query.
+    ///
+    /// Default: true
+    pub fuzzy_fallback: Option<bool>,
+    /// How each word of the query is matched against the recent files.
+    ///
+    /// Default: substring
+    pub match_mode: Option<RecentFilesMatchMode>,
+    /// How much more a word of the query counts when it's found in the file
+    /// name rather than in the directory. Values below 1 are treated as 1.
+    ///
+    /// Default: 2.0
+    pub file_name_boost: Option<f32>,
+    /// Whether files that no longer exist, including symlinks whose target is
+    /// gone, are hidden from the picker and dropped from the history on startup.
+    /// Turning it off avoids statting every file, which can hang on slow network mounts.
+    ///
+    /// Default: true
+    pub prune_missing: Option<bool>,
+    /// Whether files that no longer exist are hidden from the picker, checking
+    /// them in the background once the picker opens instead of before it shows.
+    /// With `prune_missing`, the missing files are dropped from the history too.
+    ///
+    /// Default: false
+    pub hide_missing: Option<bool>,
+    /// Whether to show the first lines of the selected file beside the list.
+    ///
+    /// Default: false
+    pub show_preview: Option<bool>,
+    /// Whether to remember files matched by the `private_files` setting, like `.env` files.
+    /// When off, they're neither recorded nor kept from earlier sessions.
+    ///
+    /// Default: false
+    pub track_private_files: Option<bool>,
+    /// Whether paths differing only in case are the same file, so they're
+    /// remembered as a single entry with the casing they were last opened with.
+    /// When unset, this is on for macOS and Windows, whose filesystems are
+    /// usually case-insensitive.
+    ///
+    /// Default: null
+    pub case_insensitive_paths: Option<bool>,
+}
+
+/// Settings for the recent projects picker.
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct RecentProjectsSettingsContent {
+    /// Whether a manually selected project stays selected while typing,
+    /// as long as it still matches the query, instead of jumping to the best match.
+    ///
+    /// Default: false
+    pub sticky_selection: Option<bool>,
+    /// Whether to show a badge with the primary language of local projects,
+    /// detected from marker files such as `Cargo.toml` or `package.json`.
+    ///
+    /// Default: false
+    pub show_language_badge: Option<bool>,
+    /// Path to the zoxide binary used by the zoxide picker. When unset, `zoxide`
+    /// is looked up on the `PATH` of your login shell.
+    ///
+    /// Default: null
+    pub zoxide_binary_path: Option<String>,
+    /// What to do when a local project is opened in place of the current window
+    /// while that window has unsaved changes.
+    ///
+    /// Default: prompt
+    pub on_dirty: Option<RecentProjectsOnDirty>,
+    /// How to group the projects in the recent projects picker.
+    ///
+    /// Default: none
+    pub group_by: Option<RecentProjectsGroupBy>,
+    /// Whether to ask for confirmation before deleting a project from the
+    /// recent projects.
+    ///
+    /// Default: false
+    pub confirm_delete: Option<bool>,
+}
+
+/// How the recent projects picker groups its entries.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    PartialEq,
+    Eq,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentProjectsGroupBy {
+    /// List the projects without grouping them.
+    #[default]
+    None,
+    /// Group the projects under headers for the parent directory of their first root,
+    /// such as the organization directory that a set of repositories is cloned into.
+    ParentDir,
+}
+
+/// How opening a recent project in place of a window with unsaved changes behaves.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    PartialEq,
+    Eq,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentProjectsOnDirty {
+    /// Ask whether to save or discard the unsaved changes before replacing the window.
+    #[default]
+    Prompt,
+    /// Open the project in a new window, leaving the current one untouched.
+    NewWindow,
+    /// Replace the window without prompting, discarding the unsaved changes.
+    Discard,
+}
+
 /// Settings for configuring the which-key popup behaviour.
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct WhichKeySettingsContent {
@@ -686,6 +686,7 @@ impl TitleBar {
                         "Recent Projects",
                         &zed_actions::OpenRecent {
                             create_new_window: false,
+                            root_filter: None,
                         },
                         cx,
                     )
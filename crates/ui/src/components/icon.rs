@@ -50,7 +50,7 @@ impl RenderOnce for AnyIcon {
     }
 }
 
-#[derive(Default, PartialEq, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum IconSize {
     /// 10px
     Indicator,
@@ -938,6 +938,27 @@ impl Domain for WorkspaceDb {
 
 db::static_connection!(DB, WorkspaceDb, []);
 
+// `sql!` can't tokenize SQL string literals such as `'unixepoch'`, so the recent files
+// queries that need them are plain strings.
+const INSERT_RECENT_FILE_AT: &str = "INSERT OR REPLACE INTO recent_files (path, last_accessed) \
+    VALUES (?1, datetime(?2, 'unixepoch'))";
+const SELECT_RECENT_FILES_WITH_ACCESS_TIME: &str = "SELECT path, \
+    COALESCE(CAST(strftime('%s', last_accessed) AS INTEGER), 0) FROM recent_files \
+    ORDER BY last_accessed DESC LIMIT ?1";
+
+/// Replaces the rows of the `recent_files` table. Only call this within a savepoint,
+/// so the table is never observed cleared but not yet refilled.
+fn clear_and_insert_recent_files(conn: &Connection, rows: Vec<(String, i64)>) -> Result<()> {
+    conn.exec_bound(sql!(
+        DELETE FROM recent_files
+    ))?(())?;
+    let mut insert = conn.exec_bound(INSERT_RECENT_FILE_AT)?;
+    for row in rows {
+        insert(row)?;
+    }
+    Ok(())
+}
+
 impl WorkspaceDb {
     /// Returns a serialized workspace for the given worktree_roots. If the passed array
     /// is empty, the most recent workspace is returned instead. If no workspace for the
@@ -1781,6 +1802,30 @@ impl WorkspaceDb {
         Ok(result)
     }
 
+    /// Deletes local workspaces whose paths resolve to the same canonical directories as a more
+    /// recently used workspace, e.g. a project that was also opened through a symlink, and
+    /// returns the deleted ids. Workspaces whose paths can't be canonicalized are left alone.
+    pub async fn merge_duplicate_workspaces(&self) -> Result<Vec<WorkspaceId>> {
+        self.merge_duplicate_workspaces_with(|path| std::fs::canonicalize(path).ok())
+            .await
+    }
+
+    async fn merge_duplicate_workspaces_with(
+        &self,
+        canonicalize: impl Fn(&Path) -> Option<PathBuf>,
+    ) -> Result<Vec<WorkspaceId>> {
+        let local_workspaces = self
+            .recent_workspaces()?
+            .into_iter()
+            .filter(|(_, _, remote_connection_id)| remote_connection_id.is_none())
+            .map(|(id, paths, _)| (id, paths));
+        let duplicate_ids = duplicate_workspace_ids(local_workspaces, canonicalize);
+        for id in &duplicate_ids {
+            self.delete_workspace_by_id(*id).await?;
+        }
+        Ok(duplicate_ids)
+    }
+
     pub async fn last_workspace(
         &self,
     ) -> Result<Option<(WorkspaceId, SerializedWorkspaceLocation, PathList)>> {
@@ -2082,13 +2127,65 @@ impl WorkspaceDb {
         .await
     }
 
-    pub async fn get_recent_files(&self, limit: usize) -> Result<Vec<PathBuf>> {
-        self.select_bound::<usize, String>(sql!(
-            SELECT path FROM recent_files
-            ORDER BY last_accessed DESC
-            LIMIT ?1
-        ))?(limit)
-        .map(|paths| paths.into_iter().map(PathBuf::from).collect())
+    /// Saves a recent file while preserving when it was last accessed,
+    /// given as seconds since the Unix epoch.
+    pub async fn save_recent_file_at(&self, path: &Path, last_accessed: i64) -> Result<()> {
+        let path_str = path.to_string_lossy().to_string();
+        self.write(move |conn| conn.exec_bound(INSERT_RECENT_FILE_AT)?((path_str, last_accessed)))
+            .await
+    }
+
+    /// Saves many recent files at once, each with when it was last accessed as
+    /// seconds since the Unix epoch.
+    ///
+    /// All rows are written in a single transaction with one prepared statement,
+    /// which is much faster than calling [`Self::save_recent_file_at`] per file
+    /// and holds the write connection only once.
+    pub async fn save_recent_files_batch(&self, files: &[(PathBuf, i64)]) -> Result<()> {
+        let rows = files
+            .iter()
+            .map(|(path, last_accessed)| (path.to_string_lossy().to_string(), *last_accessed))
+            .collect::<Vec<_>>();
+        self.write(move |conn| {
+            conn.with_savepoint("save_recent_files_batch", || {
+                let mut insert = conn.exec_bound(INSERT_RECENT_FILE_AT)?;
+                for row in rows {
+                    insert(row)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Replaces all recent files with `files`, each with when it was last accessed as
+    /// seconds since the Unix epoch.
+    ///
+    /// Clearing and inserting happen in a single transaction, so the stored list is
+    /// never left empty or partially written when the process dies in between.
+    pub async fn replace_recent_files(&self, files: &[(PathBuf, i64)]) -> Result<()> {
+        let rows = files
+            .iter()
+            .map(|(path, last_accessed)| (path.to_string_lossy().to_string(), *last_accessed))
+            .collect::<Vec<_>>();
+        self.write(move |conn| {
+            conn.with_savepoint("replace_recent_files", || {
+                clear_and_insert_recent_files(conn, rows)
+            })
+        })
+        .await
+    }
+
+    /// Returns recent files, most recent first, along with when they were
+    /// last accessed as seconds since the Unix epoch.
+    pub async fn get_recent_files(&self, limit: usize) -> Result<Vec<(PathBuf, i64)>> {
+        self.select_bound::<usize, (String, i64)>(SELECT_RECENT_FILES_WITH_ACCESS_TIME)?(limit).map(
+            |rows| {
+                rows.into_iter()
+                    .map(|(path, last_accessed)| (PathBuf::from(path), last_accessed))
+                    .collect()
+            },
+        )
     }
 
     pub async fn clear_recent_files(&self) -> Result<()> {
@@ -2385,6 +2482,31 @@ VALUES {placeholders};"#
     }
 }
 
+/// Returns the ids of the workspaces whose canonical paths equal those of an earlier workspace
+/// in `workspaces`, which is ordered from the most to the least recently used.
+fn duplicate_workspace_ids(
+    workspaces: impl IntoIterator<Item = (WorkspaceId, PathList)>,
+    canonicalize: impl Fn(&Path) -> Option<PathBuf>,
+) -> Vec<WorkspaceId> {
+    let mut seen_paths = HashSet::default();
+    workspaces
+        .into_iter()
+        .filter_map(|(id, paths)| {
+            if paths.is_empty() {
+                return None;
+            }
+            let mut canonical_paths = paths
+                .paths()
+                .iter()
+                .map(|path| canonicalize(path))
+                .collect::<Option<Vec<_>>>()?;
+            canonical_paths.sort();
+            canonical_paths.dedup();
+            (!seen_paths.insert(canonical_paths)).then_some(id)
+        })
+        .collect()
+}
+
 pub fn delete_unloaded_items(
     alive_items: Vec<ItemId>,
     workspace_id: WorkspaceId,
@@ -2427,6 +2549,103 @@ mod tests {
     use remote::SshConnectionOptions;
     use std::{thread, time::Duration};
 
+    #[gpui::test]
+    async fn test_save_recent_files_batch() {
+        zlog::init_test();
+
+        let db = WorkspaceDb::open_test_db("test_save_recent_files_batch").await;
+        let files = (0..3000)
+            .map(|ix| {
+                (
+                    PathBuf::from(format!("/project/src/file_{ix}.rs")),
+                    1_700_000_000 + ix,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Saving the files one by one issues a statement per file, each waiting for
+        // the write connection, while the batch does all of them in one transaction.
+        let start = std::time::Instant::now();
+        for (path, last_accessed) in &files {
+            db.save_recent_file_at(path, *last_accessed).await.unwrap();
+        }
+        let one_by_one = start.elapsed();
+        db.clear_recent_files().await.unwrap();
+
+        let start = std::time::Instant::now();
+        db.save_recent_files_batch(&files).await.unwrap();
+        let batched = start.elapsed();
+        log::info!(
+            "saved {} recent files in {batched:?}, {one_by_one:?} one by one",
+            files.len()
+        );
+
+        let saved = db.get_recent_files(files.len() + 1).await.unwrap();
+        assert_eq!(saved.len(), files.len());
+        assert_eq!(saved.first(), files.last());
+        assert_eq!(saved.last(), files.first());
+
+        // Saving again replaces the existing rows rather than duplicating them.
+        db.save_recent_files_batch(&files[..10]).await.unwrap();
+        assert_eq!(
+            db.get_recent_files(files.len() + 1).await.unwrap().len(),
+            files.len()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_replace_recent_files() {
+        zlog::init_test();
+
+        let db = WorkspaceDb::open_test_db("test_replace_recent_files").await;
+        let old_files = vec![
+            (PathBuf::from("/project/old.rs"), 1_700_000_000),
+            (PathBuf::from("/project/kept.rs"), 1_700_000_001),
+        ];
+        db.save_recent_files_batch(&old_files).await.unwrap();
+
+        let new_files = vec![
+            (PathBuf::from("/project/kept.rs"), 1_700_000_002),
+            (PathBuf::from("/project/new.rs"), 1_700_000_003),
+        ];
+        db.replace_recent_files(&new_files).await.unwrap();
+        assert_eq!(
+            db.get_recent_files(10).await.unwrap(),
+            [new_files[1].clone(), new_files[0].clone()]
+        );
+
+        db.replace_recent_files(&[]).await.unwrap();
+        assert!(db.get_recent_files(10).await.unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_interrupted_recent_files_replacement_keeps_previous_list() {
+        zlog::init_test();
+
+        let db = WorkspaceDb::open_test_db("test_interrupted_recent_files_replacement").await;
+        let files = vec![
+            (PathBuf::from("/project/a.rs"), 1_700_000_000),
+            (PathBuf::from("/project/b.rs"), 1_700_000_001),
+        ];
+        db.replace_recent_files(&files).await.unwrap();
+
+        // Stop the replacement right after the table was cleared, as when Zed gets killed
+        // before the new rows are written.
+        let result = db
+            .write(|conn| {
+                conn.with_savepoint("replace_recent_files", || {
+                    clear_and_insert_recent_files(conn, Vec::new())?;
+                    anyhow::bail!("killed before the new recent files were saved")
+                })
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(
+            db.get_recent_files(10).await.unwrap(),
+            [files[1].clone(), files[0].clone()]
+        );
+    }
+
     #[gpui::test]
     async fn test_breakpoints() {
         zlog::init_test();
@@ -3703,4 +3922,104 @@ mod tests {
         assert!(retrieved.display.is_some());
         assert_eq!(retrieved.display.unwrap(), display_uuid);
     }
+
+    async fn save_workspace_at(db: &WorkspaceDb, id: i64, paths: &[&str], timestamp: &'static str) {
+        let mut workspace = default_workspace(paths, &Default::default());
+        workspace.id = WorkspaceId(id);
+        db.save_workspace(workspace).await;
+        db.write(move |conn| {
+            conn.exec_bound(sql!(
+                UPDATE workspaces SET timestamp = ?2 WHERE workspace_id = ?1
+            ))?((WorkspaceId(id), timestamp))
+        })
+        .await
+        .unwrap();
+    }
+
+    /// Resolves the `/Volumes` and `/mnt` symlinks to `/Users/me`, and fails for `/gone`.
+    fn fake_canonicalize(path: &Path) -> Option<PathBuf> {
+        if path.starts_with("/gone") {
+            return None;
+        }
+        ["/Volumes", "/mnt"]
+            .iter()
+            .find_map(|mount| path.strip_prefix(mount).ok())
+            .map(|rest| Path::new("/Users/me").join(rest))
+            .or_else(|| Some(path.to_path_buf()))
+    }
+
+    fn recent_workspace_ids(db: &WorkspaceDb) -> Vec<WorkspaceId> {
+        db.recent_workspaces()
+            .unwrap()
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_merge_two_duplicate_workspaces() {
+        zlog::init_test();
+
+        let db = WorkspaceDb::open_test_db("test_merge_two_duplicate_workspaces").await;
+        save_workspace_at(&db, 1, &["/Users/me/dev/zed"], "2024-01-01 00:00:01").await;
+        save_workspace_at(&db, 2, &["/Volumes/dev/zed"], "2024-01-01 00:00:03").await;
+        save_workspace_at(&db, 3, &["/Users/me/dev/api"], "2024-01-01 00:00:02").await;
+
+        let merged = db
+            .merge_duplicate_workspaces_with(fake_canonicalize)
+            .await
+            .unwrap();
+        assert_eq!(merged, [WorkspaceId(1)]);
+        assert_eq!(recent_workspace_ids(&db), [WorkspaceId(2), WorkspaceId(3)]);
+    }
+
+    #[gpui::test]
+    async fn test_merge_three_duplicate_workspaces() {
+        zlog::init_test();
+
+        let db = WorkspaceDb::open_test_db("test_merge_three_duplicate_workspaces").await;
+        save_workspace_at(&db, 1, &["/Users/me/dev/zed"], "2024-01-01 00:00:02").await;
+        save_workspace_at(&db, 2, &["/Volumes/dev/zed"], "2024-01-01 00:00:01").await;
+        save_workspace_at(&db, 3, &["/mnt/dev/zed"], "2024-01-01 00:00:04").await;
+        // The order of the roots doesn't matter.
+        let roots = ["/Users/me/dev/zed", "/Users/me/dev/api"];
+        save_workspace_at(&db, 4, &roots, "2024-01-01 00:00:03").await;
+        save_workspace_at(
+            &db,
+            5,
+            &["/Volumes/dev/api", "/mnt/dev/zed"],
+            "2024-01-01 00:00:05",
+        )
+        .await;
+
+        let merged = db
+            .merge_duplicate_workspaces_with(fake_canonicalize)
+            .await
+            .unwrap();
+        assert_eq!(merged, [WorkspaceId(4), WorkspaceId(1), WorkspaceId(2)]);
+        assert_eq!(recent_workspace_ids(&db), [WorkspaceId(5), WorkspaceId(3)]);
+    }
+
+    #[gpui::test]
+    async fn test_merge_duplicate_workspaces_without_duplicates() {
+        zlog::init_test();
+
+        let db =
+            WorkspaceDb::open_test_db("test_merge_duplicate_workspaces_without_duplicates").await;
+        save_workspace_at(&db, 1, &["/Users/me/dev/zed"], "2024-01-01 00:00:03").await;
+        // Workspaces that can't be canonicalized are never merged.
+        save_workspace_at(&db, 2, &["/gone/dev/zed"], "2024-01-01 00:00:02").await;
+        let roots = ["/Users/me/dev/zed", "/Volumes/dev/api"];
+        save_workspace_at(&db, 3, &roots, "2024-01-01 00:00:01").await;
+
+        let merged = db
+            .merge_duplicate_workspaces_with(fake_canonicalize)
+            .await
+            .unwrap();
+        assert!(merged.is_empty());
+        assert_eq!(
+            recent_workspace_ids(&db),
+            [WorkspaceId(1), WorkspaceId(2), WorkspaceId(3)]
+        );
+    }
 }
@@ -1568,6 +1568,8 @@ fn initialize_pane(
                 )
             });
             toolbar.add_item(buffer_search_bar.clone(), window, cx);
+            let buffer_search_pill = cx.new(search::BufferSearchPill::new);
+            toolbar.add_item(buffer_search_pill, window, cx);
             let quick_action_bar =
                 cx.new(|cx| QuickActionBar::new(buffer_search_bar, workspace, cx));
             toolbar.add_item(quick_action_bar, window, cx);
@@ -131,6 +131,7 @@ pub fn app_menus(cx: &mut App) -> Vec<Menu> {
                     "Open Recent...",
                     zed_actions::OpenRecent {
                         create_new_window: false,
+                        root_filter: None,
                     },
                 ),
                 MenuItem::action(
@@ -252,6 +252,8 @@ pub mod workspace {
             OpenWithSystem,
             /// Opens a picker to copy file paths in different formats.
             CopyFilePaths,
+            /// Opens a picker of the buffers closed during this session, most recently closed first.
+            OpenRecentlyClosedBuffers,
         ]
     );
 }
@@ -263,6 +265,10 @@ pub mod workspace {
 pub struct OpenRecentFile {
     #[serde(default)]
     pub create_new_window: bool,
+    /// Whether the picker starts out only listing the files within the current
+    /// workspace's worktrees.
+    #[serde(default)]
+    pub current_workspace_only: bool,
 }
 
 /// Opens a file picker populated with files from a specific directory using `rg --files`.
@@ -502,6 +508,10 @@ pub mod debugger {
 pub struct OpenRecent {
     #[serde(default)]
     pub create_new_window: bool,
+    /// Only lists the projects with a root under this directory. Supports a `~` prefix
+    /// for the home directory.
+    #[serde(default)]
+    pub root_filter: Option<String>,
 }
 
 /// Opens the recent projects interface using zoxide.
@@ -513,6 +523,15 @@ pub struct OpenRecentZoxide {
     pub create_new_window: bool,
 }
 
+/// Opens a picker of both recent files and recent projects.
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema, Action)]
+#[action(namespace = projects)]
+#[serde(deny_unknown_fields)]
+pub struct OpenRecentAny {
+    #[serde(default)]
+    pub create_new_window: bool,
+}
+
 /// Creates a project from a selected template.
 #[derive(PartialEq, Clone, Deserialize, Default, JsonSchema, Action)]
 #[action(namespace = projects)]